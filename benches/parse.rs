@@ -0,0 +1,99 @@
+//! Throughput of parsing some representative inputs. Run with `cargo bench`
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, PathFilter};
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const MANIFEST: &str = r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  labels:
+    app: web
+    tier: frontend
+spec:
+  replicas: 3
+  selector:
+    matchLabels:
+      app: web
+  template:
+    metadata:
+      labels:
+        app: web
+    spec:
+      containers:
+        - name: nginx
+          image: "nginx:1.25"
+          ports:
+            - containerPort: 80
+              protocol: TCP
+          env:
+            - name: LOG_LEVEL
+              value: info # can be debug
+            - name: REGION
+              value: eu-west-1
+          resources:
+            limits: {cpu: 500m, memory: 128Mi}
+          args: [--port, "8080", --verbose]
+---
+"#;
+
+fn manifests() -> String {
+    MANIFEST.repeat(500)
+}
+
+fn deep_nesting() -> String {
+    let mut on = String::new();
+    for _ in 0..50 {
+        for depth in 0..40 {
+            let _ = writeln!(on, "{}level{depth}:", "  ".repeat(depth));
+        }
+        let _ = writeln!(on, "{}value: 1", "  ".repeat(40));
+    }
+    on
+}
+
+fn long_scalars() -> String {
+    let mut on = String::new();
+    for idx in 0..2000 {
+        let _ = writeln!(
+            on,
+            "key{idx}: {}",
+            "lorem ipsum dolor sit amet consectetur ".repeat(10)
+        );
+    }
+    on
+}
+
+fn bench(name: &str, on: &str, options: &ParseOptions) {
+    let mut iterations = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(2) {
+        let mut count = 0usize;
+        parse_with_exit_signal(
+            black_box(on),
+            |keys, value| {
+                count += keys.len();
+                black_box(value);
+                false
+            },
+            options,
+        )
+        .unwrap();
+        black_box(count);
+        iterations += 1;
+    }
+    let elapsed = start.elapsed() / iterations;
+    #[allow(clippy::cast_precision_loss)]
+    let throughput = on.len() as f64 / elapsed.as_secs_f64() / 1_000_000.0;
+    println!("{name:<20} {elapsed:>12.2?} {throughput:>8.1} MB/s");
+}
+
+fn main() {
+    let options = ParseOptions::default();
+    bench("manifests", &manifests(), &options);
+    bench("deep nesting", &deep_nesting(), &options);
+    bench("long scalars", &long_scalars(), &options);
+    let only_name = ParseOptions::new().only_paths(PathFilter::new(["metadata.name"]));
+    bench("manifests (one path)", &manifests(), &only_name);
+}
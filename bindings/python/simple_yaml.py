@@ -0,0 +1,202 @@
+"""Python bindings to simple-yaml-parser through its C interface (the `ffi` feature), using ctypes.
+
+Build the library with `cargo rustc --lib --release --features ffi --crate-type cdylib`. It is
+loaded from the `SIMPLE_YAML_LIB` environment variable, otherwise from `target/release`.
+"""
+
+import collections
+import ctypes
+import math
+import os
+import queue
+import sys
+import threading
+
+_VALUE_STRING, _VALUE_NUMBER, _VALUE_TIMESTAMP, _VALUE_TRUE, _VALUE_FALSE, _VALUE_NULL, _VALUE_ALIAS, _VALUE_TAGGED, _VALUE_RAW_BLOCK = range(9)
+_OK, _INVALID_ARGUMENT, _INVALID_UTF8, _PARSE_ERROR = range(4)
+_SPECIAL_FLOATS = {".inf": math.inf, "+.inf": math.inf, "-.inf": -math.inf, ".nan": math.nan}
+# Passed by the parsing thread of `items` after the last value
+_DONE = object()
+
+
+class _Error(ctypes.Structure):
+    _fields_ = [("at", ctypes.c_size_t), ("line", ctypes.c_size_t), ("column", ctypes.c_size_t)]
+
+
+_CALLBACK = ctypes.CFUNCTYPE(
+    ctypes.c_int,
+    ctypes.POINTER(ctypes.c_char),
+    ctypes.c_size_t,
+    ctypes.POINTER(ctypes.c_char),
+    ctypes.c_size_t,
+    ctypes.c_int,
+    ctypes.POINTER(ctypes.c_char),
+    ctypes.c_size_t,
+    ctypes.c_void_p,
+)
+
+
+def _library_path():
+    if "SIMPLE_YAML_LIB" in os.environ:
+        return os.environ["SIMPLE_YAML_LIB"]
+    name = {"darwin": "libsimple_yaml_parser.dylib", "win32": "simple_yaml_parser.dll"}.get(
+        sys.platform, "libsimple_yaml_parser.so"
+    )
+    root = os.path.join(os.path.dirname(__file__), "..", "..")
+    return os.path.join(root, "target", "release", name)
+
+
+_library = ctypes.CDLL(_library_path())
+_library.yaml_parse.argtypes = [
+    ctypes.c_char_p,
+    ctypes.c_size_t,
+    _CALLBACK,
+    ctypes.c_void_p,
+    ctypes.POINTER(_Error),
+]
+_library.yaml_parse.restype = ctypes.c_int
+
+
+# A value with a custom tag (`!color red`), as its tag and text
+Tagged = collections.namedtuple("Tagged", ["tag", "value"])
+
+
+class YAMLParseError(ValueError):
+    def __init__(self, message, line=0, column=0):
+        super().__init__(message)
+        self.line = line
+        self.column = column
+
+
+def _value(text, kind, tag):
+    if kind == _VALUE_NUMBER:
+        if text.lower() in _SPECIAL_FLOATS:
+            return _SPECIAL_FLOATS[text.lower()]
+        for base in (0, 10):
+            # base 0 reads `0x` and `0o` prefixes but not leading zeros, which YAML 1.2 reads as decimal
+            try:
+                return int(text, base)
+            except ValueError:
+                pass
+        return float(text)
+    if kind == _VALUE_TRUE:
+        return True
+    if kind == _VALUE_FALSE:
+        return False
+    if kind == _VALUE_NULL:
+        return None
+    if kind == _VALUE_TAGGED:
+        return Tagged(tag, text)
+    return text
+
+
+def items(source):
+    """The `(path, value)` pairs of a document, where `path` is a tuple of keys and indices. Pairs are
+    yielded as they are parsed (on another thread, which stays at most one value ahead)"""
+    encoded = source.encode("utf-8")
+    pairs = queue.Queue(maxsize=1)
+    closed = threading.Event()
+    result = {}
+
+    def callback(path, path_len, value, value_len, kind, tag, tag_len, _user_data):
+        if closed.is_set():
+            return 1
+        try:
+            path = ctypes.string_at(path, path_len).decode("utf-8")
+            value = ctypes.string_at(value, value_len).decode("utf-8")
+            tag = ctypes.string_at(tag, tag_len).decode("utf-8")
+            pair = (_split_path(path), _value(value, kind, tag))
+        except BaseException as exception:
+            # ctypes prints and ignores exceptions from callbacks, so it is raised once parsing stops
+            result["exception"] = exception
+            return 1
+        pairs.put(pair)
+        return 0
+
+    def run():
+        error = _Error()
+        c_callback = _CALLBACK(callback)
+        try:
+            result["code"] = _library.yaml_parse(
+                encoded, len(encoded), c_callback, None, ctypes.byref(error)
+            )
+            result["error"] = error
+        except BaseException as exception:
+            result["exception"] = exception
+        finally:
+            pairs.put(_DONE)
+
+    thread = threading.Thread(target=run, daemon=True)
+    thread.start()
+    pair = None
+    try:
+        pair = pairs.get()
+        while pair is not _DONE:
+            yield pair
+            pair = pairs.get()
+    finally:
+        # if the caller stopped early, the callback stops parsing at its next value
+        closed.set()
+        while pair is not _DONE:
+            pair = pairs.get()
+        thread.join()
+
+    if "exception" in result:
+        raise result["exception"]
+    code, error = result["code"], result["error"]
+    if code == _PARSE_ERROR:
+        raise YAMLParseError(f"invalid YAML at {error.line}:{error.column}", error.line, error.column)
+    if code != _OK:
+        raise YAMLParseError(f"could not parse (code {code})")
+
+
+def parse(source):
+    """Parses a document into nested `dict`s and `list`s"""
+    root = None
+    for path, value in items(source):
+        if not path:
+            return value
+        if root is None:
+            root = [] if isinstance(path[0], int) else {}
+        node = root
+        for key, next_key in zip(path, path[1:]):
+            empty = [] if isinstance(next_key, int) else {}
+            if isinstance(key, int):
+                while len(node) <= key:
+                    node.append(None)
+                if node[key] is None:
+                    node[key] = empty
+            else:
+                node.setdefault(key, empty)
+            node = node[key]
+        last = path[-1]
+        if isinstance(last, int):
+            while len(node) <= last:
+                node.append(None)
+        node[last] = value
+    return {} if root is None else root
+
+
+def _split_path(path):
+    """The inverse of `format_key_chain`: `a.b[0]["c.d"]` to `("a", "b", 0, "c.d")`"""
+    keys = []
+    idx = 0
+    while idx < len(path):
+        if path[idx] == ".":
+            idx += 1
+        if path.startswith(('["', "['"), idx):
+            quote = path[idx + 1]
+            end = path.index(quote + "]", idx + 2)
+            keys.append(path[idx + 2 : end])
+            idx = end + 2
+        elif path.startswith("[", idx):
+            end = path.index("]", idx)
+            keys.append(int(path[idx + 1 : end]))
+            idx = end + 1
+        else:
+            end = idx
+            while end < len(path) and path[end] not in ".[":
+                end += 1
+            keys.append(path[idx:end])
+            idx = end
+    return tuple(keys)
@@ -0,0 +1,88 @@
+"""Tests for the Python bindings. Build the library first (see `simple_yaml.py`), then run
+`python3 -m unittest discover bindings/python`"""
+
+import math
+import unittest
+from unittest import mock
+
+import simple_yaml
+
+
+class Scalars(unittest.TestCase):
+    def test_types(self):
+        source = 'a: text\nb: "quoted\\tvalue"\nc: 12\nd: 0x1F\ne: 0o17\nf: 017\ng: -2.5\nh: true\ni: false\nj: ~\n'
+        self.assertEqual(
+            simple_yaml.parse(source),
+            {
+                "a": "text",
+                "b": "quoted\tvalue",
+                "c": 12,
+                "d": 31,
+                "e": 15,
+                "f": 17,
+                "g": -2.5,
+                "h": True,
+                "i": False,
+                "j": None,
+            },
+        )
+
+    def test_special_floats(self):
+        values = simple_yaml.parse("a: .inf\nb: -.Inf\nc: +.INF\nd: .nan\ne: .NaN\n")
+        self.assertEqual((values["a"], values["b"], values["c"]), (math.inf, -math.inf, math.inf))
+        self.assertTrue(math.isnan(values["d"]))
+        self.assertTrue(math.isnan(values["e"]))
+
+    def test_tags(self):
+        values = simple_yaml.parse("a: !color red\nb: !!str 1\n")
+        self.assertEqual(values, {"a": simple_yaml.Tagged("!color", "red"), "b": "1"})
+        self.assertEqual(values["a"].tag, "!color")
+
+
+class Nesting(unittest.TestCase):
+    def test_parse(self):
+        source = "server:\n  hosts:\n    - a\n    - name: b\n      port: 80\n  a.b: dotted\nlist: [[1, 2], {x: y}]\n"
+        self.assertEqual(
+            simple_yaml.parse(source),
+            {
+                "server": {"hosts": ["a", {"name": "b", "port": 80}], "a.b": "dotted"},
+                "list": [[1, 2], {"x": "y"}],
+            },
+        )
+        self.assertEqual(simple_yaml.parse("- x\n- - y\n"), ["x", ["y"]])
+        self.assertEqual(simple_yaml.parse(""), {})
+
+    def test_items(self):
+        self.assertEqual(
+            list(simple_yaml.items("a:\n  b: [1, c]\n")),
+            [(("a", "b", 0), 1), (("a", "b", 1), "c")],
+        )
+
+    def test_items_are_yielded_as_parsed(self):
+        # the invalid input after the first value is not reached
+        values = simple_yaml.items("a: 1\n" + "b: 2\n" * 1000 + "c: [\n")
+        self.assertEqual(next(values), (("a",), 1))
+        values.close()
+
+
+class Errors(unittest.TestCase):
+    def test_parse_error(self):
+        with self.assertRaises(simple_yaml.YAMLParseError) as context:
+            simple_yaml.parse("a: 1\nb: [\n")
+        self.assertEqual(context.exception.line, 2)
+
+    def test_values_before_the_error(self):
+        values = simple_yaml.items("a: 1\nb: [\n")
+        self.assertEqual(next(values), (("a",), 1))
+        with self.assertRaises(simple_yaml.YAMLParseError):
+            next(values)
+
+    def test_callback_exception(self):
+        # exceptions in the ctypes callback are raised after parsing stops
+        with mock.patch.object(simple_yaml, "_value", side_effect=RuntimeError("conversion")):
+            with self.assertRaisesRegex(RuntimeError, "conversion"):
+                simple_yaml.parse("a: 1\n")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,139 @@
+//! `simple-yaml`: querying and converting YAML from the command line
+
+use simple_yaml_parser::json::yaml_to_json;
+use simple_yaml_parser::{
+    format_yaml, parse_lenient, query, FlattenOptions, FormatOptions, ParseOptions, RootYAMLValue,
+    YAMLParseError,
+};
+use std::borrow::Cow;
+use std::io::Read;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: simple-yaml <command> [file]
+
+Reads from standard input if no file (or `-`) is given
+
+Commands:
+  get <path> [file]  Prints the value at a key path (for example `server.hosts[0]`)
+  to-json [file]     Converts to JSON
+  lint [file...]     Reports every error
+  flatten [file]     Prints each value as a `KEY=value` line (for `.env` files)
+  format [file]      Prints with normalised indentation and quoting";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["get", path] => read_input(None).and_then(|source| get(&source, path)),
+        ["get", path, file] => read_input(Some(file)).and_then(|source| get(&source, path)),
+        ["to-json"] => read_input(None).and_then(|source| to_json(&source)),
+        ["to-json", file] => read_input(Some(file)).and_then(|source| to_json(&source)),
+        ["lint"] => read_input(None).map(|source| lint("<stdin>", &source)),
+        ["lint", ref files @ ..] => lint_files(files),
+        ["flatten"] => read_input(None).and_then(|source| flatten(&source)),
+        ["flatten", file] => read_input(Some(file)).and_then(|source| flatten(&source)),
+        ["format"] => read_input(None).and_then(|source| format(&source)),
+        ["format", file] => read_input(Some(file)).and_then(|source| format(&source)),
+        ["help" | "--help" | "-h"] => {
+            println!("{USAGE}");
+            Ok(true)
+        }
+        _ => Err(USAGE.to_owned()),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Reads `file`, or standard input for `None` and `-`
+fn read_input(file: Option<&str>) -> Result<String, String> {
+    match file {
+        Some(file) if file != "-" => {
+            std::fs::read_to_string(file).map_err(|err| format!("could not read {file}: {err}"))
+        }
+        _ => {
+            let mut source = String::new();
+            std::io::stdin()
+                .read_to_string(&mut source)
+                .map_err(|err| format!("could not read standard input: {err}"))?;
+            Ok(source)
+        }
+    }
+}
+
+fn parse_error(source: &str, err: &YAMLParseError) -> String {
+    err.display_with_source(source)
+}
+
+/// Prints the value at `path` (see [`query`]). Returns `false` if there is no value
+fn get(source: &str, path: &str) -> Result<bool, String> {
+    let found = query(source, path).map_err(|err| parse_error(source, &err))?;
+    if let Some(value) = found {
+        let text = value_text(&value);
+        // block scalars already end with a newline
+        if text.ends_with('\n') {
+            print!("{text}");
+        } else {
+            println!("{text}");
+        }
+        Ok(true)
+    } else {
+        eprintln!("no value at {path}");
+        Ok(false)
+    }
+}
+
+fn to_json(source: &str) -> Result<bool, String> {
+    let json = yaml_to_json(source).map_err(|err| parse_error(source, &err))?;
+    println!("{json}");
+    Ok(true)
+}
+
+fn lint_files(files: &[&str]) -> Result<bool, String> {
+    let mut valid = true;
+    for file in files {
+        let source = read_input(Some(file))?;
+        valid &= lint(file, &source);
+    }
+    Ok(valid)
+}
+
+/// Prints every error in `source`. Returns `false` if there are any
+fn lint(name: &str, source: &str) -> bool {
+    let errors = parse_lenient(source, |_, _| {}, &ParseOptions::default());
+    for err in &errors {
+        eprintln!("{name}: {}\n", err.display_with_source(source));
+    }
+    errors.is_empty()
+}
+
+fn flatten(source: &str) -> Result<bool, String> {
+    let flattened = simple_yaml_parser::flatten(source, &FlattenOptions::default())
+        .map_err(|err| parse_error(source, &err))?;
+    print!("{flattened}");
+    Ok(true)
+}
+
+fn format(source: &str) -> Result<bool, String> {
+    let formatted =
+        format_yaml(source, &FormatOptions::default()).map_err(|err| parse_error(source, &err))?;
+    print!("{formatted}");
+    Ok(true)
+}
+
+/// Strings are printed decoded, other values as written
+fn value_text<'a>(value: &RootYAMLValue<'a>) -> Cow<'a, str> {
+    match value {
+        RootYAMLValue::Number(on) | RootYAMLValue::Timestamp(on) => Cow::Borrowed(on),
+        RootYAMLValue::True => Cow::Borrowed("true"),
+        RootYAMLValue::False => Cow::Borrowed("false"),
+        RootYAMLValue::Null => Cow::Borrowed("null"),
+        RootYAMLValue::Alias(name) => Cow::Owned(format!("*{name}")),
+        RootYAMLValue::Tagged(tagged) => Cow::Owned(format!("{} {}", tagged.tag(), tagged.value())),
+        value => value.as_str().unwrap_or_default(),
+    }
+}
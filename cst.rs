@@ -0,0 +1,701 @@
+//! A lossless concrete syntax tree. Every byte of the input is in exactly one token (including
+//! whitespace, comments and indicators), so formatters and codemods can change parts of a document
+//! and write the rest back as it was
+use super::{
+    byte_order_mark_len, find_any, is_document_marker, parse_documents, quoted_end, split_comment,
+    ParseOptions, YAMLParseError,
+};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Spaces and tabs
+    Whitespace,
+    /// `\n` or `\r\n`
+    Newline,
+    /// From the `#` to the end of the line
+    Comment,
+    ByteOrderMark,
+    /// A `%YAML` or `%TAG` line
+    Directive,
+    /// `---`
+    DocumentStart,
+    /// `...`
+    DocumentEnd,
+    /// The `-` before a block sequence item
+    Dash,
+    /// The `?` before an explicit key
+    QuestionMark,
+    /// The `:` after a key
+    Colon,
+    /// The `,` between flow entries
+    Comma,
+    /// `[` or `{`
+    FlowStart,
+    /// `]` or `}`
+    FlowEnd,
+    /// `&name`
+    Anchor,
+    /// `!tag`
+    Tag,
+    /// `*name`
+    Alias,
+    /// A plain (unquoted) key or value
+    Plain,
+    /// A single or double quoted key or value, including the quotes
+    Quoted,
+    /// The `|` or `>` and the indicators after it
+    BlockScalarHeader,
+    /// A line of a block scalar, including its indentation but not the line break
+    BlockScalarLine,
+}
+
+impl TokenKind {
+    /// Whitespace, line breaks and comments
+    #[must_use]
+    pub fn is_trivia(self) -> bool {
+        matches!(self, Self::Whitespace | Self::Newline | Self::Comment)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The root, which contains the documents
+    Stream,
+    Document,
+    BlockMapping,
+    BlockSequence,
+    /// A key, `:` and value, in a block or flow mapping
+    Entry,
+    /// A `-` and the value after it
+    Item,
+    FlowMapping,
+    FlowSequence,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+impl Token {
+    #[must_use]
+    pub fn text<'a>(&self, on: &'a str) -> &'a str {
+        &on[self.span.clone()]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Element {
+    Node(Node),
+    Token(Token),
+}
+
+impl Element {
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Self::Node(node) => node.span.clone(),
+            Self::Token(token) => token.span.clone(),
+        }
+    }
+}
+
+/// A node in the tree. Children are in source order and their spans are contiguous. Whitespace,
+/// line breaks and comments after the last token of a node (other than a document) belong to its
+/// parent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub span: Range<usize>,
+    pub children: Vec<Element>,
+}
+
+impl Node {
+    #[must_use]
+    pub fn text<'a>(&self, on: &'a str) -> &'a str {
+        &on[self.span.clone()]
+    }
+
+    /// The child nodes (skipping tokens)
+    pub fn child_nodes(&self) -> impl Iterator<Item = &Node> {
+        self.children.iter().filter_map(|child| match child {
+            Element::Node(node) => Some(node),
+            Element::Token(_) => None,
+        })
+    }
+
+    /// All the tokens under this node, in source order
+    #[must_use]
+    pub fn tokens(&self) -> Vec<&Token> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a Token>) {
+        for child in &self.children {
+            match child {
+                Element::Node(node) => node.collect_tokens(tokens),
+                Element::Token(token) => tokens.push(token),
+            }
+        }
+    }
+}
+
+/// Parses `on` into a lossless tree. Concatenating the text of the tokens (from [`Node::tokens`])
+/// gives back `on`. The tree does not decode values: use the event parser for that
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input. The input is checked with the event
+/// parser first, so the errors are the same as [`crate::parse_documents`]. The tree is built (and
+/// dropped) recursively, so nesting deeper than [`MAX_DEPTH`] is an error
+pub fn parse(on: &str) -> Result<Node, YAMLParseError> {
+    parse_documents(
+        on,
+        |_, _, _| false,
+        &ParseOptions::new().max_depth(MAX_DEPTH),
+    )?;
+    Ok(Builder::new(on).run())
+}
+
+/// The deepest nesting of keys and sequence items [`parse`] accepts
+pub const MAX_DEPTH: usize = 128;
+
+struct Open {
+    node: Node,
+    /// Column of the `-` or key for items and entries, or of the contents for block collections
+    column: usize,
+}
+
+struct Builder<'a> {
+    on: &'a str,
+    at: usize,
+    /// From the stream to the innermost node
+    stack: Vec<Open>,
+    /// Whether the current line has a block scalar header
+    block_scalar: bool,
+}
+
+impl<'a> Builder<'a> {
+    fn new(on: &'a str) -> Self {
+        Self {
+            on,
+            at: 0,
+            stack: Vec::from([Open {
+                node: Node {
+                    kind: NodeKind::Stream,
+                    span: 0..0,
+                    children: Vec::new(),
+                },
+                column: 0,
+            }]),
+            block_scalar: false,
+        }
+    }
+
+    fn run(mut self) -> Node {
+        self.token(TokenKind::ByteOrderMark, byte_order_mark_len(self.on));
+        while self.at < self.on.len() {
+            self.line();
+        }
+        while self.stack.len() > 1 {
+            self.close();
+        }
+        let mut stream = self.stack.pop().unwrap().node;
+        stream.span = 0..self.on.len();
+        stream
+    }
+
+    fn bytes(&self) -> &'a [u8] {
+        &self.on.as_bytes()[self.at..]
+    }
+
+    /// The end of the current line, before the `\n` or `\r\n`
+    fn line_end(&self) -> usize {
+        let rest = &self.on[self.at..];
+        match find_any(rest, b"\n") {
+            Some(idx) => self.at + rest[..idx].strip_suffix('\r').map_or(idx, str::len),
+            None => self.on.len(),
+        }
+    }
+
+    fn top(&self) -> &Open {
+        self.stack.last().unwrap()
+    }
+
+    /// Adds a token ending at `end`, starting a document if the token is not in one
+    fn token(&mut self, kind: TokenKind, end: usize) {
+        if end <= self.at {
+            return;
+        }
+        if self.top().node.kind == NodeKind::Stream {
+            self.open(NodeKind::Document, 0);
+        }
+        let span = self.at..end;
+        self.stack
+            .last_mut()
+            .unwrap()
+            .node
+            .children
+            .push(Element::Token(Token { kind, span }));
+        self.at = end;
+    }
+
+    fn whitespace(&mut self) {
+        let len = self
+            .bytes()
+            .iter()
+            .take_while(|byte| matches!(byte, b' ' | b'\t'))
+            .count();
+        self.token(TokenKind::Whitespace, self.at + len);
+    }
+
+    fn open(&mut self, kind: NodeKind, column: usize) {
+        if kind != NodeKind::Document && self.top().node.kind == NodeKind::Stream {
+            self.open(NodeKind::Document, 0);
+        }
+        self.stack.push(Open {
+            node: Node {
+                kind,
+                span: self.at..self.at,
+                children: Vec::new(),
+            },
+            column,
+        });
+    }
+
+    /// Closes the innermost node. Trailing trivia moves to the parent
+    fn close(&mut self) {
+        let Open { mut node, .. } = self.stack.pop().unwrap();
+        let kept = if node.kind == NodeKind::Document {
+            node.children.len()
+        } else {
+            node.children
+                .iter()
+                .rposition(
+                    |child| !matches!(child, Element::Token(token) if token.kind.is_trivia()),
+                )
+                .map_or(0, |idx| idx + 1)
+        };
+        let trailing = node.children.split_off(kept);
+        let parent = &mut self.stack.last_mut().unwrap().node.children;
+        if let (Some(first), Some(last)) = (node.children.first(), node.children.last()) {
+            node.span = first.span().start..last.span().end;
+            parent.push(Element::Node(node));
+        }
+        parent.extend(trailing);
+    }
+
+    fn close_to(&mut self, kind: NodeKind) {
+        while !matches!(self.top().node.kind, NodeKind::Stream) && self.top().node.kind != kind {
+            self.close();
+        }
+    }
+
+    /// Opens a block collection, unless the innermost node is one at `column`
+    fn ensure_collection(&mut self, kind: NodeKind, column: usize) {
+        let top = self.top();
+        if top.node.kind != kind || top.column != column {
+            self.open(kind, column);
+        }
+    }
+
+    /// Whether the open document has anything other than directives and trivia
+    fn document_has_content(&self) -> bool {
+        self.stack.get(1).is_some_and(|document| {
+            document.node.children.iter().any(|child| match child {
+                Element::Token(token) => {
+                    !(token.kind.is_trivia()
+                        || matches!(token.kind, TokenKind::Directive | TokenKind::ByteOrderMark))
+                }
+                Element::Node(_) => true,
+            })
+        })
+    }
+
+    fn line(&mut self) {
+        let end = self.line_end();
+        let line = &self.on[self.at..end];
+        let content = line.trim_start_matches([' ', '\t']);
+        let column = line.len() - content.len();
+        if content.is_empty() || content.starts_with('#') {
+            self.rest_of_line();
+            return;
+        }
+        if column == 0 {
+            if is_document_marker(content, "---") {
+                if self.document_has_content() {
+                    self.close_to(NodeKind::Stream);
+                } else {
+                    self.close_to(NodeKind::Document);
+                }
+                self.token(TokenKind::DocumentStart, self.at + "---".len());
+                self.whitespace();
+                if !self.at_line_end() {
+                    self.value();
+                }
+                self.end_line();
+                return;
+            }
+            if is_document_marker(content, "...") {
+                self.close_to(NodeKind::Document);
+                self.token(TokenKind::DocumentEnd, self.at + "...".len());
+                self.rest_of_line();
+                self.close_to(NodeKind::Stream);
+                return;
+            }
+            if content.starts_with('%') {
+                if self.document_has_content() {
+                    self.close_to(NodeKind::Stream);
+                }
+                self.token(
+                    TokenKind::Directive,
+                    split_comment_end(self.on, self.at, end),
+                );
+                self.rest_of_line();
+                return;
+            }
+        }
+        self.whitespace();
+        self.close_before(column, content);
+        self.content(column);
+        self.end_line();
+    }
+
+    /// Finishes the line and any block scalar lines after it
+    fn end_line(&mut self) {
+        self.rest_of_line();
+        if core::mem::take(&mut self.block_scalar) {
+            self.block_scalar_lines(self.parent_column());
+        }
+    }
+
+    /// Closes entries, items and collections that a line at `column` is not part of
+    fn close_before(&mut self, column: usize, content: &str) {
+        let dash = is_indicator(content, '-');
+        let explicit_value = is_indicator(content, ':');
+        loop {
+            let Open { node, column: at } = self.top();
+            let close = match node.kind {
+                NodeKind::Entry => {
+                    *at > column || (*at == column && !explicit_value && (!dash || has_value(node)))
+                }
+                NodeKind::Item => *at >= column,
+                NodeKind::BlockMapping => *at > column,
+                NodeKind::BlockSequence => *at > column || (*at == column && !dash),
+                _ => false,
+            };
+            if !close {
+                break;
+            }
+            self.close();
+        }
+    }
+
+    fn at_line_end(&self) -> bool {
+        matches!(self.bytes().first(), None | Some(b'\n' | b'\r' | b'#'))
+    }
+
+    /// The content of a line, starting at `column`
+    fn content(&mut self, column: usize) {
+        let rest = &self.on[self.at..self.line_end()];
+        if is_indicator(rest, '-') {
+            self.ensure_collection(NodeKind::BlockSequence, column);
+            self.open(NodeKind::Item, column);
+            self.token(TokenKind::Dash, self.at + 1);
+            let before = self.at;
+            self.whitespace();
+            if !self.at_line_end() {
+                self.content(column + 1 + self.at - before);
+            }
+        } else if is_indicator(rest, '?') {
+            self.ensure_collection(NodeKind::BlockMapping, column);
+            self.open(NodeKind::Entry, column);
+            self.token(TokenKind::QuestionMark, self.at + 1);
+            self.whitespace();
+            if !self.at_line_end() {
+                self.value();
+            }
+        } else if is_indicator(rest, ':')
+            && matches!(self.top(), Open { node, column: at } if node.kind == NodeKind::Entry && *at == column)
+        {
+            self.token(TokenKind::Colon, self.at + 1);
+            self.whitespace();
+            if !self.at_line_end() {
+                self.value();
+            }
+        } else if let Some(colon) = key_colon(rest) {
+            self.ensure_collection(NodeKind::BlockMapping, column);
+            self.open(NodeKind::Entry, column);
+            self.key(self.at + colon);
+            self.token(TokenKind::Colon, self.at + 1);
+            self.whitespace();
+            if !self.at_line_end() {
+                self.value();
+            }
+        } else {
+            self.value();
+        }
+    }
+
+    /// Properties and a scalar before the `:` at `colon`
+    fn key(&mut self, colon: usize) {
+        while self.at < colon {
+            let rest = &self.on[self.at..colon];
+            let end = match rest.as_bytes()[0] {
+                b' ' | b'\t' => {
+                    self.whitespace();
+                    continue;
+                }
+                b'&' | b'!' | b'*' => name_end(rest, false),
+                b'"' | b'\'' => quoted_end(rest).unwrap_or(rest.len()),
+                _ => rest.trim_end_matches([' ', '\t']).len(),
+            };
+            self.token(scalar_kind(rest.as_bytes()[0]), self.at + end);
+        }
+    }
+
+    /// A value on the rest of the line (or, for quoted and flow values, the lines after)
+    fn value(&mut self) {
+        loop {
+            self.whitespace();
+            if self.at_line_end() {
+                return;
+            }
+            let rest = &self.on[self.at..];
+            let first = rest.as_bytes()[0];
+            match first {
+                b'[' | b'{' => self.flow(),
+                b'|' | b'>' => {
+                    self.token(
+                        TokenKind::BlockScalarHeader,
+                        self.at + name_end(rest, false),
+                    );
+                    self.block_scalar = true;
+                }
+                b'&' | b'!' | b'*' => {
+                    self.token(scalar_kind(first), self.at + name_end(rest, false));
+                }
+                b'"' | b'\'' => {
+                    let end = quoted_end(rest).unwrap_or(rest.len());
+                    self.token(TokenKind::Quoted, self.at + end);
+                }
+                _ => {
+                    let end = split_comment_end(self.on, self.at, self.line_end());
+                    self.token(TokenKind::Plain, end);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// The column of the entry or item that a value is in
+    fn parent_column(&self) -> Option<usize> {
+        let Open { node, column } = self.top();
+        matches!(node.kind, NodeKind::Entry | NodeKind::Item).then_some(*column)
+    }
+
+    /// A `[...]` or `{...}` collection, which can span several lines
+    fn flow(&mut self) {
+        let kind = if self.bytes()[0] == b'[' {
+            NodeKind::FlowSequence
+        } else {
+            NodeKind::FlowMapping
+        };
+        self.open(kind, 0);
+        self.token(TokenKind::FlowStart, self.at + 1);
+        while let Some(&first) = self.bytes().first() {
+            let rest = &self.on[self.at..];
+            match first {
+                b' ' | b'\t' => self.whitespace(),
+                b'\r' | b'\n' => self.newline(),
+                b'#' => {
+                    let end = self.line_end();
+                    self.token(TokenKind::Comment, end);
+                }
+                b',' => {
+                    self.close_to(kind);
+                    self.token(TokenKind::Comma, self.at + 1);
+                }
+                b']' | b'}' => {
+                    self.close_to(kind);
+                    self.token(TokenKind::FlowEnd, self.at + 1);
+                    self.close();
+                    return;
+                }
+                b':' => {
+                    self.flow_entry(kind);
+                    self.token(TokenKind::Colon, self.at + 1);
+                }
+                b'[' | b'{' => {
+                    self.flow_entry(kind);
+                    self.flow();
+                }
+                b'&' | b'!' | b'*' => {
+                    self.flow_entry(kind);
+                    self.token(scalar_kind(first), self.at + name_end(rest, true));
+                }
+                b'"' | b'\'' => {
+                    self.flow_entry(kind);
+                    let end = quoted_end(rest).unwrap_or(rest.len());
+                    self.token(TokenKind::Quoted, self.at + end);
+                }
+                _ => {
+                    self.flow_entry(kind);
+                    self.token(TokenKind::Plain, self.at + flow_plain_end(rest));
+                }
+            }
+        }
+    }
+
+    /// Starts an entry for the next token in a flow mapping
+    fn flow_entry(&mut self, kind: NodeKind) {
+        if kind == NodeKind::FlowMapping && self.top().node.kind == kind {
+            self.open(NodeKind::Entry, 0);
+        }
+    }
+
+    fn newline(&mut self) {
+        let len = if self.bytes().starts_with(b"\r\n") {
+            2
+        } else {
+            1
+        };
+        self.token(TokenKind::Newline, self.at + len);
+    }
+
+    /// Whitespace, a comment and the line break at the end of a line. Anything else left on the
+    /// line is kept as a plain token
+    fn rest_of_line(&mut self) {
+        let end = self.line_end();
+        self.whitespace();
+        let content_end = split_comment_end(self.on, self.at, end);
+        self.token(TokenKind::Plain, content_end);
+        self.whitespace();
+        if self.bytes().first() == Some(&b'#') {
+            self.token(TokenKind::Comment, end);
+        }
+        self.token(TokenKind::Plain, end);
+        if self.at < self.on.len() {
+            self.newline();
+        }
+    }
+
+    /// The lines of a block scalar: empty lines and those indented past the entry or item at
+    /// `parent` (or, at the top of a document, lines up to a document marker)
+    fn block_scalar_lines(&mut self, parent: Option<usize>) {
+        while self.at < self.on.len() {
+            let end = self.line_end();
+            let line = &self.on[self.at..end];
+            let content = line.trim_start_matches(' ');
+            let indent = line.len() - content.len();
+            let blank = content.trim_start_matches([' ', '\t']).is_empty();
+            let ended = match parent {
+                Some(parent) => !blank && indent <= parent,
+                None => is_document_marker(line, "---") || is_document_marker(line, "..."),
+            };
+            if ended {
+                return;
+            }
+            self.token(TokenKind::BlockScalarLine, end);
+            if self.at < self.on.len() {
+                self.newline();
+            }
+        }
+    }
+}
+
+/// Whether `content` starts with `indicator` followed by whitespace or the end of the line
+fn is_indicator(content: &str, indicator: char) -> bool {
+    content
+        .strip_prefix(indicator)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\r', '\n']))
+}
+
+/// Whether an entry or item has a value after its `:` or `-` (properties do not count)
+pub(crate) fn has_value(entry: &Node) -> bool {
+    entry
+        .children
+        .iter()
+        .skip_while(|child| {
+            !matches!(
+                child,
+                Element::Token(Token {
+                    kind: TokenKind::Colon | TokenKind::Dash,
+                    ..
+                })
+            )
+        })
+        .skip(1)
+        .any(|child| match child {
+            Element::Token(token) => {
+                !(token.kind.is_trivia()
+                    || matches!(token.kind, TokenKind::Anchor | TokenKind::Tag))
+            }
+            Element::Node(_) => true,
+        })
+}
+
+/// The offset of the `:` after a block mapping key, if the line has one
+fn key_colon(line: &str) -> Option<usize> {
+    if line.starts_with(['[', '{']) {
+        return None;
+    }
+    let (line, _) = split_comment(line);
+    let from = quoted_end(line).unwrap_or(0);
+    line[from..]
+        .match_indices(':')
+        .map(|(idx, _)| from + idx)
+        .find(|idx| is_indicator(&line[*idx..], ':'))
+}
+
+fn scalar_kind(first: u8) -> TokenKind {
+    match first {
+        b'&' => TokenKind::Anchor,
+        b'!' => TokenKind::Tag,
+        b'*' => TokenKind::Alias,
+        b'"' | b'\'' => TokenKind::Quoted,
+        _ => TokenKind::Plain,
+    }
+}
+
+/// The end of an anchor, tag, alias or block scalar header
+fn name_end(on: &str, in_flow: bool) -> usize {
+    let stop: &[u8] = if in_flow { b" \t\r\n,[]{}" } else { b" \t\r\n" };
+    find_any(on, stop).unwrap_or(on.len())
+}
+
+/// The end of a plain scalar in a flow collection
+fn flow_plain_end(on: &str) -> usize {
+    let mut end = 0;
+    let mut last = None;
+    for (idx, chr) in on.char_indices() {
+        let ends = match chr {
+            ',' | '[' | ']' | '{' | '}' | '\r' | '\n' => true,
+            '#' => last.is_some_and(|last: char| last == ' ' || last == '\t'),
+            ':' => on[idx + 1..]
+                .chars()
+                .next()
+                .is_none_or(|next| " \t\r\n,[]{}".contains(next)),
+            _ => false,
+        };
+        if ends {
+            break;
+        }
+        if chr != ' ' && chr != '\t' {
+            end = idx + chr.len_utf8();
+        }
+        last = Some(chr);
+    }
+    // always make progress
+    end.max(on.chars().next().map_or(0, char::len_utf8))
+}
+
+/// The end of the content of `on[start..end]` before any comment and trailing whitespace
+fn split_comment_end(on: &str, start: usize, end: usize) -> usize {
+    let (content, _) = split_comment(&on[start..end]);
+    start + content.trim_end_matches([' ', '\t']).len()
+}
@@ -0,0 +1,158 @@
+//! Exporting a sequence of mappings as CSV
+use super::flatten::value_text;
+use super::{
+    format_key_chain, parse_key_path, parse_structure, Emitted, ParseOptions, Signal, YAMLKey,
+    YAMLParseError,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Error from [`sequence_to_csv`]
+#[derive(Debug)]
+pub enum CsvError {
+    Parse(YAMLParseError),
+    /// There is no sequence at the path
+    NotFound,
+    /// An item of the sequence is not a mapping, with its index
+    NotMapping(usize),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CsvError {}
+
+impl core::fmt::Display for CsvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            CsvError::Parse(err) => core::fmt::Display::fmt(err, f),
+            CsvError::NotFound => f.write_str("no sequence at path"),
+            CsvError::NotMapping(index) => write!(f, "item {index} is not a mapping"),
+        }
+    }
+}
+
+impl From<YAMLParseError> for CsvError {
+    fn from(err: YAMLParseError) -> Self {
+        CsvError::Parse(err)
+    }
+}
+
+/// Writes the sequence of mappings at `path` (in the format of [`parse_key_path`], empty for the
+/// top of the document) as CSV: a header row of keys and then a row for each item. Values in
+/// nested collections get a column each, named by their path in the item (`address.city`,
+/// `tags[0]`).
+///
+/// Columns are in the order their keys first appear and items without a key have an empty cell, as
+/// do nulls. Aliases and merge keys are resolved. Fields are quoted (with `""` for quotes) when they
+/// contain a comma, quote or line break. Rows end with `\n`. An empty sequence (`[]`) gives an empty
+/// string
+///
+/// # Errors
+/// Returns an error if `on` is invalid YAML, there is no sequence at `path` or it has an item that
+/// is not a mapping
+pub fn sequence_to_csv(on: &str, path: &str) -> Result<String, CsvError> {
+    let path = parse_key_path(path);
+    let options = ParseOptions::new()
+        .resolve_aliases(true)
+        .resolve_merge_keys(true);
+    let mut columns: Vec<String> = Vec::new();
+    // the cells (column and text) of each item
+    let mut rows: Vec<Vec<(usize, String)>> = Vec::new();
+    // whether `path` is an empty sequence
+    let mut is_empty = false;
+    let mut error = None;
+    parse_structure(
+        on,
+        |keys, node, _| {
+            let Some(rest) = keys.strip_prefix(path.as_slice()) else {
+                return Signal::Continue;
+            };
+            let (index, field, value) = match (rest, node) {
+                ([], Emitted::EmptyCollection { is_mapping: false }) => {
+                    is_empty = true;
+                    return Signal::Continue;
+                }
+                // `{}` items and empty collections in items have no cells
+                ([YAMLKey::Index(index), field @ ..], Emitted::EmptyCollection { is_mapping })
+                    if is_mapping || !field.is_empty() =>
+                {
+                    if rows.len() <= *index {
+                        rows.resize_with(index + 1, Vec::new);
+                    }
+                    return Signal::Continue;
+                }
+                (_, Emitted::DocumentStart | Emitted::DocumentEnd | Emitted::Properties(_)) => {
+                    return Signal::Continue;
+                }
+                ([YAMLKey::Index(index), field @ ..], Emitted::Value(value))
+                    if !field.is_empty() =>
+                {
+                    (*index, field, value)
+                }
+                ([YAMLKey::Index(index)], _) => {
+                    error = Some(CsvError::NotMapping(*index));
+                    return Signal::Stop;
+                }
+                _ => {
+                    error = Some(CsvError::NotFound);
+                    return Signal::Stop;
+                }
+            };
+            let name = format_key_chain(field);
+            let column = if let Some(column) = columns.iter().position(|column| *column == name) {
+                column
+            } else {
+                columns.push(name);
+                columns.len() - 1
+            };
+            if rows.len() <= index {
+                rows.resize_with(index + 1, Vec::new);
+            }
+            rows[index].push((column, value_text(&value).into_owned()));
+            Signal::Continue
+        },
+        |_, _| {},
+        Err,
+        &options,
+    )
+    .map_err(|err| err.locate(on))?;
+    if let Some(error) = error {
+        return Err(error);
+    }
+    if rows.is_empty() {
+        return if is_empty {
+            Ok(String::new())
+        } else {
+            Err(CsvError::NotFound)
+        };
+    }
+
+    let mut output = String::new();
+    write_row(&mut output, columns.iter().map(String::as_str));
+    for row in &rows {
+        write_row(
+            &mut output,
+            (0..columns.len()).map(|column| {
+                row.iter()
+                    .rfind(|(cell, _)| *cell == column)
+                    .map_or("", |(_, text)| text.as_str())
+            }),
+        );
+    }
+    Ok(output)
+}
+
+fn write_row<'a>(output: &mut String, fields: impl Iterator<Item = &'a str>) {
+    for (idx, field) in fields.enumerate() {
+        if idx > 0 {
+            output.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            output.push('"');
+            output.push_str(&field.replace('"', "\"\""));
+            output.push('"');
+        } else {
+            output.push_str(field);
+        }
+    }
+    output.push('\n');
+}
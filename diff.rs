@@ -0,0 +1,118 @@
+use super::{parse_with_exit_signal, ParseOptions, RootYAMLValue, YAMLKey, YAMLParseError};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// A difference between two documents, from [`diff`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YAMLDiff<'a> {
+    /// A value only in the new document
+    Added {
+        keys: Vec<YAMLKey<'a>>,
+        value: RootYAMLValue<'a>,
+    },
+    /// A value only in the old document
+    Removed {
+        keys: Vec<YAMLKey<'a>>,
+        value: RootYAMLValue<'a>,
+    },
+    /// A value in both documents that is different
+    Changed {
+        keys: Vec<YAMLKey<'a>>,
+        old: RootYAMLValue<'a>,
+        new: RootYAMLValue<'a>,
+    },
+}
+
+impl<'a> YAMLDiff<'a> {
+    #[must_use]
+    pub fn keys(&self) -> &[YAMLKey<'a>] {
+        match self {
+            YAMLDiff::Added { keys, .. }
+            | YAMLDiff::Removed { keys, .. }
+            | YAMLDiff::Changed { keys, .. } => keys,
+        }
+    }
+}
+
+/// Error from [`diff`], for the document that is invalid
+#[derive(Debug)]
+pub enum DiffError {
+    Old(YAMLParseError),
+    New(YAMLParseError),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DiffError {}
+
+impl core::fmt::Display for DiffError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            DiffError::Old(err) => f.write_fmt(format_args!("in old document: {err}")),
+            DiffError::New(err) => f.write_fmt(format_args!("in new document: {err}")),
+        }
+    }
+}
+
+/// Compares the values of two documents by key chain. Changed and added values are returned in the
+/// order of `new`, followed by removed values in the order of `old`. Strings are compared by their
+/// text, so changing the quoting (or block scalar style) of a value is not a difference
+///
+/// # Errors
+/// Returns an error if either input is invalid YAML
+pub fn diff<'a>(
+    old: &'a str,
+    new: &'a str,
+    options: &ParseOptions,
+) -> Result<Vec<YAMLDiff<'a>>, DiffError> {
+    let mut old_values = BTreeMap::new();
+    // to return removed values in order
+    let mut order = 0usize;
+    parse_with_exit_signal(
+        old,
+        |keys, value| {
+            old_values.insert(keys.to_vec(), (order, value));
+            order += 1;
+            false
+        },
+        options,
+    )
+    .map_err(DiffError::Old)?;
+
+    let mut diffs = Vec::new();
+    parse_with_exit_signal(
+        new,
+        |keys, new| {
+            match old_values.remove(keys) {
+                Some((_, old)) if same_value(&old, &new) => {}
+                Some((_, old)) => diffs.push(YAMLDiff::Changed {
+                    keys: keys.to_vec(),
+                    old,
+                    new,
+                }),
+                None => diffs.push(YAMLDiff::Added {
+                    keys: keys.to_vec(),
+                    value: new,
+                }),
+            }
+            false
+        },
+        options,
+    )
+    .map_err(DiffError::New)?;
+
+    let mut removed: Vec<_> = old_values.into_iter().collect();
+    removed.sort_by_key(|(_, (order, _))| *order);
+    diffs.extend(
+        removed
+            .into_iter()
+            .map(|(keys, (_, value))| YAMLDiff::Removed { keys, value }),
+    );
+    Ok(diffs)
+}
+
+fn same_value(old: &RootYAMLValue<'_>, new: &RootYAMLValue<'_>) -> bool {
+    match (old.as_str(), new.as_str()) {
+        (Some(old), Some(new)) => old == new,
+        _ => old == new,
+    }
+}
@@ -0,0 +1,138 @@
+//! `%YAML` and `%TAG` directives
+use super::{
+    documents, offset_in, parse_with_exit_signal, split_comment, trim_whitespace, ParseOptions,
+    RootYAMLValue, YAMLKey, YAMLParseError, YAMLParseErrorReason,
+};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The directives before a document, from [`parse_documents_with_info`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentInfo<'a> {
+    /// The position of the document in the stream
+    pub index: usize,
+    /// The byte offset of the document (including its directives) in the stream
+    pub offset: usize,
+    /// The version of a `%YAML` directive (such as `1.2`)
+    pub version: Option<&'a str>,
+    /// The handles and prefixes of `%TAG` directives
+    pub tags: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> DocumentInfo<'a> {
+    /// Reads the directives at the start of `document`
+    fn read(index: usize, offset: usize, document: &'a str) -> Self {
+        let mut info = DocumentInfo {
+            index,
+            offset,
+            version: None,
+            tags: Vec::new(),
+        };
+        for line in document.lines() {
+            if line.starts_with('%') {
+                match parse_directive(document, line) {
+                    Ok(Directive::Version(version)) => info.version = Some(version),
+                    Ok(Directive::Tag { handle, prefix }) => info.tags.push((handle, prefix)),
+                    Ok(Directive::Other) | Err(_) => {}
+                }
+            } else if !(trim_whitespace(line).is_empty() || line.starts_with('#')) {
+                break;
+            }
+        }
+        info
+    }
+
+    /// Expands the handle of `tag` (as from [`crate::TaggedScalar::tag`]) with the `%TAG` directives
+    /// and the default `!!` handle. Returns `None` for local tags (`!name`) without a `%TAG !`
+    /// directive
+    #[must_use]
+    pub fn resolve_tag(&self, tag: &str) -> Option<String> {
+        if let Some(verbatim) = tag.strip_prefix("!<").and_then(|tag| tag.strip_suffix('>')) {
+            return Some(verbatim.to_string());
+        }
+        let declared = self
+            .tags
+            .iter()
+            .filter(|(handle, _)| tag.starts_with(handle))
+            .max_by_key(|(handle, _)| handle.len());
+        match declared {
+            Some((handle, prefix)) => Some(format!("{prefix}{}", &tag[handle.len()..])),
+            None => tag
+                .strip_prefix("!!")
+                .map(|suffix| format!("tag:yaml.org,2002:{suffix}")),
+        }
+    }
+}
+
+pub(crate) enum Directive<'a> {
+    Version(&'a str),
+    Tag {
+        handle: &'a str,
+        prefix: &'a str,
+    },
+    /// Reserved directives are ignored
+    Other,
+}
+
+/// Parses a `%` line of `on` (without the line break)
+pub(crate) fn parse_directive<'a>(
+    on: &'a str,
+    line: &'a str,
+) -> Result<Directive<'a>, YAMLParseError> {
+    let (line, _) = split_comment(trim_whitespace(line));
+    let mut parts = line.split_whitespace();
+    let name = parts.next().unwrap_or_default();
+    let invalid = || {
+        let at = offset_in(on, line);
+        YAMLParseError::new(YAMLParseErrorReason::InvalidDirective, at..at + line.len())
+    };
+    match name {
+        "%YAML" => {
+            let version = parts.next().ok_or_else(invalid)?;
+            let supported = version.strip_prefix("1.").is_some_and(|minor| {
+                !minor.is_empty() && minor.bytes().all(|b| b.is_ascii_digit())
+            });
+            if supported {
+                Ok(Directive::Version(version))
+            } else {
+                let at = offset_in(on, version);
+                Err(YAMLParseError::new(
+                    YAMLParseErrorReason::UnsupportedVersion,
+                    at..at + version.len(),
+                ))
+            }
+        }
+        "%TAG" => match (parts.next(), parts.next()) {
+            (Some(handle), Some(prefix)) if handle.starts_with('!') && handle.ends_with('!') => {
+                Ok(Directive::Tag { handle, prefix })
+            }
+            _ => Err(invalid()),
+        },
+        _ => Ok(Directive::Other),
+    }
+}
+
+/// [`crate::parse_documents`] where the callback receives the directives of the document (and its
+/// index). Directives apply to the document after them
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input or for a `%YAML` directive of a version
+/// other than 1.x
+pub fn parse_documents_with_info<'a>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(&'b DocumentInfo<'a>, &'b [YAMLKey<'a>], RootYAMLValue<'a>) -> bool,
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    options.check_input_len(on).map_err(|err| err.locate(on))?;
+    for (index, (offset, document)) in documents(on).enumerate() {
+        let info = DocumentInfo::read(index, offset, document);
+        let parsed =
+            parse_with_exit_signal(document, |keys, value| cb(&info, keys, value), options)
+                .map_err(|err| err.offset_by(offset).locate(on))?;
+        if parsed.exited {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,502 @@
+use super::cst::{self, has_value, Element, Node, NodeKind, Token, TokenKind};
+use super::{
+    indent_step, parse_key_path, parse_with_spans, split_comment, ParseOptions, RootYAMLValue,
+    YAMLKey, YAMLParseError,
+};
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use core::ops::Range;
+
+/// Error from [`replace_value`] and editing a [`Document`]
+#[derive(Debug)]
+pub enum EditError {
+    Parse(YAMLParseError),
+    /// There is no scalar value at the path
+    NotFound,
+    /// Renaming a key to one that is already in the mapping
+    KeyExists,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EditError {}
+
+impl core::fmt::Display for EditError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            EditError::Parse(err) => core::fmt::Display::fmt(err, f),
+            EditError::NotFound => f.write_str("no value at path"),
+            EditError::KeyExists => f.write_str("key already exists"),
+        }
+    }
+}
+
+impl From<YAMLParseError> for EditError {
+    fn from(err: YAMLParseError) -> Self {
+        EditError::Parse(err)
+    }
+}
+
+/// Replaces the scalar at `path` with `new_value`, keeping the rest of `source` (including comments,
+/// anchors and tags) as is. `new_value` is inserted as written, so strings that would be read as
+/// another type (or contain `: ` or ` #`) should be quoted. A block scalar is replaced along with
+/// its `|` or `>` header
+///
+/// # Errors
+/// Returns an error if `source` is invalid YAML or there is no scalar at `path`
+pub fn replace_value(
+    source: &str,
+    path: &[YAMLKey<'_>],
+    new_value: &str,
+) -> Result<String, EditError> {
+    let span = value_span(source, path)?;
+    Ok(splice_value(source, span, new_value))
+}
+
+fn splice_value(source: &str, span: Range<usize>, new_value: &str) -> String {
+    let mut output = String::with_capacity(source.len() + new_value.len());
+    output.push_str(&source[..span.start]);
+    // empty values end at the `:`
+    if span.is_empty() && source[..span.start].ends_with(':') {
+        output.push(' ');
+    }
+    output.push_str(new_value);
+    output.push_str(&source[span.end..]);
+    output
+}
+
+/// The span of the first scalar at `path`, including the header of block scalars
+fn value_span(source: &str, path: &[YAMLKey<'_>]) -> Result<Range<usize>, EditError> {
+    let mut found = None;
+    parse_with_spans(
+        source,
+        |keys, value, spans| {
+            if keys != path {
+                return false;
+            }
+            let mut span = spans.value;
+            if let RootYAMLValue::MultilineString(_) = value {
+                let line_start = source[..span.start].rfind('\n').map_or(0, |idx| idx + 1);
+                let (line, _) = split_comment(&source[line_start..span.start]);
+                if let Some(header) = line.rfind(['|', '>']) {
+                    span.start = line_start + header;
+                }
+            }
+            found = Some(span);
+            true
+        },
+        &ParseOptions::default(),
+    )?;
+    found.ok_or(EditError::NotFound)
+}
+
+/// A document that is edited in place. Edits only change the lines they touch: the rest of the
+/// text (including comments, blank lines and quoting) stays as it was. Paths are in the format of
+/// [`parse_key_path`] and refer to the first document in the source
+///
+/// ```
+/// let mut document = simple_yaml_parser::Document::parse("version: 1.0 # bumped by CI\n").unwrap();
+/// document.set("version", "1.1").unwrap();
+/// assert_eq!(document.to_string(), "version: 1.1 # bumped by CI\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Document {
+    source: String,
+    tree: Node,
+}
+
+impl Document {
+    /// # Errors
+    /// Returns an error if `source` is invalid YAML
+    pub fn parse(source: &str) -> Result<Self, YAMLParseError> {
+        Ok(Self {
+            tree: cst::parse(source)?,
+            source: source.to_owned(),
+        })
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// The lossless tree of the current source
+    #[must_use]
+    pub fn tree(&self) -> &Node {
+        &self.tree
+    }
+
+    /// Sets the scalar at `path` to `value` (as [`replace_value`] does, so `value` is inserted as
+    /// written). Keys that do not exist are added to the end of the block mapping that should
+    /// contain them, creating nested mappings as needed
+    ///
+    /// # Errors
+    /// Returns [`EditError::NotFound`] if `path` leads to a collection, or through a scalar, a flow
+    /// collection or a missing sequence item. Returns [`EditError::Parse`] (and leaves the document
+    /// unchanged) if the edit would make the source invalid
+    pub fn set(&mut self, path: &str, value: &str) -> Result<(), EditError> {
+        let keys = parse_key_path(path);
+        let source = match value_span(&self.source, &keys) {
+            Ok(span) => splice_value(&self.source, span, value),
+            Err(EditError::NotFound) => self.insert(&keys, value)?,
+            Err(err) => return Err(err),
+        };
+        self.apply(source)
+    }
+
+    /// Removes the entry or sequence item at `path`, along with its lines. Removing the only entry
+    /// (or item) of a collection leaves `{}` (or `[]`)
+    ///
+    /// # Errors
+    /// Returns [`EditError::NotFound`] if there is nothing at `path`
+    pub fn remove(&mut self, path: &str) -> Result<(), EditError> {
+        let keys = parse_key_path(path);
+        let (element, collection) = self.find(&keys).ok_or(EditError::NotFound)?;
+        let source = &self.source;
+        let span = element.span();
+        let siblings: alloc::vec::Vec<&Element> = collection
+            .children
+            .iter()
+            .filter(|child| is_collection_member(child))
+            .collect();
+        let position = siblings
+            .iter()
+            .position(|sibling| sibling.span() == span)
+            .ok_or(EditError::NotFound)?;
+        let next = siblings.get(position + 1).map(|next| next.span().start);
+
+        let (removed, replacement) = if siblings.len() == 1 {
+            let empty = match collection.kind {
+                NodeKind::BlockSequence | NodeKind::FlowSequence => "[]",
+                _ => "{}",
+            };
+            if matches!(
+                collection.kind,
+                NodeKind::FlowMapping | NodeKind::FlowSequence
+            ) {
+                (collection.span.clone(), empty)
+            } else {
+                (span, empty)
+            }
+        } else if matches!(
+            collection.kind,
+            NodeKind::FlowMapping | NodeKind::FlowSequence
+        ) {
+            match next {
+                Some(next) => (span.start..next, ""),
+                None => (siblings[position - 1].span().end..span.end, ""),
+            }
+        } else {
+            let start = line_start(source, span.start);
+            if source[start..span.start]
+                .trim_matches([' ', '\t'])
+                .is_empty()
+            {
+                (start..line_end(source, span.end), "")
+            } else {
+                // the first entry on a `- ` line: the next one moves up
+                (span.start..next.ok_or(EditError::NotFound)?, "")
+            }
+        };
+        self.apply(splice(source, removed, replacement))
+    }
+
+    /// Adds `value` (as written) to the end of the sequence at `path`, as a `- ` line indented
+    /// like the other items or after the last value of a flow sequence. An entry with an empty
+    /// value becomes a sequence of `value`
+    ///
+    /// # Errors
+    /// Returns [`EditError::NotFound`] if there is no sequence or empty value at `path`, and
+    /// [`EditError::Parse`] (leaving the document unchanged) if the edit would make the source invalid
+    pub fn push(&mut self, path: &str, value: &str) -> Result<(), EditError> {
+        let keys = parse_key_path(path);
+        let (collection, parent) = if keys.is_empty() {
+            (self.root(), None)
+        } else {
+            let (element, _) = self.find(&keys).ok_or(EditError::NotFound)?;
+            let Element::Node(parent) = element else {
+                return Err(EditError::NotFound);
+            };
+            (value_collection(element), Some(parent))
+        };
+        let source = &self.source;
+        let (at, text) = match collection {
+            Some(sequence) if sequence.kind == NodeKind::BlockSequence => {
+                let column = column_of(source, sequence.span.start);
+                (
+                    line_end(source, sequence.span.end),
+                    item_line(source, sequence.span.end, column, value),
+                )
+            }
+            Some(sequence) if sequence.kind == NodeKind::FlowSequence => {
+                let last = sequence
+                    .children
+                    .iter()
+                    .rfind(|child| is_collection_member(child));
+                match last {
+                    Some(last) => (last.span().end, format!(", {value}")),
+                    None => (sequence.span.start + '['.len_utf8(), value.to_owned()),
+                }
+            }
+            None => match parent {
+                Some(parent)
+                    if matches!(parent.kind, NodeKind::Entry | NodeKind::Item)
+                        && !has_value(parent) =>
+                {
+                    let column = column_of(source, parent.span.start) + indent_step(source);
+                    (
+                        line_end(source, parent.span.end),
+                        item_line(source, parent.span.end, column, value),
+                    )
+                }
+                _ => return Err(EditError::NotFound),
+            },
+            Some(_) => return Err(EditError::NotFound),
+        };
+        self.apply(splice(source, at..at, &text))
+    }
+
+    /// Renames the key of the entry at `path` to `new_name` (as written, so quote it if needed).
+    /// Only the key changes: its value, anchors and comments are kept
+    ///
+    /// # Errors
+    /// Returns [`EditError::NotFound`] if there is no entry at `path`, [`EditError::KeyExists`] if
+    /// the mapping already has `new_name` and [`EditError::Parse`] (leaving the document unchanged)
+    /// if the edit would make the source invalid
+    pub fn rename_key(&mut self, path: &str, new_name: &str) -> Result<(), EditError> {
+        let keys = parse_key_path(path);
+        let (Element::Node(entry), mapping) = self.find(&keys).ok_or(EditError::NotFound)? else {
+            return Err(EditError::NotFound);
+        };
+        if entry.kind != NodeKind::Entry {
+            return Err(EditError::NotFound);
+        }
+        let key = key_token(entry).ok_or(EditError::NotFound)?;
+        let unquoted = new_name
+            .strip_prefix(['"', '\''])
+            .and_then(|name| name.strip_suffix(['"', '\'']))
+            .unwrap_or(new_name);
+        let exists = mapping.child_nodes().any(|other| {
+            other.span != entry.span && entry_key(&self.source, other) == Some(unquoted)
+        });
+        if exists {
+            return Err(EditError::KeyExists);
+        }
+        self.apply(splice(&self.source, key.span.clone(), new_name))
+    }
+
+    /// Replaces the source, if it is valid
+    fn apply(&mut self, source: String) -> Result<(), EditError> {
+        self.tree = cst::parse(&source)?;
+        self.source = source;
+        Ok(())
+    }
+
+    /// The entry, item or flow sequence element at `keys` and the collection containing it
+    fn find(&self, keys: &[YAMLKey<'_>]) -> Option<(&Element, &Node)> {
+        let (last, parents) = keys.split_last()?;
+        let mut collection = self.root()?;
+        for key in parents {
+            collection = value_collection(member(&self.source, collection, key)?)?;
+        }
+        Some((member(&self.source, collection, last)?, collection))
+    }
+
+    /// The collection at the top of the first document
+    fn root(&self) -> Option<&Node> {
+        self.tree.child_nodes().next()?.child_nodes().next()
+    }
+
+    /// The source with the keys from the first missing one of `keys` added (with `value`)
+    fn insert(&self, keys: &[YAMLKey<'_>], value: &str) -> Result<String, EditError> {
+        let source = &self.source;
+        let mut collection = self.root();
+        let mut entry: Option<&Node> = None;
+        for (depth, key) in keys.iter().enumerate() {
+            let Some(node) = collection else {
+                // an empty value, or an empty document
+                let (at, column) = match entry {
+                    Some(entry) if !has_value(entry) => (
+                        line_end(source, entry.span.end),
+                        column_of(source, entry.span.start) + indent_step(source),
+                    ),
+                    Some(_) => return Err(EditError::NotFound),
+                    None if self.has_root_scalar() => return Err(EditError::NotFound),
+                    None => (source.len(), 0),
+                };
+                return insert_lines(source, at, column, &keys[depth..], value);
+            };
+            match member(source, node, key) {
+                Some(element @ Element::Node(found)) if depth + 1 < keys.len() => {
+                    entry = Some(found);
+                    collection = value_collection(element);
+                }
+                None if node.kind == NodeKind::BlockMapping => {
+                    let at = line_end(source, node.span.end);
+                    let column = column_of(source, node.span.start);
+                    return insert_lines(source, at, column, &keys[depth..], value);
+                }
+                _ => return Err(EditError::NotFound),
+            }
+        }
+        Err(EditError::NotFound)
+    }
+
+    /// Whether the first document is a scalar
+    fn has_root_scalar(&self) -> bool {
+        self.tree.child_nodes().next().is_some_and(|document| {
+            document.tokens().iter().any(|token| {
+                matches!(
+                    token.kind,
+                    TokenKind::Plain
+                        | TokenKind::Quoted
+                        | TokenKind::Alias
+                        | TokenKind::BlockScalarHeader
+                )
+            })
+        })
+    }
+}
+
+impl core::fmt::Display for Document {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        f.write_str(&self.source)
+    }
+}
+
+/// Entries of mappings, items of block sequences and the values in flow sequences
+fn is_collection_member(element: &Element) -> bool {
+    match element {
+        Element::Node(node) => {
+            !matches!(node.kind, NodeKind::BlockMapping | NodeKind::BlockSequence)
+        }
+        Element::Token(token) => {
+            !(token.kind.is_trivia()
+                || matches!(
+                    token.kind,
+                    TokenKind::FlowStart | TokenKind::FlowEnd | TokenKind::Comma
+                ))
+        }
+    }
+}
+
+/// The entry or item for `key` in `collection`
+fn member<'t>(source: &str, collection: &'t Node, key: &YAMLKey<'_>) -> Option<&'t Element> {
+    let mut members = collection
+        .children
+        .iter()
+        .filter(|child| is_collection_member(child));
+    match (collection.kind, key) {
+        (NodeKind::BlockMapping | NodeKind::FlowMapping, YAMLKey::Slice(key)) => {
+            members.find(|member| match member {
+                Element::Node(entry) => entry_key(source, entry) == Some(*key),
+                Element::Token(_) => false,
+            })
+        }
+        (NodeKind::BlockSequence | NodeKind::FlowSequence, YAMLKey::Index(index)) => {
+            members.nth(*index)
+        }
+        _ => None,
+    }
+}
+
+/// The text of an entry's key, without quotes. `None` for a quoted key without a closing quote
+fn entry_key<'a>(source: &'a str, entry: &Node) -> Option<&'a str> {
+    let token = key_token(entry)?;
+    let text = token.text(source);
+    match token.kind {
+        TokenKind::Quoted => {
+            let quote = text.chars().next()?;
+            text[quote.len_utf8()..].strip_suffix(quote)
+        }
+        _ => Some(text),
+    }
+}
+
+/// The token of an entry's key (after any anchor or tag)
+fn key_token(entry: &Node) -> Option<&Token> {
+    match entry.children.iter().find(|child| match child {
+        Element::Token(token) => matches!(token.kind, TokenKind::Plain | TokenKind::Quoted),
+        Element::Node(_) => true,
+    })? {
+        Element::Token(token) => Some(token),
+        Element::Node(_) => None,
+    }
+}
+
+/// The collection that is the value of an entry or item, or a nested flow collection
+fn value_collection(element: &Element) -> Option<&Node> {
+    let Element::Node(node) = element else {
+        return None;
+    };
+    match node.kind {
+        NodeKind::Entry | NodeKind::Item => node.child_nodes().next(),
+        _ => Some(node),
+    }
+}
+
+fn line_start(source: &str, at: usize) -> usize {
+    source[..at].rfind('\n').map_or(0, |idx| idx + 1)
+}
+
+/// The position after the line break that ends the line containing `at`
+fn line_end(source: &str, at: usize) -> usize {
+    source[at..]
+        .find('\n')
+        .map_or(source.len(), |idx| at + idx + 1)
+}
+
+fn column_of(source: &str, at: usize) -> usize {
+    at - line_start(source, at)
+}
+
+/// Inserts `keys` as nested entries at `at`, with the first at `column` and the others indented by
+/// the indentation of `source`
+fn insert_lines(
+    source: &str,
+    at: usize,
+    column: usize,
+    keys: &[YAMLKey<'_>],
+    value: &str,
+) -> Result<String, EditError> {
+    let step = indent_step(source);
+    let mut lines = String::new();
+    if at > 0 && !source[..at].ends_with('\n') {
+        lines.push('\n');
+    }
+    for (depth, key) in keys.iter().enumerate() {
+        let YAMLKey::Slice(key) = key else {
+            return Err(EditError::NotFound);
+        };
+        lines.extend(core::iter::repeat_n(' ', column + depth * step));
+        lines.push_str(key);
+        lines.push(':');
+        if depth + 1 == keys.len() {
+            lines.push(' ');
+            lines.push_str(value);
+        }
+        lines.push('\n');
+    }
+    Ok(splice(source, at..at, &lines))
+}
+
+/// A `- value` line at `column`, to insert after the line containing `after`
+fn item_line(source: &str, after: usize, column: usize, value: &str) -> String {
+    let mut line = String::new();
+    if !source[after..].contains('\n') {
+        line.push('\n');
+    }
+    line.extend(core::iter::repeat_n(' ', column));
+    line.push_str("- ");
+    line.push_str(value);
+    line.push('\n');
+    line
+}
+
+fn splice(source: &str, range: Range<usize>, text: &str) -> String {
+    let mut output = String::with_capacity(source.len() + text.len());
+    output.push_str(&source[..range.start]);
+    output.push_str(text);
+    output.push_str(&source[range.end..]);
+    output
+}
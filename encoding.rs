@@ -0,0 +1,94 @@
+//! Byte order marks and parsing from bytes
+use super::{
+    parse_with_exit_signal, ParseOptions, Parsed, RootYAMLValue, YAMLKey, YAMLParseError,
+    YAMLParseErrorReason,
+};
+
+const BYTE_ORDER_MARK: char = '\u{feff}';
+
+/// The length of the byte order mark at the start of `on` (zero if there is not one)
+pub(crate) fn byte_order_mark_len(on: &str) -> usize {
+    if on.starts_with(BYTE_ORDER_MARK) {
+        BYTE_ORDER_MARK.len_utf8()
+    } else {
+        0
+    }
+}
+
+/// [`crate::parse_with_exit_signal`] for input that is not known to be valid UTF-8. Input starting
+/// with a UTF-16 byte order mark is decoded (with the `utf16` feature) before parsing, in which
+/// case positions (in errors and [`crate::Parsed::offset`]) are of the decoded (UTF-8) input
+///
+/// # Errors
+/// Returns [`YAMLParseErrorReason::InvalidEncoding`] if the input is not valid UTF-8 (or UTF-16),
+/// otherwise an error if it tries to parse invalid YAML input
+pub fn parse_bytes(
+    on: &[u8],
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'b>], RootYAMLValue<'b>) -> bool,
+    options: &ParseOptions,
+) -> Result<Parsed, YAMLParseError> {
+    #[cfg(feature = "utf16")]
+    if let Some(little_endian) = utf16_byte_order(on) {
+        let decoded = decode_utf16(&on[2..], little_endian)?;
+        return parse_with_exit_signal(&decoded, |keys, value| cb(keys, value), options);
+    }
+    #[cfg(not(feature = "utf16"))]
+    if utf16_byte_order(on).is_some() {
+        return Err(YAMLParseError::new(YAMLParseErrorReason::InvalidEncoding, 0..2).locate(""));
+    }
+    match core::str::from_utf8(on) {
+        Ok(on) => parse_with_exit_signal(on, |keys, value| cb(keys, value), options),
+        Err(err) => {
+            let valid = err.valid_up_to();
+            let invalid = err.error_len().unwrap_or(on.len() - valid);
+            // the prefix is valid, so is used to find the line and column
+            let prefix = core::str::from_utf8(&on[..valid]).unwrap_or_default();
+            Err(YAMLParseError::new(
+                YAMLParseErrorReason::InvalidEncoding,
+                valid..valid + invalid,
+            )
+            .locate(prefix))
+        }
+    }
+}
+
+/// Whether `on` starts with a little endian (`FF FE`) or big endian (`FE FF`) UTF-16 byte order
+/// mark
+fn utf16_byte_order(on: &[u8]) -> Option<bool> {
+    match on {
+        [0xFF, 0xFE, ..] => Some(true),
+        [0xFE, 0xFF, ..] => Some(false),
+        _ => None,
+    }
+}
+
+/// Decodes UTF-16 (without the byte order mark). An error is at the position in the decoded input
+#[cfg(feature = "utf16")]
+fn decode_utf16(on: &[u8], little_endian: bool) -> Result<alloc::string::String, YAMLParseError> {
+    let mut decoded = alloc::string::String::with_capacity(on.len() / 2);
+    let units = on.chunks_exact(2).map(|pair| {
+        let pair = [pair[0], pair[1]];
+        if little_endian {
+            u16::from_le_bytes(pair)
+        } else {
+            u16::from_be_bytes(pair)
+        }
+    });
+    for chr in char::decode_utf16(units) {
+        match chr {
+            Ok(chr) => decoded.push(chr),
+            // an unpaired surrogate
+            Err(_) => return Err(invalid_after(&decoded)),
+        }
+    }
+    if on.len() % 2 == 1 {
+        return Err(invalid_after(&decoded));
+    }
+    Ok(decoded)
+}
+
+#[cfg(feature = "utf16")]
+fn invalid_after(decoded: &str) -> YAMLParseError {
+    let at = decoded.len();
+    YAMLParseError::new(YAMLParseErrorReason::InvalidEncoding, at..at).locate(decoded)
+}
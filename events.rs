@@ -0,0 +1,126 @@
+use super::{
+    parse_structure, Emitted, ParseOptions, RootYAMLValue, Signal, YAMLKey, YAMLParseError,
+};
+use alloc::vec::Vec;
+
+/// Structural events, from [`parse_events`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YAMLEvent<'a> {
+    /// Before the content of each document (at `---` or the first value of a document without one)
+    DocumentStart,
+    /// After the content of each document, once its collections are closed
+    DocumentEnd,
+    MapStart,
+    MapEnd,
+    SeqStart,
+    SeqEnd,
+    /// A mapping key. Followed by its value (a [`YAMLEvent::Scalar`] or the start of a collection)
+    Key(&'a str),
+    Scalar(RootYAMLValue<'a>),
+}
+
+/// Parses `on`, passing events for the start and end of documents, mappings and sequences as well as
+/// scalars (like libyaml). Start and end events are balanced within each document and empty
+/// collections (`{}` and `[]`) produce a start and end event. Return `true` from `cb` to stop
+/// parsing
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse_events<'a>(
+    on: &'a str,
+    mut cb: impl FnMut(YAMLEvent<'a>) -> bool,
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    // the key chain of the last value, whose collections are open
+    let mut previous: Vec<YAMLKey<'a>> = Vec::new();
+    // whether a root collection is open (`previous` is empty for a document that is `[]` or `{}`)
+    let mut has_root = false;
+    parse_structure(
+        on,
+        |keys, node, _| {
+            let stop = match node {
+                Emitted::DocumentStart => cb(YAMLEvent::DocumentStart),
+                Emitted::Properties(_) => false,
+                Emitted::DocumentEnd => {
+                    let closed = previous.iter().rev().any(|key| cb(end(key)));
+                    previous.clear();
+                    has_root = false;
+                    closed || cb(YAMLEvent::DocumentEnd)
+                }
+                Emitted::Value(value) => {
+                    open(keys, &mut previous, &mut has_root, &mut cb)
+                        || cb(YAMLEvent::Scalar(value))
+                }
+                Emitted::EmptyCollection { is_mapping } => {
+                    let (start, end) = if is_mapping {
+                        (YAMLEvent::MapStart, YAMLEvent::MapEnd)
+                    } else {
+                        (YAMLEvent::SeqStart, YAMLEvent::SeqEnd)
+                    };
+                    open(keys, &mut previous, &mut has_root, &mut cb) || cb(start) || cb(end)
+                }
+            };
+            if stop {
+                Signal::Stop
+            } else {
+                Signal::Continue
+            }
+        },
+        |_, _| {},
+        Err,
+        options,
+    )
+    .map_err(|err| err.locate(on))
+}
+
+/// Closes the collections of `previous` that `keys` is not in and opens those it is in (passing
+/// the keys), ready for its value. Returns whether to stop
+fn open<'a>(
+    keys: &[YAMLKey<'a>],
+    previous: &mut Vec<YAMLKey<'a>>,
+    has_root: &mut bool,
+    emit: &mut impl FnMut(YAMLEvent<'a>) -> bool,
+) -> bool {
+    // the number of keys whose collections stay open
+    let shared = keys
+        .iter()
+        .zip(previous.iter())
+        .take_while(|(key, previous)| key == previous)
+        .count()
+        .min(keys.len().saturating_sub(1));
+
+    let closing = previous.get(shared + 1..).unwrap_or_default();
+    if closing.iter().rev().any(|key| emit(end(key))) {
+        return true;
+    }
+    for (depth, key) in keys.iter().enumerate().skip(shared) {
+        let opens = depth > shared || !*has_root;
+        *has_root = true;
+        if opens && emit(start(key)) {
+            return true;
+        }
+        if let YAMLKey::Slice(key) = key {
+            if emit(YAMLEvent::Key(key)) {
+                return true;
+            }
+        }
+    }
+    previous.clear();
+    previous.extend_from_slice(keys);
+    false
+}
+
+/// The start of a collection with entries keyed by `key`
+fn start(key: &YAMLKey<'_>) -> YAMLEvent<'static> {
+    match key {
+        YAMLKey::Slice(_) => YAMLEvent::MapStart,
+        YAMLKey::Index(_) => YAMLEvent::SeqStart,
+    }
+}
+
+fn end(key: &YAMLKey<'_>) -> YAMLEvent<'static> {
+    match key {
+        YAMLKey::Slice(_) => YAMLEvent::MapEnd,
+        YAMLKey::Index(_) => YAMLEvent::SeqEnd,
+    }
+}
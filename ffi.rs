@@ -0,0 +1,152 @@
+//! C interface, declared in `include/simple_yaml_parser.h`. Build a library to link against with
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib` (or `staticlib`)
+use super::{
+    format_key_chain, parse_with_exit_signal, ParseOptions, RootYAMLValue, YAMLParseError,
+};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::ffi::{c_char, c_int, c_void};
+
+/// `yaml_value_kind`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YAMLValueKind {
+    String = 0,
+    Number = 1,
+    Timestamp = 2,
+    True = 3,
+    False = 4,
+    Null = 5,
+    Alias = 6,
+    Tagged = 7,
+    RawBlock = 8,
+}
+
+/// `yaml_error`, the position of a parse error
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct YAMLErrorPosition {
+    pub at: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// `yaml_callback`. Receives the path (in the format of [`format_key_chain`]), the text of the
+/// value, its kind and its tag (`!color`, empty unless the kind is [`YAMLValueKind::Tagged`]). The
+/// strings are NUL terminated and only valid during the call. The lengths do not include the NUL
+/// (decoded strings can contain NUL characters). Returns non-zero to stop parsing
+pub type YAMLCallback = extern "C" fn(
+    path: *const c_char,
+    path_len: usize,
+    value: *const c_char,
+    value_len: usize,
+    kind: YAMLValueKind,
+    tag: *const c_char,
+    tag_len: usize,
+    user_data: *mut c_void,
+) -> c_int;
+
+pub const YAML_OK: c_int = 0;
+pub const YAML_INVALID_ARGUMENT: c_int = 1;
+pub const YAML_INVALID_UTF8: c_int = 2;
+pub const YAML_PARSE_ERROR: c_int = 3;
+
+/// Parses `input_len` bytes of UTF-8 at `input`, calling `callback` for each value. On a parse
+/// error, the position is written to `error` (if it is not null). Returns one of the `YAML_*`
+/// codes
+///
+/// # Safety
+/// `input` must point to `input_len` readable bytes (it can be null if `input_len` is 0) and
+/// `error` must be null or point to a writable `yaml_error`
+#[no_mangle]
+pub unsafe extern "C" fn yaml_parse(
+    input: *const c_char,
+    input_len: usize,
+    callback: Option<YAMLCallback>,
+    user_data: *mut c_void,
+    error: *mut YAMLErrorPosition,
+) -> c_int {
+    let Some(callback) = callback else {
+        return YAML_INVALID_ARGUMENT;
+    };
+    let bytes: &[u8] = if input_len == 0 {
+        &[]
+    } else if input.is_null() {
+        return YAML_INVALID_ARGUMENT;
+    } else {
+        // SAFETY: the caller guarantees `input` points to `input_len` bytes
+        unsafe { core::slice::from_raw_parts(input.cast(), input_len) }
+    };
+    let Ok(on) = core::str::from_utf8(bytes) else {
+        return YAML_INVALID_UTF8;
+    };
+
+    let mut path = Vec::new();
+    let mut value_text = Vec::new();
+    let mut tag = Vec::new();
+    let result = parse_with_exit_signal(
+        on,
+        |keys, value| {
+            let (text, kind) = text_and_kind(&value);
+            nul_terminated(&mut path, format_key_chain(keys).as_bytes());
+            nul_terminated(&mut value_text, text.as_bytes());
+            let tag_text = match &value {
+                RootYAMLValue::Tagged(tagged) => tagged.tag(),
+                _ => "",
+            };
+            nul_terminated(&mut tag, tag_text.as_bytes());
+            let exit = callback(
+                path.as_ptr().cast(),
+                path.len() - 1,
+                value_text.as_ptr().cast(),
+                value_text.len() - 1,
+                kind,
+                tag.as_ptr().cast(),
+                tag.len() - 1,
+                user_data,
+            );
+            exit != 0
+        },
+        &ParseOptions::default(),
+    );
+    match result {
+        Ok(_) => YAML_OK,
+        Err(err) => {
+            // SAFETY: the caller guarantees `error` is null or writable
+            if let Some(error) = unsafe { error.as_mut() } {
+                *error = position(&err);
+            }
+            YAML_PARSE_ERROR
+        }
+    }
+}
+
+fn position(err: &YAMLParseError) -> YAMLErrorPosition {
+    YAMLErrorPosition {
+        at: err.at,
+        line: err.line,
+        column: err.column,
+    }
+}
+
+/// Copies `bytes` into `buffer` followed by a NUL
+fn nul_terminated(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    buffer.clear();
+    buffer.extend_from_slice(bytes);
+    buffer.push(0);
+}
+
+/// Strings are decoded, other values are as written. The tag of tagged values is passed separately
+fn text_and_kind<'a>(value: &RootYAMLValue<'a>) -> (Cow<'a, str>, YAMLValueKind) {
+    match value {
+        RootYAMLValue::Number(on) => (Cow::Borrowed(on), YAMLValueKind::Number),
+        RootYAMLValue::Timestamp(on) => (Cow::Borrowed(on), YAMLValueKind::Timestamp),
+        RootYAMLValue::True => (Cow::Borrowed("true"), YAMLValueKind::True),
+        RootYAMLValue::False => (Cow::Borrowed("false"), YAMLValueKind::False),
+        RootYAMLValue::Null => (Cow::Borrowed(""), YAMLValueKind::Null),
+        RootYAMLValue::Alias(name) => (Cow::Borrowed(name), YAMLValueKind::Alias),
+        RootYAMLValue::Tagged(tagged) => (Cow::Borrowed(tagged.value()), YAMLValueKind::Tagged),
+        RootYAMLValue::RawBlock(on) => (Cow::Borrowed(on), YAMLValueKind::RawBlock),
+        value => (value.as_str().unwrap_or_default(), YAMLValueKind::String),
+    }
+}
@@ -0,0 +1,127 @@
+//! Converting YAML to `KEY=value` lines
+use super::{parse_with_exit_signal, ParseOptions, RootYAMLValue, YAMLKey, YAMLParseError};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+
+/// How [`flatten_key`] writes keys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyCase {
+    /// `SERVER__PORT`. Characters other than ASCII letters and digits become `_`
+    #[default]
+    Upper,
+    /// `server__port`. Characters other than ASCII letters and digits become `_`
+    Lower,
+    /// Keys as written
+    Preserve,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FlattenOptions<'a> {
+    /// Between keys (and before sequence indices). Default `__`
+    pub separator: &'a str,
+    pub case: KeyCase,
+}
+
+impl Default for FlattenOptions<'_> {
+    fn default() -> Self {
+        Self {
+            separator: "__",
+            case: KeyCase::default(),
+        }
+    }
+}
+
+/// Converts YAML to `KEY__SUBKEY=value` lines (for `.env` files). Writes values as they are parsed,
+/// without building a tree. Aliases are resolved and null values are empty. Values containing
+/// whitespace, quotes, `#`, `$` or `\` are double quoted (with `\n` for newlines)
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn flatten(on: &str, options: &FlattenOptions<'_>) -> Result<String, YAMLParseError> {
+    let mut output = String::new();
+    let parse_options = ParseOptions::new().resolve_aliases(true);
+    parse_with_exit_signal(
+        on,
+        |keys, value| {
+            output.push_str(&flatten_key(keys, options));
+            output.push('=');
+            write_value(&mut output, &value);
+            output.push('\n');
+            false
+        },
+        &parse_options,
+    )?;
+    Ok(output)
+}
+
+/// The keys passed to a callback as a single key (`server.hosts[0]` becomes `SERVER__HOSTS__0`)
+#[must_use]
+pub fn flatten_key(keys: &[YAMLKey<'_>], options: &FlattenOptions<'_>) -> String {
+    let mut flattened = String::new();
+    for (idx, key) in keys.iter().enumerate() {
+        if idx > 0 {
+            flattened.push_str(options.separator);
+        }
+        let key = match key {
+            YAMLKey::Slice(key) => Cow::Borrowed(*key),
+            YAMLKey::Index(index) => Cow::Owned(index.to_string()),
+        };
+        match options.case {
+            KeyCase::Upper => {
+                flattened.extend(key.chars().map(|chr| env_char(chr).to_ascii_uppercase()));
+            }
+            KeyCase::Lower => {
+                flattened.extend(key.chars().map(|chr| env_char(chr).to_ascii_lowercase()));
+            }
+            KeyCase::Preserve => flattened.push_str(&key),
+        }
+    }
+    flattened
+}
+
+fn env_char(chr: char) -> char {
+    if chr.is_ascii_alphanumeric() {
+        chr
+    } else {
+        '_'
+    }
+}
+
+/// The text of a value: strings are decoded, other scalars are as written and null is empty
+pub(crate) fn value_text<'a>(value: &RootYAMLValue<'a>) -> Cow<'a, str> {
+    match value {
+        RootYAMLValue::Number(on) | RootYAMLValue::Timestamp(on) => Cow::Borrowed(*on),
+        RootYAMLValue::True => Cow::Borrowed("true"),
+        RootYAMLValue::False => Cow::Borrowed("false"),
+        RootYAMLValue::Null | RootYAMLValue::Alias(_) => Cow::Borrowed(""),
+        RootYAMLValue::Tagged(tagged) => Cow::Borrowed(tagged.value()),
+        value => value.as_str().unwrap_or_default(),
+    }
+}
+
+fn write_value(output: &mut String, value: &RootYAMLValue<'_>) {
+    let text = value_text(value);
+    let needs_quotes = text.chars().any(|chr| {
+        chr.is_whitespace()
+            || chr.is_control()
+            || matches!(chr, '"' | '\'' | '#' | '$' | '\\' | '`')
+    });
+    if !needs_quotes {
+        output.push_str(&text);
+        return;
+    }
+    output.push('"');
+    for chr in text.chars() {
+        match chr {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '$' => output.push_str("\\$"),
+            '`' => output.push_str("\\`"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            chr => output.push(chr),
+        }
+    }
+    output.push('"');
+}
@@ -0,0 +1,375 @@
+//! Re-writing documents with consistent layout
+use super::writer::write_double_quoted;
+use super::{
+    is_document_marker, offset_in, parse_structure, trim_whitespace, Emitted, ParseOptions,
+    QuotedString, RootYAMLValue, Signal, SortKeys, YAMLKey, YAMLParseError, YAMLWriter,
+};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// How [`format_yaml`] writes strings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Strings are plain, unless they would be parsed back as something else, in which case they
+    /// are double quoted
+    #[default]
+    Minimal,
+    /// Quoted strings keep their quotes
+    Preserve,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FormatOptions {
+    /// Spaces per nesting level. Default 2
+    pub indent_size: usize,
+    pub quotes: QuoteStyle,
+    /// By default keys are kept in the order they are written
+    pub sort_keys: SortKeys,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_size: 2,
+            quotes: QuoteStyle::default(),
+            sort_keys: SortKeys::Preserve,
+        }
+    }
+}
+
+/// A value (or empty collection) and the comments around it
+struct Entry<'a> {
+    keys: Vec<YAMLKey<'a>>,
+    /// A [`Emitted::Value`] or [`Emitted::EmptyCollection`]
+    node: Emitted<'a>,
+    /// Where each key starts. Sequence items use the position of what follows the `-`
+    positions: Vec<usize>,
+    value_span: Range<usize>,
+    /// Comments (the position of the `#` and the text after it) on the lines before the value
+    leading: Vec<(usize, &'a str)>,
+    /// A comment after the value on the same line
+    trailing: Option<&'a str>,
+}
+
+impl Entry<'_> {
+    /// Where the last key starts
+    fn start(&self) -> usize {
+        self.positions
+            .last()
+            .copied()
+            .unwrap_or(self.value_span.start)
+    }
+}
+
+/// Entries between document markers
+#[derive(Default)]
+struct Document<'a> {
+    entries: Vec<Entry<'a>>,
+    /// Comments after the last entry
+    comments: Vec<(usize, &'a str)>,
+    /// The `&anchor` and `!tag` properties of collections and values, by key chain
+    properties: Vec<(Vec<YAMLKey<'a>>, &'a str)>,
+    /// The `---` or `...` line that ends it
+    end_marker: Option<&'a str>,
+}
+
+/// Re-writes `on` with [`YAMLWriter`]: indentation is normalised, strings are quoted according to
+/// [`FormatOptions::quotes`] and keys are optionally sorted ([`FormatOptions::sort_keys`]). Comments are kept (next to the entry
+/// they were next to) as are single empty lines between entries and document markers.
+///
+/// Anchors, aliases, tags and empty collections (`{}` and `[]`) are kept. Quoted keys are decoded and
+/// written in double quotes (so escapes are written the same way)
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn format_yaml(on: &str, options: &FormatOptions) -> Result<String, YAMLParseError> {
+    let mut entries = Vec::new();
+    let mut comments = Vec::new();
+    let mut properties = Vec::new();
+    let parse_options = ParseOptions::new().emit_comments(true);
+    parse_structure(
+        on,
+        |keys, node, spans| {
+            match node {
+                Emitted::Properties(text) => {
+                    properties.push((keys.to_vec(), text));
+                    return Signal::Continue;
+                }
+                Emitted::DocumentStart | Emitted::DocumentEnd => return Signal::Continue,
+                Emitted::Value(_) | Emitted::EmptyCollection { .. } => {}
+            }
+            let mut positions = Vec::with_capacity(keys.len());
+            let mut next = spans.key.start;
+            for key in keys.iter().rev() {
+                if let YAMLKey::Slice(key) = key {
+                    next = offset_in(on, key);
+                }
+                positions.push(next);
+            }
+            positions.reverse();
+            entries.push(Entry {
+                keys: keys.to_vec(),
+                node,
+                positions,
+                value_span: spans.value,
+                leading: Vec::new(),
+                trailing: None,
+            });
+            Signal::Continue
+        },
+        |at, comment| comments.push((at, comment)),
+        Err,
+        &parse_options,
+    )
+    .map_err(|err| err.locate(on))?;
+
+    let documents = split_documents(on, entries, comments, properties);
+    let mut output = String::new();
+    for document in documents {
+        let mut order: Vec<usize> = (0..document.entries.len()).collect();
+        if !matches!(options.sort_keys, SortKeys::Preserve) {
+            order.sort_by(|a, b| {
+                let (a, b) = (&document.entries[*a], &document.entries[*b]);
+                options.sort_keys.compare(&a.keys, &b.keys)
+            });
+        }
+        let texts: Vec<Vec<Cow<'_, str>>> = document
+            .entries
+            .iter()
+            .map(|entry| entry.keys.iter().map(|key| key_text(on, key)).collect())
+            .collect();
+        let written_keys: Vec<Vec<YAMLKey<'_>>> = document
+            .entries
+            .iter()
+            .zip(&texts)
+            .map(|(entry, texts)| {
+                let keys = entry.keys.iter().zip(texts);
+                keys.map(|(key, text)| match key {
+                    YAMLKey::Slice(_) => YAMLKey::Slice(text),
+                    YAMLKey::Index(idx) => YAMLKey::Index(*idx),
+                })
+                .collect()
+            })
+            .collect();
+
+        let mut writer = YAMLWriter::with_indent_size(options.indent_size).keys_as_written();
+        let mut previous: &[YAMLKey<'_>] = &[];
+        for idx in order {
+            let keys = &written_keys[idx];
+            let entry = &document.entries[idx];
+            write_entry(&mut writer, on, entry, keys, previous, &document, options);
+            previous = keys;
+        }
+        for (at, comment) in document.comments {
+            if blank_line_before(on, at) {
+                writer.write_blank_line();
+            }
+            writer.write_comment(&[], comment);
+        }
+        output.push_str(&writer.finish());
+        if let Some(marker) = document.end_marker {
+            output.push_str(marker);
+            output.push('\n');
+        }
+    }
+    Ok(output)
+}
+
+/// Splits entries on document markers and attaches each comment to the entry it is next to
+fn split_documents<'a>(
+    on: &'a str,
+    entries: Vec<Entry<'a>>,
+    comments: Vec<(usize, &'a str)>,
+    properties: Vec<(Vec<YAMLKey<'a>>, &'a str)>,
+) -> Vec<Document<'a>> {
+    // markers that are not in a (block scalar) value
+    let mut markers = Vec::new();
+    let mut line_start = 0;
+    for line in on.split_inclusive('\n') {
+        let is_marker = is_document_marker(line, "---") || is_document_marker(line, "...");
+        if is_marker
+            && !entries
+                .iter()
+                .any(|entry| entry.value_span.contains(&line_start))
+        {
+            markers.push((line_start, line.trim_end()));
+        }
+        line_start += line.len();
+    }
+
+    let mut documents: Vec<Document<'a>> = Vec::new();
+    documents.resize_with(markers.len() + 1, Document::default);
+    for (document, (_, marker)) in documents.iter_mut().zip(&markers) {
+        document.end_marker = Some(marker);
+    }
+    let document_of = |at: usize| markers.partition_point(|(start, _)| *start < at);
+    for entry in entries {
+        documents[document_of(entry.value_span.start)]
+            .entries
+            .push(entry);
+    }
+    for (keys, text) in properties {
+        documents[document_of(offset_in(on, text))]
+            .properties
+            .push((keys, text));
+    }
+    // entries are in source order
+    for (at, comment) in comments {
+        let document = &mut documents[document_of(at)];
+        let entries = &mut document.entries;
+        let before = entries.partition_point(|entry| entry.value_span.end <= at);
+        let after = entries.partition_point(|entry| entry.start() <= at);
+        match before.checked_sub(1).map(|idx| &mut entries[idx]) {
+            Some(entry)
+                if !on[entry.value_span.end..at].contains('\n')
+                    && !matches!(
+                        entry.node,
+                        Emitted::Value(RootYAMLValue::MultilineString(_))
+                    ) =>
+            {
+                entry.trailing = Some(comment);
+            }
+            _ => match entries.get_mut(after) {
+                Some(entry) => entry.leading.push((at, comment)),
+                None => document.comments.push((at, comment)),
+            },
+        }
+    }
+    documents
+}
+
+/// Writes an entry with its comments and the properties of collections it opens. `keys` are the
+/// keys of `entry` as they are written (see [`key_text`])
+fn write_entry<'a>(
+    writer: &mut YAMLWriter<'a>,
+    on: &str,
+    entry: &Entry<'_>,
+    keys: &[YAMLKey<'a>],
+    previous: &[YAMLKey<'a>],
+    document: &Document<'_>,
+    options: &FormatOptions,
+) {
+    let shared = keys
+        .iter()
+        .zip(previous)
+        .take_while(|(key, previous)| key == previous)
+        .count()
+        .min(keys.len().saturating_sub(1));
+    let properties = |depth: usize| {
+        let keys = &entry.keys[..depth];
+        (document.properties.iter()).find_map(|(under, text)| (under == keys).then_some(*text))
+    };
+    // the depth up to which collections (and their properties) are written
+    let mut opened = shared;
+    let mut open = |writer: &mut YAMLWriter<'a>, depth: usize| {
+        while opened < depth {
+            opened += 1;
+            if let Some(text) = properties(opened) {
+                writer.write_collection_properties(&keys[..opened], text);
+            }
+        }
+    };
+
+    let start = entry
+        .positions
+        .get(shared)
+        .copied()
+        .unwrap_or(entry.value_span.start);
+    let start = entry
+        .leading
+        .first()
+        .map_or(start, |(at, _)| start.min(*at));
+    if blank_line_before(on, start) {
+        writer.write_blank_line();
+    }
+    for (at, comment) in &entry.leading {
+        // before the first key that follows the comment
+        let depth = (shared..keys.len())
+            .find(|depth| entry.positions[*depth] > *at)
+            .unwrap_or(keys.len().saturating_sub(1));
+        if blank_line_before(on, *at) {
+            writer.write_blank_line();
+        }
+        open(writer, depth);
+        writer.write_comment(keys.get(..=depth).unwrap_or(keys), comment);
+    }
+    open(writer, keys.len().saturating_sub(1));
+
+    let properties = properties(keys.len());
+    match &entry.node {
+        Emitted::EmptyCollection { is_mapping } => {
+            let empty = if *is_mapping { "{}" } else { "[]" };
+            match properties {
+                Some(properties) => writer.write_raw(keys, &format!("{properties} {empty}")),
+                None => writer.write_raw(keys, empty),
+            }
+        }
+        Emitted::Value(value) => {
+            let decoded = match (value, options.quotes) {
+                (RootYAMLValue::QuotedString(quoted), QuoteStyle::Minimal) => Some(quoted.decode()),
+                _ => None,
+            };
+            let string;
+            let value = match &decoded {
+                Some(decoded) => {
+                    string = RootYAMLValue::String(decoded);
+                    &string
+                }
+                None => value,
+            };
+            match (properties, value) {
+                // the properties include the tag
+                (Some(properties), RootYAMLValue::Tagged(tagged)) => {
+                    writer.write_raw(keys, &format!("{properties} {}", tagged.value()));
+                }
+                // a plain value with a tag (such as `!!str 5`) stays plain
+                (Some(properties), RootYAMLValue::String(text))
+                    if decoded.is_none() && properties.contains('!') =>
+                {
+                    writer.write_raw(keys, &format!("{properties} {text}"));
+                }
+                (Some(properties), value) => writer.write_with_properties(keys, properties, value),
+                (None, value) => writer.write(keys, value),
+            }
+        }
+        Emitted::Properties(_) | Emitted::DocumentStart | Emitted::DocumentEnd => {}
+    }
+    if let Some(comment) = entry.trailing {
+        writer.append_comment(comment);
+    }
+}
+
+/// The text a key is written as. Quoted keys are decoded and written in double quotes, plain keys
+/// are written as they are
+fn key_text<'a>(on: &'a str, key: &YAMLKey<'a>) -> Cow<'a, str> {
+    let YAMLKey::Slice(key) = key else {
+        return Cow::Borrowed("");
+    };
+    let start = offset_in(on, key);
+    let quote = on[..start].chars().next_back();
+    match quote {
+        Some(quote @ ('"' | '\'')) if on[start + key.len()..].starts_with(quote) => {
+            let quoted = QuotedString {
+                on: key,
+                double: quote == '"',
+            };
+            let mut text = String::new();
+            write_double_quoted(&mut text, &quoted.decode());
+            Cow::Owned(text)
+        }
+        _ => Cow::Borrowed(key),
+    }
+}
+
+/// Whether the line before the one containing `at` is empty
+fn blank_line_before(on: &str, at: usize) -> bool {
+    let Some(line_start) = on[..at].rfind('\n') else {
+        return false;
+    };
+    let before = &on[..line_start];
+    let previous_line = &before[before.rfind('\n').map_or(0, |idx| idx + '\n'.len_utf8())..];
+    trim_whitespace(previous_line).is_empty()
+}
@@ -0,0 +1,58 @@
+//! YAML headers of Markdown (and other text) files
+use super::{
+    is_yaml_whitespace, offset_in, parse_with_exit_signal, ParseOptions, RootYAMLValue, YAMLKey,
+    YAMLParseError,
+};
+
+/// Splits `---`-delimited front matter from the start of `input`, returning the YAML and the body
+/// after the closing `---` (or `...`) line. Returns `None` if `input` does not start with a `---`
+/// line or the front matter is not closed
+#[must_use]
+pub fn extract_front_matter(input: &str) -> Option<(&str, &str)> {
+    let rest = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let rest = marker_line(rest, "---")?;
+    let yaml_start = input.len() - rest.len();
+
+    let mut line_start = yaml_start;
+    loop {
+        let line = &input[line_start..];
+        if let Some(body) = marker_line(line, "---").or_else(|| marker_line(line, "...")) {
+            return Some((&input[yaml_start..line_start], body));
+        }
+        line_start += line.find('\n')? + '\n'.len_utf8();
+    }
+}
+
+/// If `on` starts with a line containing only `marker`, the input after the line
+fn marker_line<'a>(on: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = on.strip_prefix(marker)?;
+    let (line, after) = rest.split_once('\n').unwrap_or((rest, ""));
+    line.chars().all(is_yaml_whitespace).then_some(after)
+}
+
+/// Parses the front matter of `input` (see [`extract_front_matter`]) without parsing the rest,
+/// returning the body. If there is no front matter the callback is not called and the body is all
+/// of `input`. Error positions are relative to `input`
+///
+/// # Errors
+/// Returns an error if the front matter is invalid YAML
+pub fn parse_front_matter<'a>(
+    input: &'a str,
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>),
+    options: &ParseOptions,
+) -> Result<&'a str, YAMLParseError> {
+    let Some((yaml, body)) = extract_front_matter(input) else {
+        return Ok(input);
+    };
+    let offset = offset_in(input, yaml);
+    parse_with_exit_signal(
+        yaml,
+        |keys, value| {
+            cb(keys, value);
+            false
+        },
+        options,
+    )
+    .map_err(|err| err.offset_by(offset).locate(input))?;
+    Ok(body)
+}
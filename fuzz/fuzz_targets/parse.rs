@@ -0,0 +1,33 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, TabPolicy};
+
+// The first byte picks the options, the rest is the input
+fuzz_target!(|data: &[u8]| {
+    let Some((&flags, rest)) = data.split_first() else {
+        return;
+    };
+    let Ok(on) = std::str::from_utf8(rest) else {
+        return;
+    };
+    let mut options = ParseOptions::new()
+        .resolve_aliases(flags & 2 != 0)
+        .resolve_merge_keys(flags & 4 != 0)
+        .deny_duplicate_keys(flags & 8 != 0)
+        .implicit_typing(flags & 16 != 0)
+        .tabs(match flags >> 5 {
+            0 => TabPolicy::Error,
+            1 => TabPolicy::TreatAsSpaces(4),
+            _ => TabPolicy::Allow,
+        });
+    options.indent_size = (flags & 1 != 0).then_some(2);
+    let _ = parse_with_exit_signal(
+        on,
+        |_keys, value| {
+            let _ = value.as_str();
+            false
+        },
+        &options,
+    );
+});
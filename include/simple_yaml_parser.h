@@ -0,0 +1,54 @@
+/* C interface to simple-yaml-parser. Build the library with
+ * `cargo rustc --lib --release --features ffi --crate-type cdylib` (or `staticlib`). This header is
+ * written by hand, `tests/ffi.rs` checks it against `ffi.rs` */
+#ifndef SIMPLE_YAML_PARSER_H
+#define SIMPLE_YAML_PARSER_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum yaml_value_kind {
+    YAML_VALUE_STRING = 0,
+    YAML_VALUE_NUMBER = 1,
+    YAML_VALUE_TIMESTAMP = 2,
+    YAML_VALUE_TRUE = 3,
+    YAML_VALUE_FALSE = 4,
+    YAML_VALUE_NULL = 5,
+    YAML_VALUE_ALIAS = 6,
+    YAML_VALUE_TAGGED = 7,
+    YAML_VALUE_RAW_BLOCK = 8
+} yaml_value_kind;
+
+/* The position of a parse error. `line` and `column` are 1-based */
+typedef struct yaml_error {
+    size_t at;
+    size_t line;
+    size_t column;
+} yaml_error;
+
+/* Receives the path of a value (`person.address.city`, `places[0]`), the text of the value, its kind
+ * and its tag (`!color`, empty unless the kind is YAML_VALUE_TAGGED). The strings are NUL terminated
+ * and only valid during the call. The lengths do not include the NUL. Return non-zero to stop
+ * parsing */
+typedef int (*yaml_callback)(const char *path, size_t path_len, const char *value,
+                             size_t value_len, yaml_value_kind kind, const char *tag,
+                             size_t tag_len, void *user_data);
+
+#define YAML_OK 0
+#define YAML_INVALID_ARGUMENT 1
+#define YAML_INVALID_UTF8 2
+#define YAML_PARSE_ERROR 3
+
+/* Parses `input_len` bytes of UTF-8 at `input`, calling `callback` for each value. On a parse error
+ * the position is written to `error` (if it is not NULL). Returns one of the YAML_* codes */
+int yaml_parse(const char *input, size_t input_len, yaml_callback callback, void *user_data,
+               yaml_error *error);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,279 @@
+//! Splitting a document across files with `!include path`
+use super::{
+    line_and_column, parse_with_spans, ParseOptions, RootYAMLValue, YAMLKey, YAMLParseError,
+};
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Nesting of includes deeper than this is an error
+pub const MAX_INCLUDE_DEPTH: usize = 32;
+
+/// Reads included files for [`parse_with_includes`]. This decides which files can be read, so
+/// for untrusted documents it should only allow paths under some directory (as
+/// [`DirectoryLoader`] does)
+pub trait FileLoader {
+    /// Returns the contents of `path` or `None` if it cannot (or should not) be read. `path` is
+    /// the path after `!include` joined to the directory of the file that includes it, with `/`
+    /// separators. `.` and `..` components are left in
+    fn load(&mut self, path: &str) -> Option<String>;
+}
+
+impl<F: FnMut(&str) -> Option<String>> FileLoader for F {
+    fn load(&mut self, path: &str) -> Option<String> {
+        self(path)
+    }
+}
+
+/// Loads files from under a directory. Paths that are absolute or leave the directory (through
+/// `..`) are not loaded
+#[cfg(feature = "std")]
+pub struct DirectoryLoader {
+    root: std::path::PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl DirectoryLoader {
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl FileLoader for DirectoryLoader {
+    fn load(&mut self, path: &str) -> Option<String> {
+        let mut relative = std::path::PathBuf::new();
+        for component in path.split('/') {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    if !relative.pop() {
+                        return None;
+                    }
+                }
+                component => relative.push(component),
+            }
+        }
+        if path.starts_with('/') || relative.is_absolute() || relative.has_root() {
+            return None;
+        }
+        std::fs::read_to_string(self.root.join(relative)).ok()
+    }
+}
+
+/// Error from [`parse_with_includes`]
+#[derive(Debug)]
+pub enum IncludeError {
+    /// An error in a file, with its path
+    Parse { file: String, error: YAMLParseError },
+    /// The loader did not return the file at `path`, included from `file`
+    NotFound { file: String, path: String },
+    /// A file that includes itself (directly or through other files)
+    Cycle(String),
+    /// Includes nested deeper than [`MAX_INCLUDE_DEPTH`], at this path
+    TooDeep(String),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IncludeError {}
+
+impl core::fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            IncludeError::Parse { file, error } => write!(f, "{file}: {error}"),
+            IncludeError::NotFound { file, path } => {
+                write!(f, "{file}: could not load included {path}")
+            }
+            IncludeError::Cycle(path) => write!(f, "{path} includes itself"),
+            IncludeError::TooDeep(path) => write!(f, "includes nested too deeply at {path}"),
+        }
+    }
+}
+
+/// Parses `on` (the contents of `file`), replacing `!include path` values with the contents of the
+/// included file as loaded by `loader`. The callback receives values from every file as one stream,
+/// with the keys of included values following the keys of the `!include`:
+///
+/// ```yaml
+/// # config.yaml
+/// database: !include db/settings.yaml
+/// # db/settings.yaml (its values are passed to the callback under `database`)
+/// host: localhost
+/// ```
+///
+/// Paths are relative to the file with the `!include`. Anchors are not shared between files.
+/// Included files should be mappings or sequences (a scalar document has no values). Return `true`
+/// from `cb` to stop parsing
+///
+/// # Errors
+/// Returns an error for invalid YAML in any file, an included file that cannot be loaded and
+/// includes that are cyclic or nested deeper than [`MAX_INCLUDE_DEPTH`]
+pub fn parse_with_includes(
+    on: &str,
+    file: &str,
+    loader: &mut impl FileLoader,
+    mut cb: impl for<'b> FnMut(&[YAMLKey<'b>], RootYAMLValue<'b>) -> bool,
+    options: &ParseOptions,
+) -> Result<(), IncludeError> {
+    parse_with_provenance(on, file, loader, |keys, value, _| cb(keys, value), options)
+}
+
+/// Where a value from [`parse_with_provenance`] was written
+#[derive(Debug, Clone)]
+pub struct Provenance<'s> {
+    /// The index of the file among the files parsed, in the order they are first included (the
+    /// file passed to [`parse_with_provenance`] is 0). A file included twice has the same index
+    pub file: usize,
+    /// The path of the file, as passed to the [`FileLoader`]
+    pub path: &'s str,
+    /// The span of the value in the file. Includes quotes for quoted values
+    pub span: Range<usize>,
+    source: &'s str,
+}
+
+impl Provenance<'_> {
+    /// The line and column (both starting at 1, with the column in characters) of the value
+    #[must_use]
+    pub fn line_column(&self) -> (usize, usize) {
+        line_and_column(self.source, self.span.start)
+    }
+}
+
+/// Formats as `path:line:column`, for error messages
+impl core::fmt::Display for Provenance<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        let (line, column) = self.line_column();
+        write!(f, "{}:{line}:{column}", self.path)
+    }
+}
+
+/// [`parse_with_includes`] where the callback also receives the file and span each value comes
+/// from, so that problems with values can be reported in the file they are in
+///
+/// # Errors
+/// As [`parse_with_includes`]
+pub fn parse_with_provenance<L: FileLoader, C>(
+    on: &str,
+    file: &str,
+    loader: &mut L,
+    cb: C,
+    options: &ParseOptions,
+) -> Result<(), IncludeError>
+where
+    C: for<'b> FnMut(&[YAMLKey<'b>], RootYAMLValue<'b>, &Provenance<'_>) -> bool,
+{
+    let mut includes = Includes {
+        stack: Vec::from([0]),
+        paths: Vec::from([file.to_owned()]),
+        loader,
+        cb,
+        options,
+    };
+    includes.parse_file(on, &[]).map(|_| ())
+}
+
+struct Includes<'l, 'o, L, C> {
+    /// The file being parsed and those that include it, as indexes into `paths`
+    stack: Vec<usize>,
+    /// The paths of the files parsed so far
+    paths: Vec<String>,
+    loader: &'l mut L,
+    cb: C,
+    options: &'o ParseOptions,
+}
+
+impl<L: FileLoader, C> Includes<'_, '_, L, C>
+where
+    C: for<'b> FnMut(&[YAMLKey<'b>], RootYAMLValue<'b>, &Provenance<'_>) -> bool,
+{
+    fn current(&self) -> usize {
+        self.stack.last().copied().unwrap_or_default()
+    }
+
+    /// Parses the current file, with `prefix` before its keys. Returns whether the callback
+    /// stopped parsing
+    fn parse_file(&mut self, on: &str, prefix: &[YAMLKey<'_>]) -> Result<bool, IncludeError> {
+        let mut error = None;
+        let mut exited = false;
+        let options = self.options;
+        let result = parse_with_spans(
+            on,
+            |keys, value, spans| {
+                let mut chain: Vec<YAMLKey<'_>> = prefix.to_vec();
+                chain.extend_from_slice(keys);
+                let include = match &value {
+                    RootYAMLValue::Tagged(tagged) if tagged.tag() == "!include" => tagged.value(),
+                    _ => {
+                        let file = self.current();
+                        let provenance = Provenance {
+                            file,
+                            path: &self.paths[file],
+                            span: spans.value,
+                            source: on,
+                        };
+                        exited = (self.cb)(&chain, value, &provenance);
+                        return exited;
+                    }
+                };
+                match self.include_file(include, &chain) {
+                    Ok(stopped) => exited = stopped,
+                    Err(err) => error = Some(err),
+                }
+                exited || error.is_some()
+            },
+            options,
+        );
+        if let Some(error) = error {
+            return Err(error);
+        }
+        result.map_err(|error| IncludeError::Parse {
+            file: self.paths[self.current()].clone(),
+            error,
+        })?;
+        Ok(exited)
+    }
+
+    fn include_file(&mut self, include: &str, chain: &[YAMLKey<'_>]) -> Result<bool, IncludeError> {
+        let file = &self.paths[self.current()];
+        let include = include
+            .strip_prefix(['"', '\''])
+            .and_then(|include| include.strip_suffix(['"', '\'']))
+            .unwrap_or(include);
+        let path = join(file, include);
+        if self.stack.iter().any(|idx| self.paths[*idx] == path) {
+            return Err(IncludeError::Cycle(path));
+        }
+        if self.stack.len() > MAX_INCLUDE_DEPTH {
+            return Err(IncludeError::TooDeep(path));
+        }
+        let Some(source) = self.loader.load(&path) else {
+            return Err(IncludeError::NotFound {
+                file: file.clone(),
+                path,
+            });
+        };
+        let idx = if let Some(idx) = self.paths.iter().position(|existing| *existing == path) {
+            idx
+        } else {
+            self.paths.push(path);
+            self.paths.len() - 1
+        };
+        self.stack.push(idx);
+        let result = self.parse_file(&source, chain);
+        self.stack.pop();
+        result
+    }
+}
+
+/// `path` relative to the directory of `file`
+fn join(file: &str, path: &str) -> String {
+    match file.rfind('/') {
+        Some(idx) if !path.starts_with('/') => {
+            let mut joined = file[..=idx].to_owned();
+            joined.push_str(path);
+            joined
+        }
+        _ => path.to_owned(),
+    }
+}
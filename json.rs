@@ -0,0 +1,143 @@
+//! Converting YAML to JSON
+use super::{decode_key, parse_events, ParseOptions, RootYAMLValue, YAMLEvent, YAMLParseError};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use core::fmt::Write;
+
+/// Converts YAML to (minified) JSON. Writes events as they are parsed, without building a tree.
+/// Aliases are resolved and numbers are converted to JSON syntax (`0x1F` becomes `31`). Empty
+/// collections are written as `{}` and `[]`.
+///
+/// A stream of several documents gives a JSON value for each document, on separate lines (as in
+/// JSON Lines). Documents without content (such as `---` followed by another `---`) are `null` and
+/// input without any documents is `{}`
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn yaml_to_json(on: &str) -> Result<String, YAMLParseError> {
+    let mut output = String::new();
+    // whether the next entry follows another in the same collection
+    let mut needs_comma = false;
+    // where the current document starts in `output`
+    let mut document_start = None;
+    let options = ParseOptions::new().resolve_aliases(true);
+
+    parse_events(
+        on,
+        |event| {
+            let is_end = matches!(event, YAMLEvent::MapEnd | YAMLEvent::SeqEnd);
+            let is_scalar = matches!(event, YAMLEvent::Scalar(_));
+            let is_document = matches!(event, YAMLEvent::DocumentStart | YAMLEvent::DocumentEnd);
+            if needs_comma && !is_end && !is_document {
+                output.push(',');
+            }
+            match event {
+                YAMLEvent::DocumentStart => {
+                    if document_start.is_some() {
+                        output.push('\n');
+                    }
+                    document_start = Some(output.len());
+                }
+                YAMLEvent::DocumentEnd => {
+                    if document_start == Some(output.len()) {
+                        output.push_str("null");
+                    }
+                }
+                YAMLEvent::MapStart => output.push('{'),
+                YAMLEvent::MapEnd => output.push('}'),
+                YAMLEvent::SeqStart => output.push('['),
+                YAMLEvent::SeqEnd => output.push(']'),
+                YAMLEvent::Key(key) => {
+                    write_string(&mut output, &decode_key(on, key));
+                    output.push(':');
+                }
+                YAMLEvent::Scalar(value) => write_value(&mut output, &value),
+            }
+            needs_comma = is_scalar || is_end;
+            false
+        },
+        &options,
+    )?;
+
+    if document_start.is_none() {
+        output.push_str("{}");
+    }
+    Ok(output)
+}
+
+fn write_value(output: &mut String, value: &RootYAMLValue<'_>) {
+    match value {
+        RootYAMLValue::String(on) | RootYAMLValue::Timestamp(on) | RootYAMLValue::RawBlock(on) => {
+            write_string(output, on);
+        }
+        RootYAMLValue::QuotedString(quoted) => write_string(output, &quoted.decode()),
+        RootYAMLValue::MultilineString(multiline) => write_string(output, &multiline.resolve()),
+        RootYAMLValue::Tagged(tagged) => write_string(output, tagged.value()),
+        RootYAMLValue::Interpolated(on) => write_string(output, on),
+        RootYAMLValue::Number(on) => output.push_str(&json_number(on)),
+        RootYAMLValue::True => output.push_str("true"),
+        RootYAMLValue::False => output.push_str("false"),
+        RootYAMLValue::Null | RootYAMLValue::Alias(_) => output.push_str("null"),
+    }
+}
+
+pub(crate) fn write_string(output: &mut String, on: &str) {
+    output.push('"');
+    for chr in on.chars() {
+        match chr {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            chr if chr.is_control() => {
+                let _ = write!(output, "\\u{:04x}", u32::from(chr));
+            }
+            chr => output.push(chr),
+        }
+    }
+    output.push('"');
+}
+
+/// Converts a YAML number (which has already passed `is_number`) into JSON syntax
+pub(crate) fn json_number(on: &str) -> Cow<'_, str> {
+    let radix = if let Some(hex) = on.strip_prefix("0x") {
+        Some((hex, 16))
+    } else {
+        on.strip_prefix("0o").map(|octal| (octal, 8))
+    };
+    if let Some((digits, radix)) = radix {
+        return match u128::from_str_radix(digits, radix) {
+            Ok(value) => Cow::Owned(value.to_string()),
+            Err(_) => Cow::Borrowed("null"),
+        };
+    }
+    if is_json_number(on) {
+        return Cow::Borrowed(on);
+    }
+    match on.parse::<f64>() {
+        Ok(value) if value.is_finite() => Cow::Owned(value.to_string()),
+        _ => Cow::Borrowed("null"),
+    }
+}
+
+/// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`
+pub(crate) fn is_json_number(on: &str) -> bool {
+    fn digits(on: &str) -> bool {
+        !on.is_empty() && on.chars().all(|chr| chr.is_ascii_digit())
+    }
+
+    let unsigned = on.strip_prefix('-').unwrap_or(on);
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(idx) => (&unsigned[..idx], Some(&unsigned[idx + 1..])),
+        None => (unsigned, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let valid_integer = digits(integer) && (integer == "0" || !integer.starts_with('0'));
+    let valid_exponent = exponent
+        .is_none_or(|exponent| digits(exponent.strip_prefix(['-', '+']).unwrap_or(exponent)));
+    valid_integer && fraction.is_none_or(digits) && valid_exponent
+}
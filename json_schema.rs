@@ -0,0 +1,600 @@
+//! Validating documents against a JSON Schema
+use super::{
+    format_key_chain, parse_to_tree, parse_with_spans, ParseOptions, RootYAMLValue, YAMLKey,
+    YAMLNode, YAMLParseError,
+};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Limit on `$ref`, `allOf`, `anyOf`, `oneOf` and `not` nesting (at one position in the document),
+/// for schemas that refer to themselves
+const MAX_SCHEMA_DEPTH: usize = 16;
+
+/// A JSON Schema (written as JSON or YAML) to check documents against with [`JSONSchema::validate`].
+///
+/// Supports `type`, `enum`, `const`, `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`,
+/// `multipleOf`, `minLength`, `maxLength`, `properties`, `additionalProperties`, `required`,
+/// `minProperties`, `maxProperties`, `items`, `prefixItems`, `minItems`, `maxItems`, `allOf`,
+/// `anyOf`, `oneOf`, `not` and local `$ref`s (`#/$defs/name`). Annotations (such as `title` and
+/// `description`) are allowed, but [`JSONSchema::parse`] errors on other keywords (such as `pattern`
+/// and `format`) and on `$ref`s that it cannot resolve, rather than not checking them
+#[derive(Debug, Clone)]
+pub struct JSONSchema<'s> {
+    root: YAMLNode<'s>,
+}
+
+/// Keywords that are checked or that do not affect validation. Subschemas are checked separately
+const KEYWORDS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minLength",
+    "maxLength",
+    "required",
+    "minProperties",
+    "maxProperties",
+    "minItems",
+    "maxItems",
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+/// Error from [`JSONSchema::parse`]
+#[derive(Debug)]
+pub enum SchemaError {
+    /// The schema is not valid JSON (or YAML)
+    Parse(YAMLParseError),
+    /// A keyword that is not supported, so would not be checked. `path` is its path in the schema
+    UnsupportedKeyword { keyword: String, path: String },
+    /// A `$ref` that is not to a part of the schema
+    UnresolvedRef { reference: String, path: String },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SchemaError {}
+
+impl core::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            SchemaError::Parse(err) => core::fmt::Display::fmt(err, f),
+            SchemaError::UnsupportedKeyword { keyword, path } => {
+                write!(f, "{path}: unsupported keyword {keyword}")
+            }
+            SchemaError::UnresolvedRef { reference, path } => {
+                write!(f, "{path}: unresolved $ref {reference}")
+            }
+        }
+    }
+}
+
+impl From<YAMLParseError> for SchemaError {
+    fn from(err: YAMLParseError) -> Self {
+        SchemaError::Parse(err)
+    }
+}
+
+/// A value that does not follow the schema, from [`JSONSchema::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation<'a> {
+    pub keys: Vec<YAMLKey<'a>>,
+    /// The span of the value (or the first entry of a mapping or sequence)
+    pub span: Range<usize>,
+    /// The keyword that failed (for example `required`)
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+impl core::fmt::Display for SchemaViolation<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        if self.keys.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            f.write_fmt(format_args!(
+                "{}: {}",
+                format_key_chain(&self.keys),
+                self.message
+            ))
+        }
+    }
+}
+
+/// The entries of a mapping or sequence in the document
+struct Collection<'a> {
+    children: Vec<YAMLKey<'a>>,
+    span: Range<usize>,
+}
+
+/// A failed keyword and the number of keys (from the start of the chain) it applies to
+type Failure = (usize, &'static str, String);
+
+/// A keyword, whether a value is within it and how to describe a value that is not
+type Bound = (&'static str, fn(f64, f64) -> bool, &'static str);
+
+impl<'s> JSONSchema<'s> {
+    /// # Errors
+    /// Returns an error if `schema` is invalid JSON (or YAML), uses a keyword that is not supported
+    /// or has a `$ref` that does not resolve
+    pub fn parse(schema: &'s str) -> Result<Self, SchemaError> {
+        let schema = Self {
+            root: parse_to_tree(schema)?,
+        };
+        schema.check_schema(&schema.root, &mut Vec::new())?;
+        Ok(schema)
+    }
+
+    /// Checks the keywords of `schema` (at `path`) and its subschemas are supported
+    fn check_schema(
+        &self,
+        schema: &YAMLNode<'s>,
+        path: &mut Vec<YAMLKey<'s>>,
+    ) -> Result<(), SchemaError> {
+        // `true` and `false` have no keywords
+        let YAMLNode::Map(entries) = schema else {
+            return Ok(());
+        };
+        for (keyword, value) in entries {
+            path.push(YAMLKey::Slice(keyword));
+            match (*keyword, value) {
+                ("$ref", value) => {
+                    let reference = text(value).unwrap_or_default();
+                    if self.resolve_ref(&reference).is_none() {
+                        return Err(SchemaError::UnresolvedRef {
+                            reference,
+                            path: format_key_chain(path),
+                        });
+                    }
+                }
+                ("properties" | "$defs" | "definitions", YAMLNode::Map(schemas)) => {
+                    for (name, subschema) in schemas {
+                        path.push(YAMLKey::Slice(name));
+                        self.check_schema(subschema, path)?;
+                        path.pop();
+                    }
+                }
+                ("prefixItems" | "allOf" | "anyOf" | "oneOf", YAMLNode::Sequence(schemas)) => {
+                    for (index, subschema) in schemas.iter().enumerate() {
+                        path.push(YAMLKey::Index(index));
+                        self.check_schema(subschema, path)?;
+                        path.pop();
+                    }
+                }
+                ("additionalProperties" | "items" | "not", subschema) => {
+                    self.check_schema(subschema, path)?;
+                }
+                (keyword, _) if KEYWORDS.contains(&keyword) => {}
+                // values with the wrong shape are ignored, as when validating
+                (
+                    "properties" | "$defs" | "definitions" | "prefixItems" | "allOf" | "anyOf"
+                    | "oneOf",
+                    _,
+                ) => {}
+                (keyword, _) => {
+                    return Err(SchemaError::UnsupportedKeyword {
+                        keyword: keyword.to_string(),
+                        path: format_key_chain(path),
+                    });
+                }
+            }
+            path.pop();
+        }
+        Ok(())
+    }
+
+    /// Checks `on` against the schema. Values are checked as they are parsed, followed by the keywords
+    /// for mappings and sequences (such as `required`) once the whole document is parsed
+    ///
+    /// # Errors
+    /// Returns an error if it tries to parse invalid YAML input
+    pub fn validate<'a>(
+        &self,
+        on: &'a str,
+        options: &ParseOptions,
+    ) -> Result<Vec<SchemaViolation<'a>>, YAMLParseError> {
+        let mut violations: Vec<SchemaViolation<'a>> = Vec::new();
+        let mut collections: BTreeMap<Vec<YAMLKey<'a>>, Collection<'a>> = BTreeMap::new();
+        parse_with_spans(
+            on,
+            |keys, value, spans| {
+                for (depth, key) in keys.iter().enumerate() {
+                    let collection =
+                        collections
+                            .entry(keys[..depth].to_vec())
+                            .or_insert_with(|| Collection {
+                                children: Vec::new(),
+                                span: spans.value.clone(),
+                            });
+                    if !collection.children.contains(key) {
+                        collection.children.push(*key);
+                    }
+                }
+                let mut failures = Vec::new();
+                self.check_value(&self.root, keys, 0, &value, &mut failures, 0);
+                for (depth, keyword, message) in failures {
+                    // the type of a mapping or sequence is checked for each entry
+                    let reported = depth < keys.len()
+                        && violations.iter().any(|violation| {
+                            violation.keys == keys[..depth] && violation.message == message
+                        });
+                    if !reported {
+                        violations.push(SchemaViolation {
+                            keys: keys[..depth].to_vec(),
+                            span: spans.value.clone(),
+                            keyword,
+                            message,
+                        });
+                    }
+                }
+                false
+            },
+            options,
+        )?;
+
+        for (keys, collection) in &collections {
+            for schema in self.schemas_at(keys) {
+                let mut failures = Vec::new();
+                check_collection(schema, collection, &mut failures);
+                violations.extend(
+                    failures
+                        .into_iter()
+                        .map(|(keyword, message)| SchemaViolation {
+                            keys: keys.clone(),
+                            span: collection.span.clone(),
+                            keyword,
+                            message,
+                        }),
+                );
+            }
+        }
+        Ok(violations)
+    }
+
+    /// `schema` with the schemas it refers to with `$ref` and `allOf`
+    fn expand<'n>(
+        &'n self,
+        schema: &'n YAMLNode<'s>,
+        depth: usize,
+        into: &mut Vec<&'n YAMLNode<'s>>,
+    ) {
+        if depth > MAX_SCHEMA_DEPTH {
+            return;
+        }
+        into.push(schema);
+        if let Some(reference) = schema.get("$ref").and_then(text) {
+            if let Some(target) = self.resolve_ref(&reference) {
+                self.expand(target, depth + 1, into);
+            }
+        }
+        if let Some(YAMLNode::Sequence(branches)) = schema.get("allOf") {
+            for branch in branches {
+                self.expand(branch, depth + 1, into);
+            }
+        }
+    }
+
+    /// Follows a local JSON pointer (`#/$defs/name`)
+    fn resolve_ref(&self, reference: &str) -> Option<&YAMLNode<'s>> {
+        let pointer = reference.strip_prefix('#')?;
+        pointer
+            .split('/')
+            .skip(1)
+            .try_fold(&self.root, |node, segment| {
+                let segment = segment.replace("~1", "/").replace("~0", "~");
+                match node {
+                    YAMLNode::Sequence(_) => node.get_index(segment.parse().ok()?),
+                    _ => node.get(&segment),
+                }
+            })
+    }
+
+    /// The subschema for the entry at `key`
+    fn child<'n>(schema: &'n YAMLNode<'s>, key: &YAMLKey<'_>) -> Option<&'n YAMLNode<'s>> {
+        match key {
+            YAMLKey::Slice(key) => schema
+                .get("properties")
+                .and_then(|properties| properties.get(key))
+                .or_else(|| {
+                    schema
+                        .get("additionalProperties")
+                        .filter(|node| is_schema_object(node))
+                }),
+            YAMLKey::Index(index) => schema
+                .get("prefixItems")
+                .and_then(|items| items.get_index(*index))
+                .or_else(|| schema.get("items").filter(|node| is_schema_object(node))),
+        }
+    }
+
+    /// The schemas that apply at `keys` (not including branches of `anyOf` and `oneOf`)
+    fn schemas_at(&self, keys: &[YAMLKey<'_>]) -> Vec<&YAMLNode<'s>> {
+        let mut schemas = Vec::new();
+        self.expand(&self.root, 0, &mut schemas);
+        for key in keys {
+            let mut next = Vec::new();
+            for schema in schemas {
+                if let Some(child) = Self::child(schema, key) {
+                    self.expand(child, 0, &mut next);
+                }
+            }
+            schemas = next;
+        }
+        schemas
+    }
+
+    /// Checks the scalar at `keys[depth..]` relative to `schema`
+    fn check_value(
+        &self,
+        schema: &YAMLNode<'s>,
+        keys: &[YAMLKey<'_>],
+        depth: usize,
+        value: &RootYAMLValue<'_>,
+        failures: &mut Vec<Failure>,
+        ref_depth: usize,
+    ) {
+        let mut schemas = Vec::new();
+        self.expand(schema, ref_depth, &mut schemas);
+        for schema in schemas {
+            if let YAMLNode::Scalar(RootYAMLValue::False) = schema {
+                failures.push((depth, "false", "no value is allowed".into()));
+                continue;
+            }
+            let passes = |branch: &YAMLNode<'s>| {
+                let mut branch_failures = Vec::new();
+                self.check_value(
+                    branch,
+                    keys,
+                    depth,
+                    value,
+                    &mut branch_failures,
+                    ref_depth + 1,
+                );
+                branch_failures.is_empty()
+            };
+            if let Some(YAMLNode::Sequence(branches)) = schema.get("anyOf") {
+                if !branches.iter().any(passes) {
+                    failures.push((depth, "anyOf", "does not match any schema in anyOf".into()));
+                }
+            }
+            if let Some(YAMLNode::Sequence(branches)) = schema.get("oneOf") {
+                let matching = branches.iter().filter(|branch| passes(branch)).count();
+                if matching != 1 {
+                    failures.push((
+                        depth,
+                        "oneOf",
+                        format!("matches {matching} schemas in oneOf, expected 1"),
+                    ));
+                }
+            }
+            if let Some(not) = schema.get("not") {
+                if passes(not) {
+                    failures.push((depth, "not", "matches the schema in not".into()));
+                }
+            }
+
+            if let Some(key) = keys.get(depth) {
+                let collection_type = match key {
+                    YAMLKey::Slice(_) => "object",
+                    YAMLKey::Index(_) => "array",
+                };
+                if let Some(types) = schema.get("type") {
+                    if !type_names(types).any(|name| name == collection_type) {
+                        failures.push((depth, "type", type_message(types, collection_type)));
+                        continue;
+                    }
+                }
+                if let Some(child) = Self::child(schema, key) {
+                    self.check_value(child, keys, depth + 1, value, failures, 0);
+                }
+            } else {
+                check_scalar(schema, value, depth, failures);
+            }
+        }
+    }
+}
+
+/// Whether `node` is a schema rather than `true` or `false`
+fn is_schema_object(node: &YAMLNode<'_>) -> bool {
+    matches!(node, YAMLNode::Map(_))
+}
+
+fn text(node: &YAMLNode<'_>) -> Option<String> {
+    node.as_scalar()
+        .and_then(RootYAMLValue::as_str)
+        .map(alloc::borrow::Cow::into_owned)
+}
+
+fn number(node: &YAMLNode<'_>) -> Option<f64> {
+    node.as_scalar().and_then(RootYAMLValue::as_f64)
+}
+
+/// The names in a `type` keyword (a string or list of strings)
+fn type_names<'n>(types: &'n YAMLNode<'_>) -> impl Iterator<Item = String> + 'n {
+    let names: Vec<String> = match types {
+        YAMLNode::Sequence(items) => items.iter().filter_map(text).collect(),
+        node => text(node).into_iter().collect(),
+    };
+    names.into_iter()
+}
+
+fn type_message(types: &YAMLNode<'_>, found: &str) -> String {
+    let expected: Vec<String> = type_names(types).collect();
+    format!("expected {}, found {found}", expected.join(" or "))
+}
+
+/// The JSON Schema type of a scalar
+fn scalar_type(value: &RootYAMLValue<'_>) -> &'static str {
+    match value {
+        RootYAMLValue::Number(_) => "number",
+        RootYAMLValue::True | RootYAMLValue::False => "boolean",
+        RootYAMLValue::Null | RootYAMLValue::Alias(_) => "null",
+        _ => "string",
+    }
+}
+
+fn is_type(name: &str, value: &RootYAMLValue<'_>) -> bool {
+    match name {
+        "integer" => {
+            value.as_i64().is_some() || value.as_f64().is_some_and(|value| value % 1.0 == 0.0)
+        }
+        name => name == scalar_type(value),
+    }
+}
+
+/// Compares a value in the schema (for `enum` and `const`) with a value in the document
+#[allow(clippy::float_cmp)]
+fn same_scalar(schema: &YAMLNode<'_>, value: &RootYAMLValue<'_>) -> bool {
+    let Some(expected) = schema.as_scalar() else {
+        return false;
+    };
+    match (expected.as_f64(), value.as_f64()) {
+        (Some(expected), Some(value)) => expected == value,
+        _ => match (expected.as_str(), value.as_str()) {
+            (Some(expected), Some(value)) => expected == value,
+            _ => expected == value,
+        },
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn check_scalar(
+    schema: &YAMLNode<'_>,
+    value: &RootYAMLValue<'_>,
+    depth: usize,
+    failures: &mut Vec<Failure>,
+) {
+    if let Some(types) = schema.get("type") {
+        if !type_names(types).any(|name| is_type(&name, value)) {
+            failures.push((depth, "type", type_message(types, scalar_type(value))));
+            return;
+        }
+    }
+    if let Some(YAMLNode::Sequence(options)) = schema.get("enum") {
+        if !options.iter().any(|option| same_scalar(option, value)) {
+            failures.push((depth, "enum", "is not one of the values in enum".into()));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if !same_scalar(constant, value) {
+            failures.push((depth, "const", "is not the value in const".into()));
+        }
+    }
+
+    if let Some(value) = value.as_f64() {
+        let bounds: [Bound; 4] = [
+            ("minimum", |value, bound| value >= bound, "less than"),
+            ("maximum", |value, bound| value <= bound, "greater than"),
+            ("exclusiveMinimum", |value, bound| value > bound, "at most"),
+            ("exclusiveMaximum", |value, bound| value < bound, "at least"),
+        ];
+        for (keyword, within, relation) in bounds {
+            if let Some(bound) = schema.get(keyword).and_then(number) {
+                if !within(value, bound) {
+                    failures.push((depth, keyword, format!("{value} is {relation} {bound}")));
+                }
+            }
+        }
+        if let Some(divisor) = schema.get("multipleOf").and_then(number) {
+            if divisor != 0.0 && (value / divisor) % 1.0 != 0.0 {
+                failures.push((
+                    depth,
+                    "multipleOf",
+                    format!("{value} is not a multiple of {divisor}"),
+                ));
+            }
+        }
+    }
+
+    if let Some(text) = value.as_str() {
+        let length = text.chars().count() as f64;
+        if let Some(min) = schema.get("minLength").and_then(number) {
+            if length < min {
+                failures.push((depth, "minLength", format!("shorter than {min} characters")));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(number) {
+            if length > max {
+                failures.push((depth, "maxLength", format!("longer than {max} characters")));
+            }
+        }
+    }
+}
+
+/// Checks the keywords for mappings and sequences
+#[allow(clippy::cast_precision_loss)]
+fn check_collection(
+    schema: &YAMLNode<'_>,
+    collection: &Collection<'_>,
+    failures: &mut Vec<(&'static str, String)>,
+) {
+    let count = collection.children.len() as f64;
+    let is_mapping = matches!(collection.children.first(), Some(YAMLKey::Slice(_)));
+    let keys = || {
+        collection.children.iter().filter_map(|key| match key {
+            YAMLKey::Slice(key) => Some(*key),
+            YAMLKey::Index(_) => None,
+        })
+    };
+    let (min_keyword, max_keyword, noun) = if is_mapping {
+        ("minProperties", "maxProperties", "properties")
+    } else {
+        ("minItems", "maxItems", "items")
+    };
+    if let Some(min) = schema.get(min_keyword).and_then(number) {
+        if count < min {
+            failures.push((min_keyword, format!("fewer than {min} {noun}")));
+        }
+    }
+    if let Some(max) = schema.get(max_keyword).and_then(number) {
+        if count > max {
+            failures.push((max_keyword, format!("more than {max} {noun}")));
+        }
+    }
+
+    if is_mapping {
+        if let Some(YAMLNode::Sequence(required)) = schema.get("required") {
+            for name in required.iter().filter_map(text) {
+                if !keys().any(|key| key == name) {
+                    failures.push(("required", format!("missing required property {name}")));
+                }
+            }
+        }
+        let closed = matches!(
+            schema.get("additionalProperties"),
+            Some(YAMLNode::Scalar(RootYAMLValue::False))
+        );
+        if closed {
+            let properties = schema.get("properties");
+            for key in keys() {
+                if properties
+                    .and_then(|properties| properties.get(key))
+                    .is_none()
+                {
+                    failures.push(("additionalProperties", format!("unexpected property {key}")));
+                }
+            }
+        }
+    } else if let Some(YAMLNode::Scalar(RootYAMLValue::False)) = schema.get("items") {
+        let allowed = match schema.get("prefixItems") {
+            Some(YAMLNode::Sequence(items)) => items.len(),
+            _ => 0,
+        };
+        if collection.children.len() > allowed {
+            failures.push(("items", format!("more than {allowed} items")));
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! The keys of the value being parsed, with the columns of the block entries
+use super::{stack::InlineStack, YAMLKey};
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+/// Keys from block entries (`key:` and `-` lines) have a column, keys from flow collections do not.
+/// Block entries always come before flow entries, so `columns[i]` is the column of `keys[i]` (other
+/// than while an anchor is replayed under a merge key, see [`KeyChain::split_off`])
+pub(crate) struct KeyChain<'a, const N: usize> {
+    keys: InlineStack<YAMLKey<'a>, N>,
+    /// Entries are closed by a line at or before their column
+    columns: InlineStack<usize, N>,
+}
+
+impl<'a, const N: usize> KeyChain<'a, N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            keys: InlineStack::new(YAMLKey::Index(0)),
+            columns: InlineStack::new(0),
+        }
+    }
+
+    /// A key chain that never allocates, for up to `N` keys. Deeper keys are dropped but counted
+    /// in [`KeyChain::depth`] (so are caught by [`crate::ParseOptions::max_depth`])
+    pub(crate) fn fixed() -> Self {
+        Self {
+            keys: InlineStack::fixed(YAMLKey::Index(0)),
+            columns: InlineStack::fixed(0),
+        }
+    }
+
+    /// The number of keys
+    pub(crate) fn depth(&self) -> usize {
+        self.keys.pushed()
+    }
+
+    /// The index of the last key if it is a sequence item
+    pub(crate) fn last_index(&self) -> Option<usize> {
+        match self.keys.last() {
+            Some(YAMLKey::Index(index)) => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// The column of the block entry at `depth`. `None` if it is in a flow collection
+    pub(crate) fn column(&self, depth: usize) -> Option<usize> {
+        self.columns.get(depth).copied()
+    }
+
+    /// Whether the last key is from a block entry (or there are no keys)
+    pub(crate) fn in_block(&self) -> bool {
+        self.columns.len() == self.keys.len()
+    }
+
+    /// Adds a key from a flow collection
+    pub(crate) fn push(&mut self, key: YAMLKey<'a>) {
+        self.keys.push(key);
+    }
+
+    /// Adds a key from a block entry at `column`
+    pub(crate) fn push_block(&mut self, key: YAMLKey<'a>, column: usize) {
+        debug_assert!(self.in_block());
+        self.keys.push(key);
+        self.columns.push(column);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<YAMLKey<'a>> {
+        if self.in_block() {
+            self.columns.pop();
+        }
+        self.keys.pop()
+    }
+
+    /// Removes the block entries with a column greater than `column` (or equal to it if `inclusive`)
+    pub(crate) fn close_blocks(&mut self, column: usize, inclusive: bool) {
+        while let Some(last) = self.columns.last() {
+            if *last > column || (inclusive && *last == column) {
+                self.pop();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// The column of the last block entry
+    pub(crate) fn last_column(&self) -> Option<usize> {
+        self.columns.last().copied()
+    }
+
+    /// Removes the keys from `depth` on. For keys added with [`KeyChain::push`] and [`KeyChain::extend`]
+    /// (or to restore them after [`KeyChain::split_off`]), so does not change the columns
+    pub(crate) fn truncate(&mut self, depth: usize) {
+        self.keys.truncate(depth);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.keys.clear();
+        self.columns.clear();
+    }
+
+    /// Adds keys for a value relative to the current chain (such as from an anchor). Remove them with
+    /// [`KeyChain::truncate`]
+    pub(crate) fn extend(&mut self, keys: impl IntoIterator<Item = YAMLKey<'a>>) {
+        self.keys.extend(keys);
+    }
+
+    /// Removes the keys from `depth` on, to be restored with [`KeyChain::extend`]. Does not change
+    /// the columns
+    pub(crate) fn split_off(&mut self, depth: usize) -> Vec<YAMLKey<'a>> {
+        self.keys.split_off(depth)
+    }
+
+    pub(crate) fn as_slice(&self) -> &[YAMLKey<'a>] {
+        self.keys.as_slice()
+    }
+}
+
+impl<'a, const N: usize> Deref for KeyChain<'a, N> {
+    type Target = [YAMLKey<'a>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.keys
+    }
+}
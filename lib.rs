@@ -1,252 +1,3372 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+use directives::parse_directive;
+use encoding::byte_order_mark_len;
+use key_chain::KeyChain;
+use scan::find_any;
+
+pub mod cst;
+pub mod csv;
+mod diff;
+mod directives;
+mod edit;
+mod encoding;
+mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod flatten;
+mod format;
+mod front_matter;
+#[cfg(feature = "include")]
+mod includes;
+pub mod json;
+#[cfg(feature = "json-schema")]
+mod json_schema;
+mod key_chain;
+mod merge;
+mod owned;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod parser;
+mod path;
+mod query;
+#[cfg(feature = "std")]
+mod reader;
+mod scan;
+mod sink;
+mod stack;
+pub mod toml;
+#[cfg(feature = "tree")]
+mod tree;
+mod validate;
+mod warning;
+mod writer;
+
+pub use diff::{diff, DiffError, YAMLDiff};
+pub use directives::{parse_documents_with_info, DocumentInfo};
+pub use edit::{replace_value, Document, EditError};
+pub use encoding::parse_bytes;
+pub use events::{parse_events, YAMLEvent};
+pub use flatten::{flatten, flatten_key, FlattenOptions, KeyCase};
+pub use format::{format_yaml, FormatOptions, QuoteStyle};
+pub use front_matter::{extract_front_matter, parse_front_matter};
+#[cfg(all(feature = "include", feature = "std"))]
+pub use includes::DirectoryLoader;
+#[cfg(feature = "include")]
+pub use includes::{
+    parse_with_includes, parse_with_provenance, FileLoader, IncludeError, Provenance,
+    MAX_INCLUDE_DEPTH,
+};
+#[cfg(feature = "json-schema")]
+pub use json_schema::{JSONSchema, SchemaError, SchemaViolation};
+pub use merge::{merge, MergeError};
+pub use owned::{parse_collect, to_owned_keys, OwnedKeyChain, OwnedYAMLKey, OwnedYAMLValue};
+#[cfg(feature = "parallel")]
+pub use parallel::parse_documents_parallel;
+pub use parser::Parser;
+pub use path::{format_key_chain, parse_key_path, path_at_offset, PathFilter};
+pub use query::{extract_section, indices_at, keys_at, query};
+#[cfg(feature = "async")]
+pub use reader::{parse_async_reader, AsyncChunkRead};
+#[cfg(feature = "std")]
+pub use reader::{parse_reader, ReadError};
+pub use sink::{parse_with_sink, YAMLSink};
+#[cfg(feature = "tree")]
+pub use tree::{parse_to_tree, parse_to_tree_with_policy, DuplicateKeyPolicy, YAMLNode};
+pub use validate::{validate, Constraint, Rule, ValueType, Violation, ViolationKind};
+pub use warning::{YAMLWarning, YAMLWarningKind};
+pub use writer::{SortKeys, YAMLWriter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum YAMLKey<'a> {
     Slice(&'a str),
     Index(usize),
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum RootYAMLValue<'a> {
-    String(&'a str),
-    MultilineString(MultilineString<'a>),
-    Number(&'a str),
-    True,
-    False,
-    // Null,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RootYAMLValue<'a> {
+    String(&'a str),
+    MultilineString(MultilineString<'a>),
+    /// A `"double"` or `'single'` quoted value
+    QuotedString(QuotedString<'a>),
+    /// An integer or float as written, including hexadecimal, octal and `.inf`, `-.inf` and `.nan`
+    Number(&'a str),
+    /// A date or date time as written, with [`ParseOptions::detect_timestamps`]
+    Timestamp(&'a str),
+    True,
+    False,
+    /// A `*alias` reference. Only emitted when [`ParseOptions::resolve_aliases`] is not set
+    Alias(&'a str),
+    /// A scalar with a tag that is not one of the core schema tags (for example `!Ref name`)
+    Tagged(TaggedScalar<'a>),
+    /// A string with `${VAR}` placeholders expanded by [`ParseOptions::interpolate`]
+    Interpolated(String),
+    /// A collection that was not parsed because of [`ParseOptions::raw_depth`], as written. For
+    /// block collections this is the lines nested under the key (or the text after the `- ` of a
+    /// sequence item up to the end of its lines) and for flow collections the text between (and
+    /// including) the brackets
+    RawBlock(&'a str),
+    /// `null`, `~` or an empty value. A key (or sequence item) with nothing after it is only null
+    /// when nothing is nested under it, otherwise the nested entries are emitted instead
+    Null,
+}
+
+#[derive(Debug)]
+pub enum YAMLParseErrorReason {
+    ExpectedColon,
+    ExpectedEndOfValue,
+    ExpectedBracket,
+    /// Missing `}` for a flow mapping
+    ExpectedBrace,
+    /// Empty key in a flow mapping
+    ExpectedFlowKey,
+    ExpectedTrueFalseNull,
+    ExpectedValue,
+    /// A `*alias` without a matching `&anchor` before it
+    UnknownAlias,
+    /// Resolving aliases went over [`ParseOptions::max_alias_expansions`] or
+    /// [`ParseOptions::max_total_expanded_size`]
+    AliasLimitExceeded,
+    /// With [`ParseOptions::strict`], a plain value that YAML 1.1 types differently (such as `yes`,
+    /// which is a boolean under YAML 1.1)
+    AmbiguousScalar,
+    /// With [`ParseOptions::strict`], a plain value containing a `:`. The span covers the `:`
+    UnquotedColon,
+    /// A `*alias` inside the value of the `&anchor` it refers to, with [`ParseOptions::resolve_aliases`]
+    /// (or [`ParseOptions::resolve_merge_keys`]). `anchor_at` is the byte offset of the anchor name
+    CyclicAlias {
+        anchor_at: usize,
+    },
+    /// A quoted value without a closing quote
+    ExpectedClosingQuote,
+    /// A tab in indentation, with [`TabPolicy::Error`]
+    TabIndentation,
+    /// A key that already appeared in the same mapping, with [`ParseOptions::deny_duplicate_keys`].
+    /// `first_at` is the byte offset of the first occurrence
+    DuplicateKey {
+        key: String,
+        first_at: usize,
+    },
+    /// Nesting deeper than [`ParseOptions::max_depth`], or more than 128 nested flow collections
+    MaxDepthExceeded,
+    /// Input longer than [`ParseOptions::max_input_len`]. The span covers the bytes past the limit
+    InputTooLong,
+    /// A `%YAML` directive for a version other than 1.x
+    UnsupportedVersion,
+    /// A `%YAML` or `%TAG` directive without its parameters
+    InvalidDirective,
+    /// A `? key` explicit key that is not a scalar on one line (such as a collection, a block scalar
+    /// or a key continued on the following lines)
+    UnsupportedComplexKey,
+    /// Input to [`parse_bytes`] that is not valid UTF-8 (or UTF-16 after a byte order mark)
+    InvalidEncoding,
+    /// A value starting with `@` or `` ` ``, which are reserved
+    ReservedIndicator,
+    /// A value starting with a character that cannot start a plain scalar: `,`, `[`, `]`, `{`, `}`
+    /// or `%`, or `-`, `?` or `:` followed by a space
+    UnexpectedIndicator,
+    /// A `|` or `>` followed by something other than chomping and indentation indicators (and a
+    /// comment)
+    InvalidBlockScalarHeader,
+    /// A `&` or `*` without a name after it
+    ExpectedAnchorName,
+    /// A block entry indented under an entry whose value is on its line (`b` in `a: x\n b: y`), or
+    /// with [`ParseOptions::indent_size`] a line indented by other than a multiple of it
+    InvalidIndentation,
+}
+
+#[derive(Debug)]
+pub struct YAMLParseError {
+    /// Byte offset of the error (same as `span.start`)
+    pub at: usize,
+    /// 1-based line of `at`
+    pub line: usize,
+    /// 1-based column (in characters) of `at`
+    pub column: usize,
+    pub span: Range<usize>,
+    pub reason: YAMLParseErrorReason,
+}
+
+impl YAMLParseError {
+    /// `line` and `column` are filled in by [`YAMLParseError::locate`]
+    fn new(reason: YAMLParseErrorReason, span: Range<usize>) -> Self {
+        Self {
+            at: span.start,
+            line: 0,
+            column: 0,
+            span,
+            reason,
+        }
+    }
+
+    /// Computes `line` and `column` from `at`
+    fn locate(mut self, on: &str) -> Self {
+        (self.line, self.column) = line_and_column(on, self.at);
+        self
+    }
+
+    /// Moves the positions by `offset`, for errors from parsing part of the input
+    fn offset_by(mut self, offset: usize) -> Self {
+        self.at += offset;
+        self.span = (self.span.start + offset)..(self.span.end + offset);
+        match &mut self.reason {
+            YAMLParseErrorReason::DuplicateKey { first_at: at, .. }
+            | YAMLParseErrorReason::CyclicAlias { anchor_at: at } => *at += offset,
+            _ => {}
+        }
+        self
+    }
+
+    /// Renders the error with the line of `on` it is on and the span underlined. `on` must be the
+    /// input that produced the error
+    #[must_use]
+    pub fn display_with_source(&self, on: &str) -> String {
+        let line_start = on[..self.at]
+            .rfind('\n')
+            .map_or(0, |idx| idx + '\n'.len_utf8());
+        let line_end = on[self.at..]
+            .find('\n')
+            .map_or(on.len(), |idx| self.at + idx);
+        let line = on[line_start..line_end].trim_end_matches('\r');
+        let offset = on[line_start..self.at].chars().count();
+        let underline = on[self.at..self.span.end.clamp(self.at, line_end)]
+            .chars()
+            .count()
+            .max(1);
+
+        let gutter = self.line.to_string();
+        let padding = " ".repeat(gutter.len());
+        format!(
+            "{self}\n{padding} |\n{gutter} | {line}\n{padding} | {}{}",
+            " ".repeat(offset),
+            "^".repeat(underline)
+        )
+    }
+}
+
+/// The 1-based line and column (in characters) of the byte offset `at` in `on`
+fn line_and_column(on: &str, at: usize) -> (usize, usize) {
+    let before = &on[..at];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + '\n'.len_utf8());
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for YAMLParseError {}
+
+impl core::fmt::Display for YAMLParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        f.write_fmt(format_args!(
+            "YAMLParseError: {:?} at {}:{}",
+            self.reason, self.line, self.column
+        ))
+    }
+}
+
+/// If you want to return early (not parse the whole input) use [`parse_with_exit_signal`]
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse<'a>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>),
+) -> Result<(), YAMLParseError> {
+    parse_with_exit_signal(
+        on,
+        |k, v| {
+            cb(k, v);
+            false
+        },
+        &ParseOptions::default(),
+    )?;
+    Ok(())
+}
+
+impl<'a> RootYAMLValue<'a> {
+    /// The text of a string value. Quoted strings are decoded and block scalars are resolved
+    #[must_use]
+    pub fn as_str(&self) -> Option<Cow<'a, str>> {
+        match self {
+            RootYAMLValue::String(on) => Some(Cow::Borrowed(on)),
+            RootYAMLValue::QuotedString(quoted) => Some(quoted.decode()),
+            RootYAMLValue::MultilineString(multiline) => Some(Cow::Owned(multiline.resolve())),
+            RootYAMLValue::Interpolated(on) => Some(Cow::Owned(on.clone())),
+            _ => None,
+        }
+    }
+
+    /// The value of an integer [`RootYAMLValue::Number`] (including hexadecimal and octal)
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        let RootYAMLValue::Number(on) = self else {
+            return None;
+        };
+        if let Some(hex) = on.strip_prefix("0x") {
+            i64::from_str_radix(hex, 16).ok()
+        } else if let Some(octal) = on.strip_prefix("0o") {
+            i64::from_str_radix(octal, 8).ok()
+        } else {
+            on.parse().ok()
+        }
+    }
+
+    /// The value of a [`RootYAMLValue::Number`] (including infinities and NaN)
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn as_f64(&self) -> Option<f64> {
+        let RootYAMLValue::Number(on) = self else {
+            return None;
+        };
+        if let Some(value) = special_float(on) {
+            Some(value)
+        } else if on.starts_with("0x") || on.starts_with("0o") {
+            self.as_i64().map(|value| value as f64)
+        } else {
+            on.parse().ok()
+        }
+    }
+
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            RootYAMLValue::True => Some(true),
+            RootYAMLValue::False => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Parses the text of the value (as written for numbers, timestamps and booleans, see [`RootYAMLValue::as_str`]
+    /// for strings) with [`core::str::FromStr`]. Returns `None` for null, aliases and tagged values
+    #[must_use]
+    pub fn parse_into<T: core::str::FromStr>(&self) -> Option<Result<T, T::Err>> {
+        let text = match self {
+            RootYAMLValue::Number(on) | RootYAMLValue::Timestamp(on) => Cow::Borrowed(*on),
+            RootYAMLValue::True => Cow::Borrowed("true"),
+            RootYAMLValue::False => Cow::Borrowed("false"),
+            value => value.as_str()?,
+        };
+        Some(text.parse())
+    }
+}
+
+/// For `|` and `>` based values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultilineString<'a> {
+    on: &'a str,
+    style: BlockStyle,
+    chomping: Chomping,
+    /// the indentation of the content, from an indentation indicator (`|2`). The first line may
+    /// then start with spaces
+    indentation: Option<usize>,
+    /// from [`ParseOptions::normalize_newlines`]
+    normalize_newlines: bool,
+}
+
+/// Whether a block scalar keeps its line breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStyle {
+    /// `|`: lines are kept as written
+    Literal,
+    /// `>`: line breaks between lines of text are replaced with spaces. Empty lines and more
+    /// indented lines keep their line breaks
+    Folded,
+}
+
+/// What happens to the line breaks at the end of a block scalar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Chomping {
+    /// `-`: remove all trailing line breaks
+    Strip,
+    /// The default: keep a single trailing line break
+    #[default]
+    Clip,
+    /// `+`: keep all trailing line breaks
+    Keep,
+}
+
+impl<'a> MultilineString<'a> {
+    fn new(
+        on: &'a str,
+        (style, chomping, indentation): (BlockStyle, Chomping, Option<usize>),
+        options: &ParseOptions,
+    ) -> Self {
+        Self {
+            on,
+            style,
+            chomping,
+            indentation,
+            normalize_newlines: options.normalize_newlines,
+        }
+    }
+
+    /// The text after the header as written, from the line break that ends the header
+    #[must_use]
+    pub fn raw(&self) -> &'a str {
+        self.on
+    }
+
+    #[must_use]
+    pub fn style(&self) -> BlockStyle {
+        self.style
+    }
+
+    #[must_use]
+    pub fn chomping(&self) -> Chomping {
+        self.chomping
+    }
+
+    /// The number of columns to remove from each line. Either from the indentation indicator
+    /// or the least indented (non-empty) line
+    pub(crate) fn content_indent(&self) -> usize {
+        self.indentation.unwrap_or_else(|| {
+            self.on
+                .lines()
+                .filter(|line| !trim_whitespace(line).is_empty())
+                .map(|line| line.len() - line.trim_start_matches(is_yaml_whitespace).len())
+                .min()
+                .unwrap_or(0)
+        })
+    }
+
+    /// The lines of the content with the indentation removed, without folding or chomping.
+    /// Leading empty lines are skipped
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> + Clone {
+        let indent = self.content_indent();
+        self.on
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .skip_while(|line| trim_whitespace(line).is_empty())
+            .map(move |line| {
+                let whitespace = line.len() - line.trim_start_matches(is_yaml_whitespace).len();
+                &line[whitespace.min(indent)..]
+            })
+    }
+
+    /// The value of the block scalar: indentation removed, lines folded for `>` and trailing line
+    /// breaks chomped. Line breaks are `\n` unless [`ParseOptions::normalize_newlines`] is disabled
+    #[must_use]
+    pub fn resolve(&self) -> String {
+        fn is_text(line: &str) -> bool {
+            !trim_whitespace(line).is_empty() && !line.starts_with(is_yaml_whitespace)
+        }
+
+        // `on` ends before the line break after the last line
+        let is_crlf = self.on.contains("\r\n") || self.on.ends_with('\r');
+        let newline = if !self.normalize_newlines && is_crlf {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let lines: Vec<&str> = self.lines().collect();
+        let content_end = lines
+            .iter()
+            .rposition(|line| !trim_whitespace(line).is_empty())
+            .map_or(0, |idx| idx + 1);
+        let (content, trailing) = lines.split_at(content_end);
+
+        let folded = self.style == BlockStyle::Folded;
+        let mut resolved = String::new();
+        for (idx, line) in content.iter().enumerate() {
+            if let Some(previous) = idx.checked_sub(1).map(|idx| content[idx]) {
+                if !folded {
+                    resolved.push_str(newline);
+                } else if is_text(previous) && is_text(line) {
+                    resolved.push(' ');
+                } else if !(is_text(previous) && trim_whitespace(line).is_empty()) {
+                    // the line break before empty lines is folded away
+                    resolved.push_str(newline);
+                }
+            }
+            if !trim_whitespace(line).is_empty() {
+                resolved.push_str(line);
+            }
+        }
+
+        if !content.is_empty() && self.chomping != Chomping::Strip {
+            resolved.push_str(newline);
+        }
+        if self.chomping == Chomping::Keep {
+            resolved.extend(trailing.iter().map(|_| newline));
+        }
+        resolved
+    }
+}
+
+/// Whether the line after the line break at `line_break` ends a block scalar whose parent is at
+/// column `indent` (it is not empty and not indented more than the parent, or the input ends)
+fn block_scalar_ends(on: &str, line_break: usize, indent: usize) -> bool {
+    let upcoming_line = on.get(line_break + '\n'.len_utf8()..).unwrap_or_default();
+    let mut upcoming_indent = 0;
+    for chr in upcoming_line.chars() {
+        match chr {
+            '\n' | '\r' => return false,
+            '\t' | ' ' => upcoming_indent += 1,
+            _ => break,
+        }
+    }
+    upcoming_indent <= indent
+}
+
+/// Parses a block scalar header: `|` or `>` followed by an optional indentation indicator and
+/// chomping indicator (in either order). Returns the style, the chomping and the indentation
+fn block_scalar_header(header: &str) -> Option<(BlockStyle, Chomping, Option<usize>)> {
+    let mut chars = header.chars();
+    let style = match chars.next()? {
+        '|' => BlockStyle::Literal,
+        '>' => BlockStyle::Folded,
+        _ => return None,
+    };
+    let mut chomping = None;
+    let mut indentation = None;
+    for chr in chars {
+        match chr {
+            '-' if chomping.is_none() => chomping = Some(Chomping::Strip),
+            '+' if chomping.is_none() => chomping = Some(Chomping::Keep),
+            '1'..='9' if indentation.is_none() => {
+                indentation = chr.to_digit(10).map(|digit| digit as usize);
+            }
+            _ => return None,
+        }
+    }
+    Some((style, chomping.unwrap_or_default(), indentation))
+}
+
+/// For `"..."` and `'...'` based values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotedString<'a> {
+    /// contents between the quotes
+    on: &'a str,
+    double: bool,
+}
+
+/// A scalar with a custom tag, for applications to construct themselves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaggedScalar<'a> {
+    tag: &'a str,
+    value: &'a str,
+}
+
+impl<'a> TaggedScalar<'a> {
+    /// The tag including the leading `!` (for example `!Ref` or `!!binary`)
+    #[must_use]
+    pub fn tag(&self) -> &'a str {
+        self.tag
+    }
+
+    /// The text after the tag, as written (quotes are not removed)
+    #[must_use]
+    pub fn value(&self) -> &'a str {
+        self.value
+    }
+}
+
+impl<'a> QuotedString<'a> {
+    /// The text between the quotes, with escapes left as is
+    #[must_use]
+    pub fn raw(&self) -> &'a str {
+        self.on
+    }
+
+    #[must_use]
+    pub fn is_double_quoted(&self) -> bool {
+        self.double
+    }
+
+    /// The value with escapes (`\n`, `\u00e9` etc for double quoted, `''` for single quoted) decoded.
+    /// Only allocates if there are escapes. Invalid escapes are left as is
+    #[must_use]
+    pub fn decode(&self) -> Cow<'a, str> {
+        if !self.double {
+            return if self.on.contains("''") {
+                Cow::Owned(self.on.replace("''", "'"))
+            } else {
+                Cow::Borrowed(self.on)
+            };
+        }
+        if !self.on.contains('\\') {
+            return Cow::Borrowed(self.on);
+        }
+
+        let mut decoded = String::with_capacity(self.on.len());
+        let mut chars = self.on.chars();
+        while let Some(chr) = chars.next() {
+            if chr != '\\' {
+                decoded.push(chr);
+                continue;
+            }
+            let Some(escaped) = chars.next() else {
+                decoded.push('\\');
+                break;
+            };
+            let chr = match escaped {
+                '0' => '\0',
+                'a' => '\x07',
+                'b' => '\x08',
+                't' | '\t' => '\t',
+                'n' => '\n',
+                'v' => '\x0B',
+                'f' => '\x0C',
+                'r' => '\r',
+                'e' => '\x1B',
+                'N' => '\u{85}',
+                '_' => '\u{A0}',
+                'L' => '\u{2028}',
+                'P' => '\u{2029}',
+                ' ' | '"' | '/' | '\\' => escaped,
+                'x' | 'u' | 'U' => {
+                    let length = match escaped {
+                        'x' => 2,
+                        'u' => 4,
+                        _ => 8,
+                    };
+                    let rest = chars.as_str();
+                    let code_point = rest
+                        .get(..length)
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                        .and_then(char::from_u32);
+                    if let Some(chr) = code_point {
+                        chars = rest[length..].chars();
+                        chr
+                    } else {
+                        decoded.push('\\');
+                        escaped
+                    }
+                }
+                _ => {
+                    decoded.push('\\');
+                    escaped
+                }
+            };
+            decoded.push(chr);
+        }
+        Cow::Owned(decoded)
+    }
+}
+
+/// Options for parsing. Construct with [`ParseOptions::new`] (or [`ParseOptions::default`]) and the
+/// builder methods, as fields may be added
+#[allow(clippy::struct_excessive_bools)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// Fixed number of spaces per nesting level. Lines must be indented by a multiple of it (or line
+    /// up with an entry after a `- `), otherwise [`YAMLParseErrorReason::InvalidIndentation`] is
+    /// returned. By default (and for 0) the indentation is detected from the columns of the parent
+    /// keys, so any consistent indentation works
+    pub indent_size: Option<usize>,
+    /// Replace `*alias` references with the values under the matching `&anchor`
+    /// rather than emitting [`RootYAMLValue::Alias`]
+    pub resolve_aliases: bool,
+    /// Place the values of `<<: *alias` (and `<<: [*a, *b]`) merge keys into the mapping containing them.
+    /// Merged values are emitted at the position of the `<<`, so keys after it override them. The
+    /// mappings of a list are emitted in order, so a key in more than one of them is emitted for each
+    /// (the YAML merge key specification gives the first precedence)
+    pub resolve_merge_keys: bool,
+    /// Only treat `null`, `~` and empty values as [`RootYAMLValue::Null`] (not `Null` or `NULL`)
+    pub strict_null: bool,
+    /// Pass `# comments` to the comment callback of [`parse_with_comments`]
+    pub emit_comments: bool,
+    /// How tabs in indentation are handled
+    pub tabs: TabPolicy,
+    /// Return [`YAMLParseErrorReason::DuplicateKey`] if a key appears twice in one mapping
+    pub deny_duplicate_keys: bool,
+    /// Resolve plain scalars to [`RootYAMLValue::Number`], booleans and null. When
+    /// disabled all plain scalars are [`RootYAMLValue::String`]
+    pub implicit_typing: bool,
+    /// Resolve plain scalars that are ISO 8601 dates (`2001-12-14`) or date times
+    /// (`2001-12-14t21:59:43.10-05:00`) to [`RootYAMLValue::Timestamp`] (with [`ParseOptions::implicit_typing`])
+    pub detect_timestamps: bool,
+    /// Which spellings of plain scalars are [`RootYAMLValue::True`] and [`RootYAMLValue::False`]
+    pub bool_style: BoolStyle,
+    /// How plain scalars are typed. [`ParseOptions::bool_style`] and [`ParseOptions::detect_timestamps`]
+    /// only apply to [`Schema::Core`]
+    pub schema: Schema,
+    /// Return [`YAMLParseErrorReason::MaxDepthExceeded`] for values nested under more than this many keys
+    /// and indices. Flow collections are parsed recursively, so set this for untrusted input
+    pub max_depth: Option<usize>,
+    /// Return [`YAMLParseErrorReason::InputTooLong`] (before parsing anything) for input longer than
+    /// this many bytes. For [`Parser`] (and so `parse_reader`) this is the total of the chunks fed
+    pub max_input_len: Option<usize>,
+    /// Return [`YAMLParseErrorReason::AliasLimitExceeded`] after resolving this many `*alias`es (with
+    /// [`ParseOptions::resolve_aliases`] or [`ParseOptions::resolve_merge_keys`])
+    pub max_alias_expansions: Option<usize>,
+    /// Return [`YAMLParseErrorReason::AliasLimitExceeded`] once resolving aliases has emitted more than
+    /// this many values in total. Anchors can contain aliases of earlier anchors, so the number of
+    /// values grows exponentially with the nesting (a "billion laughs" document). Set this (or
+    /// [`ParseOptions::max_alias_expansions`]) when resolving aliases in untrusted input
+    pub max_total_expanded_size: Option<usize>,
+    /// Resolve line breaks in block scalars ([`MultilineString::resolve`]) to `\n`. When disabled, values
+    /// from input with `\r\n` line endings keep them
+    pub normalize_newlines: bool,
+    /// Expand `${VAR}` placeholders in strings with the value returned for `VAR` (for example
+    /// `|name| std::env::var(name).ok()`), giving [`RootYAMLValue::Interpolated`]. Placeholders can
+    /// have a default (`${VAR:-default}`) for when `None` is returned, otherwise they are left as is.
+    /// `$${` is a literal `${`
+    pub interpolate: Option<fn(&str) -> Option<String>>,
+    /// Do not parse collections nested under this many keys and indices (at least 1), instead
+    /// passing their text as [`RootYAMLValue::RawBlock`]. With `Some(1)` only top level entries are
+    /// parsed
+    pub raw_depth: Option<usize>,
+    /// Only pass values under these paths to the callback. Unless aliases or merge keys are
+    /// resolved (when anchors anywhere could be needed), the lines of block entries outside the
+    /// paths are skipped without being parsed, so are not checked for errors
+    pub only_paths: Option<PathFilter>,
+    /// Reject input outside a subset of YAML that reads the same to any parser:
+    /// - tabs in indentation ([`YAMLParseErrorReason::TabIndentation`], whatever [`ParseOptions::tabs`] is)
+    /// - duplicate keys (as [`ParseOptions::deny_duplicate_keys`])
+    /// - plain values that YAML 1.1 types differently: `yes`, `no`, `on`, `off`, `y` and `n` (in any
+    ///   case) and integers with a leading zero ([`YAMLParseErrorReason::AmbiguousScalar`])
+    /// - plain values containing `:` ([`YAMLParseErrorReason::UnquotedColon`])
+    ///
+    /// Quoted values are always allowed
+    pub strict: bool,
+}
+
+/// Tabs are not allowed in indentation by the YAML spec, but are common in hand written files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TabPolicy {
+    /// Return [`YAMLParseErrorReason::TabIndentation`]
+    Error,
+    /// Count a tab as this many spaces
+    TreatAsSpaces(usize),
+    /// Count a tab as a single column, so files indented only with tabs work
+    #[default]
+    Allow,
+}
+
+/// The spellings of booleans. Other spellings are strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoolStyle {
+    /// YAML 1.1: `true`, `false`, `yes`, `no`, `on`, `off`, `y` and `n`, in lowercase, titlecase or
+    /// uppercase
+    Yaml11,
+    /// The YAML 1.2 core schema: `true` and `false` in lowercase, titlecase or uppercase
+    Yaml12Core,
+    /// Only `true` and `false`
+    #[default]
+    StrictLowercase,
+}
+
+impl BoolStyle {
+    /// The value of `value` if it is a boolean in this style
+    fn parse(self, value: &str) -> Option<bool> {
+        let (truthy, falsy): (&[&str], &[&str]) = match self {
+            Self::Yaml11 => (
+                &[
+                    "true", "True", "TRUE", "yes", "Yes", "YES", "on", "On", "ON", "y", "Y",
+                ],
+                &[
+                    "false", "False", "FALSE", "no", "No", "NO", "off", "Off", "OFF", "n", "N",
+                ],
+            ),
+            Self::Yaml12Core => (&["true", "True", "TRUE"], &["false", "False", "FALSE"]),
+            Self::StrictLowercase => (&["true"], &["false"]),
+        };
+        if truthy.contains(&value) {
+            Some(true)
+        } else if falsy.contains(&value) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// Presets for typing plain scalars, matching the schemas in the YAML specifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Schema {
+    /// All plain scalars are strings (the same as disabling [`ParseOptions::implicit_typing`])
+    Failsafe,
+    /// Only `null`, `true`, `false` and numbers in JSON syntax. Everything else is a string
+    Json,
+    /// Booleans from [`ParseOptions::bool_style`], `null`, `~` and (unless [`ParseOptions::strict_null`])
+    /// `Null` and `NULL`, decimal, hexadecimal (`0x`) and octal (`0o`) numbers, `.inf` and `.nan`.
+    /// Timestamps with [`ParseOptions::detect_timestamps`]
+    #[default]
+    Core,
+    /// As [`Schema::Core`] with [`BoolStyle::Yaml11`] booleans (`yes`, `off`, ...) and timestamps
+    Yaml11,
+}
+
+impl ParseOptions {
+    /// The default options, to be changed with the methods named after each field (for example
+    /// `ParseOptions::new().indent_size(4).resolve_aliases(true)`)
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets [`ParseOptions::indent_size`]
+    #[must_use]
+    pub fn indent_size(mut self, indent_size: usize) -> Self {
+        self.indent_size = Some(indent_size);
+        self
+    }
+
+    /// Sets [`ParseOptions::resolve_aliases`]
+    #[must_use]
+    pub fn resolve_aliases(mut self, resolve_aliases: bool) -> Self {
+        self.resolve_aliases = resolve_aliases;
+        self
+    }
+
+    /// Sets [`ParseOptions::resolve_merge_keys`]
+    #[must_use]
+    pub fn resolve_merge_keys(mut self, resolve_merge_keys: bool) -> Self {
+        self.resolve_merge_keys = resolve_merge_keys;
+        self
+    }
+
+    /// Sets [`ParseOptions::strict_null`]
+    #[must_use]
+    pub fn strict_null(mut self, strict_null: bool) -> Self {
+        self.strict_null = strict_null;
+        self
+    }
+
+    /// Sets [`ParseOptions::emit_comments`]
+    #[must_use]
+    pub fn emit_comments(mut self, emit_comments: bool) -> Self {
+        self.emit_comments = emit_comments;
+        self
+    }
+
+    /// Sets [`ParseOptions::tabs`]
+    #[must_use]
+    pub fn tabs(mut self, tabs: TabPolicy) -> Self {
+        self.tabs = tabs;
+        self
+    }
+
+    /// Sets [`ParseOptions::deny_duplicate_keys`]
+    #[must_use]
+    pub fn deny_duplicate_keys(mut self, deny_duplicate_keys: bool) -> Self {
+        self.deny_duplicate_keys = deny_duplicate_keys;
+        self
+    }
+
+    /// Sets [`ParseOptions::implicit_typing`]
+    #[must_use]
+    pub fn implicit_typing(mut self, implicit_typing: bool) -> Self {
+        self.implicit_typing = implicit_typing;
+        self
+    }
+
+    /// Sets [`ParseOptions::detect_timestamps`]
+    #[must_use]
+    pub fn detect_timestamps(mut self, detect_timestamps: bool) -> Self {
+        self.detect_timestamps = detect_timestamps;
+        self
+    }
+
+    /// Sets [`ParseOptions::bool_style`]
+    #[must_use]
+    pub fn bool_style(mut self, bool_style: BoolStyle) -> Self {
+        self.bool_style = bool_style;
+        self
+    }
+
+    /// Sets [`ParseOptions::schema`]
+    #[must_use]
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Sets [`ParseOptions::max_depth`]
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets [`ParseOptions::max_input_len`]
+    #[must_use]
+    pub fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.max_input_len = Some(max_input_len);
+        self
+    }
+
+    /// Sets [`ParseOptions::max_alias_expansions`]
+    #[must_use]
+    pub fn max_alias_expansions(mut self, max_alias_expansions: usize) -> Self {
+        self.max_alias_expansions = Some(max_alias_expansions);
+        self
+    }
+
+    /// Sets [`ParseOptions::max_total_expanded_size`]
+    #[must_use]
+    pub fn max_total_expanded_size(mut self, max_total_expanded_size: usize) -> Self {
+        self.max_total_expanded_size = Some(max_total_expanded_size);
+        self
+    }
+
+    /// Sets [`ParseOptions::normalize_newlines`]
+    #[must_use]
+    pub fn normalize_newlines(mut self, normalize_newlines: bool) -> Self {
+        self.normalize_newlines = normalize_newlines;
+        self
+    }
+
+    /// Sets [`ParseOptions::interpolate`]
+    #[must_use]
+    pub fn interpolate(mut self, interpolate: fn(&str) -> Option<String>) -> Self {
+        self.interpolate = Some(interpolate);
+        self
+    }
+
+    /// Sets [`ParseOptions::raw_depth`]
+    #[must_use]
+    pub fn raw_depth(mut self, raw_depth: usize) -> Self {
+        self.raw_depth = Some(raw_depth);
+        self
+    }
+
+    /// Sets [`ParseOptions::only_paths`]
+    #[must_use]
+    pub fn only_paths(mut self, only_paths: PathFilter) -> Self {
+        self.only_paths = Some(only_paths);
+        self
+    }
+
+    /// Sets [`ParseOptions::strict`]
+    #[must_use]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    fn tab_policy(&self) -> TabPolicy {
+        if self.strict {
+            TabPolicy::Error
+        } else {
+            self.tabs
+        }
+    }
+
+    fn denies_duplicate_keys(&self) -> bool {
+        self.deny_duplicate_keys || self.strict
+    }
+
+    fn tab_width(&self) -> usize {
+        match self.tabs {
+            TabPolicy::TreatAsSpaces(width) => width,
+            TabPolicy::Error | TabPolicy::Allow => 1,
+        }
+    }
+
+    fn check_input_len(&self, on: &str) -> Result<(), YAMLParseError> {
+        match self.max_input_len {
+            Some(max) if on.len() > max => Err(YAMLParseError::new(
+                YAMLParseErrorReason::InputTooLong,
+                max..on.len(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            indent_size: None,
+            resolve_aliases: false,
+            resolve_merge_keys: false,
+            strict_null: false,
+            emit_comments: false,
+            deny_duplicate_keys: false,
+            tabs: TabPolicy::default(),
+            implicit_typing: true,
+            detect_timestamps: false,
+            bool_style: BoolStyle::default(),
+            schema: Schema::default(),
+            max_depth: None,
+            max_input_len: None,
+            max_alias_expansions: None,
+            max_total_expanded_size: None,
+            normalize_newlines: true,
+            interpolate: None,
+            raw_depth: None,
+            only_paths: None,
+            strict: false,
+        }
+    }
+}
+
+/// Values recorded under an `&anchor`, for [`ParseOptions::resolve_aliases`]
+struct Anchor<'a> {
+    name: &'a str,
+    /// The key chain at the point of the anchor
+    prefix: Vec<YAMLKey<'a>>,
+    /// Whether still recording values under `prefix`
+    open: bool,
+    values: Vec<(Vec<YAMLKey<'a>>, Emitted<'a>)>,
+}
+
+/// Splits a leading `&anchor` off a value
+fn split_anchor(value: &str) -> (Option<&str>, &str) {
+    let name = value
+        .strip_prefix('&')
+        .filter(|rest| rest.starts_with(|chr: char| !is_yaml_whitespace(chr)));
+    if let Some(rest) = name {
+        let end = rest.find(is_yaml_whitespace).unwrap_or(rest.len());
+        (
+            Some(&rest[..end]),
+            rest[end..].trim_start_matches(is_yaml_whitespace),
+        )
+    } else {
+        (None, value)
+    }
+}
+
+/// Splits a leading `!tag` off a value
+fn split_tag(value: &str) -> (Option<&str>, &str) {
+    if value.starts_with('!') {
+        let end = value.find(is_yaml_whitespace).unwrap_or(value.len());
+        (
+            Some(&value[..end]),
+            value[end..].trim_start_matches(is_yaml_whitespace),
+        )
+    } else {
+        (None, value)
+    }
+}
+
+/// Splits the `&anchor` and `!tag` properties (in either order) off a value
+fn split_properties(value: &str) -> (Option<&str>, Option<&str>, &str) {
+    let (anchor, value) = split_anchor(value);
+    let (tag, value) = split_tag(value);
+    if anchor.is_some() {
+        (anchor, tag, value)
+    } else {
+        let (anchor, value) = split_anchor(value);
+        (anchor, tag, value)
+    }
+}
+
+fn is_core_tag(tag: &str) -> bool {
+    matches!(tag, "!!str" | "!!int" | "!!float" | "!!bool" | "!!null")
+}
+
+/// Resolves a plain scalar with a `!tag`. Core schema tags (`!!str`, `!!int` etc) are applied to
+/// the value, other tags give [`RootYAMLValue::Tagged`]
+fn tagged_value<'a>(tag: &'a str, value: &'a str, options: &ParseOptions) -> RootYAMLValue<'a> {
+    match tag {
+        "!!str" => RootYAMLValue::String(value),
+        "!!int" | "!!float" if is_number(value) => RootYAMLValue::Number(value),
+        "!!bool" | "!!null" | "!!int" | "!!float" => scalar_value(value, options),
+        tag => RootYAMLValue::Tagged(TaggedScalar { tag, value }),
+    }
+}
+
+fn scalar_value<'a>(value: &'a str, options: &ParseOptions) -> RootYAMLValue<'a> {
+    let bool_style = match options.schema {
+        Schema::Failsafe => return RootYAMLValue::String(value),
+        _ if !options.implicit_typing => return RootYAMLValue::String(value),
+        Schema::Json => {
+            return match value {
+                "null" => RootYAMLValue::Null,
+                "true" => RootYAMLValue::True,
+                "false" => RootYAMLValue::False,
+                value if json::is_json_number(value) => RootYAMLValue::Number(value),
+                value => RootYAMLValue::String(value),
+            }
+        }
+        Schema::Core => options.bool_style,
+        Schema::Yaml11 => BoolStyle::Yaml11,
+    };
+    let detect_timestamps = options.detect_timestamps || options.schema == Schema::Yaml11;
+    match bool_style.parse(value) {
+        Some(true) => return RootYAMLValue::True,
+        Some(false) => return RootYAMLValue::False,
+        None => {}
+    }
+    match value {
+        "" | "~" | "null" => RootYAMLValue::Null,
+        "Null" | "NULL" if !options.strict_null => RootYAMLValue::Null,
+        value if is_number(value) => RootYAMLValue::Number(value),
+        value if detect_timestamps && is_timestamp(value) => RootYAMLValue::Timestamp(value),
+        value => RootYAMLValue::String(value),
+    }
+}
+
+/// Whether `value` is an integer, float, hexadecimal or octal number (under the YAML core schema)
+fn is_number(value: &str) -> bool {
+    fn digits(on: &str) -> bool {
+        on.chars().all(|chr| chr.is_ascii_digit())
+    }
+
+    if special_float(value).is_some() {
+        return true;
+    }
+    if let Some(hex) = value.strip_prefix("0x") {
+        return !hex.is_empty() && hex.chars().all(|chr| chr.is_ascii_hexdigit());
+    }
+    if let Some(octal) = value.strip_prefix("0o") {
+        return !octal.is_empty() && octal.chars().all(|chr| matches!(chr, '0'..='7'));
+    }
+
+    let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(idx) => (&unsigned[..idx], Some(&unsigned[idx + 1..])),
+        None => (unsigned, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (mantissa, ""),
+    };
+    let valid_mantissa =
+        digits(integer) && digits(fraction) && !(integer.is_empty() && fraction.is_empty());
+    let valid_exponent = exponent.is_none_or(|exponent| {
+        let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+        !exponent.is_empty() && digits(exponent)
+    });
+    valid_mantissa && valid_exponent
+}
+
+/// Expands the `${VAR}` placeholders in `text`, see [`ParseOptions::interpolate`]
+fn interpolate(text: &str, lookup: fn(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find('$') {
+        output.push_str(&rest[..idx]);
+        let after = &rest[idx + '$'.len_utf8()..];
+        if let Some(after) = after.strip_prefix("${") {
+            output.push_str("${");
+            rest = after;
+        } else if let Some((placeholder, after)) = after
+            .strip_prefix('{')
+            .and_then(|inner| inner.split_once('}'))
+        {
+            let (name, default) = match placeholder.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (placeholder, None),
+            };
+            match (lookup(name), default) {
+                (Some(value), _) => output.push_str(&value),
+                (None, Some(default)) => output.push_str(default),
+                (None, None) => output.push_str(&rest[idx..rest.len() - after.len()]),
+            }
+            rest = after;
+        } else {
+            output.push('$');
+            rest = after;
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Whether `value` is a date (`YYYY-MM-DD`) or a date time, as in the YAML timestamp type: the date then
+/// `T`, `t` or spaces, `H:MM:SS`, optional fractional seconds and an optional `Z` or `±H[:MM]` offset
+fn is_timestamp(value: &str) -> bool {
+    /// Splits off between `min` and `max` leading digits
+    fn digits(on: &str, min: usize, max: usize) -> Option<&str> {
+        let count = on.bytes().take(max).take_while(u8::is_ascii_digit).count();
+        (count >= min).then(|| &on[count..])
+    }
+
+    fn date_time(value: &str) -> Option<()> {
+        let rest = digits(value, 4, 4)?.strip_prefix('-')?;
+        let day = digits(rest, 2, 2)
+            .and_then(|rest| rest.strip_prefix('-'))
+            .and_then(|rest| digits(rest, 2, 2));
+        if day == Some("") {
+            return Some(());
+        }
+        // date times allow one digit months and days
+        let rest = digits(rest, 1, 2)?.strip_prefix('-')?;
+        let rest = digits(rest, 1, 2)?;
+        let rest = match rest.strip_prefix(['T', 't']) {
+            Some(rest) => rest,
+            None => rest
+                .strip_prefix([' ', '\t'])?
+                .trim_start_matches([' ', '\t']),
+        };
+        let rest = digits(rest, 1, 2)?.strip_prefix(':')?;
+        let rest = digits(rest, 2, 2)?.strip_prefix(':')?;
+        let mut rest = digits(rest, 2, 2)?;
+        if let Some(fraction) = rest.strip_prefix('.') {
+            rest = digits(fraction, 0, usize::MAX)?;
+        }
+        let rest = rest.trim_start_matches([' ', '\t']);
+        if rest.is_empty() || rest == "Z" {
+            return Some(());
+        }
+        let rest = digits(rest.strip_prefix(['+', '-'])?, 1, 2)?;
+        match rest.strip_prefix(':') {
+            Some(minutes) => digits(minutes, 2, 2)?.is_empty().then_some(()),
+            None => rest.is_empty().then_some(()),
+        }
+    }
+
+    date_time(value).is_some()
+}
+
+/// The core schema infinity (`.inf`, `-.inf`, `+.inf`) and not-a-number (`.nan`) values, in lowercase,
+/// titlecase or uppercase
+fn special_float(value: &str) -> Option<f64> {
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    match unsigned {
+        ".inf" | ".Inf" | ".INF" if negative => Some(f64::NEG_INFINITY),
+        ".inf" | ".Inf" | ".INF" => Some(f64::INFINITY),
+        ".nan" | ".NaN" | ".NAN" if unsigned.len() == value.len() => Some(f64::NAN),
+        _ => None,
+    }
+}
+
+/// Whether the next non-empty line is nested under an entry at `indent`. Sequences
+/// can be at the same indentation as their key
+fn has_nested_block(on: &str, indent: usize, options: &ParseOptions) -> bool {
+    for line in on.lines() {
+        let trimmed = line.trim_start_matches(is_yaml_whitespace);
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let line_indent = indent_width(&line[..line.len() - trimmed.len()], options);
+        let is_list_item = trimmed == "-" || trimmed.starts_with("- ");
+        return line_indent > indent || (line_indent == indent && is_list_item);
+    }
+    false
+}
+
+/// For skipping an entry at `column` on the line containing `at`: the end of the last following
+/// line nested under it. Blank lines and comments do not end it and sequence items at `column`
+/// are part of it if `items_at_column` (as they can be for keys)
+fn nested_block_end(
+    on: &str,
+    at: usize,
+    column: usize,
+    items_at_column: bool,
+    options: &ParseOptions,
+) -> usize {
+    let mut end = on[at..].find('\n').map_or(on.len(), |end| at + end);
+    let mut line_start = end + '\n'.len_utf8();
+    for line in on
+        .get(line_start..)
+        .unwrap_or_default()
+        .split_inclusive('\n')
+    {
+        let trimmed = line.trim_start_matches(is_yaml_whitespace);
+        if !(trimmed.is_empty() || trimmed.starts_with('#')) {
+            let indent = indent_width(&line[..line.len() - trimmed.len()], options);
+            let is_item = trimmed.starts_with('-')
+                && trimmed[1..].chars().next().is_none_or(is_yaml_whitespace);
+            let is_nested = indent > column || (items_at_column && indent == column && is_item);
+            if !is_nested || is_document_marker(line, "---") || is_document_marker(line, "...") {
+                break;
+            }
+            end = line_start + line.trim_end_matches('\n').len();
+        }
+        line_start += line.len();
+    }
+    end
+}
+
+/// For a `? key` line (where the `?` is at byte offset `at` and column `indent`) ending at
+/// `line_end`, returns the offset after the `:` of the `: value` line following it, or `None` if
+/// there is no value line (so the value is null)
+fn explicit_key_value(
+    on: &str,
+    at: usize,
+    key: &str,
+    line_end: usize,
+    indent: usize,
+    options: &ParseOptions,
+) -> Result<Option<usize>, YAMLParseError> {
+    let unsupported =
+        || YAMLParseError::new(YAMLParseErrorReason::UnsupportedComplexKey, at..line_end);
+    let is_collection = key == "-" || key.starts_with("- ") || key.starts_with("? ");
+    if key.is_empty() || is_collection || key.starts_with(['[', '{', '|', '>', '&', '!', '*']) {
+        return Err(unsupported());
+    }
+    let mut line_start = (line_end + '\n'.len_utf8()).min(on.len());
+    while line_start < on.len() {
+        let line = on[line_start..].split('\n').next().unwrap_or_default();
+        let trimmed = line.trim_start_matches(is_yaml_whitespace);
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            line_start += line.len() + '\n'.len_utf8();
+            continue;
+        }
+        let line_indent = indent_width(&line[..line.len() - trimmed.len()], options);
+        if line_indent > indent {
+            return Err(unsupported());
+        }
+        let after_colon = trimmed.strip_prefix(':').filter(|after| {
+            trim_whitespace(after).is_empty() || after.starts_with(is_yaml_whitespace)
+        });
+        return Ok(match (line_indent == indent, after_colon) {
+            (true, Some(after)) => Some(offset_in(on, after)),
+            _ => None,
+        });
+    }
+    Ok(None)
+}
+
+/// The offset of the first line after `line_end` that is not part of a `? key` entry at column
+/// `indent` (its continuation lines, `: value` line and lines nested under it)
+fn end_of_explicit_entry(
+    on: &str,
+    line_end: usize,
+    indent: usize,
+    options: &ParseOptions,
+) -> usize {
+    let mut line_start = (line_end + '\n'.len_utf8()).min(on.len());
+    while line_start < on.len() {
+        let line = on[line_start..].split('\n').next().unwrap_or_default();
+        let trimmed = line.trim_start_matches(is_yaml_whitespace);
+        let line_indent = indent_width(&line[..line.len() - trimmed.len()], options);
+        let is_part = trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || line_indent > indent
+            || (line_indent == indent && trimmed.starts_with(':'));
+        if !is_part {
+            return line_start;
+        }
+        line_start += line.len() + '\n'.len_utf8();
+    }
+    on.len()
+}
+
+/// The number of spaces the first nested line of `on` is indented by from the line before it,
+/// otherwise 2. For writing lines that match the indentation of `on`
+pub(crate) fn indent_step(on: &str) -> usize {
+    let mut previous = 0;
+    for line in on.lines() {
+        let trimmed = line.trim_start_matches(' ');
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        if indent > previous {
+            return indent - previous;
+        }
+        previous = indent;
+    }
+    2
+}
+
+/// The width of indentation, counting tabs as [`ParseOptions::tabs`] says
+fn indent_width(whitespace: &str, options: &ParseOptions) -> usize {
+    whitespace
+        .chars()
+        .map(|chr| if chr == '\t' { options.tab_width() } else { 1 })
+        .sum()
+}
+
+/// With the `smallkeys` feature, the key chain is stored inline for up to this many levels of nesting
+#[cfg(feature = "smallkeys")]
+const INLINE_DEPTH: usize = 16;
+#[cfg(not(feature = "smallkeys"))]
+const INLINE_DEPTH: usize = 0;
+
+/// Flow collections are parsed recursively, so nesting is limited (regardless of
+/// [`ParseOptions::max_depth`]) to avoid overflowing the stack
+const MAX_FLOW_NESTING: usize = 128;
+
+/// What the block parser passes to its callback. Key chains do not show empty collections, where
+/// documents start and end or the properties of collections, so these are passed as well (for
+/// [`parse_events`] and [`format_yaml`])
+#[derive(Debug, Clone)]
+enum Emitted<'a> {
+    Value(RootYAMLValue<'a>),
+    /// `{}` (if `is_mapping`) or `[]`, under the key chain
+    EmptyCollection {
+        is_mapping: bool,
+    },
+    /// Before the first value of a document or at a `---`. Passed with an empty key chain
+    DocumentStart,
+    /// At a `---` or `...` after a document and at the end of the input
+    DocumentEnd,
+    /// The `&anchor` and `!tag` properties (as written) of the collection or value under the key
+    /// chain. Passed before it
+    Properties(&'a str),
+}
+
+/// State shared between the block parser and the flow (`[...]` and `{...}`) parser
+struct Emitter<'a, 'o, C, D, const N: usize> {
+    on: &'a str,
+    key_chain: KeyChain<'a, N>,
+    anchors: Vec<Anchor<'a>>,
+    /// The key chain of the collection being skipped, from [`Signal::SkipSubtree`]
+    skip: Option<Vec<YAMLKey<'a>>>,
+    /// The number of aliases resolved and values emitted from them, for
+    /// [`ParseOptions::max_alias_expansions`] and [`ParseOptions::max_total_expanded_size`]
+    alias_expansions: usize,
+    expanded_values: usize,
+    /// Key chains of the keys so far (and where they are), for [`ParseOptions::deny_duplicate_keys`]
+    /// and duplicate key warnings
+    seen_keys: BTreeMap<Vec<YAMLKey<'a>>, usize>,
+    /// The columns the first nested block entry is indented by, for
+    /// [`YAMLWarningKind::InconsistentIndentation`]
+    indent_step: Option<usize>,
+    /// The column of the last block entry if its value was on its line, for
+    /// [`YAMLParseErrorReason::InvalidIndentation`]
+    value_column: Option<usize>,
+    /// Receives warnings, from [`parse_with_warnings`]
+    on_warning: Option<&'o mut dyn FnMut(YAMLWarning)>,
+    /// Whether [`Emitted::DocumentStart`] has been passed for the current document
+    in_document: bool,
+    options: &'o ParseOptions,
+    cb: C,
+    on_comment: D,
+}
+
+impl<'a, C, D, const N: usize> Emitter<'a, '_, C, D, N>
+where
+    C: for<'b> FnMut(&'b [YAMLKey<'a>], Emitted<'a>, ValueSpans) -> Signal,
+    D: FnMut(usize, &'a str),
+{
+    /// `comment` is the text after the `#`, which is at `at`
+    fn comment(&mut self, at: usize, comment: &'a str) {
+        if self.options.emit_comments {
+            (self.on_comment)(at, comment);
+        }
+    }
+
+    /// Skips whitespace and `# comments` inside a flow collection
+    fn skip_flow_whitespace(&mut self, mut idx: usize) -> usize {
+        let on = self.on;
+        loop {
+            idx = skip_whitespace(on, idx);
+            if !on[idx..].starts_with('#') {
+                return idx;
+            }
+            let end = on[idx..].find('\n').map_or(on.len(), |end| idx + end);
+            self.comment(idx, &on[idx + '#'.len_utf8()..end]);
+            idx = end;
+        }
+    }
+
+    /// Adds a mapping key to the key chain (with the column of block keys), checking it has not been
+    /// seen before
+    fn push_key(&mut self, key: &'a str, column: Option<usize>) -> Result<(), YAMLParseError> {
+        match column {
+            Some(column) => self.key_chain.push_block(YAMLKey::Slice(key), column),
+            None => self.key_chain.push(YAMLKey::Slice(key)),
+        }
+        let at = offset_in(self.on, key);
+        self.check_depth(at..at + key.len())?;
+        if self.options.denies_duplicate_keys() || self.on_warning.is_some() {
+            if let Some(&first_at) = self.seen_keys.get(&self.key_chain[..]) {
+                let span = at..at + key.len();
+                let key = key.to_string();
+                if self.options.denies_duplicate_keys() {
+                    return Err(YAMLParseError::new(
+                        YAMLParseErrorReason::DuplicateKey { key, first_at },
+                        span,
+                    ));
+                }
+                self.warn(YAMLWarningKind::DuplicateKey { key, first_at }, span);
+            } else {
+                self.seen_keys
+                    .insert(self.key_chain.as_slice().to_vec(), at);
+            }
+        }
+        Ok(())
+    }
+
+    /// Passes a warning for `span` to the warning callback (if there is one)
+    fn warn(&mut self, kind: YAMLWarningKind, span: Range<usize>) {
+        if let Some(on_warning) = &mut self.on_warning {
+            on_warning(warning::new(self.on, kind, span));
+        }
+    }
+
+    /// Errors if the key chain is deeper than [`ParseOptions::max_depth`]
+    fn check_depth(&self, span: Range<usize>) -> Result<(), YAMLParseError> {
+        match self.options.max_depth {
+            Some(max) if self.key_chain.depth() > max => Err(YAMLParseError::new(
+                YAMLParseErrorReason::MaxDepthExceeded,
+                span,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes the block entries that a line at `column` is not nested under
+    fn close_blocks(&mut self, column: usize) {
+        self.key_chain.close_blocks(column, true);
+    }
+
+    /// Warns if a block entry at `column` (starting at byte offset `at`) is indented from its
+    /// parent mapping key by a different number of columns than the first nested entry. Entries
+    /// in a sequence item are not checked, as their column depends on the space after the `-`
+    fn check_indentation(&mut self, column: usize, at: usize) {
+        if self.on_warning.is_none() {
+            return;
+        }
+        let (Some(parent), Some(YAMLKey::Slice(_))) =
+            (self.key_chain.last_column(), self.key_chain.last())
+        else {
+            return;
+        };
+        if column <= parent {
+            return;
+        }
+        let step = column - parent;
+        match self.indent_step {
+            None => self.indent_step = Some(step),
+            Some(expected) if expected != step => {
+                let line_start = self.on[..at]
+                    .rfind('\n')
+                    .map_or(0, |idx| idx + '\n'.len_utf8());
+                let kind = YAMLWarningKind::InconsistentIndentation {
+                    expected,
+                    found: step,
+                };
+                self.warn(kind, line_start..at);
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Adds a mapping key (that starts a line or follows a `-`) at `column`
+    fn push_block_key(&mut self, key: &'a str, column: usize) -> Result<(), YAMLParseError> {
+        let at = offset_in(self.on, key);
+        let nesting = self.check_nesting(column, at..at + key.len());
+        self.check_indentation(column, at);
+        // the key is added even if it is a duplicate, for `parse_lenient`
+        self.push_key(key, Some(column)).and(nesting)
+    }
+
+    /// Errors if a block entry at `column` is nested under an entry whose value was on its line.
+    /// That entry is closed, so (for `parse_lenient`) the entry is added as its sibling
+    fn check_nesting(&mut self, column: usize, span: Range<usize>) -> Result<(), YAMLParseError> {
+        match self.value_column.take() {
+            Some(value_column) if column > value_column => {
+                self.key_chain.close_blocks(value_column, true);
+                Err(YAMLParseError::new(
+                    YAMLParseErrorReason::InvalidIndentation,
+                    span,
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// With [`ParseOptions::indent_size`], errors if a line starting at `at` is indented by `indent`
+    /// which is not a multiple of it, unless it lines up with an open block entry (such as the keys
+    /// of a mapping after a `- `)
+    fn check_indent_size(&self, indent: usize, at: usize) -> Result<(), YAMLParseError> {
+        let Some(indent_size) = self.options.indent_size.filter(|size| *size > 0) else {
+            return Ok(());
+        };
+        let is_open_column =
+            (0..self.key_chain.len()).any(|depth| self.key_chain.column(depth) == Some(indent));
+        if indent.is_multiple_of(indent_size) || is_open_column {
+            Ok(())
+        } else {
+            let line_start = self.on[..at]
+                .rfind('\n')
+                .map_or(0, |idx| idx + '\n'.len_utf8());
+            Err(YAMLParseError::new(
+                YAMLParseErrorReason::InvalidIndentation,
+                line_start..at,
+            ))
+        }
+    }
+
+    /// Adds a sequence item for a `-` at `column` (and byte offset `at`). The index follows on from a
+    /// previous item at the same column
+    fn push_block_item(&mut self, column: usize, at: usize) -> Result<(), YAMLParseError> {
+        let nesting = self.check_nesting(column, at..at + '-'.len_utf8());
+        self.key_chain.close_blocks(column, false);
+        self.check_indentation(column, at);
+        let mut index = 0;
+        if let (Some(last), Some(previous)) =
+            (self.key_chain.last_column(), self.key_chain.last_index())
+        {
+            if last == column {
+                index = previous + 1;
+                self.key_chain.pop();
+            }
+        }
+        self.key_chain.push_block(YAMLKey::Index(index), column);
+        self.check_depth(at..at + '-'.len_utf8()).and(nesting)
+    }
+
+    /// For an entry just added to the key chain whose value starts at `at` (after the `:` or `-`)
+    /// and which is at `column`: if it is not parsed, returns the end of its lines (and whether to
+    /// stop). Entries are skipped if nothing under them is in [`ParseOptions::only_paths`] (unless
+    /// resolving aliases, as there could be anchors in them) and collections at
+    /// [`ParseOptions::raw_depth`] are emitted as [`RootYAMLValue::RawBlock`]
+    fn unparsed_entry(&mut self, at: usize, column: usize, is_item: bool) -> Option<(usize, bool)> {
+        let on = self.on;
+        let resolves = self.options.resolve_aliases || self.options.resolve_merge_keys;
+        let skipped = self
+            .options
+            .only_paths
+            .as_ref()
+            .is_some_and(|filter| !resolves && !filter.may_contain(&self.key_chain));
+        if skipped {
+            return Some((
+                nested_block_end(on, at, column, !is_item, self.options),
+                false,
+            ));
+        }
+        let raw_depth = self.options.raw_depth?;
+        if self.key_chain.depth() < raw_depth {
+            return None;
+        }
+        let line_end = on[at..].find('\n').map_or(on.len(), |end| at + end);
+        let (value, _) = split_comment(trim_whitespace(&on[at..line_end]));
+        let (anchor, _, value) = split_properties(value);
+        if value.starts_with(['[', '{']) {
+            return self.raw_flow_collection(offset_in(on, value), anchor);
+        }
+        let end = nested_block_end(on, at, column, !is_item, self.options);
+        let is_collection =
+            value == "-" || value.starts_with("- ") || (is_item && has_mapping_colon(value));
+        let raw = if value.is_empty() && end > line_end {
+            line_end + '\n'.len_utf8()..end
+        } else if is_collection {
+            offset_in(on, value)..end
+        } else {
+            return None;
+        };
+        if let Some(name) = anchor {
+            self.begin_anchor(name);
+        }
+        let exit = self.emit(RootYAMLValue::RawBlock(&on[raw.clone()]), raw.clone());
+        Some((raw.end, exit))
+    }
+
+    /// Splits the `&anchor` and `!tag` properties off `value` (of the current key chain), starting
+    /// the anchor and passing them on. Returns the rest of `value` and whether to stop
+    fn properties(&mut self, value: &'a str) -> (&'a str, bool) {
+        let (anchor, tag, rest) = split_properties(value);
+        if let Some(name) = anchor {
+            self.begin_anchor(name);
+        }
+        if anchor.is_none() && tag.is_none() {
+            return (rest, false);
+        }
+        let properties = trim_whitespace(&value[..value.len() - rest.len()]);
+        let at = offset_in(self.on, properties);
+        let exit = self.emit_node(Emitted::Properties(properties), at..at + properties.len());
+        (rest, exit)
+    }
+
+    /// Starts recording values under the current key chain
+    fn begin_anchor(&mut self, name: &'a str) {
+        if self.options.resolve_aliases || self.options.resolve_merge_keys {
+            self.anchors.push(Anchor {
+                name,
+                prefix: self.key_chain.as_slice().to_vec(),
+                open: true,
+                values: Vec::new(),
+            });
+        }
+    }
+
+    /// Records `value` under any open anchors and then passes it to the callback (unless it is in a
+    /// skipped subtree). Returns whether to stop
+    fn emit(&mut self, value: RootYAMLValue<'a>, span: Range<usize>) -> bool {
+        // `as_str` allocates for escaped strings, so is only called when interpolating
+        let interpolated = self.options.interpolate.and_then(|lookup| {
+            let text = value.as_str()?;
+            text.contains("${")
+                .then(|| RootYAMLValue::Interpolated(interpolate(&text, lookup)))
+        });
+        self.emit_node(Emitted::Value(interpolated.unwrap_or(value)), span)
+    }
+
+    /// [`Emitter::emit`] for values and empty collections
+    fn emit_node(&mut self, node: Emitted<'a>, span: Range<usize>) -> bool {
+        for anchor in self.anchors.iter_mut().filter(|anchor| anchor.open) {
+            if self.key_chain.starts_with(&anchor.prefix) {
+                // aliases are replaced by values, not properties
+                if matches!(node, Emitted::Properties(_)) {
+                    continue;
+                }
+                let relative = self.key_chain[anchor.prefix.len()..].to_vec();
+                anchor.values.push((relative, node.clone()));
+            } else {
+                anchor.open = false;
+            }
+        }
+        if !self.in_document && self.start_document(span.start) {
+            return true;
+        }
+        if !matches!(node, Emitted::Properties(_)) {
+            self.value_column = self.key_chain.last_column();
+        }
+        if let Some(filter) = &self.options.only_paths {
+            if !filter.matches(&self.key_chain) {
+                return false;
+            }
+        }
+        let key = match self.key_chain.last() {
+            Some(YAMLKey::Slice(key)) => {
+                let start = offset_in(self.on, key);
+                start..start + key.len()
+            }
+            Some(YAMLKey::Index(_)) | None => span.clone(),
+        };
+        if let Some(prefix) = &self.skip {
+            if self.key_chain.depth() > prefix.len() && self.key_chain.starts_with(prefix) {
+                return false;
+            }
+            self.skip = None;
+        }
+        match (self.cb)(&self.key_chain, node, ValueSpans { key, value: span }) {
+            Signal::Continue => false,
+            Signal::SkipSubtree => {
+                let parent = self.key_chain.depth().saturating_sub(1);
+                self.skip = Some(self.key_chain[..parent].to_vec());
+                false
+            }
+            Signal::Stop => true,
+        }
+    }
+
+    /// Passes [`Emitted::DocumentStart`] for a document starting at `at`. Returns whether to stop
+    fn start_document(&mut self, at: usize) -> bool {
+        self.in_document = true;
+        // a skipped top level collection ends with its document
+        self.skip = None;
+        self.value_column = None;
+        let spans = ValueSpans {
+            key: at..at,
+            value: at..at,
+        };
+        matches!((self.cb)(&[], Emitted::DocumentStart, spans), Signal::Stop)
+    }
+
+    /// Passes [`Emitted::DocumentEnd`] if in a document. Returns whether to stop
+    fn end_document(&mut self, at: usize) -> bool {
+        if !self.in_document {
+            return false;
+        }
+        self.in_document = false;
+        let spans = ValueSpans {
+            key: at..at,
+            value: at..at,
+        };
+        matches!((self.cb)(&[], Emitted::DocumentEnd, spans), Signal::Stop)
+    }
+
+    /// With [`Signal::SkipSubtree`], finds the end of the lines in the skipped block collection
+    /// starting at `line_start`
+    fn skip_block_lines(&self, line_start: usize) -> Option<usize> {
+        let parent = self.skip.as_ref()?.len();
+        // flow collections do not have columns
+        if !self.key_chain.in_block() {
+            return None;
+        }
+        let column = self.key_chain.column(parent)?;
+        let is_sequence = matches!(self.key_chain.get(parent), Some(YAMLKey::Index(_)));
+        let mut end = line_start;
+        for line in self.on[line_start..].split_inclusive('\n') {
+            let trimmed = line.trim_start_matches(is_yaml_whitespace);
+            let indent = indent_width(&line[..line.len() - trimmed.len()], self.options);
+            let is_item = trimmed.starts_with('-') && trimmed[1..].starts_with(is_yaml_whitespace);
+            let skipped = trimmed.is_empty()
+                || trimmed.starts_with('#')
+                || indent > column
+                || (indent == column && (!is_sequence || is_item));
+            if !skipped || is_document_marker(line, "---") || is_document_marker(line, "...") {
+                break;
+            }
+            end += line.len();
+        }
+        Some(end)
+    }
+
+    /// For a key or sequence item with nothing after it on its line (ending at `line_end`). If
+    /// the following lines are nested deeper than `column`, they are its value and emit their own
+    /// entries, so nothing is emitted for the key itself. Otherwise the value is null (whatever
+    /// the [`Schema`]). Returns whether to stop
+    fn empty_value(&mut self, line_end: usize, column: usize, at: usize) -> bool {
+        let rest = self
+            .on
+            .get(line_end + '\n'.len_utf8()..)
+            .unwrap_or_default();
+        if has_nested_block(rest, column, self.options) {
+            false
+        } else {
+            self.emit(RootYAMLValue::Null, at..at)
+        }
+    }
+
+    /// Emits a plain (single line) value, handling `&anchor`s, `!tag`s and `*alias`es
+    fn emit_scalar(&mut self, value: &'a str) -> Result<bool, YAMLParseError> {
+        let (_, tag, _) = split_properties(value);
+        let (value, exit) = self.properties(value);
+        if exit {
+            return Ok(true);
+        }
+        let at = offset_in(self.on, value);
+        if !value.starts_with(['"', '\'']) {
+            check_plain_start(self.on, value)?;
+        }
+
+        // core schema tags on quoted values leave them as quoted strings
+        if let Some(tag) = tag.filter(|tag| !(is_core_tag(tag) && value.starts_with(['"', '\'']))) {
+            let span = at..at + value.len();
+            return Ok(self.emit(tagged_value(tag, value, self.options), span));
+        }
+
+        if let Some(name) = value.strip_prefix('*') {
+            if let Some(end) = name.find(is_yaml_whitespace) {
+                let rest = trim_whitespace(&name[end..]);
+                let rest_at = offset_in(self.on, rest);
+                return Err(YAMLParseError::new(
+                    YAMLParseErrorReason::ExpectedEndOfValue,
+                    rest_at..rest_at + rest.len(),
+                ));
+            }
+            let span = at..at + value.len();
+            if let (true, Some(depth)) = (self.options.resolve_merge_keys, self.merge_key_depth()) {
+                // `<<: *name` and `<<: [*name]` place the values in the parent mapping
+                let merge_key = self.key_chain.split_off(depth);
+                let exit = self.replay(name, span);
+                self.key_chain.extend(merge_key);
+                exit
+            } else if self.options.resolve_aliases {
+                self.replay(name, span)
+            } else {
+                Ok(self.emit(RootYAMLValue::Alias(name), span))
+            }
+        } else if let Some(quote @ ('"' | '\'')) = value.chars().next() {
+            let Some(end) = quoted_end(value) else {
+                return Err(YAMLParseError::new(
+                    YAMLParseErrorReason::ExpectedClosingQuote,
+                    at..at + value.len(),
+                ));
+            };
+            if end != value.len() {
+                return Err(YAMLParseError::new(
+                    YAMLParseErrorReason::ExpectedEndOfValue,
+                    at + end..at + value.len(),
+                ));
+            }
+            let quoted = QuotedString {
+                on: &value[1..end - 1],
+                double: quote == '"',
+            };
+            Ok(self.emit(RootYAMLValue::QuotedString(quoted), at..at + end))
+        } else {
+            if self.options.strict {
+                check_strict_scalar(self.on, value)?;
+            }
+            let span = at..at + value.len();
+            Ok(self.emit(scalar_value(value, self.options), span))
+        }
+    }
+
+    /// Emits the values recorded under the `&name` anchor, relative to the current key chain
+    fn replay(&mut self, name: &str, span: Range<usize>) -> Result<bool, YAMLParseError> {
+        let Some(anchor) = self.anchors.iter().rev().find(|anchor| anchor.name == name) else {
+            return Err(YAMLParseError::new(
+                YAMLParseErrorReason::UnknownAlias,
+                span,
+            ));
+        };
+        if anchor.open && self.is_under(&anchor.prefix) {
+            let anchor_at = offset_in(self.on, anchor.name);
+            return Err(YAMLParseError::new(
+                YAMLParseErrorReason::CyclicAlias { anchor_at },
+                span,
+            ));
+        }
+        self.alias_expansions += 1;
+        self.expanded_values += anchor.values.len();
+        let over = |count, max: Option<usize>| max.is_some_and(|max| count > max);
+        if over(self.alias_expansions, self.options.max_alias_expansions)
+            || over(self.expanded_values, self.options.max_total_expanded_size)
+        {
+            return Err(YAMLParseError::new(
+                YAMLParseErrorReason::AliasLimitExceeded,
+                span,
+            ));
+        }
+        let values = anchor.values.clone();
+        let length = self.key_chain.depth();
+        for (relative, node) in values {
+            self.key_chain.extend(relative);
+            let exit = self.emit_node(node, span.clone());
+            self.key_chain.truncate(length);
+            if exit {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Whether the current value is under the entry with the key chain `prefix`. Mapping keys are
+    /// compared by position, so a later entry with the same key is not under it
+    fn is_under(&self, prefix: &[YAMLKey<'a>]) -> bool {
+        self.key_chain.len() >= prefix.len()
+            && prefix
+                .iter()
+                .zip(self.key_chain.iter())
+                .all(|(a, b)| match (a, b) {
+                    (YAMLKey::Slice(a), YAMLKey::Slice(b)) => core::ptr::eq(*a, *b),
+                    (a, b) => a == b,
+                })
+    }
+
+    /// If the current value is under a `<<` merge key, returns the depth of the key
+    fn merge_key_depth(&self) -> Option<usize> {
+        match self.key_chain.as_slice() {
+            [.., YAMLKey::Slice("<<")] => Some(self.key_chain.depth() - 1),
+            [.., YAMLKey::Slice("<<"), YAMLKey::Index(_)] => Some(self.key_chain.depth() - 2),
+            _ => None,
+        }
+    }
+
+    /// Parses a flow collection (`[a, b]` or `{a: b}`) where `on[at..]` starts with the opening bracket.
+    /// Returns the offset after the closing bracket and the exit signal. `nesting` is the number of flow
+    /// collections this is inside
+    /// If the key chain is at [`ParseOptions::raw_depth`], emits the flow collection at `at` as a
+    /// [`RootYAMLValue::RawBlock`] and returns its end (and whether to stop). `None` if it is not
+    /// closed, so that it is parsed for the error
+    fn raw_flow_collection(&mut self, at: usize, anchor: Option<&'a str>) -> Option<(usize, bool)> {
+        let raw_depth = self.options.raw_depth?;
+        if self.key_chain.depth() < raw_depth {
+            return None;
+        }
+        let end = at + flow_collection_len(&self.on[at..])?;
+        if let Some(name) = anchor {
+            self.begin_anchor(name);
+        }
+        let exit = self.emit(RootYAMLValue::RawBlock(&self.on[at..end]), at..end);
+        Some((end, exit))
+    }
+
+    /// Parses the flow collection at `at` that is the value of a block entry at `column`. If it is
+    /// not closed, it only continues over the lines nested under the entry, so the error is at the
+    /// end of those lines and (for `parse_lenient`) parsing continues with the next entry
+    fn block_flow_collection(
+        &mut self,
+        at: usize,
+        column: usize,
+    ) -> Result<(usize, bool), YAMLParseError> {
+        let on = self.on;
+        if flow_collection_len(&on[at..]).is_some() {
+            return self.flow_collection(at, 0);
+        }
+        self.on = &on[..nested_block_end(on, at, column, false, self.options)];
+        let result = self.flow_collection(at, 0);
+        self.on = on;
+        result
+    }
+
+    fn flow_collection(
+        &mut self,
+        at: usize,
+        nesting: usize,
+    ) -> Result<(usize, bool), YAMLParseError> {
+        if nesting >= MAX_FLOW_NESTING {
+            return Err(YAMLParseError::new(
+                YAMLParseErrorReason::MaxDepthExceeded,
+                at..at + 1,
+            ));
+        }
+        let on = self.on;
+        let (is_mapping, closing, unclosed) = if on[at..].starts_with('{') {
+            (true, '}', YAMLParseErrorReason::ExpectedBrace)
+        } else {
+            (false, ']', YAMLParseErrorReason::ExpectedBracket)
+        };
+        let mut idx = at + '['.len_utf8();
+        let mut list_idx: usize = 0;
+        loop {
+            idx = self.skip_flow_whitespace(idx);
+            if on[idx..].starts_with(closing) {
+                let end = idx + closing.len_utf8();
+                let exit = list_idx == 0
+                    && self.emit_node(Emitted::EmptyCollection { is_mapping }, at..end);
+                return Ok((end, exit));
+            }
+
+            if is_mapping {
+                let end = idx + flow_scalar_end(&on[idx..], &[':', ',', closing]);
+                let key = trim_whitespace(&on[idx..end]);
+                if key.is_empty() {
+                    return Err(YAMLParseError::new(
+                        YAMLParseErrorReason::ExpectedFlowKey,
+                        idx..idx,
+                    ));
+                }
+                if key.starts_with(['"', '\'']) && quoted_end(key).is_none() {
+                    let key_at = offset_in(on, key);
+                    return Err(YAMLParseError::new(
+                        YAMLParseErrorReason::ExpectedClosingQuote,
+                        key_at..key_at + key.len(),
+                    ));
+                }
+                if !on[end..].starts_with(':') {
+                    return Err(YAMLParseError::new(
+                        YAMLParseErrorReason::ExpectedColon,
+                        end..end,
+                    ));
+                }
+                self.push_key(unquote(key), None)?;
+                idx = self.skip_flow_whitespace(end + ':'.len_utf8());
+            } else {
+                self.key_chain.push(YAMLKey::Index(list_idx));
+                self.check_depth(idx..idx)?;
+            }
+
+            let (end, exit) = match on[idx..].chars().next() {
+                Some('[' | '{') => match self.raw_flow_collection(idx, None) {
+                    Some(result) => result,
+                    None => self.flow_collection(idx, nesting + 1)?,
+                },
+                Some(_) => {
+                    let end = idx + flow_scalar_end(&on[idx..], &[',', closing]);
+                    let value = trim_whitespace(&on[idx..end]);
+                    if value.is_empty() && !is_mapping {
+                        return Err(YAMLParseError::new(
+                            YAMLParseErrorReason::ExpectedValue,
+                            idx..idx,
+                        ));
+                    }
+                    (end, self.emit_scalar(value)?)
+                }
+                None => return Err(YAMLParseError::new(unclosed, idx..idx)),
+            };
+            self.key_chain.pop();
+            if exit {
+                return Ok((end, true));
+            }
+            list_idx += 1;
+
+            idx = self.skip_flow_whitespace(end);
+            match on[idx..].chars().next() {
+                Some(',') => idx += ','.len_utf8(),
+                Some(chr) if chr == closing => return Ok((idx + closing.len_utf8(), false)),
+                Some(_) => {
+                    return Err(YAMLParseError::new(
+                        YAMLParseErrorReason::ExpectedEndOfValue,
+                        idx..idx,
+                    ))
+                }
+                None => return Err(YAMLParseError::new(unclosed, idx..idx)),
+            }
+        }
+    }
+}
+
+/// Checks the start of a plain scalar (after its properties). Indicators cannot start one, apart
+/// from `-`, `?` and `:` followed by a non-space character
+fn check_plain_start(on: &str, value: &str) -> Result<(), YAMLParseError> {
+    let mut chars = value.chars();
+    let Some(first) = chars.next() else {
+        return Ok(());
+    };
+    let followed_by_space = chars.next().is_none_or(is_yaml_whitespace);
+    let reason = match first {
+        '@' | '`' => YAMLParseErrorReason::ReservedIndicator,
+        '|' | '>' => YAMLParseErrorReason::InvalidBlockScalarHeader,
+        '&' | '*' if followed_by_space => YAMLParseErrorReason::ExpectedAnchorName,
+        ',' | '[' | ']' | '{' | '}' | '%' => YAMLParseErrorReason::UnexpectedIndicator,
+        '-' | '?' | ':' if followed_by_space => YAMLParseErrorReason::UnexpectedIndicator,
+        _ => return Ok(()),
+    };
+    let at = offset_in(on, value);
+    Err(YAMLParseError::new(reason, at..at + first.len_utf8()))
+}
+
+/// For [`ParseOptions::strict`], checks a plain value is not typed differently under YAML 1.1 and
+/// does not contain a `:`
+fn check_strict_scalar(on: &str, value: &str) -> Result<(), YAMLParseError> {
+    let at = offset_in(on, value);
+    if let Some(colon) = value.find(':') {
+        let span = at + colon..at + colon + ':'.len_utf8();
+        return Err(YAMLParseError::new(
+            YAMLParseErrorReason::UnquotedColon,
+            span,
+        ));
+    }
+    let yaml11_bool = ["yes", "no", "on", "off", "y", "n"]
+        .iter()
+        .any(|word| value.eq_ignore_ascii_case(word));
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let leading_zero = digits.len() > 1
+        && digits.starts_with('0')
+        && digits.bytes().all(|byte| byte.is_ascii_digit());
+    if yaml11_bool || leading_zero {
+        return Err(YAMLParseError::new(
+            YAMLParseErrorReason::AmbiguousScalar,
+            at..at + value.len(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that a plain value of a block mapping entry does not contain a `:` followed by a space,
+/// which would start a second entry on the same line (`key: value other: more`). Quoted values and
+/// aliases are checked when they are emitted
+fn check_single_entry(on: &str, value: &str) -> Result<(), YAMLParseError> {
+    let (_, _, value) = split_properties(value);
+    if value.starts_with(['"', '\'', '*']) {
+        return Ok(());
+    }
+    let start = offset_in(on, value);
+    // a `:` at the start is checked with the other indicators when emitted
+    let colon = value
+        .match_indices(':')
+        .skip_while(|(idx, _)| *idx == 0)
+        .map(|(idx, _)| start + idx)
+        .find(|idx| is_mapping_colon(on, *idx));
+    match colon {
+        Some(at) => Err(YAMLParseError::new(
+            YAMLParseErrorReason::ExpectedEndOfValue,
+            at..at + ':'.len_utf8(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks that there is only whitespace and a comment after a value ending at `at`
+fn expect_end_of_line(on: &str, at: usize) -> Result<(), YAMLParseError> {
+    let line_end = on[at..].find('\n').map_or(on.len(), |end| at + end);
+    let rest = &on[at..line_end];
+    let content = trim_whitespace(rest);
+    let is_comment = content.starts_with('#') && rest.starts_with(is_yaml_whitespace);
+    if content.is_empty() || is_comment {
+        Ok(())
+    } else {
+        let content_at = offset_in(on, content);
+        Err(YAMLParseError::new(
+            YAMLParseErrorReason::ExpectedEndOfValue,
+            content_at..content_at + content.len(),
+        ))
+    }
+}
+
+/// YAML only treats spaces, tabs and line breaks as whitespace (not for example non-breaking spaces)
+fn is_yaml_whitespace(chr: char) -> bool {
+    matches!(chr, ' ' | '\t' | '\r' | '\n')
+}
+
+/// Removes leading and trailing YAML whitespace (spaces, tabs and line breaks). Unlike [`str::trim`],
+/// other Unicode whitespace is kept and a leading whitespace character followed by a combining
+/// character (such as U+0301) is kept, as together they form a single grapheme
+#[must_use]
+pub fn trim_whitespace(on: &str) -> &str {
+    let on = on.trim_end_matches(is_yaml_whitespace);
+    let start = on.len() - on.trim_start_matches(is_yaml_whitespace).len();
+    let start = match (on[..start].chars().next_back(), on[start..].chars().next()) {
+        (Some(last), Some(next)) if is_combining(next) => start - last.len_utf8(),
+        _ => start,
+    };
+    &on[start..]
+}
+
+/// Characters that extend the previous character into one grapheme: combining marks, zero width
+/// joiners and variation selectors
+fn is_combining(chr: char) -> bool {
+    matches!(
+        chr,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{200C}'..='\u{200D}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+fn skip_whitespace(on: &str, idx: usize) -> usize {
+    idx + (on[idx..].len() - on[idx..].trim_start_matches(is_yaml_whitespace).len())
+}
+
+/// Finds the end of a scalar inside a flow collection: the first of `terminators` (or a comment)
+/// after any quoted part
+fn flow_scalar_end(on: &str, terminators: &[char]) -> usize {
+    let leading = on.len() - on.trim_start_matches(is_yaml_whitespace).len();
+    let from = quoted_end(&on[leading..]).map_or(0, |end| leading + end);
+    let mut last = None;
+    for (idx, chr) in on[from..].char_indices() {
+        let is_comment = chr == '#' && last.is_some_and(is_yaml_whitespace);
+        if is_comment || terminators.contains(&chr) {
+            return from + idx;
+        }
+        last = Some(chr);
+    }
+    on.len()
+}
+
+/// The length of the flow collection at the start of `on`, up to and including its closing
+/// bracket. `None` if it is not closed
+fn flow_collection_len(on: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut last = None;
+    let mut idx = 0;
+    while let Some(chr) = on[idx..].chars().next() {
+        let at_token_start =
+            last.is_none_or(|last| is_yaml_whitespace(last) || "[{,:".contains(last));
+        match chr {
+            '[' | '{' => depth += 1,
+            ']' | '}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(idx + chr.len_utf8());
+                }
+            }
+            '"' | '\'' if at_token_start => {
+                idx += quoted_end(&on[idx..])?;
+                last = Some(chr);
+                continue;
+            }
+            '#' if last.is_some_and(is_yaml_whitespace) => {
+                idx += on[idx..].find('\n')?;
+                continue;
+            }
+            _ => {}
+        }
+        last = Some(chr);
+        idx += chr.len_utf8();
+    }
+    None
+}
+
+/// Whether a line of a block sequence item (without a comment) is a mapping entry, with a `:`
+/// followed by whitespace (after any quoted key)
+fn has_mapping_colon(line: &str) -> bool {
+    let from = quoted_end(line).unwrap_or(0);
+    line[from..]
+        .match_indices(':')
+        .any(|(idx, _)| is_mapping_colon(line, from + idx))
+}
+
+/// Splits a trailing `# comment` off a line, returning the text after the `#`. A `#`
+/// only starts a comment at the beginning of the line or after whitespace
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    if !line.contains('#') {
+        return (line, None);
+    }
+    let mut idx = 0;
+    let mut last = None;
+    while let Some(chr) = line[idx..].chars().next() {
+        let at_token_start = last.is_none_or(is_yaml_whitespace);
+        if at_token_start && chr == '#' {
+            return (
+                line[..idx].trim_end_matches(is_yaml_whitespace),
+                Some(&line[idx + '#'.len_utf8()..]),
+            );
+        }
+        if let (true, Some(end)) = (at_token_start, quoted_end(&line[idx..])) {
+            idx += end;
+        } else {
+            idx += chr.len_utf8();
+        }
+        last = Some(chr);
+    }
+    (line, None)
+}
+
+/// A mapping key with any escapes decoded. Keys are the slice of `on` between their quotes, so a
+/// key with a quote on both sides was quoted
+pub(crate) fn decode_key<'a>(on: &'a str, key: &'a str) -> Cow<'a, str> {
+    let start = (key.as_ptr() as usize).wrapping_sub(on.as_ptr() as usize);
+    let end = start.wrapping_add(key.len());
+    let (Some(before), Some(after)) = (on.get(..start), on.get(end..)) else {
+        return Cow::Borrowed(key);
+    };
+    match (before.chars().next_back(), after.chars().next()) {
+        (Some(quote @ ('"' | '\'')), Some(closing)) if quote == closing => QuotedString {
+            on: key,
+            double: quote == '"',
+        }
+        .decode(),
+        _ => Cow::Borrowed(key),
+    }
+}
+
+/// Byte offset of `part`, which is a slice of `on`
+fn offset_in(on: &str, part: &str) -> usize {
+    part.as_ptr() as usize - on.as_ptr() as usize
+}
+
+/// For a value starting with a quote, finds the offset after the closing quote
+fn quoted_end(on: &str) -> Option<usize> {
+    let quote = on.chars().next().filter(|chr| matches!(chr, '"' | '\''))?;
+    closing_quote_end(&on[quote.len_utf8()..], quote).map(|end| quote.len_utf8() + end)
+}
+
+/// For the text after an opening `quote`, finds the offset after the closing quote
+fn closing_quote_end(on: &str, quote: char) -> Option<usize> {
+    let mut chars = on.char_indices();
+    while let Some((idx, chr)) = chars.next() {
+        if chr == '\\' && quote == '"' {
+            chars.next();
+        } else if chr == quote {
+            if quote == '\'' && on[idx + 1..].starts_with('\'') {
+                chars.next();
+            } else {
+                return Some(idx + 1);
+            }
+        }
+    }
+    None
+}
+
+/// Removes surrounding quotes (without decoding)
+fn unquote(on: &str) -> &str {
+    if quoted_end(on) == Some(on.len()) {
+        &on[1..on.len() - 1]
+    } else {
+        on
+    }
+}
+
+/// Whether the `:` at `idx` separates a key from its value, rather than being part of a plain
+/// scalar (such as `http://example.com:8080`). It must be followed by whitespace or the end
+fn is_mapping_colon(on: &str, idx: usize) -> bool {
+    on.get(idx + ':'.len_utf8()..)
+        .unwrap_or_default()
+        .chars()
+        .next()
+        .is_none_or(is_yaml_whitespace)
+}
+
+/// How far [`parse_with_exit_signal`] got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parsed {
+    /// Byte offset after the value the callback stopped on, or the length of the input if it was
+    /// not stopped. The rest of the input (`&on[offset..]`) can then be processed separately
+    pub offset: usize,
+    /// Whether the callback stopped parsing
+    pub exited: bool,
+}
+
+/// [`parse`] where returning `true` from the callback stops parsing
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse_with_exit_signal<'a>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>) -> bool,
+    options: &ParseOptions,
+) -> Result<Parsed, YAMLParseError> {
+    let mut parsed = Parsed {
+        offset: on.len(),
+        exited: false,
+    };
+    parse_block(
+        on,
+        |keys, value, spans| {
+            parsed.exited = cb(keys, value);
+            if parsed.exited {
+                parsed.offset = spans.value.end;
+            }
+            parsed.exited.into()
+        },
+        |_, _| {},
+        Err,
+        options,
+    )
+    .map_err(|err| err.locate(on))?;
+    Ok(parsed)
+}
+
+/// [`parse_with_exit_signal`] without heap allocation, for targets without an allocator to spare. The key
+/// chain is stored in an array of `MAX_DEPTH` keys and nesting deeper than that returns
+/// [`YAMLParseErrorReason::MaxDepthExceeded`]. Uses the default [`ParseOptions`] (so aliases are not resolved
+/// and duplicate keys are not checked, which both allocate). Decoding escapes in quoted strings
+/// ([`QuotedString::decode`]) and resolving block scalars in the callback still allocate
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input or nesting is deeper than `MAX_DEPTH`
+pub fn parse_fixed<'a, const MAX_DEPTH: usize>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>) -> bool,
+) -> Result<Parsed, YAMLParseError> {
+    let options = ParseOptions::new().max_depth(MAX_DEPTH);
+    let mut parsed = Parsed {
+        offset: on.len(),
+        exited: false,
+    };
+    parse_block_with_keys(
+        on,
+        KeyChain::<MAX_DEPTH>::fixed(),
+        only_values(|keys, value, spans| {
+            parsed.exited = cb(keys, value);
+            if parsed.exited {
+                parsed.offset = spans.value.end;
+            }
+            parsed.exited.into()
+        }),
+        |_, _| {},
+        Err,
+        None,
+        &options,
+    )
+    .map_err(|err| err.locate(on))?;
+    Ok(parsed)
 }
 
+/// Error from [`try_parse_with_exit_signal`]
 #[derive(Debug)]
-pub enum YAMLParseErrorReason {
-    ExpectedColon,
-    ExpectedEndOfValue,
-    ExpectedBracket,
-    ExpectedTrueFalseNull,
-    ExpectedValue,
+pub enum ParseOrUserError<E> {
+    Parse(YAMLParseError),
+    /// Returned by the callback. `span` is the value it was called with
+    User {
+        error: E,
+        span: Range<usize>,
+    },
 }
 
-#[derive(Debug)]
-pub struct YAMLParseError {
-    pub at: usize,
-    pub reason: YAMLParseErrorReason,
+#[cfg(feature = "std")]
+impl<E: std::error::Error> std::error::Error for ParseOrUserError<E> {}
+
+impl<E: core::fmt::Display> core::fmt::Display for ParseOrUserError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            ParseOrUserError::Parse(err) => core::fmt::Display::fmt(err, f),
+            ParseOrUserError::User { error, span } => {
+                f.write_fmt(format_args!("{error} (at {}..{})", span.start, span.end))
+            }
+        }
+    }
 }
 
-impl std::error::Error for YAMLParseError {}
+/// [`parse_with_exit_signal`] where the callback can return an error, which stops parsing
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input or if the callback returns an error
+pub fn try_parse_with_exit_signal<'a, E>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>) -> Result<bool, E>,
+    options: &ParseOptions,
+) -> Result<(), ParseOrUserError<E>> {
+    let mut user_error = None;
+    let result = parse_with_spans(
+        on,
+        |keys, value, spans| match cb(keys, value) {
+            Ok(exit) => exit,
+            Err(error) => {
+                user_error = Some(ParseOrUserError::User {
+                    error,
+                    span: spans.value,
+                });
+                true
+            }
+        },
+        options,
+    );
+    match user_error {
+        Some(err) => Err(err),
+        None => result.map_err(ParseOrUserError::Parse),
+    }
+}
 
-impl std::fmt::Display for YAMLParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        f.write_fmt(format_args!(
-            "YAMLParseError: {:?} at {:?}",
-            self.reason, self.at
-        ))
+/// What the parser should do after a value, for [`parse_with_signal`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Continue,
+    /// Skip the rest of the mapping or sequence that contains the value (its later entries and
+    /// everything under them). Skipped lines in block collections are not parsed
+    SkipSubtree,
+    /// Stop parsing
+    Stop,
+}
+
+/// `true` is the exit signal of [`parse_with_exit_signal`]
+impl From<bool> for Signal {
+    fn from(exit: bool) -> Self {
+        if exit {
+            Signal::Stop
+        } else {
+            Signal::Continue
+        }
     }
 }
 
-/// If you want to return early (not parse the whole input) use [`parse_with_exit_signal`]
+/// [`parse_with_exit_signal`] where the callback can also skip parts of the input with [`Signal::SkipSubtree`]
 ///
 /// # Errors
 /// Returns an error if it tries to parse invalid YAML input
-pub fn parse<'a>(
+pub fn parse_with_signal<'a>(
     on: &'a str,
-    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>),
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>) -> Signal,
+    options: &ParseOptions,
 ) -> Result<(), YAMLParseError> {
-    parse_with_exit_signal(
+    parse_block(
         on,
-        |k, v| {
-            cb(k, v);
-            false
-        },
-        &ParseOptions::default(),
+        |keys, value, _| cb(keys, value),
+        |_, _| {},
+        Err,
+        options,
     )
+    .map_err(|err| err.locate(on))
 }
 
-/// For `|` and `>` based values
-#[derive(Debug, PartialEq, Eq)]
-pub struct MultilineString<'a> {
+/// Whether `line` is a `---` or `...` document marker
+fn is_document_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(is_yaml_whitespace))
+}
+
+/// Iterator over the documents in a stream, from [`documents`]
+pub struct Documents<'a> {
     on: &'a str,
-    /// replace new lines with spaces. Done using `>`
-    collapse: bool,
-    /// with `|+` etc
-    preserve_leading_whitespace: bool,
+    position: usize,
+    /// Start of the current document and whether it began with `---`
+    current: Option<(usize, bool)>,
+    /// Whether the current document has any non-comment content
+    has_content: bool,
+    /// Start of the `%` directive lines before the next `---`, which are part of the next document
+    directives: Option<usize>,
 }
 
-pub struct ParseOptions {
-    pub indent_size: usize,
+impl<'a> Documents<'a> {
+    fn take_current(&mut self, end: usize) -> Option<(usize, &'a str)> {
+        let (start, explicit) = self.current.take()?;
+        let has_content = core::mem::take(&mut self.has_content);
+        (explicit || has_content).then(|| (start, &self.on[start..end]))
+    }
 }
 
-impl Default for ParseOptions {
-    fn default() -> Self {
-        Self { indent_size: 2 }
+impl<'a> Iterator for Documents<'a> {
+    /// The offset of the document in the stream and its source
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.on.len() {
+            let line_start = self.position;
+            let line_end = self.on[line_start..]
+                .find('\n')
+                .map_or(self.on.len(), |idx| line_start + idx + '\n'.len_utf8());
+            self.position = line_end;
+
+            let line = &self.on[line_start..line_end];
+            if is_document_marker(line, "---") {
+                let directives = self.directives.take();
+                let document = self.take_current(directives.unwrap_or(line_start));
+                // content after the `---` (such as a block scalar header) is part of the document
+                let (after, _) = split_comment(trim_whitespace(&line[3..]));
+                let start = if after.is_empty() {
+                    line_end
+                } else {
+                    line_start
+                };
+                self.current = Some((directives.unwrap_or(start), true));
+                if document.is_some() {
+                    return document;
+                }
+            } else if is_document_marker(line, "...") {
+                let document = self.take_current(line_start);
+                if document.is_some() {
+                    return document;
+                }
+            } else if line.starts_with('%') && !self.has_content {
+                self.directives.get_or_insert(line_start);
+            } else {
+                let trimmed = trim_whitespace(line);
+                let is_content = !trimmed.is_empty() && !trimmed.starts_with('#');
+                // directives followed by content (rather than `---`) are part of it
+                let directives = if is_content {
+                    self.directives.take()
+                } else {
+                    None
+                };
+                match (&mut self.current, directives) {
+                    (None, directives) => {
+                        self.current = Some((directives.unwrap_or(line_start), false));
+                    }
+                    (Some((start, _)), Some(directives)) => *start = (*start).min(directives),
+                    (Some(_), None) => {}
+                }
+                self.has_content |= is_content;
+            }
+        }
+        self.take_current(self.on.len())
+    }
+}
+
+/// Splits a stream on `---` separators and `...` terminators. Leading content is a document if
+/// it is not just comments. `%` directive lines are part of the document after them
+#[must_use]
+pub fn documents(on: &str) -> Documents<'_> {
+    Documents {
+        on,
+        position: byte_order_mark_len(on),
+        current: None,
+        has_content: false,
+        directives: None,
     }
 }
 
+/// Parses each document in a (`---` separated) stream. The callback receives the index of the document
+///
 /// # Errors
 /// Returns an error if it tries to parse invalid YAML input
-#[allow(clippy::too_many_lines)]
-pub fn parse_with_exit_signal<'a>(
+pub fn parse_documents<'a>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(usize, &'b [YAMLKey<'a>], RootYAMLValue<'a>) -> bool,
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    options.check_input_len(on).map_err(|err| err.locate(on))?;
+    for (index, (offset, document)) in documents(on).enumerate() {
+        let parsed =
+            parse_with_exit_signal(document, |keys, value| cb(index, keys, value), options)
+                .map_err(|err| err.offset_by(offset).locate(on))?;
+        if parsed.exited {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// [`parse_with_exit_signal`] which also passes `# comments` to `on_comment` when [`ParseOptions::emit_comments`]
+/// is set. `on_comment` receives the position of the `#` and the text after it (up to the end of the line)
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse_with_comments<'a>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>) -> bool,
+    on_comment: impl FnMut(usize, &'a str),
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    parse_block(
+        on,
+        |keys, value, _| cb(keys, value).into(),
+        on_comment,
+        Err,
+        options,
+    )
+    .map_err(|err| err.locate(on))
+}
+
+/// [`parse_with_exit_signal`] which also passes non-fatal findings to `on_warning`: tabs in
+/// indentation (when allowed by [`ParseOptions::tabs`]), trailing whitespace, inconsistent
+/// indentation and (without [`ParseOptions::deny_duplicate_keys`]) duplicate keys. Trailing
+/// whitespace is reported before parsing, other warnings as they are found
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse_with_warnings<'a>(
     on: &'a str,
     mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>) -> bool,
+    mut on_warning: impl FnMut(YAMLWarning),
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    parse_block_with_keys(
+        on,
+        KeyChain::<INLINE_DEPTH>::new(),
+        only_values(|keys, value, _| cb(keys, value).into()),
+        |_, _| {},
+        Err,
+        Some(&mut on_warning),
+        options,
+    )
+    .map_err(|err| err.locate(on))
+}
+
+/// Byte ranges of an emitted value, from [`parse_with_spans`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueSpans {
+    /// The last key. For sequence items (where the last key is a [`YAMLKey::Index`]) this is the same as `value`
+    pub key: Range<usize>,
+    /// Includes quotes for quoted values. Empty for values left blank
+    pub value: Range<usize>,
+}
+
+/// [`parse_with_exit_signal`] where the callback also receives the spans of the key and value
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse_with_spans<'a>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>, ValueSpans) -> bool,
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    parse_block(
+        on,
+        |keys, value, spans| cb(keys, value, spans).into(),
+        |_, _| {},
+        Err,
+        options,
+    )
+    .map_err(|err| err.locate(on))
+}
+
+/// [`parse_with_exit_signal`] where the callback also receives the ordinal of each key: its position
+/// among the entries of its parent mapping or sequence (so the same as the index for
+/// [`YAMLKey::Index`]). Ordinals are counted from the values emitted, so entries whose values emit
+/// nothing (empty collections) are not counted
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse_with_ordinals<'a>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], &'b [usize], RootYAMLValue<'a>) -> bool,
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    let mut previous: Vec<YAMLKey<'a>> = Vec::new();
+    let mut ordinals: Vec<usize> = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |keys, value| {
+            let shared = keys
+                .iter()
+                .zip(&previous)
+                .take_while(|(key, previous)| key == previous)
+                .count();
+            // the first new key follows the previous key at its depth
+            if let Some(ordinal) = ordinals.get_mut(shared) {
+                if shared < keys.len() {
+                    *ordinal += 1;
+                }
+            }
+            ordinals.truncate((shared + 1).min(keys.len()));
+            ordinals.resize(keys.len(), 0);
+            previous.clear();
+            previous.extend_from_slice(keys);
+            cb(keys, &ordinals, value)
+        },
+        options,
+    )?;
+    Ok(())
+}
+
+/// Parses the whole input, continuing after errors and returning all of them. After an error the rest of
+/// the line is skipped, so values may be missing or (for errors in multi-line flow collections) extra.
+/// An unclosed flow collection ends at the next line that is not nested under its entry
+#[must_use]
+pub fn parse_lenient<'a>(
+    on: &'a str,
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>),
+    options: &ParseOptions,
+) -> Vec<YAMLParseError> {
+    let mut errors = Vec::new();
+    let on_error = |err: YAMLParseError| {
+        errors.push(err.locate(on));
+        Ok(())
+    };
+    let result = parse_block(
+        on,
+        |keys, value, _| {
+            cb(keys, value);
+            Signal::Continue
+        },
+        |_, _| {},
+        on_error,
+        options,
+    );
+    // `on_error` does not return errors
+    debug_assert!(result.is_ok());
+    errors
+}
+
+/// For `parse_block`: returns `None` if there was an error which `on_error` accepted
+fn recover<T>(
+    result: Result<T, YAMLParseError>,
+    on_error: &mut impl FnMut(YAMLParseError) -> Result<(), YAMLParseError>,
+) -> Result<Option<T>, YAMLParseError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) => on_error(err).map(|()| None),
+    }
+}
+
+fn parse_block<'a>(
+    on: &'a str,
+    cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>, ValueSpans) -> Signal,
+    on_comment: impl FnMut(usize, &'a str),
+    on_error: impl FnMut(YAMLParseError) -> Result<(), YAMLParseError>,
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    parse_structure(on, only_values(cb), on_comment, on_error, options)
+}
+
+/// [`parse_block`] which also passes empty collections and the start and end of documents
+fn parse_structure<'a>(
+    on: &'a str,
+    cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], Emitted<'a>, ValueSpans) -> Signal,
+    on_comment: impl FnMut(usize, &'a str),
+    on_error: impl FnMut(YAMLParseError) -> Result<(), YAMLParseError>,
     options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    let key_chain = KeyChain::<INLINE_DEPTH>::new();
+    parse_block_with_keys(on, key_chain, cb, on_comment, on_error, None, options)
+}
+
+/// Adapts a callback of values to [`parse_structure`]
+fn only_values<'a>(
+    mut cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>, ValueSpans) -> Signal,
+) -> impl for<'b> FnMut(&'b [YAMLKey<'a>], Emitted<'a>, ValueSpans) -> Signal {
+    move |keys: &[YAMLKey<'a>], node, spans| match node {
+        Emitted::Value(value) => cb(keys, value, spans),
+        _ => Signal::Continue,
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+fn parse_block_with_keys<'a, 'o, const N: usize>(
+    on: &'a str,
+    key_chain: KeyChain<'a, N>,
+    cb: impl for<'b> FnMut(&'b [YAMLKey<'a>], Emitted<'a>, ValueSpans) -> Signal,
+    on_comment: impl FnMut(usize, &'a str),
+    mut on_error: impl FnMut(YAMLParseError) -> Result<(), YAMLParseError>,
+    on_warning: Option<&'o mut (dyn FnMut(YAMLWarning) + 'o)>,
+    options: &'o ParseOptions,
 ) -> Result<(), YAMLParseError> {
     enum State {
         Value,
         Identifier,
         ListItem,
         Multiline {
-            collapse: bool,
-            preserve_leading_whitespace: bool,
+            style: BlockStyle,
+            chomping: Chomping,
+            indentation: Option<usize>,
             indent: usize,
         },
+        Comment,
+        /// `---` and `...` lines. Use [`parse_documents`] to separate documents
+        DocumentMarker,
         Skip,
     }
 
-    let chars = on.char_indices();
-
-    let mut key_chain = Vec::new();
-    let mut state = State::Identifier;
-    let mut list_idx: usize = 0;
+    options.check_input_len(on)?;
+    let mut emitter = Emitter {
+        on,
+        key_chain,
+        anchors: Vec::new(),
+        skip: None,
+        alias_expansions: 0,
+        expanded_values: 0,
+        seen_keys: BTreeMap::new(),
+        indent_step: None,
+        value_column: None,
+        on_warning,
+        in_document: false,
+        options,
+        cb,
+        on_comment,
+    };
+    if emitter.on_warning.is_some() {
+        warning::trailing_whitespace(on, |kind, span| emitter.warn(kind, span));
+    }
+    let mut state = State::Skip;
     let mut indent = 0;
-    let mut start = 0;
+    // The column of the key whose value is being parsed
+    let mut key_column = 0;
+    // The column of the `-` of the current sequence item
+    let mut item_column = 0;
+    let mut start = byte_order_mark_len(on);
+    let mut idx = start;
+    // Set after parsing a flow collection, which consumes input ahead of `idx`
+    let mut skip_to = 0;
+
+    loop {
+        // The end of the input is treated as a newline, so that the last line is handled the same
+        // whether or not the input ends with one
+        let chr = match on.get(idx..).map(|rest| rest.chars().next()) {
+            Some(Some(chr)) => chr,
+            Some(None) => '\n',
+            None => break,
+        };
+        // Other than `Skip`, states only act on a few characters. Jumping to them avoids per
+        // character work in keys and values
+        let significant: &[u8] = match state {
+            // `-`, `[` and `{` only start collections at the start of the value (or after an anchor)
+            State::Value => match on[start..idx]
+                .trim_start_matches(is_yaml_whitespace)
+                .chars()
+                .next()
+            {
+                None | Some('&' | '!') => b"\n-[{",
+                Some(_) => b"\n",
+            },
+            State::Identifier => b":\n",
+            State::ListItem => b"\n:-[{",
+            State::Multiline { .. } | State::Comment | State::DocumentMarker => b"\n",
+            State::Skip => b"",
+        };
+        let is_significant = u8::try_from(chr).is_ok_and(|byte| significant.contains(&byte));
+        if !significant.is_empty() && !is_significant && idx < on.len() {
+            idx = find_any(&on[idx..], significant).map_or(on.len(), |next| idx + next);
+            continue;
+        }
 
-    for (idx, chr) in chars {
         match state {
             State::Value => {
-                let rest_of_line = on[start..idx].trim();
-                if let (true, '-') = (rest_of_line.is_empty(), chr) {
+                let rest_of_line = trim_whitespace(&on[start..idx]);
+                let after = on.get(idx + chr.len_utf8()..).unwrap_or_default();
+                let is_list_item = after.starts_with(is_yaml_whitespace);
+                let line_start = on[..idx].rfind('\n').map_or(0, |idx| idx + '\n'.len_utf8());
+                // only the value of an explicit key (`: - item`) can be a sequence starting on
+                // its line
+                let is_explicit_value = on[line_start..idx]
+                    .trim_start_matches(is_yaml_whitespace)
+                    .starts_with(':');
+                if let (true, '-', true, false) = (
+                    rest_of_line.is_empty(),
+                    chr,
+                    is_list_item,
+                    is_explicit_value,
+                ) {
+                    let error = YAMLParseError::new(
+                        YAMLParseErrorReason::UnexpectedIndicator,
+                        idx..idx + '-'.len_utf8(),
+                    );
+                    on_error(error)?;
+                    skip_to = on[idx..].find('\n').map_or(on.len(), |end| idx + end);
+                    state = State::Skip;
+                } else if let (true, '-', true) = (rest_of_line.is_empty(), chr, is_list_item) {
+                    item_column = key_column + 1 + indent_width(&on[start..idx], options);
+                    recover(emitter.push_block_item(item_column, idx), &mut on_error)?;
                     state = State::ListItem;
                     start = idx + '-'.len_utf8();
+                    if let Some((end, exit)) =
+                        emitter.unparsed_entry(idx + '-'.len_utf8(), item_column, true)
+                    {
+                        if exit {
+                            return Ok(());
+                        }
+                        skip_to = end;
+                        state = State::Skip;
+                    }
+                } else if let ('[' | '{', (_, _, "")) = (chr, split_properties(rest_of_line)) {
+                    if emitter.properties(rest_of_line).1 {
+                        return Ok(());
+                    }
+                    let depth = emitter.key_chain.depth();
+                    match emitter.block_flow_collection(idx, key_column) {
+                        Ok((_, true)) => return Ok(()),
+                        Ok((end, false)) => {
+                            skip_to = end;
+                            if recover(expect_end_of_line(on, end), &mut on_error)?.is_none() {
+                                skip_to = on[end..]
+                                    .find('\n')
+                                    .map_or(on.len(), |line_end| end + line_end);
+                            }
+                        }
+                        Err(err) => {
+                            // continue from the end of the line with the error
+                            emitter.key_chain.truncate(depth);
+                            skip_to = on[err.at..].find('\n').map_or(on.len(), |end| err.at + end);
+                            on_error(err)?;
+                        }
+                    }
+                    state = State::Skip;
                 } else if let '\n' = chr {
+                    let (rest_of_line, comment) = split_comment(rest_of_line);
+                    // tags on collections and block scalars are skipped
+                    let rest_of_line = match split_properties(rest_of_line) {
+                        (_, _, value)
+                            if value.is_empty() || block_scalar_header(value).is_some() =>
+                        {
+                            let (value, exit) = emitter.properties(rest_of_line);
+                            if exit {
+                                return Ok(());
+                            }
+                            value
+                        }
+                        _ => rest_of_line,
+                    };
                     if rest_of_line.is_empty() {
+                        if emitter.empty_value(idx, key_column, start) {
+                            return Ok(());
+                        }
                         // ready for identifier
                         state = State::Skip;
-                    } else {
-                        let modifier = match rest_of_line {
-                            "|" => Some((true, false)),
-                            ">" => Some((false, false)),
-                            _ => None,
+                    } else if let Some((style, chomping, indentation)) =
+                        block_scalar_header(rest_of_line)
+                    {
+                        state = State::Multiline {
+                            style,
+                            chomping,
+                            indentation: indentation.map(|indentation| key_column + indentation),
+                            indent: key_column,
                         };
-                        if let Some((collapse, preserve_leading_whitespace)) = modifier {
-                            state = State::Multiline {
-                                collapse,
-                                preserve_leading_whitespace,
-                                indent,
-                            };
-                            start = idx;
-                        } else {
-                            let value = on[start..idx].trim();
-                            let value = match value {
-                                "true" => RootYAMLValue::True,
-                                "false" => RootYAMLValue::False,
-                                value => RootYAMLValue::String(value),
-                            };
-                            cb(&key_chain, value);
-                            key_chain.pop();
+                        start = idx;
+                        if block_scalar_ends(on, idx, key_column) {
+                            let header = (style, chomping, indentation);
+                            let value = MultilineString::new("", header, options);
+                            if emitter.emit(RootYAMLValue::MultilineString(value), idx..idx) {
+                                return Ok(());
+                            }
                             state = State::Skip;
                         }
+                    } else {
+                        let value = check_single_entry(on, rest_of_line)
+                            .and_then(|()| emitter.emit_scalar(rest_of_line));
+                        if let Some(true) = recover(value, &mut on_error)? {
+                            return Ok(());
+                        }
+                        state = State::Skip;
+                    }
+                    if let Some(comment) = comment {
+                        emitter.comment(offset_in(on, comment) - '#'.len_utf8(), comment);
                     }
                     indent = 0;
                 }
             }
             State::Multiline {
-                collapse,
-                preserve_leading_whitespace,
+                style,
+                chomping,
+                indentation,
                 indent: current_indent,
             } => {
                 if let '\n' = chr {
-                    let upcoming_line = &on[(idx + '\n'.len_utf8())..];
-                    let mut upcoming_indent = 0;
-                    let mut is_empty = false;
-                    for chr in upcoming_line.chars() {
-                        if let '\n' = chr {
-                            is_empty = true;
-                            break;
-                        }
-                        if let '\t' | ' ' = chr {
-                            upcoming_indent += 1;
-                        } else {
-                            break;
+                    if block_scalar_ends(on, idx, current_indent) {
+                        let multiline_string = MultilineString::new(
+                            &on[start..idx],
+                            (style, chomping, indentation),
+                            options,
+                        );
+                        let value = RootYAMLValue::MultilineString(multiline_string);
+                        if emitter.emit(value, start..idx) {
+                            return Ok(());
                         }
-                    }
-                    if !is_empty && upcoming_indent <= current_indent {
-                        let multiline_string = MultilineString {
-                            on: &on[start..idx],
-                            collapse,
-                            preserve_leading_whitespace,
-                        };
-                        cb(&key_chain, RootYAMLValue::MultilineString(multiline_string));
-                        key_chain.pop();
                         state = State::Skip;
                         indent = 0;
                     }
                 }
             }
             State::Identifier => {
-                if let ':' = chr {
-                    let key = YAMLKey::Slice(on[start..idx].trim());
-                    let current_level = indent / options.indent_size;
-                    let keys = key_chain
-                        .iter()
-                        .filter(|key| matches!(key, YAMLKey::Slice(_)))
-                        .count();
-                    if current_level < keys {
-                        drop(key_chain.drain(current_level..));
-                        match key_chain.last() {
-                            Some(YAMLKey::Index(idx)) => {
-                                list_idx = *idx;
-                            }
-                            _ => {
-                                list_idx = 0;
-                            }
-                        }
-                    }
-                    key_chain.push(key);
+                if let (':', true) = (chr, is_mapping_colon(on, idx)) {
+                    key_column = indent;
+                    recover(emitter.check_indent_size(indent, start), &mut on_error)?;
+                    emitter.close_blocks(key_column);
+                    recover(
+                        emitter
+                            .push_block_key(unquote(trim_whitespace(&on[start..idx])), key_column),
+                        &mut on_error,
+                    )?;
                     state = State::Value;
                     start = idx + ':'.len_utf8();
+                    if let Some((end, exit)) =
+                        emitter.unparsed_entry(idx + ':'.len_utf8(), key_column, false)
+                    {
+                        if exit {
+                            return Ok(());
+                        }
+                        skip_to = end;
+                        state = State::Skip;
+                    }
+                } else if let '\n' = chr {
+                    // a line that is not an entry (plain scalars spanning lines are not supported)
+                    let (key, _) = split_comment(trim_whitespace(&on[start..idx]));
+                    let end = offset_in(on, key) + key.len();
+                    on_error(YAMLParseError::new(
+                        YAMLParseErrorReason::ExpectedColon,
+                        end..end,
+                    ))?;
+                    state = State::Skip;
+                    indent = 0;
                 }
-                // TODO whitespace warning etc...?
             }
             State::ListItem => {
-                if let ':' = chr {
-                    let current_level = indent / options.indent_size;
-                    if current_level < key_chain.len() {
-                        drop(key_chain.drain((current_level + 1)..));
-                    }
-                    key_chain.push(YAMLKey::Index(list_idx));
-                    key_chain.push(YAMLKey::Slice(on[start..idx].trim()));
+                let item = trim_whitespace(&on[start..idx]);
+                let (_, comment) = split_comment(item);
+                let after = on.get(idx + chr.len_utf8()..).unwrap_or_default();
+                let is_list_item = after.starts_with(is_yaml_whitespace);
+                // a `:` in a quoted item is part of the item
+                let in_quotes = item.starts_with(['"', '\'']) && quoted_end(item).is_none();
+                if let (':', None, true, false) =
+                    (chr, comment, is_mapping_colon(on, idx), in_quotes)
+                {
+                    // keys after the `-` are a level deeper
+                    let whitespace = &on[start..idx][..on[start..idx].len()
+                        - on[start..idx].trim_start_matches(is_yaml_whitespace).len()];
+                    key_column = item_column + 1 + indent_width(whitespace, options);
+                    recover(
+                        emitter.push_block_key(unquote(item), key_column),
+                        &mut on_error,
+                    )?;
                     state = State::Value;
                     start = idx + ':'.len_utf8();
-                    list_idx += 1;
+                    if let Some((end, exit)) =
+                        emitter.unparsed_entry(idx + ':'.len_utf8(), key_column, false)
+                    {
+                        if exit {
+                            return Ok(());
+                        }
+                        skip_to = end;
+                        state = State::Skip;
+                    }
+                } else if let ("", '-', true) = (item, chr, is_list_item) {
+                    // `- - item`
+                    item_column += 1 + indent_width(&on[start..idx], options);
+                    recover(emitter.push_block_item(item_column, idx), &mut on_error)?;
+                    start = idx + '-'.len_utf8();
+                    if let Some((end, exit)) =
+                        emitter.unparsed_entry(idx + '-'.len_utf8(), item_column, true)
+                    {
+                        if exit {
+                            return Ok(());
+                        }
+                        skip_to = end;
+                        state = State::Skip;
+                    }
+                } else if let ('[' | '{', (_, _, ""), None) = (chr, split_properties(item), comment)
+                {
+                    if emitter.properties(item).1 {
+                        return Ok(());
+                    }
+                    let depth = emitter.key_chain.depth();
+                    match emitter.block_flow_collection(idx, item_column) {
+                        Ok((_, true)) => return Ok(()),
+                        Ok((end, false)) => {
+                            skip_to = end;
+                            if recover(expect_end_of_line(on, end), &mut on_error)?.is_none() {
+                                skip_to = on[end..]
+                                    .find('\n')
+                                    .map_or(on.len(), |line_end| end + line_end);
+                            }
+                        }
+                        Err(err) => {
+                            // continue from the end of the line with the error
+                            emitter.key_chain.truncate(depth);
+                            skip_to = on[err.at..].find('\n').map_or(on.len(), |end| err.at + end);
+                            on_error(err)?;
+                        }
+                    }
+                    state = State::Skip;
+                } else if let '\n' = chr {
+                    let (value, comment) = split_comment(item);
+                    // as with keys, tags on an item without a value are for a nested collection
+                    // (and are skipped on block scalars)
+                    let value = match split_properties(value) {
+                        (_, _, properties_of)
+                            if properties_of.is_empty()
+                                || block_scalar_header(properties_of).is_some() =>
+                        {
+                            let (properties_of, exit) = emitter.properties(value);
+                            if exit {
+                                return Ok(());
+                            }
+                            properties_of
+                        }
+                        _ => value,
+                    };
+                    state = State::Skip;
+                    if value.is_empty() {
+                        if emitter.empty_value(idx, item_column + 1, start) {
+                            return Ok(());
+                        }
+                    } else if let Some((style, chomping, indentation)) = block_scalar_header(value)
+                    {
+                        // lines of the block scalar are indented more than the `-`
+                        let line_start = on[..start].rfind('\n').map_or(0, |idx| idx + 1);
+                        let dash_column = on[line_start..start].chars().count() - 1;
+                        state = State::Multiline {
+                            style,
+                            chomping,
+                            indentation: indentation.map(|indentation| dash_column + indentation),
+                            indent: dash_column,
+                        };
+                        start = idx;
+                        if block_scalar_ends(on, idx, dash_column) {
+                            let header = (style, chomping, indentation);
+                            let value = MultilineString::new("", header, options);
+                            if emitter.emit(RootYAMLValue::MultilineString(value), idx..idx) {
+                                return Ok(());
+                            }
+                            state = State::Skip;
+                        }
+                    } else if let Some(true) = recover(emitter.emit_scalar(value), &mut on_error)? {
+                        return Ok(());
+                    }
+                    if let Some(comment) = comment {
+                        emitter.comment(offset_in(on, comment) - '#'.len_utf8(), comment);
+                    }
+                    indent = 0;
                 }
+            }
+            State::Comment => {
+                if let '\n' = chr {
+                    let comment = &on[start + '#'.len_utf8()..idx];
+                    emitter.comment(start, comment.strip_suffix('\r').unwrap_or(comment));
+                    state = State::Skip;
+                    indent = 0;
+                }
+            }
+            State::DocumentMarker => {
                 if let '\n' = chr {
-                    key_chain.push(YAMLKey::Index(list_idx));
-                    let value = on[start..idx].trim();
-                    let value = match value {
-                        "true" => RootYAMLValue::True,
-                        "false" => RootYAMLValue::False,
-                        value => RootYAMLValue::String(value),
-                    };
-                    cb(&key_chain, value);
-                    key_chain.pop();
-                    list_idx += 1;
                     state = State::Skip;
                     indent = 0;
                 }
             }
             State::Skip => {
-                if let '-' = chr {
+                let line = &on[idx..];
+                let line_start = idx == 0 || on[..idx].ends_with('\n');
+                if let (true, Some(end)) = (line_start, emitter.skip_block_lines(idx)) {
+                    if end > idx {
+                        idx = end;
+                        continue;
+                    }
+                }
+                if let (true, '-' | '.') = (line_start, chr) {
+                    let is_start = is_document_marker(line, "---");
+                    if is_start || is_document_marker(line, "...") {
+                        let exit = emitter.end_document(idx)
+                            || (is_start && emitter.start_document(idx + "---".len()));
+                        if exit {
+                            return Ok(());
+                        }
+                        emitter.key_chain.clear();
+                        emitter.seen_keys.clear();
+                        emitter.indent_step = None;
+                        // the lines of a block scalar document (`--- |`) are skipped
+                        let line_end = line.find('\n').unwrap_or(line.len());
+                        let (after, _) = split_comment(trim_whitespace(&line[3..line_end]));
+                        if block_scalar_header(split_properties(after).2).is_some() {
+                            idx = nested_block_end(on, idx, 0, false, options);
+                            continue;
+                        }
+                        state = State::DocumentMarker;
+                        idx += chr.len_utf8();
+                        continue;
+                    }
+                }
+                if let ('%', true, true) = (chr, line_start, emitter.key_chain.is_empty()) {
+                    // `%YAML` and `%TAG` directives before a document
+                    let line_end = on[idx..].find('\n').map_or(on.len(), |end| idx + end);
+                    recover(parse_directive(on, &on[idx..line_end]), &mut on_error)?;
+                    skip_to = line_end;
+                } else if let '#' = chr {
+                    state = State::Comment;
+                    start = idx;
+                } else if let '-' = chr {
+                    item_column = indent;
+                    recover(emitter.check_indent_size(indent, idx), &mut on_error)?;
+                    recover(emitter.push_block_item(item_column, idx), &mut on_error)?;
                     state = State::ListItem;
                     start = idx + '-'.len_utf8();
+                    if let Some((end, exit)) =
+                        emitter.unparsed_entry(idx + '-'.len_utf8(), item_column, true)
+                    {
+                        if exit {
+                            return Ok(());
+                        }
+                        skip_to = end;
+                        state = State::Skip;
+                    }
+                } else if let ('?', true) = (
+                    chr,
+                    on.get(idx + '?'.len_utf8()..)
+                        .is_some_and(|rest| rest.chars().next().is_none_or(is_yaml_whitespace)),
+                ) {
+                    // `? key` explicit key, with the value on a following `: value` line
+                    let line_end = on[idx..].find('\n').map_or(on.len(), |end| idx + end);
+                    let (key, comment) =
+                        split_comment(trim_whitespace(&on[idx + '?'.len_utf8()..line_end]));
+                    if let Some(comment) = comment {
+                        emitter.comment(offset_in(on, comment) - '#'.len_utf8(), comment);
+                    }
+                    let value = explicit_key_value(on, idx, key, line_end, indent, options);
+                    if let Some(value_at) = recover(value, &mut on_error)? {
+                        key_column = indent;
+                        recover(emitter.check_indent_size(indent, idx), &mut on_error)?;
+                        emitter.close_blocks(key_column);
+                        recover(
+                            emitter.push_block_key(unquote(key), key_column),
+                            &mut on_error,
+                        )?;
+                        if let Some(value_at) = value_at {
+                            state = State::Value;
+                            start = value_at;
+                            skip_to = value_at;
+                        } else if emitter.emit(RootYAMLValue::Null, line_end..line_end) {
+                            return Ok(());
+                        } else {
+                            skip_to = line_end;
+                        }
+                    } else {
+                        skip_to = end_of_explicit_entry(on, line_end, indent, options);
+                        indent = 0;
+                    }
+                } else if let ('[' | '{', true) = (chr, emitter.key_chain.is_empty()) {
+                    // a flow collection as the whole document (such as JSON)
+                    match emitter.flow_collection(idx, 0) {
+                        Ok((_, true)) => return Ok(()),
+                        Ok((end, false)) => {
+                            skip_to = end;
+                            if recover(expect_end_of_line(on, end), &mut on_error)?.is_none() {
+                                skip_to = on[end..]
+                                    .find('\n')
+                                    .map_or(on.len(), |line_end| end + line_end);
+                            }
+                        }
+                        Err(err) => {
+                            emitter.key_chain.clear();
+                            skip_to = on[err.at..].find('\n').map_or(on.len(), |end| err.at + end);
+                            on_error(err)?;
+                        }
+                    }
+                } else if let '\n' = chr {
+                    indent = 0;
                 } else if let '\t' = chr {
-                    indent += options.indent_size;
+                    let before = on[..idx].trim_end_matches([' ', '\t']);
+                    let in_indentation = before.is_empty() || before.ends_with('\n');
+                    let span = idx..idx + '\t'.len_utf8();
+                    match (options.tab_policy(), in_indentation) {
+                        (TabPolicy::Error, true) => on_error(YAMLParseError::new(
+                            YAMLParseErrorReason::TabIndentation,
+                            span,
+                        ))?,
+                        (_, true) => emitter.warn(YAMLWarningKind::TabIndentation, span),
+                        (_, false) => {}
+                    }
+                    indent += options.tab_width();
                 } else if let ' ' = chr {
-                    indent += 1;
-                } else if !chr.is_whitespace() {
+                    let spaces = on[idx..].len() - on[idx..].trim_start_matches(' ').len();
+                    indent += spaces;
+                    skip_to = idx + spaces;
+                } else if !is_yaml_whitespace(chr) {
                     state = State::Identifier;
                     start = idx;
+                    // a quoted key can contain `: `
+                    let line_end = on[idx..].find('\n').map_or(on.len(), |end| idx + end);
+                    if let Some(end) = quoted_end(&on[idx..line_end]) {
+                        skip_to = idx + end;
+                    } else if let '"' | '\'' = chr {
+                        let key = trim_whitespace(&on[idx..line_end]);
+                        on_error(YAMLParseError::new(
+                            YAMLParseErrorReason::ExpectedClosingQuote,
+                            idx..idx + key.len(),
+                        ))?;
+                        skip_to = line_end;
+                        state = State::Skip;
+                    }
                 }
             }
         }
+        idx = skip_to.max(idx + chr.len_utf8());
     }
 
-    // TODO left over stuff here
+    // A block scalar header on the last line (without a newline) has no content
+    if let State::Multiline {
+        style,
+        chomping,
+        indentation,
+        ..
+    } = state
+    {
+        let multiline_string = MultilineString::new("", (style, chomping, indentation), options);
+        let value = RootYAMLValue::MultilineString(multiline_string);
+        if emitter.emit(value, on.len()..on.len()) {
+            return Ok(());
+        }
+    }
+    emitter.end_document(on.len());
 
     Ok(())
 }
@@ -0,0 +1,135 @@
+use super::{
+    indent_step, parse_structure, Emitted, ParseOptions, Signal, YAMLKey, YAMLParseError,
+    YAMLWriter,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Error from [`merge`], for the document that is invalid
+#[derive(Debug)]
+pub enum MergeError {
+    Base(YAMLParseError),
+    Overlay(YAMLParseError),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MergeError {}
+
+impl core::fmt::Display for MergeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            MergeError::Base(err) => f.write_fmt(format_args!("in base document: {err}")),
+            MergeError::Overlay(err) => f.write_fmt(format_args!("in overlay document: {err}")),
+        }
+    }
+}
+
+/// Values and empty collections ([`Emitted::Value`] and [`Emitted::EmptyCollection`])
+type Entries<'a> = Vec<(Vec<YAMLKey<'a>>, Emitted<'a>)>;
+
+/// Layers `overlay` over `base`: values in `overlay` replace those at the same key chain in `base`
+/// and new keys are added after the last entry of their parent. A value replaces everything nested
+/// under its key in `base` (and the reverse). Sequences are not merged: a sequence in `overlay`
+/// replaces the one in `base` as a whole. An empty mapping (`{}`) in `overlay` keeps the mapping in
+/// `base`. The result is written with [`YAMLWriter`] using the indentation of `base`, so comments
+/// are not kept
+///
+/// # Errors
+/// Returns an error if either input is invalid YAML
+pub fn merge(base: &str, overlay: &str, options: &ParseOptions) -> Result<String, MergeError> {
+    let mut entries = collect(base, options).map_err(MergeError::Base)?;
+    let overlay_entries = collect(overlay, options).map_err(MergeError::Overlay)?;
+
+    // the sequences of `overlay` that have replaced those in `base`
+    let mut replaced_sequences: Vec<Vec<YAMLKey<'_>>> = Vec::new();
+    for (keys, value) in overlay_entries {
+        let is_nested =
+            |existing: &[YAMLKey<'_>]| existing.len() > keys.len() && existing.starts_with(&keys);
+        if matches!(value, Emitted::EmptyCollection { is_mapping: true })
+            && entries.iter().any(|(existing, _)| {
+                is_nested(existing) && matches!(existing[keys.len()], YAMLKey::Slice(_))
+            })
+        {
+            continue;
+        }
+        // the first item of a sequence takes the place of the sequence in `base`
+        let sequence = keys
+            .iter()
+            .position(|key| matches!(key, YAMLKey::Index(_)))
+            .map(|idx| &keys[..idx]);
+        if let Some(sequence) = sequence {
+            if !replaced_sequences
+                .iter()
+                .any(|replaced| replaced == sequence)
+            {
+                let position = entries
+                    .iter()
+                    .position(|(existing, _)| existing.starts_with(sequence));
+                entries.retain(|(existing, _)| !existing.starts_with(sequence));
+                replaced_sequences.push(sequence.to_vec());
+                if let Some(position) = position {
+                    entries.insert(position, (keys, value));
+                    continue;
+                }
+            }
+        }
+        if let Some(existing) = entries.iter_mut().find(|(existing, _)| *existing == keys) {
+            existing.1 = value;
+            continue;
+        }
+        // entries with a different shape at this key chain are replaced
+        let shared = |existing: &[YAMLKey<'_>]| {
+            existing
+                .iter()
+                .zip(&keys)
+                .take_while(|(existing, key)| existing == key)
+                .count()
+        };
+        let replaced = entries
+            .iter()
+            .position(|(existing, _)| shared(existing) == existing.len().min(keys.len()));
+        entries.retain(|(existing, _)| shared(existing) != existing.len().min(keys.len()));
+
+        let position = replaced.unwrap_or_else(|| {
+            // after the last entry with the most keys in common
+            let mut best = (0, entries.len());
+            for (idx, (existing, _)) in entries.iter().enumerate() {
+                let common = shared(existing);
+                if common > 0 && common >= best.0 {
+                    best = (common, idx + 1);
+                }
+            }
+            best.1
+        });
+        entries.insert(position, (keys, value));
+    }
+
+    let mut writer = YAMLWriter::with_indent_size(indent_step(base));
+    for (keys, node) in &entries {
+        match node {
+            Emitted::Value(value) => writer.write(keys, value),
+            Emitted::EmptyCollection { is_mapping: true } => writer.write_raw(keys, "{}"),
+            Emitted::EmptyCollection { is_mapping: false } => writer.write_raw(keys, "[]"),
+            _ => {}
+        }
+    }
+    Ok(writer.finish())
+}
+
+fn collect<'a>(on: &'a str, options: &ParseOptions) -> Result<Entries<'a>, YAMLParseError> {
+    let mut entries = Vec::new();
+    parse_structure(
+        on,
+        |keys, node, _| {
+            if let Emitted::Value(_) | Emitted::EmptyCollection { .. } = node {
+                entries.push((keys.to_vec(), node));
+            }
+            Signal::Continue
+        },
+        |_, _| {},
+        Err,
+        options,
+    )
+    .map_err(|err| err.locate(on))?;
+    Ok(entries)
+}
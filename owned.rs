@@ -0,0 +1,221 @@
+use super::{
+    format_key_chain, parse_with_exit_signal, ParseOptions, RootYAMLValue, TaggedScalar, YAMLKey,
+    YAMLParseError,
+};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// A [`YAMLKey`] that does not borrow from the input
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OwnedYAMLKey {
+    Key(String),
+    Index(usize),
+}
+
+impl From<&YAMLKey<'_>> for OwnedYAMLKey {
+    fn from(key: &YAMLKey<'_>) -> Self {
+        match key {
+            YAMLKey::Slice(key) => Self::Key((*key).to_string()),
+            YAMLKey::Index(index) => Self::Index(*index),
+        }
+    }
+}
+
+impl From<YAMLKey<'_>> for OwnedYAMLKey {
+    fn from(key: YAMLKey<'_>) -> Self {
+        Self::from(&key)
+    }
+}
+
+impl OwnedYAMLKey {
+    #[must_use]
+    pub fn as_key(&self) -> YAMLKey<'_> {
+        match self {
+            Self::Key(key) => YAMLKey::Slice(key),
+            Self::Index(index) => YAMLKey::Index(*index),
+        }
+    }
+}
+
+/// The keys passed to a callback, copied so that they can be stored
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OwnedKeyChain(pub Vec<OwnedYAMLKey>);
+
+impl From<&[YAMLKey<'_>]> for OwnedKeyChain {
+    fn from(keys: &[YAMLKey<'_>]) -> Self {
+        Self(keys.iter().map(OwnedYAMLKey::from).collect())
+    }
+}
+
+/// Copies the keys passed to a callback (the same as [`OwnedKeyChain::from`])
+#[must_use]
+pub fn to_owned_keys(keys: &[YAMLKey<'_>]) -> OwnedKeyChain {
+    OwnedKeyChain::from(keys)
+}
+
+impl OwnedKeyChain {
+    /// The keys borrowing from this chain, for comparing with callback keys or [`crate::parse_key_path`]
+    #[must_use]
+    pub fn as_keys(&self) -> Vec<YAMLKey<'_>> {
+        self.0.iter().map(OwnedYAMLKey::as_key).collect()
+    }
+}
+
+impl core::ops::Deref for OwnedKeyChain {
+    type Target = [OwnedYAMLKey];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl PartialEq<[YAMLKey<'_>]> for OwnedKeyChain {
+    fn eq(&self, other: &[YAMLKey<'_>]) -> bool {
+        self.0.len() == other.len()
+            && self
+                .0
+                .iter()
+                .zip(other)
+                .all(|(key, other)| key.as_key() == *other)
+    }
+}
+
+impl core::fmt::Display for OwnedKeyChain {
+    /// In the format of [`format_key_chain`]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&format_key_chain(&self.as_keys()))
+    }
+}
+
+/// A [`RootYAMLValue`] that does not borrow from the input. Quoted strings are decoded and block
+/// scalars are resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OwnedYAMLValue {
+    String(String),
+    Number(String),
+    Timestamp(String),
+    True,
+    False,
+    Alias(String),
+    Tagged { tag: String, value: String },
+    RawBlock(String),
+    Null,
+}
+
+impl From<&RootYAMLValue<'_>> for OwnedYAMLValue {
+    fn from(value: &RootYAMLValue<'_>) -> Self {
+        match value {
+            RootYAMLValue::String(_)
+            | RootYAMLValue::QuotedString(_)
+            | RootYAMLValue::MultilineString(_)
+            | RootYAMLValue::Interpolated(_) => {
+                Self::String(value.as_str().unwrap_or_default().into_owned())
+            }
+            RootYAMLValue::Number(on) => Self::Number((*on).to_string()),
+            RootYAMLValue::Timestamp(on) => Self::Timestamp((*on).to_string()),
+            RootYAMLValue::True => Self::True,
+            RootYAMLValue::False => Self::False,
+            RootYAMLValue::Alias(name) => Self::Alias((*name).to_string()),
+            RootYAMLValue::Tagged(tagged) => Self::Tagged {
+                tag: tagged.tag().to_string(),
+                value: tagged.value().to_string(),
+            },
+            RootYAMLValue::RawBlock(on) => Self::RawBlock((*on).to_string()),
+            RootYAMLValue::Null => Self::Null,
+        }
+    }
+}
+
+impl From<RootYAMLValue<'_>> for OwnedYAMLValue {
+    fn from(value: RootYAMLValue<'_>) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl RootYAMLValue<'_> {
+    /// Copies the value so it can outlive the input (the same as [`OwnedYAMLValue::from`])
+    #[must_use]
+    pub fn to_owned_value(&self) -> OwnedYAMLValue {
+        OwnedYAMLValue::from(self)
+    }
+}
+
+impl OwnedYAMLValue {
+    /// The value borrowing from this, to use the accessors of [`RootYAMLValue`]. Strings are
+    /// [`RootYAMLValue::String`] as they are already decoded
+    #[must_use]
+    pub fn as_value(&self) -> RootYAMLValue<'_> {
+        match self {
+            Self::String(on) => RootYAMLValue::String(on),
+            Self::Number(on) => RootYAMLValue::Number(on),
+            Self::Timestamp(on) => RootYAMLValue::Timestamp(on),
+            Self::True => RootYAMLValue::True,
+            Self::False => RootYAMLValue::False,
+            Self::Alias(name) => RootYAMLValue::Alias(name),
+            Self::Tagged { tag, value } => RootYAMLValue::Tagged(TaggedScalar { tag, value }),
+            Self::RawBlock(on) => RootYAMLValue::RawBlock(on),
+            Self::Null => RootYAMLValue::Null,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(on) => Some(on),
+            _ => None,
+        }
+    }
+
+    /// See [`RootYAMLValue::as_i64`]
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_value().as_i64()
+    }
+
+    /// See [`RootYAMLValue::as_f64`]
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_value().as_f64()
+    }
+
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_value().as_bool()
+    }
+
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+}
+
+impl PartialEq<RootYAMLValue<'_>> for OwnedYAMLValue {
+    fn eq(&self, other: &RootYAMLValue<'_>) -> bool {
+        match self {
+            Self::String(on) => other.as_str().is_some_and(|text| text == on.as_str()),
+            value => value.as_value() == *other,
+        }
+    }
+}
+
+/// Parses `on` into a list of owned `(keys, value)` pairs, for when values need to outlive the input
+/// or to use iterator combinators on them (with `parse_collect(on, &options)?.into_iter()`). The
+/// callback API ([`crate::parse_with_exit_signal`]) does not allocate and can stop early
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse_collect(
+    on: &str,
+    options: &ParseOptions,
+) -> Result<Vec<(OwnedKeyChain, OwnedYAMLValue)>, YAMLParseError> {
+    let mut values = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |keys, value| {
+            values.push((OwnedKeyChain::from(keys), value.to_owned_value()));
+            false
+        },
+        options,
+    )?;
+    Ok(values)
+}
@@ -0,0 +1,75 @@
+//! Parsing the documents of a stream on multiple threads
+use super::{
+    documents, parse_with_exit_signal, ParseOptions, RootYAMLValue, YAMLKey, YAMLParseError,
+};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// [`crate::parse_documents`] with the documents parsed on a thread per available core (from
+/// [`std::thread::available_parallelism`]). Threads take the next unparsed document when they
+/// finish one, so a stream of many documents of different sizes is spread evenly.
+///
+/// The callback receives the index of the document and is called from all the threads at once:
+/// the values of each document are in order but values of different documents are interleaved.
+/// Return `true` from `cb` to stop parsing every document
+///
+/// # Errors
+/// Returns the error in the first document (by index) that has one. Documents after it may still
+/// have been passed to `cb`
+pub fn parse_documents_parallel<'a>(
+    on: &'a str,
+    cb: impl for<'b> Fn(usize, &'b [YAMLKey<'a>], RootYAMLValue<'a>) -> bool + Sync,
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    options.check_input_len(on).map_err(|err| err.locate(on))?;
+    let documents: Vec<(usize, &str)> = documents(on).collect();
+    let threads = std::thread::available_parallelism()
+        .map_or(1, usize::from)
+        .min(documents.len());
+    let next = AtomicUsize::new(0);
+    let stopped = AtomicBool::new(false);
+    // the index and error of the first invalid document
+    let error: Mutex<Option<(usize, YAMLParseError)>> = Mutex::new(None);
+
+    let work = || loop {
+        let index = next.fetch_add(1, Ordering::Relaxed);
+        let Some((offset, document)) = documents.get(index) else {
+            break;
+        };
+        if stopped.load(Ordering::Relaxed) {
+            break;
+        }
+        let result = parse_with_exit_signal(
+            document,
+            |keys, value| {
+                if cb(index, keys, value) {
+                    stopped.store(true, Ordering::Relaxed);
+                }
+                stopped.load(Ordering::Relaxed)
+            },
+            options,
+        );
+        if let Err(err) = result {
+            let mut error = error
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if error.as_ref().is_none_or(|(first, _)| index < *first) {
+                *error = Some((index, err.offset_by(*offset).locate(on)));
+            }
+        }
+    };
+    std::thread::scope(|scope| {
+        for _ in 1..threads {
+            scope.spawn(work);
+        }
+        work();
+    });
+
+    match error
+        .into_inner()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+    {
+        Some((_, error)) => Err(error),
+        None => Ok(()),
+    }
+}
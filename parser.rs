@@ -0,0 +1,368 @@
+use super::{
+    block_scalar_header, closing_quote_end, is_document_marker, is_mapping_colon,
+    is_yaml_whitespace, parse_block, quoted_end, split_comment, ParseOptions, RootYAMLValue,
+    Signal, YAMLKey, YAMLParseError, YAMLParseErrorReason,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Parser for input that arrives in chunks. Use [`Parser::feed`] as input arrives and then [`Parser::finish`].
+///
+/// Input is buffered until the start of the next top level entry (a key, or an item of a top level sequence), at
+/// which point the entries so far are parsed and the callback receives slices into the buffer. Each line is only
+/// looked at once. When resolving aliases or merge keys, top level entries containing a `&` are kept (and parsed
+/// again, without being passed to the callback) so that later entries can refer to their anchors. Repeated top
+/// level keys are not caught by [`ParseOptions::deny_duplicate_keys`] and the limits on resolving aliases apply to
+/// each part of the input separately
+pub struct Parser<'o, C> {
+    /// Entries kept for their anchors followed by the unparsed input
+    buffer: String,
+    /// The length of the entries kept for their anchors at the start of `buffer`
+    kept: usize,
+    /// The number of top level sequence items in the kept entries of the current document
+    kept_items: usize,
+    /// Where the lines of `buffer` that have not been looked at start
+    scanned: usize,
+    /// Starts of top level entries and document markers in `buffer` (after `kept`)
+    entries: Vec<(usize, Line)>,
+    /// What the lines after the scanned ones are inside of
+    continuation: Continuation,
+    /// Whether the current document is a sequence, once its first entry has been found
+    is_sequence: Option<bool>,
+    /// The number of top level sequence items parsed in the current document
+    items: usize,
+    cb: C,
+    options: &'o ParseOptions,
+    /// of the input before the unparsed part of `buffer`, for error positions
+    offset: usize,
+    lines: usize,
+    /// The number of bytes fed, for [`ParseOptions::max_input_len`]
+    fed: usize,
+    exited: bool,
+}
+
+/// The line that starts a top level entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line {
+    Key,
+    Item,
+    /// `---` or `...`
+    DocumentMarker,
+}
+
+impl<'o, C> Parser<'o, C>
+where
+    C: for<'b> FnMut(&'b [YAMLKey<'b>], RootYAMLValue<'b>) -> bool,
+{
+    pub fn new(cb: C, options: &'o ParseOptions) -> Self {
+        Self {
+            buffer: String::new(),
+            kept: 0,
+            kept_items: 0,
+            scanned: 0,
+            entries: Vec::new(),
+            continuation: Continuation::default(),
+            is_sequence: None,
+            items: 0,
+            cb,
+            options,
+            offset: 0,
+            lines: 0,
+            fed: 0,
+            exited: false,
+        }
+    }
+
+    /// Adds input, parsing any complete top level entries
+    ///
+    /// # Errors
+    /// Returns an error if it tries to parse invalid YAML input
+    pub fn feed(&mut self, chunk: &str) -> Result<(), YAMLParseError> {
+        if self.exited {
+            return Ok(());
+        }
+        self.buffer.push_str(chunk);
+        self.fed += chunk.len();
+        if let Some(max) = self.options.max_input_len.filter(|max| self.fed > *max) {
+            // the input before this chunk was within the limit, so the byte at `max` is in `buffer`
+            let start = max - self.offset + self.kept;
+            let span = start..self.buffer.len();
+            return Err(self.locate(YAMLParseError::new(
+                YAMLParseErrorReason::InputTooLong,
+                span,
+            )));
+        }
+        while let Some(end) = self.buffer[self.scanned..].find('\n') {
+            let start = self.scanned;
+            self.scanned += end + '\n'.len_utf8();
+            if let Some(line) = self.entry_start(start) {
+                self.entries.push((start, line));
+            }
+        }
+        // entries are complete once the next one starts
+        let split = self.entries.last().map_or(0, |(start, _)| *start);
+        if split > self.kept {
+            self.parse_up_to(split)?;
+        }
+        Ok(())
+    }
+
+    /// Parses the rest of the input
+    ///
+    /// # Errors
+    /// Returns an error if it tries to parse invalid YAML input
+    pub fn finish(mut self) -> Result<(), YAMLParseError> {
+        if !self.exited {
+            self.parse_up_to(self.buffer.len())?;
+        }
+        Ok(())
+    }
+
+    /// Whether the callback has returned the exit signal. Further input is ignored
+    pub fn exited(&self) -> bool {
+        self.exited
+    }
+
+    /// Whether the line of `buffer` at `start` (up to `scanned`) starts a top level entry
+    fn entry_start(&mut self, start: usize) -> Option<Line> {
+        let line = &self.buffer[start..];
+        if is_document_marker(line, "---") || is_document_marker(line, "...") {
+            self.is_sequence = None;
+            self.continuation = Continuation::default();
+            return Some(Line::DocumentMarker);
+        }
+        if self
+            .continuation
+            .continues(&self.buffer[start..self.scanned])
+        {
+            return None;
+        }
+        let first = line.chars().next()?;
+        if matches!(first, ' ' | '\t' | '\r' | '\n' | '#' | '%') {
+            return None;
+        }
+        let is_item = line
+            .strip_prefix('-')
+            .is_some_and(|rest| rest.starts_with([' ', '\t', '\r', '\n']));
+        match *self.is_sequence.get_or_insert(is_item) {
+            true if is_item => Some(Line::Item),
+            false if !matches!(first, '-' | '.' | ']' | '}' | ',' | ':') => Some(Line::Key),
+            _ => None,
+        }
+    }
+
+    /// Positions an error with a span in `buffer` in the whole input
+    fn locate(&self, err: YAMLParseError) -> YAMLParseError {
+        let prefix_lines = self.buffer[..self.kept].matches('\n').count();
+        let mut err = err.locate(&self.buffer).offset_by(self.offset - self.kept);
+        err.line = err.line - prefix_lines + self.lines;
+        err
+    }
+
+    fn parse_up_to(&mut self, split: usize) -> Result<(), YAMLParseError> {
+        let source = &self.buffer[..split];
+        let kept = self.kept;
+        let parsed = &self.entries[..self.entries.partition_point(|(start, _)| *start < split)];
+        // items before the first document marker continue the numbering of the current document
+        let continued = parsed
+            .iter()
+            .find(|(_, line)| *line == Line::DocumentMarker)
+            .map_or(split, |(start, _)| *start);
+        let shift = (self.kept_items, self.items);
+
+        let mut exited = false;
+        let mut shifted: Vec<YAMLKey<'_>> = Vec::new();
+        let cb = &mut self.cb;
+        let result = parse_block(
+            source,
+            |keys, value, spans| {
+                // values of the kept entries have already been passed
+                if spans.value.start < kept {
+                    return Signal::Continue;
+                }
+                exited = match keys.first() {
+                    Some(YAMLKey::Index(idx)) if spans.value.start < continued => {
+                        shifted.clear();
+                        shifted.extend_from_slice(keys);
+                        shifted[0] = YAMLKey::Index(idx - shift.0 + shift.1);
+                        cb(&shifted, value)
+                    }
+                    _ => cb(keys, value),
+                };
+                exited.into()
+            },
+            |_, _| {},
+            Err,
+            self.options,
+        );
+        if let Err(err) = result {
+            return Err(self.locate(err));
+        }
+        self.exited = exited;
+        self.offset += split - kept;
+        self.lines += source[kept..].matches('\n').count();
+
+        // the entries to keep for their anchors, and the number of items in the current document
+        let resolves = self.options.resolve_aliases || self.options.resolve_merge_keys;
+        let mut keep = String::new();
+        // the lines before the first entry (comments) have no entry start
+        let first = (parsed.first().map(|(start, _)| *start) != Some(kept)).then_some((kept, None));
+        let starts = parsed.iter().map(|(start, line)| (*start, Some(*line)));
+        let mut bounds = first.into_iter().chain(starts).peekable();
+        while let Some((start, line)) = bounds.next() {
+            let end = bounds.peek().map_or(split, |(end, _)| *end);
+            let text = &source[start..end];
+            if line == Some(Line::DocumentMarker) {
+                self.items = 0;
+                self.kept_items = 0;
+            }
+            if line == Some(Line::Item) {
+                self.items += 1;
+            }
+            if resolves && (line == Some(Line::DocumentMarker) || text.contains('&')) {
+                keep.push_str(text);
+                self.kept_items += usize::from(line == Some(Line::Item));
+            }
+        }
+
+        let entries = self.entries.len() - parsed.len();
+        self.entries.drain(..self.entries.len() - entries);
+        keep.insert_str(0, &self.buffer[..kept]);
+        self.buffer.replace_range(..split, &keep);
+        let moved_to = keep.len();
+        for (start, _) in &mut self.entries {
+            *start = *start - split + moved_to;
+        }
+        // (`finish` parses the last line, which has not been looked at)
+        self.scanned = self.scanned.max(split) - split + moved_to;
+        self.kept = moved_to;
+        Ok(())
+    }
+}
+
+/// The flow collections (and quoted scalar in them) or block scalar that the lines scanned so far
+/// end inside of. Lines inside of them continue an entry, even if they are not indented
+#[derive(Debug, Default)]
+struct Continuation {
+    flow_depth: usize,
+    quote: Option<char>,
+    /// The indentation of the line with the header of the block scalar
+    block_scalar: Option<usize>,
+}
+
+impl Continuation {
+    /// Scans `line`, returning whether it is inside of the lines before it
+    fn continues(&mut self, line: &str) -> bool {
+        let content = line.trim_start_matches([' ', '\t']);
+        let indent = line.len() - content.len();
+        if let Some(header_indent) = self.block_scalar {
+            if content.trim_end_matches(is_yaml_whitespace).is_empty() || indent > header_indent {
+                return true;
+            }
+            self.block_scalar = None;
+        }
+        let continues = self.flow_depth > 0 || self.quote.is_some();
+        let mut rest = content;
+        if let Some(quote) = self.quote {
+            let Some(end) = closing_quote_end(rest, quote) else {
+                return true;
+            };
+            self.quote = None;
+            rest = &rest[end..];
+        }
+        if self.flow_depth > 0 {
+            self.scan_flow(rest);
+        } else {
+            self.scan_block(rest, indent);
+        }
+        continues
+    }
+
+    /// Finds the value of a block entry or item, which may start a flow collection or a scalar
+    /// that continues over lines
+    fn scan_block(&mut self, line: &str, indent: usize) {
+        let (mut rest, _) = split_comment(line);
+        loop {
+            rest = rest.trim_start_matches(is_yaml_whitespace);
+            let Some(first) = rest.chars().next() else {
+                return;
+            };
+            let after = &rest[first.len_utf8()..];
+            match first {
+                '-' | '?' | ':' if after.chars().next().is_none_or(is_yaml_whitespace) => {
+                    rest = after;
+                }
+                '&' | '!' => {
+                    rest = after
+                        .find(is_yaml_whitespace)
+                        .map_or("", |end| &after[end..]);
+                }
+                '[' | '{' => return self.scan_flow(rest),
+                '|' | '>' => {
+                    if block_scalar_header(rest).is_some() {
+                        self.block_scalar = Some(indent);
+                    }
+                    return;
+                }
+                '"' | '\'' => {
+                    // quoted scalars only continue over lines in flow collections
+                    let Some(end) = quoted_end(rest) else {
+                        return;
+                    };
+                    // a quoted key
+                    match rest[end..]
+                        .trim_start_matches(is_yaml_whitespace)
+                        .strip_prefix(':')
+                    {
+                        Some(value) if value.chars().next().is_none_or(is_yaml_whitespace) => {
+                            rest = value;
+                        }
+                        _ => return,
+                    }
+                }
+                _ => {
+                    let Some(colon) = rest
+                        .match_indices(':')
+                        .map(|(idx, _)| idx)
+                        .find(|idx| is_mapping_colon(rest, *idx))
+                    else {
+                        return;
+                    };
+                    rest = &rest[colon + ':'.len_utf8()..];
+                }
+            }
+        }
+    }
+
+    /// Counts the brackets of flow collections, skipping quoted scalars and comments
+    fn scan_flow(&mut self, line: &str) {
+        let mut idx = 0;
+        let mut last = None;
+        while let Some(chr) = line[idx..].chars().next() {
+            let at_token_start =
+                last.is_none_or(|last| is_yaml_whitespace(last) || "[{,:".contains(last));
+            match chr {
+                '[' | '{' => self.flow_depth += 1,
+                ']' | '}' => {
+                    self.flow_depth = self.flow_depth.saturating_sub(1);
+                    if self.flow_depth == 0 {
+                        return;
+                    }
+                }
+                '"' | '\'' if at_token_start => {
+                    let Some(end) = quoted_end(&line[idx..]) else {
+                        self.quote = Some(chr);
+                        return;
+                    };
+                    idx += end;
+                    last = Some(chr);
+                    continue;
+                }
+                '#' if last.is_none_or(is_yaml_whitespace) => return,
+                _ => {}
+            }
+            last = Some(chr);
+            idx += chr.len_utf8();
+        }
+    }
+}
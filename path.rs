@@ -0,0 +1,197 @@
+use super::{parse_with_spans, OwnedYAMLKey, ParseOptions, YAMLKey};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Paths to parse, for [`crate::ParseOptions::only_paths`]. Patterns are in the format of
+/// [`parse_key_path`], where `*` matches any mapping key and `[*]` any sequence index
+/// (`person.*`, `places.list[*].name`). A value is included if its keys start with those of a
+/// pattern, so a pattern includes everything under it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathFilter {
+    patterns: Vec<Vec<Segment>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    AnyKey,
+    AnyIndex,
+}
+
+impl Segment {
+    fn matches(&self, key: &YAMLKey<'_>) -> bool {
+        match (self, key) {
+            (Segment::Key(segment), YAMLKey::Slice(key)) => segment == key,
+            (Segment::Index(segment), YAMLKey::Index(index)) => segment == index,
+            (Segment::AnyKey, YAMLKey::Slice(_)) | (Segment::AnyIndex, YAMLKey::Index(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl PathFilter {
+    #[must_use]
+    pub fn new<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Self {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                // `[*]` is not a bracketed key for `parse_key_path`, so is split off first
+                let mut segments = Vec::new();
+                for (idx, part) in pattern.split("[*]").enumerate() {
+                    if idx > 0 {
+                        segments.push(Segment::AnyIndex);
+                    }
+                    let part = part.strip_prefix('.').unwrap_or(part);
+                    segments.extend(parse_key_path(part).into_iter().map(|key| match key {
+                        YAMLKey::Slice("*") => Segment::AnyKey,
+                        YAMLKey::Slice(key) => Segment::Key(key.to_string()),
+                        YAMLKey::Index(index) => Segment::Index(index),
+                    }));
+                }
+                segments
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether the value at `keys` is included
+    #[must_use]
+    pub fn matches(&self, keys: &[YAMLKey<'_>]) -> bool {
+        self.patterns.iter().any(|pattern| {
+            pattern.len() <= keys.len()
+                && pattern
+                    .iter()
+                    .zip(keys)
+                    .all(|(segment, key)| segment.matches(key))
+        })
+    }
+
+    /// Whether anything under `keys` can be included
+    pub(crate) fn may_contain(&self, keys: &[YAMLKey<'_>]) -> bool {
+        self.patterns.iter().any(|pattern| {
+            pattern
+                .iter()
+                .zip(keys)
+                .all(|(segment, key)| segment.matches(key))
+        })
+    }
+}
+
+/// Formats keys as a path: mapping keys separated by `.` and sequence indices in brackets
+/// (`person.address.city`, `places[0]`). Keys containing `.`, `[` or `]` are written as `["a.b"]`
+/// (or `['a.b']` if they contain a `"`). The inverse of [`parse_key_path`]
+#[must_use]
+pub fn format_key_chain(keys: &[YAMLKey<'_>]) -> String {
+    let mut path = String::new();
+    for key in keys {
+        match key {
+            YAMLKey::Slice(key) if key.contains(['.', '[', ']']) || key.is_empty() => {
+                let quote = if key.contains('"') { '\'' } else { '"' };
+                path.push('[');
+                path.push(quote);
+                path.push_str(key);
+                path.push(quote);
+                path.push(']');
+            }
+            YAMLKey::Slice(key) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            YAMLKey::Index(index) => {
+                path.push('[');
+                path.push_str(&index.to_string());
+                path.push(']');
+            }
+        }
+    }
+    path
+}
+
+/// The keys of the entry at `offset` in `on` (such as the position of a cursor): the keys up to
+/// the key or value containing it (the position just after is included). Between entries it is the
+/// keys shared by the entries before and after it, so the collection it is in. `None` if that is
+/// the top level, `offset` is after the last entry or `on` is invalid before it
+#[must_use]
+pub fn path_at_offset(on: &str, offset: usize) -> Option<Vec<OwnedYAMLKey>> {
+    let contains = |start: usize, end: usize| start <= offset && offset <= end;
+    let mut previous: Vec<YAMLKey<'_>> = Vec::new();
+    let mut found = None;
+    let _ = parse_with_spans(
+        on,
+        |keys, _, spans| {
+            // keys are slices of `on`, unless resolved from an alias
+            let key_at = keys.iter().position(|key| match key {
+                YAMLKey::Slice(key) => {
+                    let start = (key.as_ptr() as usize).wrapping_sub(on.as_ptr() as usize);
+                    start <= on.len() && contains(start, start + key.len())
+                }
+                YAMLKey::Index(_) => false,
+            });
+            let length = if let Some(depth) = key_at {
+                depth + 1
+            } else if contains(spans.key.start, spans.key.end)
+                || contains(spans.value.start, spans.value.end)
+            {
+                keys.len()
+            } else if spans.key.start > offset {
+                keys.iter()
+                    .zip(&previous)
+                    .take_while(|(key, previous)| key == previous)
+                    .count()
+            } else {
+                previous.clear();
+                previous.extend_from_slice(keys);
+                return false;
+            };
+            found = Some(keys[..length].iter().map(OwnedYAMLKey::from).collect());
+            true
+        },
+        &ParseOptions::default(),
+    );
+    found.filter(|keys: &Vec<OwnedYAMLKey>| !keys.is_empty())
+}
+
+/// Parses a path in the format of [`format_key_chain`], for comparing against the keys passed to the
+/// callback (`keys == parse_key_path("places[0].name")`). Brackets that do not contain an index or
+/// a quoted key are treated as part of the key
+#[must_use]
+pub fn parse_key_path(path: &str) -> Vec<YAMLKey<'_>> {
+    let mut keys = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some((key, after)) = bracketed(rest) {
+            keys.push(key);
+            rest = after.strip_prefix('.').unwrap_or(after);
+            continue;
+        }
+        // a bracket only ends the key if it is a valid index or quoted key
+        let mut end = rest.len();
+        for (idx, chr) in rest.char_indices() {
+            if chr == '.' || (chr == '[' && bracketed(&rest[idx..]).is_some()) {
+                end = idx;
+                break;
+            }
+        }
+        keys.push(YAMLKey::Slice(&rest[..end]));
+        rest = rest[end..].strip_prefix('.').unwrap_or(&rest[end..]);
+    }
+    keys
+}
+
+/// Parses `[0]`, `["key"]` or `['key']` at the start of `on`, returning the key and what is after it
+pub(crate) fn bracketed(on: &str) -> Option<(YAMLKey<'_>, &str)> {
+    let inner = on.strip_prefix('[')?;
+    if let Some(quote @ ('"' | '\'')) = inner.chars().next() {
+        let quoted = &inner[quote.len_utf8()..];
+        let end = quoted.find([quote])?;
+        let after = quoted[end + quote.len_utf8()..].strip_prefix(']')?;
+        Some((YAMLKey::Slice(&quoted[..end]), after))
+    } else {
+        let end = inner.find(']')?;
+        let index = inner[..end].parse().ok()?;
+        Some((YAMLKey::Index(index), &inner[end + ']'.len_utf8()..]))
+    }
+}
@@ -0,0 +1,195 @@
+use super::{
+    parse_with_exit_signal, parse_with_spans, path::bracketed, ParseOptions, RootYAMLValue,
+    YAMLKey, YAMLParseError,
+};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Finds the value at a dotted `path` (such as `person.address.city`), stopping parsing once it is found.
+/// Numeric segments match sequence indices (`places.list.0`), as do indices in brackets
+/// (`places.list[0]`), so paths from [`crate::format_key_chain`] work. Use `\.` for keys containing dots
+/// and `\\` for backslashes (or `["a.b"]`). Aliases are resolved. Returns `None` if there is no value at
+/// the path (or it is a mapping or sequence)
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn query<'a>(on: &'a str, path: &str) -> Result<Option<RootYAMLValue<'a>>, YAMLParseError> {
+    let segments = path_segments(path);
+    let options = ParseOptions::new().resolve_aliases(true);
+    let mut found = None;
+    parse_with_exit_signal(
+        on,
+        |keys, value| {
+            let matches = is_path(keys, &segments);
+            if matches {
+                found = Some(value);
+            }
+            matches
+        },
+        &options,
+    )?;
+    Ok(found)
+}
+
+/// The source of the value at `path` (in the format of [`query`]) as written. For mappings and
+/// sequences in block style and block scalars this is their lines (including indentation and
+/// comments), otherwise the value (with its quotes or brackets). Aliases are not resolved.
+/// Returns `None` if there is no value at the path or `on` is invalid before it is found
+#[must_use]
+pub fn extract_section<'a>(on: &'a str, path: &str) -> Option<&'a str> {
+    let segments = path_segments(path);
+    let options = ParseOptions::new().raw_depth(segments.len());
+    let mut found = None;
+    let _ = parse_with_spans(
+        on,
+        |keys, value, spans| {
+            if !is_path(keys, &segments) {
+                return false;
+            }
+            found = Some(match value {
+                RootYAMLValue::RawBlock(raw) => raw,
+                // the span starts at the end of the header line
+                RootYAMLValue::MultilineString(_) => on[spans.value]
+                    .split_once('\n')
+                    .map_or("", |(_, lines)| lines),
+                _ => &on[spans.value],
+            });
+            true
+        },
+        &options,
+    );
+    found
+}
+
+/// The keys of the mapping at `path` (in the format of [`query`], or empty for the top level), in
+/// order. Keys before invalid YAML are still returned, so this can be used on partially written
+/// input (for completions)
+#[must_use]
+pub fn keys_at<'a>(on: &'a str, path: &str) -> Vec<&'a str> {
+    let mut keys = Vec::new();
+    for_each_child(on, path, |key| {
+        if let YAMLKey::Slice(key) = key {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    });
+    keys
+}
+
+/// The number of items of the sequence at `path` (in the format of [`query`], or empty for the top
+/// level). Like [`keys_at`], items before invalid YAML are counted
+#[must_use]
+pub fn indices_at(on: &str, path: &str) -> usize {
+    let mut count = 0;
+    for_each_child(on, path, |key| {
+        if let YAMLKey::Index(index) = key {
+            count = count.max(index + 1);
+        }
+    });
+    count
+}
+
+/// Calls `cb` with the key of each value directly under `path`, stopping after them. Values deeper
+/// are not parsed
+fn for_each_child<'a>(on: &'a str, path: &str, mut cb: impl FnMut(YAMLKey<'a>)) {
+    let segments = path_segments(path);
+    let options = ParseOptions::new().raw_depth(segments.len() + 1);
+    let mut inside = false;
+    let _ = parse_with_exit_signal(
+        on,
+        |keys, _| {
+            let is_child =
+                keys.len() > segments.len() && is_path(&keys[..segments.len()], &segments);
+            if is_child {
+                cb(keys[segments.len()]);
+            }
+            // children are together, so there are no more after leaving them
+            let left = inside && !is_child;
+            inside = is_child;
+            left
+        },
+        &options,
+    );
+}
+
+/// A segment of a [`query`] path
+enum Segment<'a> {
+    /// Between dots. Matches a mapping key or, if it is a number, a sequence index
+    Dotted(Cow<'a, str>),
+    /// `["key"]` or `['key']`
+    Key(&'a str),
+    /// `[0]`
+    Index(usize),
+}
+
+fn is_path(keys: &[YAMLKey<'_>], segments: &[Segment<'_>]) -> bool {
+    keys.len() == segments.len()
+        && keys
+            .iter()
+            .zip(segments)
+            .all(|(key, segment)| match (segment, key) {
+                (Segment::Dotted(segment), YAMLKey::Slice(key)) => segment == key,
+                (Segment::Dotted(segment), YAMLKey::Index(index)) => segment.parse() == Ok(*index),
+                (Segment::Key(segment), YAMLKey::Slice(key)) => segment == key,
+                (Segment::Index(segment), YAMLKey::Index(index)) => segment == index,
+                _ => false,
+            })
+}
+
+/// Splits on `.` (handling `\.` and `\\` escapes) and before brackets
+fn path_segments(path: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some((key, after)) = bracketed(rest) {
+            segments.push(match key {
+                YAMLKey::Slice(key) => Segment::Key(key),
+                YAMLKey::Index(index) => Segment::Index(index),
+            });
+            rest = after.strip_prefix('.').unwrap_or(after);
+            continue;
+        }
+        let mut end = rest.len();
+        let mut has_escapes = false;
+        let mut chars = rest.char_indices();
+        while let Some((idx, chr)) = chars.next() {
+            match chr {
+                '\\' => {
+                    chars.next();
+                    has_escapes = true;
+                }
+                '.' => {
+                    end = idx;
+                    break;
+                }
+                // a bracket only ends the segment if it is a valid index or quoted key
+                '[' if bracketed(&rest[idx..]).is_some() => {
+                    end = idx;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        segments.push(Segment::Dotted(segment(&rest[..end], has_escapes)));
+        rest = rest[end..].strip_prefix('.').unwrap_or(&rest[end..]);
+    }
+    segments
+}
+
+fn segment(on: &str, has_escapes: bool) -> Cow<'_, str> {
+    if !has_escapes {
+        return Cow::Borrowed(on);
+    }
+    let mut unescaped = String::with_capacity(on.len());
+    let mut chars = on.chars();
+    while let Some(chr) = chars.next() {
+        if chr == '\\' {
+            unescaped.extend(chars.next());
+        } else {
+            unescaped.push(chr);
+        }
+    }
+    Cow::Owned(unescaped)
+}
@@ -0,0 +1,152 @@
+use super::{ParseOptions, Parser, RootYAMLValue, YAMLKey, YAMLParseError};
+use std::io::Read;
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+#[derive(Debug)]
+pub enum ReadError {
+    IO(std::io::Error),
+    /// The input is not valid UTF-8. The offset is from the start of the input
+    InvalidUTF8(usize),
+    Parse(YAMLParseError),
+}
+
+impl std::error::Error for ReadError {}
+
+impl std::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ReadError::IO(err) => std::fmt::Display::fmt(err, f),
+            ReadError::InvalidUTF8(at) => f.write_fmt(format_args!("Invalid UTF-8 at {at}")),
+            ReadError::Parse(err) => std::fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+impl From<std::io::Error> for ReadError {
+    fn from(err: std::io::Error) -> Self {
+        ReadError::IO(err)
+    }
+}
+
+impl From<YAMLParseError> for ReadError {
+    fn from(err: YAMLParseError) -> Self {
+        ReadError::Parse(err)
+    }
+}
+
+/// Parses from a reader without reading the whole input into memory first. See [`Parser`] for
+/// how input is buffered
+///
+/// # Errors
+/// Returns an error if reading fails, if the input is not UTF-8 or if it is invalid YAML
+pub fn parse_reader<R: Read>(
+    mut reader: R,
+    cb: impl for<'b> FnMut(&'b [YAMLKey<'b>], RootYAMLValue<'b>) -> bool,
+    options: &ParseOptions,
+) -> Result<(), ReadError> {
+    let mut feeder = Feeder::new(cb, options);
+    let mut chunk = vec![0; CHUNK_SIZE];
+    while !feeder.parser.exited() {
+        let read = reader.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        feeder.feed(&chunk[..read])?;
+    }
+    feeder.finish()
+}
+
+/// A source of bytes for [`parse_async_reader`]. This can wrap the reader of any async runtime, for
+/// example with tokio:
+///
+/// ```ignore
+/// struct Tokio<R>(R);
+///
+/// impl<R: tokio::io::AsyncRead + Unpin> AsyncChunkRead for Tokio<R> {
+///     async fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+///         tokio::io::AsyncReadExt::read(&mut self.0, buf).await
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub trait AsyncChunkRead {
+    /// Reads into `buf`, returning the number of bytes read. `0` is the end of the input
+    fn read(&mut self, buf: &mut [u8])
+        -> impl std::future::Future<Output = std::io::Result<usize>>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncChunkRead for &[u8] {
+    async fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Read::read(self, buf)
+    }
+}
+
+/// [`parse_reader`] for an asynchronous reader. Input is parsed as it arrives (see [`Parser`])
+///
+/// # Errors
+/// Returns an error if reading fails, if the input is not UTF-8 or if it is invalid YAML
+#[cfg(feature = "async")]
+pub async fn parse_async_reader<R: AsyncChunkRead>(
+    mut reader: R,
+    cb: impl for<'b> FnMut(&'b [YAMLKey<'b>], RootYAMLValue<'b>) -> bool,
+    options: &ParseOptions,
+) -> Result<(), ReadError> {
+    let mut feeder = Feeder::new(cb, options);
+    let mut chunk = vec![0; CHUNK_SIZE];
+    while !feeder.parser.exited() {
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        feeder.feed(&chunk[..read])?;
+    }
+    feeder.finish()
+}
+
+/// Passes bytes to a [`Parser`], holding back characters split across reads
+struct Feeder<'o, C> {
+    parser: Parser<'o, C>,
+    pending: Vec<u8>,
+    /// of the input before `pending`, for error positions
+    offset: usize,
+}
+
+impl<'o, C> Feeder<'o, C>
+where
+    C: for<'b> FnMut(&'b [YAMLKey<'b>], RootYAMLValue<'b>) -> bool,
+{
+    fn new(cb: C, options: &'o ParseOptions) -> Self {
+        Self {
+            parser: Parser::new(cb, options),
+            pending: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    fn feed(&mut self, bytes: &[u8]) -> Result<(), ReadError> {
+        self.pending.extend_from_slice(bytes);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(valid) => valid,
+            Err(err) if err.error_len().is_none() => {
+                // the rest is an incomplete character. `valid_up_to` is a char boundary
+                std::str::from_utf8(&self.pending[..err.valid_up_to()]).unwrap_or_default()
+            }
+            Err(err) => return Err(ReadError::InvalidUTF8(self.offset + err.valid_up_to())),
+        };
+        let length = valid.len();
+        self.parser.feed(valid)?;
+        self.offset += length;
+        self.pending.drain(..length);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), ReadError> {
+        if !self.pending.is_empty() && !self.parser.exited() {
+            return Err(ReadError::InvalidUTF8(self.offset));
+        }
+        self.parser.finish()?;
+        Ok(())
+    }
+}
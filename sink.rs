@@ -0,0 +1,66 @@
+use super::{documents, parse_block, ParseOptions, RootYAMLValue, Signal, YAMLKey, YAMLParseError};
+use core::cell::RefCell;
+use core::ops::ControlFlow;
+
+/// A consumer of parse events, for [`parse_with_sink`]. Implemented for closures that take the keys
+/// and value (and return a [`ControlFlow`])
+pub trait YAMLSink<'a> {
+    /// Receives each value and the keys leading to it. [`ControlFlow::Break`] stops parsing
+    fn value(&mut self, keys: &[YAMLKey<'a>], value: RootYAMLValue<'a>) -> ControlFlow<()>;
+
+    /// Receives each error. [`ControlFlow::Continue`] skips the rest of the line and carries on
+    /// parsing (as [`crate::parse_lenient`] does). By default the first error stops parsing
+    fn error(&mut self, _error: &YAMLParseError) -> ControlFlow<()> {
+        ControlFlow::Break(())
+    }
+
+    /// Called after each document in the stream is parsed, with its index
+    fn document_end(&mut self, _index: usize) {}
+}
+
+impl<'a, F> YAMLSink<'a> for F
+where
+    F: for<'b> FnMut(&'b [YAMLKey<'a>], RootYAMLValue<'a>) -> ControlFlow<()>,
+{
+    fn value(&mut self, keys: &[YAMLKey<'a>], value: RootYAMLValue<'a>) -> ControlFlow<()> {
+        self(keys, value)
+    }
+}
+
+/// Parses each document in `on` into `sink`
+///
+/// # Errors
+/// Returns the error that [`YAMLSink::error`] stopped parsing on
+pub fn parse_with_sink<'a>(
+    on: &'a str,
+    sink: &mut impl YAMLSink<'a>,
+    options: &ParseOptions,
+) -> Result<(), YAMLParseError> {
+    options.check_input_len(on).map_err(|err| err.locate(on))?;
+    // both callbacks use the sink
+    let sink = RefCell::new(sink);
+    for (index, (offset, document)) in documents(on).enumerate() {
+        let mut stopped = false;
+        parse_block(
+            document,
+            |keys, value, _| {
+                stopped = sink.borrow_mut().value(keys, value).is_break();
+                Signal::from(stopped)
+            },
+            |_, _| {},
+            |err| {
+                let err = err.offset_by(offset).locate(on);
+                match sink.borrow_mut().error(&err) {
+                    ControlFlow::Continue(()) => Ok(()),
+                    ControlFlow::Break(()) => Err(err),
+                }
+            },
+            options,
+        )?;
+        if stopped {
+            break;
+        }
+        sink.borrow_mut().document_end(index);
+    }
+    Ok(())
+}
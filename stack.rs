@@ -0,0 +1,106 @@
+//! For the key chain: a stack that stores its first `N` items inline, only allocating for deeper nesting (with
+//! `smallkeys`) or never allocating (for [`crate::parse_fixed`])
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+pub(crate) enum InlineStack<T: Copy, const N: usize> {
+    /// Items and the number of them in use. `T` values past the length are placeholders
+    Inline([T; N], usize),
+    Heap(Vec<T>),
+    /// Items and the number pushed, which can be more than `N`. Items past `N` are dropped rather
+    /// than moved to the heap, so the stack is only usable until the length exceeds `N`
+    Fixed([T; N], usize),
+}
+
+impl<T: Copy, const N: usize> InlineStack<T, N> {
+    /// `placeholder` fills the unused inline slots
+    pub(crate) fn new(placeholder: T) -> Self {
+        Self::Inline([placeholder; N], 0)
+    }
+
+    /// A stack that never allocates. See [`InlineStack::Fixed`]
+    pub(crate) fn fixed(placeholder: T) -> Self {
+        Self::Fixed([placeholder; N], 0)
+    }
+
+    /// The number of items pushed (and not popped). Only differs from the length of the slice for
+    /// a [`InlineStack::Fixed`] stack pushed to while full
+    pub(crate) fn pushed(&self) -> usize {
+        match self {
+            Self::Fixed(_, length) => *length,
+            _ => self.as_slice().len(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        match self {
+            Self::Inline(items, length) if *length < N => {
+                items[*length] = item;
+                *length += 1;
+            }
+            Self::Inline(items, length) => {
+                let mut spilled = Vec::with_capacity(N * 2);
+                spilled.extend_from_slice(&items[..*length]);
+                spilled.push(item);
+                *self = Self::Heap(spilled);
+            }
+            Self::Heap(items) => items.push(item),
+            Self::Fixed(items, length) => {
+                if let Some(slot) = items.get_mut(*length) {
+                    *slot = item;
+                }
+                *length += 1;
+            }
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        match self {
+            Self::Inline(_, 0) | Self::Fixed(_, 0) => None,
+            Self::Inline(items, length) | Self::Fixed(items, length) => {
+                *length -= 1;
+                items.get(*length).copied()
+            }
+            Self::Heap(items) => items.pop(),
+        }
+    }
+
+    pub(crate) fn truncate(&mut self, new_length: usize) {
+        match self {
+            Self::Inline(_, length) | Self::Fixed(_, length) => *length = new_length.min(*length),
+            Self::Heap(items) => items.truncate(new_length),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub(crate) fn extend(&mut self, items: impl IntoIterator<Item = T>) {
+        for item in items {
+            self.push(item);
+        }
+    }
+
+    pub(crate) fn split_off(&mut self, at: usize) -> Vec<T> {
+        let rest = self[at..].to_vec();
+        self.truncate(at);
+        rest
+    }
+
+    pub(crate) fn as_slice(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: Copy, const N: usize> Deref for InlineStack<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Self::Inline(items, length) => &items[..*length],
+            Self::Fixed(items, length) => &items[..(*length).min(N)],
+            Self::Heap(items) => items,
+        }
+    }
+}
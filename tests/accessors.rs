@@ -0,0 +1,72 @@
+//! Typed accessors on `RootYAMLValue`
+
+use simple_yaml_parser::{parse, RootYAMLValue};
+
+fn values(on: &str) -> Vec<RootYAMLValue<'_>> {
+    let mut values = Vec::new();
+    parse(on, |_, value| values.push(value)).unwrap();
+    values
+}
+
+#[test]
+fn as_str() {
+    let values = values("a: plain\nb: \"esc\\taped\"\nc: |\n  block\nd: 12\ne: true\nf: ~\n");
+    let strings: Vec<Option<String>> = values
+        .iter()
+        .map(|value| value.as_str().map(std::borrow::Cow::into_owned))
+        .collect();
+    assert_eq!(
+        strings,
+        [
+            Some("plain".to_owned()),
+            Some("esc\taped".to_owned()),
+            Some("block\n".to_owned()),
+            None,
+            None,
+            None
+        ]
+    );
+}
+
+#[test]
+fn numbers_and_booleans() {
+    let values = values(
+        "a: 42\nb: -0x10\nc: 0x10\nd: 2.5\ne: true\nf: no\ng: \"7\"\nh: 99999999999999999999\n",
+    );
+    let integers: Vec<Option<i64>> = values.iter().map(RootYAMLValue::as_i64).collect();
+    assert_eq!(
+        integers,
+        [Some(42), None, Some(16), None, None, None, None, None]
+    );
+    let floats: Vec<Option<f64>> = values.iter().map(RootYAMLValue::as_f64).collect();
+    assert_eq!(
+        floats,
+        [
+            Some(42.0),
+            None,
+            Some(16.0),
+            Some(2.5),
+            None,
+            None,
+            None,
+            Some(1e20)
+        ]
+    );
+    let booleans: Vec<Option<bool>> = values.iter().map(RootYAMLValue::as_bool).collect();
+    assert_eq!(
+        booleans,
+        [None, None, None, None, Some(true), None, None, None]
+    );
+}
+
+#[test]
+fn parse_into() {
+    let values = values("port: 8080\nratio: \"0.5\"\nflag: false\nname: x\nnothing: null\n");
+    assert_eq!(values[0].parse_into::<u16>(), Some(Ok(8080)));
+    assert_eq!(values[1].parse_into::<f32>(), Some(Ok(0.5)));
+    assert_eq!(values[2].parse_into::<bool>(), Some(Ok(false)));
+    assert!(matches!(values[3].parse_into::<u8>(), Some(Err(_))));
+    assert_eq!(values[4].parse_into::<u8>(), None);
+    // out of range for the type
+    assert!(matches!(values[0].parse_into::<u8>(), Some(Err(_))));
+}
@@ -0,0 +1,183 @@
+//! Resolving `*alias`es and `<<` merge keys
+
+mod common;
+
+use common::entry;
+use simple_yaml_parser::{
+    format_key_chain, parse_with_exit_signal, ParseOptions, RootYAMLValue, YAMLParseError,
+    YAMLParseErrorReason,
+};
+
+fn resolved(on: &str) -> Result<Vec<String>, YAMLParseError> {
+    let options = ParseOptions::new()
+        .resolve_aliases(true)
+        .resolve_merge_keys(true);
+    let mut values = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |keys, value| {
+            values.push(entry(keys, &value));
+            false
+        },
+        &options,
+    )?;
+    Ok(values)
+}
+
+#[test]
+fn aliases() {
+    let on = "base: &base\n  host: a\n  port: b\ncopy: *base\nitems:\n  - &one x\n  - *one\n";
+    assert_eq!(
+        resolved(on).unwrap(),
+        [
+            "base.host = a",
+            "base.port = b",
+            "copy.host = a",
+            "copy.port = b",
+            "items[0] = x",
+            "items[1] = x"
+        ]
+    );
+    let on = "base: &base\n  host: a\nserver:\n  <<: *base\n  port: b\n";
+    assert_eq!(
+        resolved(on).unwrap(),
+        ["base.host = a", "server.host = a", "server.port = b"]
+    );
+}
+
+#[test]
+fn cycles() {
+    for (on, alias) in [
+        ("a: &x\n  b: 1\n  c: *x\n", "*x\n"),
+        ("a: &x\n  - 1\n  - [2, *x]\n", "*x]"),
+        ("a: &x\n  b:\n    <<: *x\n", "*x\n"),
+        ("- &x\n  - *x\n", "*x\n"),
+    ] {
+        let err = resolved(on).unwrap_err();
+        let YAMLParseErrorReason::CyclicAlias { anchor_at } = err.reason else {
+            panic!("{:?} for {on:?}", err.reason);
+        };
+        assert_eq!(anchor_at, on.find('x').unwrap(), "{on:?}");
+        assert_eq!(err.at, on.find(alias).unwrap(), "{on:?}");
+    }
+}
+
+#[test]
+fn not_cycles() {
+    // after the anchored value, and a later entry with the same key
+    let on = "a: &x\n  b: y\nc: *x\n";
+    assert_eq!(resolved(on).unwrap(), ["a.b = y", "c.b = y"]);
+    let on = "a: &x y\na: *x\n";
+    assert_eq!(resolved(on).unwrap(), ["a = y", "a = y"]);
+    let on = "a: &x\n  - y\nb:\n  - *x\n";
+    assert_eq!(resolved(on).unwrap(), ["a[0] = y", "b[0][0] = y"]);
+}
+
+/// Each level has ten aliases of the one before, so resolving `l5` emits 10^5 values
+const LAUGHS: &str = "a: &l0 lol
+b: &l1 [*l0, *l0, *l0, *l0, *l0, *l0, *l0, *l0, *l0, *l0]
+c: &l2 [*l1, *l1, *l1, *l1, *l1, *l1, *l1, *l1, *l1, *l1]
+d: &l3 [*l2, *l2, *l2, *l2, *l2, *l2, *l2, *l2, *l2, *l2]
+e: &l4 [*l3, *l3, *l3, *l3, *l3, *l3, *l3, *l3, *l3, *l3]
+f: &l5 [*l4, *l4, *l4, *l4, *l4, *l4, *l4, *l4, *l4, *l4]
+";
+
+fn count_values(options: &ParseOptions) -> Result<usize, YAMLParseError> {
+    let mut count = 0;
+    parse_with_exit_signal(
+        LAUGHS,
+        |_, _| {
+            count += 1;
+            false
+        },
+        options,
+    )?;
+    Ok(count)
+}
+
+#[test]
+fn expansion_limits() {
+    let options = ParseOptions::new().resolve_aliases(true);
+    assert_eq!(count_values(&options).unwrap(), 111_111);
+
+    let options = ParseOptions::new()
+        .resolve_aliases(true)
+        .max_total_expanded_size(10_000);
+    let err = count_values(&options).unwrap_err();
+    assert!(matches!(
+        err.reason,
+        YAMLParseErrorReason::AliasLimitExceeded
+    ));
+    assert_eq!(err.line, 5);
+
+    let options = ParseOptions::new()
+        .resolve_aliases(true)
+        .max_alias_expansions(25);
+    let err = count_values(&options).unwrap_err();
+    assert!(matches!(
+        err.reason,
+        YAMLParseErrorReason::AliasLimitExceeded
+    ));
+    assert_eq!((err.line, err.column), (4, 34));
+    // aliases are not counted when they are not resolved
+    let options = ParseOptions::new().max_alias_expansions(0);
+    assert_eq!(count_values(&options).unwrap(), 51);
+}
+
+#[test]
+fn unresolved() {
+    let on = "base: &base\n  host: a\ncopy: *base\nname: &name x\nother: *name\nitems: [*name]\n";
+    let mut values = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |keys, value| {
+            values.push((format_key_chain(keys), value.clone()));
+            false
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        values,
+        [
+            ("base.host".to_owned(), RootYAMLValue::String("a")),
+            ("copy".to_owned(), RootYAMLValue::Alias("base")),
+            ("name".to_owned(), RootYAMLValue::String("x")),
+            ("other".to_owned(), RootYAMLValue::Alias("name")),
+            ("items[0]".to_owned(), RootYAMLValue::Alias("name")),
+        ]
+    );
+}
+
+#[test]
+fn unknown_alias() {
+    let err = resolved("a: &x 1\nb: *y\n").unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::UnknownAlias));
+    assert_eq!((err.line, err.column), (2, 4));
+    // anchors are defined in order
+    let err = resolved("a: *x\nb: &x 1\n").unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::UnknownAlias));
+}
+
+#[test]
+fn merge_keys() {
+    let on = "a: &a\n  x: a\n  y: a\nb: &b\n  y: b\n  z: b\nc:\n  <<: [*a, *b]\n  z: c\n";
+    // each mapping in order, then the keys of `c`
+    assert_eq!(
+        &resolved(on).unwrap()[4..],
+        ["c.x = a", "c.y = a", "c.y = b", "c.z = b", "c.z = c"]
+    );
+    // only with `resolve_merge_keys`
+    let options = ParseOptions::new().resolve_aliases(true);
+    let mut keys = Vec::new();
+    parse_with_exit_signal(
+        "a: &a\n  x: 1\nb:\n  <<: *a\n",
+        |path, _| {
+            keys.push(format_key_chain(path));
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    assert_eq!(keys, ["a.x", "b.<<.x"]);
+}
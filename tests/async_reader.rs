@@ -0,0 +1,106 @@
+//! `parse_async_reader`
+#![cfg(feature = "async")]
+
+mod common;
+
+use common::entry;
+use simple_yaml_parser::{parse_async_reader, AsyncChunkRead, ParseOptions};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+/// Runs a future on the current thread
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(std::thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+/// Returns one byte per read, yielding to the executor before each
+struct Trickle<'a> {
+    on: &'a [u8],
+    yielded: bool,
+}
+
+impl AsyncChunkRead for Trickle<'_> {
+    async fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        std::future::poll_fn(|cx| {
+            if self.yielded {
+                Poll::Ready(())
+            } else {
+                self.yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await;
+        self.yielded = false;
+        let Some((first, rest)) = self.on.split_first() else {
+            return Ok(0);
+        };
+        buf[0] = *first;
+        self.on = rest;
+        Ok(1)
+    }
+}
+
+fn values(reader: impl AsyncChunkRead) -> Vec<String> {
+    let mut values = Vec::new();
+    block_on(parse_async_reader(
+        reader,
+        |keys, value| {
+            values.push(entry(keys, &value));
+            false
+        },
+        &ParseOptions::default(),
+    ))
+    .unwrap();
+    values
+}
+
+const SOURCE: &str = "name: café\nitems:\n  - a\n  - b\nlast: z\n";
+
+#[test]
+fn slice() {
+    assert_eq!(
+        values(SOURCE.as_bytes()),
+        ["name = café", "items[0] = a", "items[1] = b", "last = z"]
+    );
+}
+
+#[test]
+fn chunks() {
+    let reader = Trickle {
+        on: SOURCE.as_bytes(),
+        yielded: false,
+    };
+    assert_eq!(
+        values(reader),
+        ["name = café", "items[0] = a", "items[1] = b", "last = z"]
+    );
+}
+
+#[test]
+fn invalid_utf8() {
+    let result = block_on(parse_async_reader(
+        &b"a: \xff\n"[..],
+        |_, _| false,
+        &ParseOptions::default(),
+    ));
+    assert!(result.is_err());
+}
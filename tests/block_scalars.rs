@@ -0,0 +1,119 @@
+//! Resolving `|` (literal) and `>` (folded) block scalars
+
+use simple_yaml_parser::{
+    parse, BlockStyle, Chomping, MultilineString, RootYAMLValue, YAMLParseErrorReason,
+};
+
+/// The block scalar under `value:` in `on`
+fn block_scalar(on: &str) -> MultilineString<'_> {
+    let mut block_scalar = None;
+    parse(on, |_, value| {
+        if let RootYAMLValue::MultilineString(multiline) = value {
+            block_scalar = Some(multiline.clone());
+        }
+    })
+    .unwrap();
+    block_scalar.unwrap()
+}
+
+fn source(header_and_lines: &str) -> String {
+    format!("value: {header_and_lines}after: 1\n")
+}
+
+/// The resolved value of the block scalar with `header_and_lines`
+fn resolve(header_and_lines: &str) -> String {
+    block_scalar(&source(header_and_lines)).resolve()
+}
+
+#[test]
+fn literal() {
+    assert_eq!(resolve("|\n  one\n  two\n"), "one\ntwo\n");
+    assert_eq!(resolve("|\n  one\n\n    indented\n"), "one\n\n  indented\n");
+}
+
+#[test]
+fn folded() {
+    assert_eq!(resolve(">\n  one\n  two\n"), "one two\n");
+    // an empty line is a line break
+    assert_eq!(resolve(">\n  one\n\n  two\n"), "one\ntwo\n");
+    // more indented lines are kept as they are
+    assert_eq!(
+        resolve(">\n  one\n    indented\n  two\n"),
+        "one\n  indented\ntwo\n"
+    );
+}
+
+#[test]
+fn chomping() {
+    let lines = "\n  one\n  two\n\n\n";
+    assert_eq!(resolve(&format!("|-{lines}")), "one\ntwo");
+    assert_eq!(resolve(&format!("|{lines}")), "one\ntwo\n");
+    assert_eq!(resolve(&format!("|+{lines}")), "one\ntwo\n\n\n");
+    assert_eq!(resolve(&format!(">-{lines}")), "one two");
+    assert_eq!(resolve(&format!(">+{lines}")), "one two\n\n\n");
+}
+
+#[test]
+fn indentation_indicator() {
+    assert_eq!(resolve("|2\n    leading\n  text\n"), "  leading\ntext\n");
+    assert_eq!(resolve(">1-\n   a\n  b\n"), "  a\n b");
+}
+
+#[test]
+fn accessors() {
+    let on = source(">-\n  one\n    two\n\n");
+    let multiline = block_scalar(&on);
+    assert_eq!(multiline.raw(), "\n  one\n    two\n");
+    assert_eq!(multiline.style(), BlockStyle::Folded);
+    assert_eq!(multiline.chomping(), Chomping::Strip);
+    let lines: Vec<&str> = multiline.lines().collect();
+    assert_eq!(lines, ["one", "  two", ""]);
+
+    let on = source("|2\n    leading\n  text\n");
+    let multiline = block_scalar(&on);
+    assert_eq!(multiline.style(), BlockStyle::Literal);
+    assert_eq!(multiline.chomping(), Chomping::Clip);
+    assert_eq!(multiline.lines().next(), Some("  leading"));
+}
+
+#[test]
+fn headers() {
+    // indicators in either order, and a comment after them
+    assert_eq!(resolve("|-2\n    a\n"), "  a");
+    assert_eq!(resolve("|2-\n    a\n"), "  a");
+    assert_eq!(resolve("|+ # keep\n  a\n\n"), "a\n\n");
+    for header in ["|x\n  a\n", "|0\n  a\n", "|--\n  a\n", ">+-\n  a\n"] {
+        let err = parse(&source(header), |_, _| {}).unwrap_err();
+        assert!(
+            matches!(err.reason, YAMLParseErrorReason::InvalidBlockScalarHeader),
+            "{header:?}: {:?}",
+            err.reason
+        );
+    }
+}
+
+#[test]
+fn resolve_in_other_places() {
+    // sequence items and the end of the input
+    let mut resolved = Vec::new();
+    parse("- >\n  a\n  b\n- |+\n  c\n\n", |_, value| {
+        if let RootYAMLValue::MultilineString(multiline) = value {
+            resolved.push(multiline.resolve());
+        }
+    })
+    .unwrap();
+    assert_eq!(resolved, ["a b\n", "c\n\n"]);
+
+    // without content, before the next entry
+    assert_eq!(resolve("|\n"), "");
+    assert_eq!(resolve(">-\n\n"), "");
+    let mut values = Vec::new();
+    parse("- |\n- x\n", |_, value| values.push(value)).unwrap();
+    assert!(matches!(
+        &values[..],
+        [
+            RootYAMLValue::MultilineString(_),
+            RootYAMLValue::String("x")
+        ]
+    ));
+}
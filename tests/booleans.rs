@@ -0,0 +1,73 @@
+//! `ParseOptions::bool_style`
+
+use simple_yaml_parser::{
+    parse_with_exit_signal, BoolStyle, ParseOptions, RootYAMLValue, YAMLKey, YAMLWriter,
+};
+
+fn value(on: &str, bool_style: BoolStyle) -> RootYAMLValue<'_> {
+    let options = ParseOptions::new().bool_style(bool_style);
+    let mut first = None;
+    parse_with_exit_signal(
+        on,
+        |_, value| {
+            first = Some(value);
+            true
+        },
+        &options,
+    )
+    .unwrap();
+    first.unwrap()
+}
+
+#[test]
+fn spellings() {
+    // (spelling, value, accepted by Yaml12Core, accepted by StrictLowercase)
+    let cases = [
+        ("true", true, true, true),
+        ("false", false, true, true),
+        ("True", true, true, false),
+        ("FALSE", false, true, false),
+        ("yes", true, false, false),
+        ("No", false, false, false),
+        ("ON", true, false, false),
+        ("off", false, false, false),
+        ("y", true, false, false),
+        ("N", false, false, false),
+    ];
+    for (spelling, expected, core, strict) in cases {
+        let on = format!("a: {spelling}\n");
+        let expected = if expected {
+            RootYAMLValue::True
+        } else {
+            RootYAMLValue::False
+        };
+        let string = RootYAMLValue::String(spelling);
+        assert_eq!(value(&on, BoolStyle::Yaml11), expected, "{spelling}");
+        let core_expected = if core { &expected } else { &string };
+        assert_eq!(
+            &value(&on, BoolStyle::Yaml12Core),
+            core_expected,
+            "{spelling}"
+        );
+        let strict_expected = if strict { &expected } else { &string };
+        assert_eq!(
+            &value(&on, BoolStyle::StrictLowercase),
+            strict_expected,
+            "{spelling}"
+        );
+    }
+}
+
+#[test]
+fn mixed_case_is_string() {
+    for style in [BoolStyle::Yaml11, BoolStyle::Yaml12Core] {
+        assert_eq!(value("a: tRUE\n", style), RootYAMLValue::String("tRUE"));
+    }
+}
+
+#[test]
+fn writer_quotes_yaml11_booleans() {
+    let mut writer = YAMLWriter::new();
+    writer.write(&[YAMLKey::Slice("a")], &RootYAMLValue::String("yes"));
+    assert_eq!(writer.finish(), "a: \"yes\"\n");
+}
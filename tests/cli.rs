@@ -0,0 +1,80 @@
+//! The `simple-yaml` binary
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const SOURCE: &str = "server:\n  port: 80\n  hosts: [a, \"b c\"]\n  note: |\n    hi\n";
+
+/// Runs with `SOURCE` on standard input, returning the exit code and standard output
+fn run(args: &[&str]) -> (i32, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_simple-yaml"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    // commands given a file do not read standard input
+    let _ = child.stdin.take().unwrap().write_all(SOURCE.as_bytes());
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn get() {
+    assert_eq!(run(&["get", "server.hosts[1]"]), (0, "b c\n".to_owned()));
+    assert_eq!(run(&["get", "server.note", "-"]), (0, "hi\n".to_owned()));
+    assert_eq!(run(&["get", "server.missing"]).0, 1);
+    // dotted indices, as for `query`
+    assert_eq!(run(&["get", "server.hosts.1"]), (0, "b c\n".to_owned()));
+}
+
+#[test]
+fn to_json() {
+    assert_eq!(
+        run(&["to-json"]),
+        (
+            0,
+            "{\"server\":{\"port\":80,\"hosts\":[\"a\",\"b c\"],\"note\":\"hi\\n\"}}\n".to_owned()
+        )
+    );
+}
+
+#[test]
+fn flatten() {
+    assert_eq!(
+        run(&["flatten"]),
+        (
+            0,
+            "SERVER__PORT=80\nSERVER__HOSTS__0=a\nSERVER__HOSTS__1=\"b c\"\nSERVER__NOTE=\"hi\\n\"\n"
+                .to_owned()
+        )
+    );
+}
+
+#[test]
+fn format() {
+    assert_eq!(
+        run(&["format"]),
+        (
+            0,
+            "server:\n  port: 80\n  hosts:\n    - a\n    - b c\n  note: |\n    hi\n".to_owned()
+        )
+    );
+}
+
+#[test]
+fn lint() {
+    assert_eq!(run(&["lint"]).0, 0);
+    let invalid = std::env::temp_dir().join("simple-yaml-cli-lint.yml");
+    std::fs::write(&invalid, "a: [\nb: 2\n").unwrap();
+    assert_eq!(run(&["lint", invalid.to_str().unwrap()]).0, 1);
+}
+
+#[test]
+fn usage() {
+    assert_eq!(run(&["unknown"]).0, 2);
+}
@@ -0,0 +1,110 @@
+//! A `:` only separates a key from its value when followed by whitespace, and quoted keys can
+//! contain `: `
+
+use simple_yaml_parser::{parse, parse_lenient, ParseOptions, YAMLKey, YAMLParseErrorReason};
+
+/// Values as `keys = value`
+fn values(on: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    parse(on, |keys, value| {
+        let keys: Vec<String> = keys
+            .iter()
+            .map(|key| match key {
+                YAMLKey::Slice(key) => (*key).to_owned(),
+                YAMLKey::Index(index) => index.to_string(),
+            })
+            .collect();
+        let value = value.as_str().unwrap_or_default().into_owned();
+        values.push(format!("{} = {value}", keys.join("/")));
+    })
+    .unwrap();
+    values
+}
+
+#[test]
+fn colons_in_values() {
+    let on = "url: http://example.com:8080\ntime: 12:30\nempty:\n";
+    assert_eq!(
+        values(on),
+        ["url = http://example.com:8080", "time = 12:30", "empty = "]
+    );
+}
+
+#[test]
+fn colons_in_keys() {
+    assert_eq!(values("a:b: x\nc:d:\n"), ["a:b = x", "c:d = "]);
+}
+
+#[test]
+fn quoted_keys() {
+    let on = "\"a: b\": x\n'c:d': y\n\"plain\" : z\n";
+    assert_eq!(values(on), ["a: b = x", "c:d = y", "plain = z"]);
+}
+
+#[test]
+fn sequence_items() {
+    let on = "- \"x: y\"\n- a:b\n- 'k: v': z\n- http://example.com\n";
+    assert_eq!(
+        values(on),
+        [
+            "0 = x: y",
+            "1 = a:b",
+            "2/k: v = z",
+            "3 = http://example.com"
+        ]
+    );
+}
+
+#[test]
+fn lines_without_colons() {
+    // a key does not continue onto the next line
+    let error = parse("just text\nkey: value\n", |_, _| {}).unwrap_err();
+    assert!(matches!(error.reason, YAMLParseErrorReason::ExpectedColon));
+    assert_eq!((error.line, error.column), (1, 10));
+
+    let error = parse("hello", |_, _| {}).unwrap_err();
+    assert!(matches!(error.reason, YAMLParseErrorReason::ExpectedColon));
+    assert_eq!(error.span, 5..5);
+
+    let error = parse("a: 1\n\"quoted\" # comment\n", |_, _| {}).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 9));
+
+    // later entries are still parsed in lenient mode
+    let mut keys = Vec::new();
+    let errors = parse_lenient(
+        "a: 1\nhello\nb: 2\n",
+        |chain, _| keys.push(format!("{chain:?}")),
+        &ParseOptions::default(),
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(keys, ["[Slice(\"a\")]", "[Slice(\"b\")]"]);
+}
+
+#[test]
+fn unclosed_quoted_keys() {
+    for (on, span) in [
+        ("'a: 1\n", 0..5),
+        ("\"a: 1", 0..5),
+        ("': 1\n", 0..4),
+        ("b: 2\n  \"a: 1 # x\n", 7..16),
+        ("{'a: 1}\n", 1..3),
+        ("x: {\"a\": 1, \"b: 2}\n", 12..14),
+    ] {
+        let error = parse(on, |_, _| {}).unwrap_err();
+        assert!(
+            matches!(error.reason, YAMLParseErrorReason::ExpectedClosingQuote),
+            "{on:?} {error:?}"
+        );
+        assert_eq!(error.span, span, "{on:?}");
+    }
+
+    // the line is skipped in lenient mode
+    let mut keys = Vec::new();
+    let errors = parse_lenient(
+        "'a: 1\nb: 2\n",
+        |chain, _| keys.push(format!("{chain:?}")),
+        &ParseOptions::default(),
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(keys, ["[Slice(\"b\")]"]);
+}
@@ -0,0 +1,71 @@
+//! Comments are not part of values, and are passed to `parse_with_comments` with `emit_comments`
+
+mod common;
+
+use common::entry;
+use simple_yaml_parser::{parse, parse_with_comments, ParseOptions};
+
+const SOURCE: &str = "# header
+name: app # trailing
+url: http://example.com/#anchor
+list:
+  # before an item
+  - a # after an item
+  - b#c
+quoted: \"# not a comment\"
+";
+
+#[test]
+fn stripped() {
+    let mut values = Vec::new();
+    parse(SOURCE, |keys, value| {
+        values.push(entry(keys, &value));
+    })
+    .unwrap();
+    assert_eq!(
+        values,
+        [
+            "name = app",
+            "url = http://example.com/#anchor",
+            "list[0] = a",
+            "list[1] = b#c",
+            "quoted = # not a comment"
+        ]
+    );
+}
+
+#[test]
+fn emitted() {
+    let options = ParseOptions::new().emit_comments(true);
+    let mut comments = Vec::new();
+    parse_with_comments(
+        SOURCE,
+        |_, _| false,
+        |at, comment| comments.push((at, comment)),
+        &options,
+    )
+    .unwrap();
+    let expected = [" header", " trailing", " before an item", " after an item"];
+    assert_eq!(
+        comments
+            .iter()
+            .map(|(_, comment)| *comment)
+            .collect::<Vec<_>>(),
+        expected
+    );
+    // positions are of the `#`
+    for (at, comment) in &comments {
+        assert_eq!(&SOURCE[*at..*at + 1 + comment.len()], format!("#{comment}"));
+    }
+
+    // not passed without the option
+    let mut count = 0;
+    parse_with_comments(
+        SOURCE,
+        |_, _| false,
+        |_, _| count += 1,
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(count, 0);
+}
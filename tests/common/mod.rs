@@ -0,0 +1,36 @@
+//! Helpers shared by the tests. Each test file only uses some of them
+#![allow(dead_code)]
+
+use simple_yaml_parser::{
+    format_key_chain, parse_with_exit_signal, ParseOptions, RootYAMLValue, YAMLKey, YAMLParseError,
+};
+
+/// A value as `path = value`. Numbers are as written, aliases as `*name` and other values that
+/// are not strings are empty
+pub fn entry(keys: &[YAMLKey<'_>], value: &RootYAMLValue<'_>) -> String {
+    let value = match value {
+        RootYAMLValue::Number(number) => (*number).to_owned(),
+        RootYAMLValue::Alias(name) => format!("*{name}"),
+        value => value.as_str().unwrap_or_default().into_owned(),
+    };
+    format!("{} = {value}", format_key_chain(keys))
+}
+
+/// The values of `on` as `path = value`
+pub fn values(on: &str) -> Vec<String> {
+    try_values(on, &ParseOptions::default()).unwrap()
+}
+
+/// The values of `on` (parsed with `options`) as `path = value`
+pub fn try_values(on: &str, options: &ParseOptions) -> Result<Vec<String>, YAMLParseError> {
+    let mut values = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |keys, value| {
+            values.push(entry(keys, &value));
+            false
+        },
+        options,
+    )
+    .map(|_| values)
+}
@@ -0,0 +1,49 @@
+//! `keys_at` and `indices_at`
+
+use simple_yaml_parser::{indices_at, keys_at};
+
+const SOURCE: &str = "name: app
+server:
+  host: example.com
+  tls:
+    cert: a.pem
+  ports: [80, 443]
+  empty: {}
+items:
+  - a: 1
+    b: 2
+  - two
+  - [x, y]
+after: 1
+";
+
+#[test]
+fn mapping_keys() {
+    assert_eq!(keys_at(SOURCE, ""), ["name", "server", "items", "after"]);
+    assert_eq!(keys_at(SOURCE, "server"), ["host", "tls", "ports", "empty"]);
+    assert_eq!(keys_at(SOURCE, "server.tls"), ["cert"]);
+    assert_eq!(keys_at(SOURCE, "items.0"), ["a", "b"]);
+}
+
+#[test]
+fn not_mappings() {
+    assert!(keys_at(SOURCE, "name").is_empty());
+    assert!(keys_at(SOURCE, "items").is_empty());
+    assert!(keys_at(SOURCE, "missing").is_empty());
+}
+
+#[test]
+fn sequence_lengths() {
+    assert_eq!(indices_at(SOURCE, "items"), 3);
+    assert_eq!(indices_at(SOURCE, "items.2"), 2);
+    assert_eq!(indices_at(SOURCE, "server.ports"), 2);
+    assert_eq!(indices_at(SOURCE, "server"), 0);
+    assert_eq!(indices_at("- a\n- b\n", ""), 2);
+}
+
+#[test]
+fn partial_input() {
+    // the last line is being written
+    let on = "server:\n  host: a\n  port: 80\n  tls: [\n";
+    assert_eq!(keys_at(on, "server"), ["host", "port"]);
+}
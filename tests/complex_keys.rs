@@ -0,0 +1,52 @@
+//! `? key` explicit keys
+
+mod common;
+
+use common::values;
+use simple_yaml_parser::{
+    format_key_chain, parse, parse_lenient, ParseOptions, YAMLParseErrorReason,
+};
+
+#[test]
+fn scalar_keys() {
+    let on =
+        "? a\n: one\n? \"b c\" # comment\n:\n  - x\n? lone\nafter: two\nnested:\n  ? k\n  : v\n";
+    assert_eq!(
+        values(on),
+        [
+            "a = one",
+            "b c[0] = x",
+            "lone = ",
+            "after = two",
+            "nested.k = v"
+        ]
+    );
+}
+
+#[test]
+fn unsupported() {
+    for on in [
+        "? [a, b]\n: 1\n",
+        "? |\n  text\n: 2\n",
+        "? multi\n  line\n: 3\n",
+    ] {
+        let err = parse(on, |_, _| {}).unwrap_err();
+        assert!(
+            matches!(err.reason, YAMLParseErrorReason::UnsupportedComplexKey),
+            "{on}"
+        );
+        assert_eq!(err.at, 0);
+    }
+}
+
+#[test]
+fn lenient_skips_complex_keys() {
+    let mut values = Vec::new();
+    let errors = parse_lenient(
+        "? - a\n  - b\n: 1\nok: 2\n? {x: 1}\n:\n  nested: 3\nlast: 4\n",
+        |keys, _| values.push(format_key_chain(keys)),
+        &ParseOptions::default(),
+    );
+    assert_eq!(errors.len(), 2);
+    assert_eq!(values, ["ok", "last"]);
+}
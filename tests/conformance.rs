@@ -0,0 +1,225 @@
+//! Runs the parser over the fixtures in `tests/conformance`. These use the layout of the `data`
+//! branch of the YAML test suite (<https://github.com/yaml/yaml-test-suite>): a directory per test
+//! with `in.yaml` and either `in.json` (each document as JSON) or an empty `error` file.
+//!
+//! The result of each test and the pass rate are written to `conformance.md` in the test target
+//! directory (and printed with `--nocapture`). Tests named in `known-failures.txt` are expected to
+//! fail. Any other failure fails the test, as does a listed test passing (so that the list is kept
+//! up to date)
+
+use simple_yaml_parser::{documents, json::yaml_to_json};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::Path;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/conformance");
+
+#[test]
+fn conformance() {
+    let known_failures =
+        std::fs::read_to_string(Path::new(FIXTURES).join("known-failures.txt")).unwrap_or_default();
+    let known_failures: BTreeSet<&str> = known_failures
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    let mut tests: Vec<_> = std::fs::read_dir(FIXTURES)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.join("in.yaml").exists()
+                && (path.join("in.json").exists() || path.join("error").exists())
+        })
+        .collect();
+    tests.sort();
+
+    let mut report = String::from("| Test | Result |\n| --- | --- |\n");
+    let mut passed = 0;
+    let mut unexpected = Vec::new();
+    for test in &tests {
+        let name = test.file_name().unwrap().to_str().unwrap();
+        let result = run(test);
+        let outcome = match &result {
+            Ok(()) => "pass".to_owned(),
+            Err(reason) => format!("fail: {}", reason.replace('|', "\\|").replace('\n', " ")),
+        };
+        writeln!(report, "| {name} | {outcome} |").unwrap();
+        passed += usize::from(result.is_ok());
+        match (result, known_failures.contains(name)) {
+            (Err(reason), false) => unexpected.push(format!("{name} failed: {reason}")),
+            (Ok(()), true) => {
+                unexpected.push(format!("{name} passed (remove it from known-failures.txt)"));
+            }
+            _ => {}
+        }
+    }
+    for name in &known_failures {
+        if !tests.iter().any(|test| test.ends_with(name)) {
+            unexpected.push(format!("{name} in known-failures.txt does not exist"));
+        }
+    }
+
+    let summary = format!(
+        "{passed}/{} tests pass ({:.1}%)",
+        tests.len(),
+        percentage(passed, tests.len())
+    );
+    report.insert_str(0, &format!("# Conformance\n\n{summary}\n\n"));
+    std::fs::write(
+        Path::new(env!("CARGO_TARGET_TMPDIR")).join("conformance.md"),
+        report,
+    )
+    .unwrap();
+    println!("{summary}");
+
+    assert!(unexpected.is_empty(), "{}", unexpected.join("\n"));
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    let [part, total] = [part, total].map(|value| f64::from(u32::try_from(value).unwrap()));
+    100.0 * part / total.max(1.0)
+}
+
+/// Runs the test in `directory`, returning why it failed
+fn run(directory: &Path) -> Result<(), String> {
+    let input =
+        std::fs::read_to_string(directory.join("in.yaml")).map_err(|err| err.to_string())?;
+    let converted: Result<Vec<String>, _> = documents(&input)
+        .map(|(_, document)| yaml_to_json(document))
+        .collect();
+
+    if directory.join("error").exists() {
+        return match converted {
+            Ok(_) => Err("expected an error".to_owned()),
+            Err(_) => Ok(()),
+        };
+    }
+    let converted = converted.map_err(|err| err.to_string())?;
+    let expected =
+        std::fs::read_to_string(directory.join("in.json")).map_err(|err| err.to_string())?;
+    let expected = JSONValue::parse_stream(&expected).ok_or("invalid in.json")?;
+    let mut actual = Vec::new();
+    for json in &converted {
+        actual
+            .extend(JSONValue::parse_stream(json).ok_or_else(|| format!("invalid output {json}"))?);
+    }
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("expected {expected:?}, found {actual:?}"))
+    }
+}
+
+/// For comparing JSON ignoring formatting (and the format of numbers)
+#[derive(Debug, PartialEq)]
+enum JSONValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JSONValue>),
+    /// In order, as key order is kept
+    Object(Vec<(String, JSONValue)>),
+}
+
+impl JSONValue {
+    /// Parses whitespace separated values
+    fn parse_stream(on: &str) -> Option<Vec<JSONValue>> {
+        let mut rest = on.trim_start();
+        let mut values = Vec::new();
+        while !rest.is_empty() {
+            let (value, after) = JSONValue::parse(rest)?;
+            values.push(value);
+            rest = after.trim_start();
+        }
+        Some(values)
+    }
+
+    /// Parses a value at the start of `on`, returning it and the rest of `on`
+    fn parse(on: &str) -> Option<(JSONValue, &str)> {
+        let on = on.trim_start();
+        if let Some(rest) = on.strip_prefix("null") {
+            Some((JSONValue::Null, rest))
+        } else if let Some(rest) = on.strip_prefix("true") {
+            Some((JSONValue::Bool(true), rest))
+        } else if let Some(rest) = on.strip_prefix("false") {
+            Some((JSONValue::Bool(false), rest))
+        } else if on.starts_with('"') {
+            let (value, rest) = parse_string(on)?;
+            Some((JSONValue::String(value), rest))
+        } else if let Some(mut rest) = on.strip_prefix('[') {
+            let mut items = Vec::new();
+            loop {
+                rest = rest.trim_start();
+                if let Some(after) = rest.strip_prefix(']') {
+                    return Some((JSONValue::Array(items), after));
+                }
+                rest = rest.strip_prefix(',').unwrap_or(rest);
+                let (item, after) = JSONValue::parse(rest)?;
+                items.push(item);
+                rest = after;
+            }
+        } else if let Some(mut rest) = on.strip_prefix('{') {
+            let mut entries = Vec::new();
+            loop {
+                rest = rest.trim_start();
+                if let Some(after) = rest.strip_prefix('}') {
+                    return Some((JSONValue::Object(entries), after));
+                }
+                rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+                let (key, after) = parse_string(rest)?;
+                let (value, after) = JSONValue::parse(after.trim_start().strip_prefix(':')?)?;
+                entries.push((key, value));
+                rest = after;
+            }
+        } else {
+            let end = on
+                .find(|chr: char| {
+                    !(chr.is_ascii_digit() || matches!(chr, '-' | '+' | '.' | 'e' | 'E'))
+                })
+                .unwrap_or(on.len());
+            let number = on[..end].parse().ok()?;
+            Some((JSONValue::Number(number), &on[end..]))
+        }
+    }
+}
+
+fn parse_string(on: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = on.strip_prefix('"')?.char_indices();
+    let mut pending_surrogate = None;
+    while let Some((idx, chr)) = chars.next() {
+        match chr {
+            '"' => return Some((value, &on[1 + idx + 1..])),
+            '\\' => {
+                let escaped = match chars.next()?.1 {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'u' => {
+                        let hex: String = (0..4)
+                            .filter_map(|_| chars.next().map(|(_, chr)| chr))
+                            .collect();
+                        let unit = u16::from_str_radix(&hex, 16).ok()?;
+                        let units = match pending_surrogate.take() {
+                            Some(high) => vec![high, unit],
+                            None if (0xD800..0xDC00).contains(&unit) => {
+                                pending_surrogate = Some(unit);
+                                continue;
+                            }
+                            None => vec![unit],
+                        };
+                        char::decode_utf16(units).next()?.ok()?
+                    }
+                    other => other,
+                };
+                value.push(escaped);
+            }
+            chr => value.push(chr),
+        }
+    }
+    None
+}
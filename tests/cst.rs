@@ -0,0 +1,234 @@
+//! The lossless syntax tree (`cst`)
+
+use simple_yaml_parser::cst::{self, Element, Node, NodeKind, TokenKind};
+use simple_yaml_parser::YAMLParseErrorReason;
+use std::fmt::Write;
+
+/// Writes the tree as indented lines of kinds, with the text of tokens
+fn outline(on: &str, node: &Node, depth: usize, output: &mut String) {
+    writeln!(output, "{}{:?}", "  ".repeat(depth), node.kind).unwrap();
+    for child in &node.children {
+        match child {
+            Element::Node(node) => outline(on, node, depth + 1, output),
+            Element::Token(token) => writeln!(
+                output,
+                "{}{:?} {:?}",
+                "  ".repeat(depth + 1),
+                token.kind,
+                token.text(on)
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// Checks that each node's children are contiguous and cover its span
+fn check_spans(node: &Node) {
+    let mut at = node.span.start;
+    for child in &node.children {
+        assert_eq!(child.span().start, at, "{node:?}");
+        at = child.span().end;
+        if let Element::Node(child) = child {
+            check_spans(child);
+        }
+    }
+    assert_eq!(at, node.span.end, "{node:?}");
+}
+
+/// Checks that the tokens cover the input
+fn assert_lossless(on: &str) {
+    let tree = cst::parse(on).unwrap_or_else(|err| panic!("{err:?} for {on:?}"));
+    let text: String = tree.tokens().iter().map(|token| token.text(on)).collect();
+    assert_eq!(text, on);
+    check_spans(&tree);
+}
+
+const INPUTS: &[&str] = &[
+    "",
+    "\n\n",
+    "# only a comment",
+    "a: 1 # trailing\nb:\n  - x\n  -   y\n",
+    "\u{feff}a: b\r\nc:\r\n  - d\r\n",
+    "a: [x, {y: z}, 'q', \"r\"]\nb: {c: [1, 2], d: }\n",
+    "a: [\n  x, # one\n  y\n]\n",
+    "a: &anchor !!str value\nb: *anchor\nc: &seq\n  - 1\n",
+    "? complex\n: value\n",
+    "text: |-\n  line one\n\n    indented\n  # not a comment\nnext: >\n  folded\n",
+    "- |\n  in a sequence\n- >+\n\n  kept\n\n",
+    "key:\n- no indent\n- items\nother: 1\n",
+    "%YAML 1.2\n---\na: 1\n...\n---\n- b\n--- |\n  top level\n",
+    "a:\n  b:\n    c: d\n  e: f\ng: h\n",
+    "'quoted key': v\n\"x\": 'it''s'\nurl: http://x.y/z\n",
+    "a: b\n\n\n# between\n\nc: d\n   \n",
+    "- - - deep\n    - x\n  - y\n- z",
+    "e: \"é 日本 🦀\"\nplain: multi word value # and comment\n",
+];
+
+#[test]
+fn lossless() {
+    for on in INPUTS {
+        assert_lossless(on);
+    }
+}
+
+#[test]
+fn conformance_fixtures() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/conformance");
+    for entry in std::fs::read_dir(fixtures).unwrap() {
+        let Ok(on) = std::fs::read_to_string(entry.unwrap().path().join("in.yaml")) else {
+            continue;
+        };
+        if cst::parse(&on).is_ok() {
+            assert_lossless(&on);
+        }
+    }
+}
+
+/// xorshift, as in `tests/no_panic.rs`
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        usize::try_from(self.0 % (u64::from(u32::MAX) + 1)).unwrap()
+    }
+}
+
+const LINES: &[&str] = &[
+    "a: b",
+    "key:",
+    "- x",
+    "-",
+    "  c: d",
+    "  - e",
+    "    f",
+    "# comment",
+    "",
+    "  ",
+    "g: [h, {i: j}]",
+    "k: 'l' # m",
+    "n: |",
+    "  text",
+    "---",
+    "...",
+    "o: &p q",
+    "r: *p",
+    "s:\t t",
+    "\r",
+];
+
+#[test]
+fn generated_inputs() {
+    let mut rng = Rng(0x5eed);
+    let mut valid = 0;
+    for _ in 0..20_000 {
+        let mut on = String::new();
+        for _ in 0..rng.next() % 8 {
+            on.push_str(LINES[rng.next() % LINES.len()]);
+            on.push('\n');
+        }
+        if cst::parse(&on).is_ok() {
+            assert_lossless(&on);
+            valid += 1;
+        }
+    }
+    assert!(valid > 1_000);
+}
+
+#[test]
+fn structure() {
+    let on = "a: 1 # c\nb:\n  - x\n  - [y, {z: w}]\n";
+    let mut output = String::new();
+    outline(on, &cst::parse(on).unwrap(), 0, &mut output);
+    assert_eq!(
+        output,
+        r##"Stream
+  Document
+    BlockMapping
+      Entry
+        Plain "a"
+        Colon ":"
+        Whitespace " "
+        Plain "1"
+      Whitespace " "
+      Comment "# c"
+      Newline "\n"
+      Entry
+        Plain "b"
+        Colon ":"
+        Newline "\n"
+        Whitespace "  "
+        BlockSequence
+          Item
+            Dash "-"
+            Whitespace " "
+            Plain "x"
+          Newline "\n"
+          Whitespace "  "
+          Item
+            Dash "-"
+            Whitespace " "
+            FlowSequence
+              FlowStart "["
+              Plain "y"
+              Comma ","
+              Whitespace " "
+              FlowMapping
+                FlowStart "{"
+                Entry
+                  Plain "z"
+                  Colon ":"
+                  Whitespace " "
+                  Plain "w"
+                FlowEnd "}"
+              FlowEnd "]"
+    Newline "\n"
+"##
+    );
+}
+
+#[test]
+fn documents_and_block_scalars() {
+    let on = "%YAML 1.2\n---\nnote: |\n  a\n\n  b\n...\n---\n- c\n";
+    let tree = cst::parse(on).unwrap();
+    let documents: Vec<_> = tree.child_nodes().collect();
+    assert_eq!(documents.len(), 2);
+    assert!(documents
+        .iter()
+        .all(|document| document.kind == NodeKind::Document));
+    assert!(documents[0].text(on).starts_with("%YAML 1.2\n---\n"));
+    assert!(documents[0].text(on).ends_with("...\n"));
+
+    let lines: Vec<_> = documents[0]
+        .tokens()
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::BlockScalarLine)
+        .map(|token| token.text(on))
+        .collect();
+    assert_eq!(lines, ["  a", "  b"]);
+
+    let sequence = documents[1].child_nodes().next().unwrap();
+    assert_eq!(sequence.kind, NodeKind::BlockSequence);
+    assert_eq!(sequence.text(on), "- c");
+}
+
+#[test]
+fn errors() {
+    let err = cst::parse("a: 'open\nb: c\n").unwrap_err();
+    assert!(matches!(
+        err.reason,
+        YAMLParseErrorReason::ExpectedClosingQuote
+    ));
+    assert_eq!(err.line, 1);
+}
+
+#[test]
+fn depth_limit() {
+    let on = "- ".repeat(cst::MAX_DEPTH);
+    assert!(cst::parse(&on).is_ok());
+    let on = "- ".repeat(cst::MAX_DEPTH + 1);
+    let err = cst::parse(&on).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::MaxDepthExceeded));
+}
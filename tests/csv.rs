@@ -0,0 +1,94 @@
+//! Exporting sequences of mappings with `csv::sequence_to_csv`
+
+use simple_yaml_parser::csv::{sequence_to_csv, CsvError};
+
+const PEOPLE: &str = "people:
+  - name: Ada
+    age: 36
+    address:
+      city: London
+    tags: [maths, engines]
+  - name: 'Grace, Rear Admiral'
+    age: 85
+    quote: \"It's easier to ask \\\"forgiveness\\\"\"
+  - name: Linus
+    address: {city: Helsinki}
+    age: ~
+";
+
+#[test]
+fn records() {
+    assert_eq!(
+        sequence_to_csv(PEOPLE, "people").unwrap(),
+        "name,age,address.city,tags[0],tags[1],quote
+Ada,36,London,maths,engines,
+\"Grace, Rear Admiral\",85,,,,\"It's easier to ask \"\"forgiveness\"\"\"
+Linus,,Helsinki,,,
+"
+    );
+}
+
+#[test]
+fn top_level_and_nested_paths() {
+    assert_eq!(
+        sequence_to_csv("- a: 1\n  b: 2\n- a: 3\n  b: 4\n", "").unwrap(),
+        "a,b\n1,2\n3,4\n"
+    );
+    assert_eq!(
+        sequence_to_csv(
+            "x:\n  rows:\n    - a: |\n        two\n        lines\n",
+            "x.rows"
+        )
+        .unwrap(),
+        "a\n\"two\nlines\n\"\n"
+    );
+}
+
+#[test]
+fn aliases() {
+    let on = "defaults: &defaults\n  region: eu\nservers:\n  - <<: *defaults\n    name: a\n  - name: b\n    region: us\n";
+    assert_eq!(
+        sequence_to_csv(on, "servers").unwrap(),
+        "region,name\neu,a\nus,b\n"
+    );
+}
+
+#[test]
+fn errors() {
+    assert!(matches!(
+        sequence_to_csv(PEOPLE, "missing"),
+        Err(CsvError::NotFound)
+    ));
+    assert!(matches!(
+        sequence_to_csv("a:\n  b: 1\n", "a"),
+        Err(CsvError::NotFound)
+    ));
+    assert!(matches!(
+        sequence_to_csv("- a: 1\n- text\n", ""),
+        Err(CsvError::NotMapping(1))
+    ));
+    assert!(matches!(
+        sequence_to_csv("a: {}\n", "a"),
+        Err(CsvError::NotFound)
+    ));
+    assert!(matches!(
+        sequence_to_csv("- a: 1\n- []\n", ""),
+        Err(CsvError::NotMapping(1))
+    ));
+    assert!(matches!(
+        sequence_to_csv("a: ]", "a"),
+        Err(CsvError::Parse(_))
+    ));
+}
+
+#[test]
+fn empty() {
+    // an empty sequence is not a missing one
+    assert_eq!(sequence_to_csv("r: []\n", "r").unwrap(), "");
+    assert_eq!(sequence_to_csv("[]\n", "").unwrap(), "");
+    // empty items and collections in items
+    assert_eq!(
+        sequence_to_csv("r:\n  - {}\n  - a: 1\n    b: []\n", "r").unwrap(),
+        "a\n\n1\n"
+    );
+}
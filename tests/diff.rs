@@ -0,0 +1,51 @@
+//! Comparing documents with `diff`
+
+use simple_yaml_parser::{diff, format_key_chain, DiffError, ParseOptions, YAMLDiff};
+
+/// Differences as `+ path`, `- path` and `~ path`
+fn summary(old: &str, new: &str) -> Vec<String> {
+    diff(old, new, &ParseOptions::default())
+        .unwrap()
+        .iter()
+        .map(|diff| {
+            let symbol = match diff {
+                YAMLDiff::Added { .. } => '+',
+                YAMLDiff::Removed { .. } => '-',
+                YAMLDiff::Changed { .. } => '~',
+            };
+            format!("{symbol} {}", format_key_chain(diff.keys()))
+        })
+        .collect()
+}
+
+#[test]
+fn added_removed_changed() {
+    let old = "name: app\nreplicas: 2\nports:\n  - 80\n  - 443\nlegacy: true\n";
+    let new = "name: app\nreplicas: 3\nports:\n  - 80\nimage: app:1.0\n";
+    assert_eq!(
+        summary(old, new),
+        ["~ replicas", "+ image", "- ports[1]", "- legacy"]
+    );
+}
+
+#[test]
+fn same_text_different_quoting() {
+    assert!(summary("a: text\nb: |\n  x\n", "a: 'text'\nb: \"x\\n\"\n").is_empty());
+    // but typed values differ from strings
+    assert_eq!(summary("a: 1\n", "a: '1'\n"), ["~ a"]);
+}
+
+#[test]
+fn changed_values() {
+    let diffs = diff("a: 1\n", "a: 2\n", &ParseOptions::default()).unwrap();
+    let [YAMLDiff::Changed { old, new, .. }] = diffs.as_slice() else {
+        panic!("{diffs:?}");
+    };
+    assert_eq!((old.as_i64(), new.as_i64()), (Some(1), Some(2)));
+}
+
+#[test]
+fn invalid_document() {
+    let result = diff("a: 1\n", "a: [1\n", &ParseOptions::default());
+    assert!(matches!(result, Err(DiffError::New(_))));
+}
@@ -0,0 +1,68 @@
+//! `%YAML` and `%TAG` directives
+
+use simple_yaml_parser::{
+    documents, format_key_chain, parse, parse_documents_with_info, ParseOptions, RootYAMLValue,
+    YAMLParseErrorReason,
+};
+
+const STREAM: &str = "%YAML 1.2\n%TAG !e! tag:example.com,2000:app/ # comment\n---\na: !e!widget x\n---\nb: 2\n...\n%YAML 1.1\n---\nc: 3\n";
+
+#[test]
+fn info() {
+    let mut values = Vec::new();
+    parse_documents_with_info(
+        STREAM,
+        |info, keys, value| {
+            let tag = match value {
+                RootYAMLValue::Tagged(tagged) => info.resolve_tag(tagged.tag()),
+                _ => None,
+            };
+            values.push((info.index, info.version, format_key_chain(keys), tag));
+            false
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        values,
+        [
+            (
+                0,
+                Some("1.2"),
+                "a".to_owned(),
+                Some("tag:example.com,2000:app/widget".to_owned())
+            ),
+            (1, None, "b".to_owned(), None),
+            (2, Some("1.1"), "c".to_owned(), None),
+        ]
+    );
+}
+
+#[test]
+fn directives_belong_to_the_next_document() {
+    let documents: Vec<&str> = documents(STREAM).map(|(_, document)| document).collect();
+    assert_eq!(documents.len(), 3);
+    assert!(documents[0].starts_with("%YAML 1.2\n"));
+    assert_eq!(documents[1], "b: 2\n");
+    assert!(documents[2].starts_with("%YAML 1.1\n---\n"));
+}
+
+#[test]
+fn directives_are_not_values() {
+    let mut keys = Vec::new();
+    parse(STREAM, |chain, _| keys.push(format_key_chain(chain))).unwrap();
+    assert_eq!(keys, ["a", "b", "c"]);
+}
+
+#[test]
+fn errors() {
+    let err = parse("%YAML 2.0\n---\na: 1\n", |_, _| {}).unwrap_err();
+    assert!(matches!(
+        err.reason,
+        YAMLParseErrorReason::UnsupportedVersion
+    ));
+    assert_eq!((err.line, err.column), (1, 7));
+
+    let err = parse("%TAG !e!\n---\na: 1\n", |_, _| {}).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::InvalidDirective));
+}
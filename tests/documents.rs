@@ -0,0 +1,71 @@
+//! Splitting streams with `documents` and `parse_documents`
+
+use simple_yaml_parser::{documents, format_key_chain, parse_documents, ParseOptions};
+
+fn values(on: &str) -> Vec<(usize, String)> {
+    let mut values = Vec::new();
+    parse_documents(
+        on,
+        |index, keys, value| {
+            let value = value.as_str().unwrap_or_default().into_owned();
+            values.push((index, format!("{} = {value}", format_key_chain(keys))));
+            false
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    values
+}
+
+#[test]
+fn separators() {
+    let on = "kind: a\n---\nkind: b\nitems:\n  - x\n...\n---\n- y\n";
+    assert_eq!(
+        values(on),
+        [
+            (0, "kind = a".to_owned()),
+            (1, "kind = b".to_owned()),
+            (1, "items[0] = x".to_owned()),
+            (2, "[0] = y".to_owned())
+        ]
+    );
+}
+
+#[test]
+fn splitting() {
+    let on = "# only a comment\n---\na: 1\n--- # comment\nb: 2\n...\n";
+    let split: Vec<(usize, &str)> = documents(on).collect();
+    assert_eq!(split.len(), 2);
+    // offsets are of the document in the stream
+    for (offset, document) in &split {
+        assert_eq!(&on[*offset..*offset + document.len()], *document);
+    }
+    assert!(split[0].1.contains("a: 1") && split[1].1.contains("b: 2"));
+    // `---` inside a value is not a separator
+    assert_eq!(documents("a: x---y\nb: '---'\n").count(), 1);
+}
+
+#[test]
+fn errors_are_located_in_the_stream() {
+    let on = "a: 1\n---\nb: 2\nc: ]\n";
+    let err = parse_documents(on, |_, _, _| false, &ParseOptions::default()).unwrap_err();
+    assert_eq!(
+        (err.at, err.line, err.column),
+        (on.find(']').unwrap(), 4, 4)
+    );
+}
+
+#[test]
+fn exit() {
+    let mut indices = Vec::new();
+    parse_documents(
+        "a: 1\n---\nb: 2\n---\nc: 3\n",
+        |index, _, _| {
+            indices.push(index);
+            index == 1
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(indices, [0, 1]);
+}
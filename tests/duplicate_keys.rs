@@ -0,0 +1,47 @@
+//! `ParseOptions::deny_duplicate_keys`
+
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, YAMLParseErrorReason};
+
+/// The key, position of the first occurrence and line of the error
+fn duplicate(on: &str) -> Option<(String, usize, usize)> {
+    let options = ParseOptions::new().deny_duplicate_keys(true);
+    match parse_with_exit_signal(on, |_, _| false, &options) {
+        Ok(_) => None,
+        Err(err) => match err.reason {
+            YAMLParseErrorReason::DuplicateKey { key, first_at } => {
+                assert_eq!(&on[err.span.clone()], key);
+                Some((key, first_at, err.line))
+            }
+            reason => panic!("{reason:?}"),
+        },
+    }
+}
+
+#[test]
+fn duplicates() {
+    assert_eq!(
+        duplicate("a: 1\nb: 2\na: 3\n"),
+        Some(("a".to_owned(), 0, 3))
+    );
+    let on = "server:\n  host: a\n  port: 1\n  host: b\n";
+    assert_eq!(
+        duplicate(on),
+        Some(("host".to_owned(), on.find("host").unwrap(), 4))
+    );
+    let on = "items:\n  - name: a\n    name: b\n";
+    assert_eq!(
+        duplicate(on),
+        Some(("name".to_owned(), on.find("name").unwrap(), 3))
+    );
+    assert_eq!(duplicate("a: {x: 1, x: 2}\n"), Some(("x".to_owned(), 4, 1)));
+}
+
+#[test]
+fn not_duplicates() {
+    // the same key in different mappings, sequence items and documents
+    assert_eq!(duplicate("a:\n  x: 1\nb:\n  x: 2\n"), None);
+    assert_eq!(duplicate("- x: 1\n- x: 2\n"), None);
+    assert_eq!(duplicate("a: 1\n---\na: 2\n"), None);
+    // allowed without the option
+    parse_with_exit_signal("a: 1\na: 2\n", |_, _| false, &ParseOptions::default()).unwrap();
+}
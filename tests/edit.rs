@@ -0,0 +1,309 @@
+//! Editing the source text with `replace_value` and `Document`
+
+use simple_yaml_parser::{parse_key_path, replace_value, Document, EditError};
+
+fn replace(source: &str, path: &str, new_value: &str) -> String {
+    replace_value(source, &parse_key_path(path), new_value).unwrap()
+}
+
+#[test]
+fn keeps_formatting_and_comments() {
+    let source = "# config\nname: app   # the name\nreplicas:   2\nports:\n  - 80\n  - 443\n";
+    assert_eq!(
+        replace(source, "replicas", "3"),
+        "# config\nname: app   # the name\nreplicas:   3\nports:\n  - 80\n  - 443\n"
+    );
+    assert_eq!(
+        replace(source, "ports[1]", "8443"),
+        "# config\nname: app   # the name\nreplicas:   2\nports:\n  - 80\n  - 8443\n"
+    );
+}
+
+#[test]
+fn quoted_and_flow_values() {
+    assert_eq!(
+        replace("a: 'old'\nb: [1, 2]\n", "a", "\"new\""),
+        "a: \"new\"\nb: [1, 2]\n"
+    );
+    assert_eq!(
+        replace("a: 'old'\nb: [1, 2]\n", "b[1]", "3"),
+        "a: 'old'\nb: [1, 3]\n"
+    );
+}
+
+#[test]
+fn empty_value() {
+    assert_eq!(replace("a:\nb: 1\n", "a", "x"), "a: x\nb: 1\n");
+}
+
+#[test]
+fn keeps_anchors() {
+    assert_eq!(replace("a: &x 1\n", "a", "2"), "a: &x 2\n");
+}
+
+#[test]
+fn block_scalar() {
+    assert_eq!(
+        replace("a: |- # text\n  line\n  more\nb: 1\n", "a", "short"),
+        "a: short\nb: 1\n"
+    );
+}
+
+#[test]
+fn not_found() {
+    let result = replace_value("a: 1\n", &parse_key_path("b"), "2");
+    assert!(matches!(result, Err(EditError::NotFound)));
+    let result = replace_value("a:\n  b: 1\n", &parse_key_path("a"), "2");
+    assert!(matches!(result, Err(EditError::NotFound)));
+}
+
+const CONFIG: &str = "# CI configuration
+version: 1.2.0  # bumped on release
+
+jobs:
+  test:
+    image: 'rust:1.80'
+    steps: [checkout, test]
+  # deploys on tags
+  deploy:
+    - name: publish
+      token: secret
+    - name: notify
+";
+
+fn edited(edit: impl FnOnce(&mut Document) -> Result<(), EditError>) -> String {
+    let mut document = Document::parse(CONFIG).unwrap();
+    edit(&mut document).unwrap();
+    document.to_string()
+}
+
+#[test]
+fn document_set() {
+    assert_eq!(
+        edited(|document| document.set("version", "1.3.0")),
+        CONFIG.replace("1.2.0 ", "1.3.0 ")
+    );
+    assert_eq!(
+        edited(|document| document.set("jobs.test.image", "'rust:1.81'")),
+        CONFIG.replace("1.80", "1.81")
+    );
+    assert_eq!(
+        edited(|document| document.set("jobs.test.steps[1]", "lint")),
+        CONFIG.replace("test]", "lint]")
+    );
+    assert_eq!(
+        edited(|document| document.set("jobs.deploy[1].name", "email")),
+        CONFIG.replace("notify", "email")
+    );
+}
+
+#[test]
+fn document_set_new_keys() {
+    assert_eq!(
+        edited(|document| document.set("jobs.test.timeout", "10")),
+        CONFIG.replace(
+            "    steps: [checkout, test]\n",
+            "    steps: [checkout, test]\n    timeout: 10\n"
+        )
+    );
+    assert_eq!(
+        edited(|document| document.set("jobs.lint.image.tag", "latest")),
+        format!("{CONFIG}  lint:\n    image:\n      tag: latest\n")
+    );
+    assert_eq!(
+        edited(|document| document.set("jobs.deploy[0].retries", "3")),
+        CONFIG.replace(
+            "      token: secret\n",
+            "      token: secret\n      retries: 3\n"
+        )
+    );
+
+    let mut document = Document::parse("a:\nb: 1").unwrap();
+    document.set("a.c", "x").unwrap();
+    document.set("d", "y").unwrap();
+    assert_eq!(document.as_str(), "a:\n  c: x\nb: 1\nd: y\n");
+
+    let mut document = Document::parse("").unwrap();
+    document.set("a.b", "1").unwrap();
+    assert_eq!(document.as_str(), "a:\n  b: 1\n");
+}
+
+#[test]
+fn document_remove() {
+    assert_eq!(
+        edited(|document| document.remove("version")),
+        CONFIG.replace("version: 1.2.0  # bumped on release\n", "")
+    );
+    assert_eq!(
+        edited(|document| document.remove("jobs.test.steps[0]")),
+        CONFIG.replace("[checkout, test]", "[test]")
+    );
+    assert_eq!(
+        edited(|document| document.remove("jobs.test.steps[1]")),
+        CONFIG.replace("[checkout, test]", "[checkout]")
+    );
+    assert_eq!(
+        edited(|document| document.remove("jobs.deploy[0].name")),
+        CONFIG.replace("- name: publish\n      token", "- token")
+    );
+    assert_eq!(
+        edited(|document| document.remove("jobs.deploy[1]")),
+        CONFIG.replace("    - name: notify\n", "")
+    );
+    assert_eq!(
+        edited(|document| document.remove("jobs.deploy[1].name")),
+        CONFIG.replace("- name: notify", "- {}")
+    );
+    // the comment above the removed entry is kept
+    assert_eq!(
+        edited(|document| document.remove("jobs.deploy")),
+        CONFIG.replace(
+            "  deploy:\n    - name: publish\n      token: secret\n    - name: notify\n",
+            ""
+        )
+    );
+}
+
+#[test]
+fn document_errors() {
+    let mut document = Document::parse(CONFIG).unwrap();
+    for path in [
+        "jobs",
+        "jobs.test.steps[5]",
+        "version.major",
+        "jobs.test.steps.a",
+    ] {
+        let result = document.set(path, "x");
+        assert!(matches!(result, Err(EditError::NotFound)), "{path}");
+    }
+    assert!(matches!(
+        document.remove("missing"),
+        Err(EditError::NotFound)
+    ));
+    // values are inserted as written
+    let result = document.set("version", "a: b");
+    assert!(matches!(result, Err(EditError::Parse(_))));
+    assert_eq!(document.as_str(), CONFIG);
+}
+
+#[test]
+fn document_push() {
+    assert_eq!(
+        edited(|document| document.push("jobs.deploy", "name: archive")),
+        format!("{CONFIG}    - name: archive\n")
+    );
+    assert_eq!(
+        edited(|document| document.push("jobs.test.steps", "lint")),
+        CONFIG.replace("test]", "test, lint]")
+    );
+
+    // comments after the last item stay after the new one
+    let mut document =
+        Document::parse("steps:\n- a # first\n- b\n# end of steps\nnext: 1").unwrap();
+    document.push("steps", "c").unwrap();
+    assert_eq!(
+        document.as_str(),
+        "steps:\n- a # first\n- b\n- c\n# end of steps\nnext: 1"
+    );
+
+    let mut document = Document::parse("- a\n- - b").unwrap();
+    document.push("", "c").unwrap();
+    document.push("[1]", "d").unwrap();
+    assert_eq!(document.as_str(), "- a\n- - b\n  - d\n- c\n");
+
+    let mut document = Document::parse("empty:\nflow: []\n").unwrap();
+    document.push("empty", "x").unwrap();
+    document.push("flow", "y").unwrap();
+    assert_eq!(document.as_str(), "empty:\n  - x\nflow: [y]\n");
+
+    let mut document = Document::parse(CONFIG).unwrap();
+    for path in ["version", "jobs.test", "missing"] {
+        let result = document.push(path, "x");
+        assert!(matches!(result, Err(EditError::NotFound)), "{path}");
+    }
+}
+
+#[test]
+fn document_rename_key() {
+    assert_eq!(
+        edited(|document| document.rename_key("version", "release")),
+        CONFIG.replace("version:", "release:")
+    );
+    assert_eq!(
+        edited(|document| document.rename_key("jobs.deploy", "'publish'")),
+        CONFIG.replace("  deploy:", "  'publish':")
+    );
+    assert_eq!(
+        edited(|document| document.rename_key("jobs.deploy[1].name", "title")),
+        CONFIG.replace("- name: notify", "- title: notify")
+    );
+
+    let mut document =
+        Document::parse("base: &base\n  a: 1\n\"old\": *base # alias\nflow: {x: 1, y: 2}\n")
+            .unwrap();
+    document.rename_key("old", "new").unwrap();
+    document.rename_key("base", "defaults").unwrap();
+    document.rename_key("flow.y", "z").unwrap();
+    assert_eq!(
+        document.as_str(),
+        "defaults: &base\n  a: 1\nnew: *base # alias\nflow: {x: 1, z: 2}\n"
+    );
+
+    assert!(matches!(
+        document.rename_key("flow.x", "z"),
+        Err(EditError::KeyExists)
+    ));
+    assert!(matches!(
+        document.rename_key("flow.x", "'z'"),
+        Err(EditError::KeyExists)
+    ));
+    for path in ["missing", "defaults.b", "jobs[0]"] {
+        let result = document.rename_key(path, "x");
+        assert!(matches!(result, Err(EditError::NotFound)), "{path}");
+    }
+    let mut document = Document::parse("- a\n").unwrap();
+    assert!(matches!(
+        document.rename_key("[0]", "b"),
+        Err(EditError::NotFound)
+    ));
+}
+
+#[test]
+fn unclosed_quoted_keys() {
+    for on in ["': 1\n", "*x~1]- \":  x"] {
+        // either the source is rejected or the key is not matched
+        let Ok(document) = Document::parse(on) else {
+            continue;
+        };
+        for path in ["'", "\"", "x"] {
+            let mut edited = document.clone();
+            let _ = edited.set(path, "1");
+            let mut edited = document.clone();
+            let _ = edited.remove(path);
+            let mut edited = document.clone();
+            let _ = edited.push(path, "1");
+            let mut edited = document.clone();
+            let _ = edited.rename_key(path, "z");
+        }
+    }
+}
+
+#[test]
+fn uses_document_indentation() {
+    let mut document = Document::parse("a:\n    b: 1\nc:\n").unwrap();
+    document.set("a.x.y", "1").unwrap();
+    document.set("c.d", "2").unwrap();
+    document.set("e.f.g", "3").unwrap();
+    document.push("h", "4").unwrap_err();
+    assert_eq!(
+        document.as_str(),
+        "a:\n    b: 1\n    x:\n        y: 1\nc:\n    d: 2\ne:\n    f:\n        g: 3\n"
+    );
+
+    let mut document = Document::parse("list:\nnext: 1\n  # comment\nnested:\n   a: 1\n").unwrap();
+    document.push("list", "x").unwrap();
+    assert_eq!(
+        document.as_str(),
+        "list:\n   - x\nnext: 1\n  # comment\nnested:\n   a: 1\n"
+    );
+}
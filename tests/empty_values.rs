@@ -0,0 +1,63 @@
+//! A key or sequence item with nothing after it is null, unless a block is nested under it
+
+use simple_yaml_parser::{format_key_chain, parse_with_exit_signal, ParseOptions, Schema};
+
+/// Values as `path = value`
+fn values(on: &str, schema: Schema) -> Vec<String> {
+    let mut values = Vec::new();
+    let options = ParseOptions::new().schema(schema);
+    parse_with_exit_signal(
+        on,
+        |keys, value| {
+            values.push(format!("{} = {value:?}", format_key_chain(keys)));
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    values
+}
+
+#[test]
+fn nothing_nested_is_null() {
+    let on = "a:\nb:   # comment\nc:\n  # only a comment\nd: &x\ne:";
+    assert_eq!(
+        values(on, Schema::Core),
+        ["a = Null", "b = Null", "c = Null", "d = Null", "e = Null"]
+    );
+}
+
+#[test]
+fn nested_block_is_the_value() {
+    let on = "a:\n\n  b:\n    c: 1\nd: &x !!map\n  e: 2\nf:\n- 3\n";
+    assert_eq!(
+        values(on, Schema::Core),
+        [
+            "a.b.c = Number(\"1\")",
+            "d.e = Number(\"2\")",
+            "f[0] = Number(\"3\")"
+        ]
+    );
+}
+
+#[test]
+fn sequence_items() {
+    let on = "-\n- # comment\n- &x\n  a: 1\n- !!seq\n  - 2\n-";
+    assert_eq!(
+        values(on, Schema::Core),
+        [
+            "[0] = Null",
+            "[1] = Null",
+            "[2].a = Number(\"1\")",
+            "[3][0] = Number(\"2\")",
+            "[4] = Null"
+        ]
+    );
+}
+
+#[test]
+fn null_in_every_schema() {
+    for schema in [Schema::Failsafe, Schema::Json, Schema::Core, Schema::Yaml11] {
+        assert_eq!(values("a:\nb:\n  -\n", schema), ["a = Null", "b[0] = Null"]);
+    }
+}
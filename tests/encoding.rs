@@ -0,0 +1,111 @@
+//! Byte order marks and `parse_bytes`
+
+use simple_yaml_parser::{
+    documents, parse, parse_bytes, ParseOptions, RootYAMLValue, YAMLKey, YAMLParseErrorReason,
+};
+
+fn entries(on: &[u8]) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    parse_bytes(
+        on,
+        |keys, value| {
+            let YAMLKey::Slice(key) = keys[0] else {
+                panic!("{keys:?}")
+            };
+            let RootYAMLValue::String(value) = value else {
+                panic!("{value:?}")
+            };
+            entries.push((key.to_owned(), value.to_owned()));
+            false
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    entries
+}
+
+#[test]
+fn byte_order_mark_is_skipped() {
+    let mut keys = Vec::new();
+    parse("\u{feff}a: 1\nb: 2\n", |path, _| {
+        keys.push(format!("{path:?}"));
+    })
+    .unwrap();
+    assert_eq!(keys, [r#"[Slice("a")]"#, r#"[Slice("b")]"#]);
+
+    let on = "\u{feff}a: 1\n---\nb: 2\n";
+    let documents: Vec<_> = documents(on).collect();
+    assert_eq!(documents, [(3, "a: 1\n"), (12, "b: 2\n")]);
+}
+
+#[test]
+fn utf8() {
+    assert_eq!(
+        entries("\u{feff}name: caf\u{e9}\n".as_bytes()),
+        [("name".to_owned(), "caf\u{e9}".to_owned())]
+    );
+}
+
+#[test]
+fn invalid_utf8() {
+    let err = parse_bytes(
+        b"a: 1\nb: \xFF\xFE!\n",
+        |_, _| false,
+        &ParseOptions::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::InvalidEncoding));
+    assert_eq!(err.span, 8..9);
+    assert_eq!((err.line, err.column), (2, 4));
+
+    // truncated character
+    let err = parse_bytes(b"a: \xE2\x82", |_, _| false, &ParseOptions::default()).unwrap_err();
+    assert_eq!(err.span, 3..5);
+}
+
+#[cfg(feature = "utf16")]
+#[test]
+fn utf16() {
+    let encode = |on: &str, little_endian: bool| -> Vec<u8> {
+        let mut bytes = if little_endian {
+            vec![0xFF, 0xFE]
+        } else {
+            vec![0xFE, 0xFF]
+        };
+        for unit in on.encode_utf16() {
+            if little_endian {
+                bytes.extend(unit.to_le_bytes());
+            } else {
+                bytes.extend(unit.to_be_bytes());
+            }
+        }
+        bytes
+    };
+    let expected = [
+        ("a".to_owned(), "\u{1F600}".to_owned()),
+        ("b".to_owned(), "x".to_owned()),
+    ];
+    assert_eq!(entries(&encode("a: \u{1F600}\nb: x\n", true)), expected);
+    assert_eq!(entries(&encode("a: \u{1F600}\nb: x\n", false)), expected);
+
+    // unpaired surrogate after "a: "
+    let mut bytes = encode("a: ", true);
+    bytes.extend([0x00, 0xD8, 0x61, 0x00]);
+    let err = parse_bytes(&bytes, |_, _| false, &ParseOptions::default()).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::InvalidEncoding));
+    assert_eq!(err.at, 3);
+
+    // odd length
+    let mut bytes = encode("a: b", true);
+    bytes.push(0);
+    let err = parse_bytes(&bytes, |_, _| false, &ParseOptions::default()).unwrap_err();
+    assert_eq!(err.at, 4);
+}
+
+#[cfg(not(feature = "utf16"))]
+#[test]
+fn utf16_requires_feature() {
+    let err = parse_bytes(b"\xFF\xFEa\0", |_, _| false, &ParseOptions::default()).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::InvalidEncoding));
+    assert_eq!(err.span, 0..2);
+}
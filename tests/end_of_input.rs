@@ -0,0 +1,66 @@
+//! The last line is parsed the same whether or not the input ends with a newline
+
+use simple_yaml_parser::{format_key_chain, parse_with_comments, ParseOptions};
+
+/// Values as `path = value` and comments as `# comment`
+fn events(on: &str) -> Vec<String> {
+    let mut events = Vec::new();
+    let mut comments = Vec::new();
+    let options = ParseOptions::new().emit_comments(true);
+    parse_with_comments(
+        on,
+        |keys, value| {
+            // strings are written resolved, other values with `Debug`
+            let value = match value.as_str() {
+                Some(text) => format!("{text:?}"),
+                None => format!("{value:?}"),
+            };
+            events.push(format!("{} = {value}", format_key_chain(keys)));
+            false
+        },
+        |_, comment| comments.push(format!("#{comment}")),
+        &options,
+    )
+    .unwrap();
+    events.extend(comments);
+    events
+}
+
+fn assert_same_with_newline(on: &str, expected: &[&str]) {
+    assert_eq!(events(on), expected, "without newline: {on:?}");
+    assert_eq!(events(&format!("{on}\n")), expected, "with newline: {on:?}");
+}
+
+#[test]
+fn value() {
+    assert_same_with_newline("a: 1\nb: text", &["a = Number(\"1\")", "b = \"text\""]);
+    assert_same_with_newline("a: 1\nb:", &["a = Number(\"1\")", "b = Null"]);
+    assert_same_with_newline("a: 'quoted'", &["a = \"quoted\""]);
+}
+
+#[test]
+fn list_item() {
+    assert_same_with_newline("- x\n- y", &["[0] = \"x\"", "[1] = \"y\""]);
+    assert_same_with_newline("a:\n  - k: v", &["a[0].k = \"v\""]);
+}
+
+#[test]
+fn flow_collection() {
+    assert_same_with_newline(
+        "a: [1, 2]",
+        &["a[0] = Number(\"1\")", "a[1] = Number(\"2\")"],
+    );
+}
+
+#[test]
+fn multiline() {
+    assert_same_with_newline("a: |\n  line\n  two", &["a = \"line\\ntwo\\n\""]);
+    assert_same_with_newline("a: >-\n  folded\n  text", &["a = \"folded text\""]);
+    assert_same_with_newline("a: |", &["a = \"\""]);
+}
+
+#[test]
+fn comment() {
+    assert_same_with_newline("a: 1 # end", &["a = Number(\"1\")", "# end"]);
+    assert_same_with_newline("a: 1\n# end", &["a = Number(\"1\")", "# end"]);
+}
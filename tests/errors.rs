@@ -0,0 +1,55 @@
+//! `YAMLParseError` positions and `display_with_source`
+
+use simple_yaml_parser::{parse, YAMLParseError};
+
+fn error(on: &str) -> YAMLParseError {
+    parse(on, |_, _| {}).unwrap_err()
+}
+
+#[test]
+fn positions() {
+    let on = "name: app\nlist: [a, b\nnext: c\n";
+    let err = error(on);
+    assert_eq!(err.at, err.span.start);
+    assert_eq!(line_and_column(on, err.at), (err.line, err.column));
+
+    // columns are in characters
+    let on = "é: ü\nkey: \"ßß";
+    let err = error(on);
+    assert_eq!((err.line, err.column), (2, 6));
+    assert_eq!(err.at, on.find('"').unwrap());
+}
+
+fn line_and_column(on: &str, at: usize) -> (usize, usize) {
+    let before = &on[..at];
+    let line = before.lines().count().max(1) + usize::from(before.ends_with('\n'));
+    let column = before.rsplit('\n').next().unwrap().chars().count() + 1;
+    (line, column)
+}
+
+#[test]
+fn display_with_source() {
+    let on = "a: 1\nb: ]\n";
+    let err = error(on);
+    assert_eq!(
+        err.display_with_source(on),
+        format!("{err}\n  |\n2 | b: ]\n  |    ^")
+    );
+    assert!(err.to_string().ends_with(" at 2:4"), "{err}");
+
+    // the line is found with `\r\n` line endings, without the `\r`
+    let on = "a: 1\r\nb: ]\r\n";
+    assert!(error(on).display_with_source(on).contains("\n2 | b: ]\n"));
+}
+
+/// `std::error::Error` is implemented with the `std` feature (the default)
+#[cfg(feature = "std")]
+#[test]
+fn std_error() {
+    fn parse_boxed(on: &str) -> Result<(), Box<dyn std::error::Error>> {
+        parse(on, |_, _| {})?;
+        Ok(())
+    }
+    let err = parse_boxed("a: ]\n").unwrap_err();
+    assert!(err.to_string().starts_with("YAMLParseError"), "{err}");
+}
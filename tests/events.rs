@@ -0,0 +1,117 @@
+//! `parse_events` start, end and scalar events
+
+use simple_yaml_parser::{parse_events, ParseOptions, RootYAMLValue, YAMLEvent};
+
+/// Events in a compact form: `{`, `}`, `[`, `]`, `<` and `>` for documents, `key:` and scalar text
+fn events(on: &str, options: &ParseOptions) -> Vec<String> {
+    let mut events = Vec::new();
+    parse_events(
+        on,
+        |event| {
+            events.push(match event {
+                YAMLEvent::DocumentStart => "<".to_owned(),
+                YAMLEvent::DocumentEnd => ">".to_owned(),
+                YAMLEvent::MapStart => "{".to_owned(),
+                YAMLEvent::MapEnd => "}".to_owned(),
+                YAMLEvent::SeqStart => "[".to_owned(),
+                YAMLEvent::SeqEnd => "]".to_owned(),
+                YAMLEvent::Key(key) => format!("{key}:"),
+                YAMLEvent::Scalar(RootYAMLValue::Number(number)) => number.to_owned(),
+                YAMLEvent::Scalar(value) => value.as_str().unwrap_or_default().into_owned(),
+            });
+            false
+        },
+        options,
+    )
+    .unwrap();
+    events
+}
+
+fn default(on: &str) -> String {
+    events(on, &ParseOptions::default()).join(" ")
+}
+
+#[test]
+fn nesting() {
+    let on = "name: app\nspec:\n  ports:\n    - a\n    - b\n  env:\n    - name: x\n      value: y\nlast: z\n";
+    assert_eq!(
+        default(on),
+        "< { name: app spec: { ports: [ a b ] env: [ { name: x value: y } ] } last: z } >"
+    );
+    assert_eq!(default("- - a\n  - b\n- c\n"), "< [ [ a b ] c ] >");
+    assert_eq!(default("a: [x, {y: z}]\n"), "< { a: [ x { y: z } ] } >");
+}
+
+#[test]
+fn empty_collections() {
+    assert_eq!(default("a: []\n"), "< { a: [ ] } >");
+    assert_eq!(
+        default("a: []\nb: 1\nc: {}\n"),
+        "< { a: [ ] b: 1 c: { } } >"
+    );
+    assert_eq!(default("- []\n- {}\n- x\n"), "< [ [ ] { } x ] >");
+    assert_eq!(
+        default("a: [[], {b: []}]\n"),
+        "< { a: [ [ ] { b: [ ] } ] } >"
+    );
+    assert_eq!(default("[]\n"), "< [ ] >");
+    assert_eq!(default("{}\n"), "< { } >");
+    assert_eq!(default(""), "");
+
+    // through aliases
+    let options = ParseOptions::new().resolve_aliases(true);
+    assert_eq!(
+        events("a: &empty []\nb: *empty\n", &options).join(" "),
+        "< { a: [ ] b: [ ] } >"
+    );
+}
+
+#[test]
+fn documents() {
+    assert_eq!(
+        default("a: 1\nb:\n  c: 2\n---\n- x\n- y: z\n"),
+        "< { a: 1 b: { c: 2 } } > < [ x { y: z } ] >"
+    );
+    assert_eq!(
+        default("---\na: [1]\n...\n---\nb: {}\n"),
+        "< { a: [ 1 ] } > < { b: { } } >"
+    );
+    // explicit documents without content
+    assert_eq!(default("---\n---\na: b\n"), "< > < { a: b } >");
+    // comments before the first document are not a document
+    assert_eq!(default("# header\n---\na: b\n"), "< { a: b } >");
+}
+
+#[test]
+fn balanced() {
+    let on = "a:\n  b:\n    - c: []\n      d: {e: [f]}\n---\n- - - g\n---\nh: {}\n...\n";
+    let events = events(on, &ParseOptions::default());
+    let mut stack = Vec::new();
+    for event in &events {
+        match event.as_str() {
+            "<" | "{" | "[" => stack.push(event.as_str()),
+            ">" => assert_eq!(stack.pop(), Some("<")),
+            "}" => assert_eq!(stack.pop(), Some("{")),
+            "]" => assert_eq!(stack.pop(), Some("[")),
+            _ => {}
+        }
+    }
+    assert!(stack.is_empty(), "{events:?}");
+    assert_eq!(events.iter().filter(|event| *event == "<").count(), 3);
+}
+
+#[test]
+fn exit() {
+    let mut count = 0;
+    parse_events(
+        "a: [1, 2]\nb: 3\n",
+        |event| {
+            count += 1;
+            event == YAMLEvent::SeqStart
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    // `<`, `{`, `a:` and `[`
+    assert_eq!(count, 4);
+}
@@ -0,0 +1,56 @@
+//! The position returned by `parse_with_exit_signal`
+
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, Parsed, RootYAMLValue, YAMLKey};
+
+/// Parses until the value of `key`
+fn until(on: &str, key: &str) -> Parsed {
+    parse_with_exit_signal(
+        on,
+        |keys, _| keys.last() == Some(&YAMLKey::Slice(key)),
+        &ParseOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn whole_input() {
+    let on = "a: 1\nb: 2\n";
+    assert_eq!(
+        until(on, "c"),
+        Parsed {
+            offset: on.len(),
+            exited: false
+        }
+    );
+}
+
+#[test]
+fn after_value() {
+    let on = "a: 1\nb: \"two\" # comment\nc: [3, 4]\n";
+    let parsed = until(on, "b");
+    assert!(parsed.exited);
+    assert_eq!(&on[parsed.offset..], " # comment\nc: [3, 4]\n");
+
+    let on = "a: |\n  text\nb: 2\n";
+    assert_eq!(&on[until(on, "a").offset..], "\nb: 2\n");
+}
+
+#[test]
+fn continue_with_rest() {
+    // a header with the length of the body, followed by the body
+    let on = "length: 5\nbody\nnext";
+    let mut length = 0;
+    let parsed = parse_with_exit_signal(
+        on,
+        |_, value| {
+            if let RootYAMLValue::Number(number) = value {
+                length = number.parse().unwrap();
+            }
+            true
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    let body = &on[parsed.offset + 1..];
+    assert_eq!(&body[..length], "body\n");
+}
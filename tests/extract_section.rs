@@ -0,0 +1,76 @@
+//! `extract_section`
+
+use simple_yaml_parser::extract_section;
+
+const SOURCE: &str = "version: 3
+services:
+  api:
+    image: api:1 # pinned
+    env:
+      - A=1
+  web:
+    image: 'web'
+    ports: [80, 443]
+    command: |
+      run
+      --fast
+  workers:
+    - name: a
+      count: 2
+    - b
+";
+
+#[test]
+fn mappings_keep_indentation() {
+    assert_eq!(
+        extract_section(SOURCE, "services.api"),
+        Some("    image: api:1 # pinned\n    env:\n      - A=1")
+    );
+    assert_eq!(
+        extract_section(SOURCE, "services.web"),
+        Some("    image: 'web'\n    ports: [80, 443]\n    command: |\n      run\n      --fast")
+    );
+}
+
+#[test]
+fn sequences() {
+    assert_eq!(
+        extract_section(SOURCE, "services.workers"),
+        Some("    - name: a\n      count: 2\n    - b")
+    );
+    assert_eq!(
+        extract_section(SOURCE, "services.workers.0"),
+        Some("name: a\n      count: 2")
+    );
+    assert_eq!(extract_section(SOURCE, "services.workers.1"), Some("b"));
+}
+
+#[test]
+fn scalars_as_written() {
+    assert_eq!(extract_section(SOURCE, "version"), Some("3"));
+    assert_eq!(extract_section(SOURCE, "services.web.image"), Some("'web'"));
+    assert_eq!(
+        extract_section(SOURCE, "services.web.ports"),
+        Some("[80, 443]")
+    );
+    assert_eq!(
+        extract_section(SOURCE, "services.web.command"),
+        Some("      run\n      --fast")
+    );
+}
+
+#[test]
+fn missing() {
+    assert_eq!(extract_section(SOURCE, "services.db"), None);
+    assert_eq!(extract_section(SOURCE, "services.web.ports.2"), None);
+    assert_eq!(extract_section("a: [1\n", "a"), None);
+}
+
+#[test]
+fn split_by_service() {
+    for name in ["api", "web", "workers"] {
+        let section = extract_section(SOURCE, &format!("services.{name}")).unwrap();
+        // a slice of the source, directly after the line of the key
+        assert!(SOURCE.contains(&format!("\n  {name}:\n{section}\n")));
+    }
+}
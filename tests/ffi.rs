@@ -0,0 +1,183 @@
+//! The C interface
+#![cfg(feature = "ffi")]
+
+use simple_yaml_parser::ffi::{
+    yaml_parse, YAMLErrorPosition, YAMLValueKind, YAML_INVALID_ARGUMENT, YAML_INVALID_UTF8,
+    YAML_OK, YAML_PARSE_ERROR,
+};
+use std::ffi::{c_char, c_int, c_void, CStr};
+
+/// The path, value, kind and tag
+type Value = (String, String, YAMLValueKind, String);
+
+extern "C" fn collect(
+    path: *const c_char,
+    path_len: usize,
+    value: *const c_char,
+    value_len: usize,
+    kind: YAMLValueKind,
+    tag: *const c_char,
+    tag_len: usize,
+    user_data: *mut c_void,
+) -> c_int {
+    // SAFETY: `user_data` is the `Vec` passed to `parse` and the strings are NUL terminated
+    let (values, path, value, tag) = unsafe {
+        (
+            &mut *user_data.cast::<Vec<Value>>(),
+            CStr::from_ptr(path).to_str().unwrap(),
+            CStr::from_ptr(value).to_str().unwrap(),
+            CStr::from_ptr(tag).to_str().unwrap(),
+        )
+    };
+    assert_eq!(
+        (path.len(), value.len(), tag.len()),
+        (path_len, value_len, tag_len)
+    );
+    values.push((path.to_owned(), value.to_owned(), kind, tag.to_owned()));
+    c_int::from(value == "stop")
+}
+
+fn parse(on: &[u8]) -> (c_int, Vec<Value>, YAMLErrorPosition) {
+    let mut values = Vec::new();
+    let mut error = YAMLErrorPosition::default();
+    // SAFETY: `on` and `error` are valid for the call
+    let code = unsafe {
+        yaml_parse(
+            on.as_ptr().cast(),
+            on.len(),
+            Some(collect),
+            std::ptr::from_mut(&mut values).cast(),
+            std::ptr::from_mut(&mut error),
+        )
+    };
+    (code, values, error)
+}
+
+#[test]
+fn values() {
+    let (code, values, _) = parse(b"a:\n  b: \"x\\ty\"\n  c: [1, true]\nd:\n");
+    assert_eq!(code, YAML_OK);
+    let expected = [
+        ("a.b", "x\ty", YAMLValueKind::String),
+        ("a.c[0]", "1", YAMLValueKind::Number),
+        ("a.c[1]", "true", YAMLValueKind::True),
+        ("d", "", YAMLValueKind::Null),
+    ];
+    assert_eq!(
+        values,
+        expected.map(|(path, value, kind)| (
+            path.to_owned(),
+            value.to_owned(),
+            kind,
+            String::new()
+        ))
+    );
+}
+
+#[test]
+fn tags() {
+    let (code, values, _) = parse(
+        b"a: !color red
+b: !!str 1
+",
+    );
+    assert_eq!(code, YAML_OK);
+    let expected = [
+        ("a", "red", YAMLValueKind::Tagged, "!color"),
+        ("b", "1", YAMLValueKind::String, ""),
+    ];
+    assert_eq!(
+        values,
+        expected.map(|(path, value, kind, tag)| (
+            path.to_owned(),
+            value.to_owned(),
+            kind,
+            tag.to_owned()
+        ))
+    );
+}
+
+#[test]
+fn stop() {
+    let (code, values, _) = parse(b"a: 1\nb: stop\nc: 3\n");
+    assert_eq!(code, YAML_OK);
+    assert_eq!(values.len(), 2);
+}
+
+#[test]
+fn errors() {
+    let (code, _, error) = parse(b"a: 1\nb: [\n");
+    assert_eq!(code, YAML_PARSE_ERROR);
+    assert_eq!(error.line, 2);
+    assert_eq!(parse(b"a: \xff\n").0, YAML_INVALID_UTF8);
+    // SAFETY: null pointers are checked
+    let code = unsafe {
+        yaml_parse(
+            std::ptr::null(),
+            1,
+            Some(collect),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    assert_eq!(code, YAML_INVALID_ARGUMENT);
+}
+
+/// The header is written by hand, so check that it declares what `ffi.rs` defines
+#[test]
+fn header_matches() {
+    let header = include_str!("../include/simple_yaml_parser.h");
+    let source = include_str!("../ffi.rs");
+
+    let (_, kinds) = source.split_once("pub enum YAMLValueKind {").unwrap();
+    let (kinds, _) = kinds.split_once('}').unwrap();
+    for kind in kinds.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let (name, value) = kind.trim_end_matches(',').split_once(" = ").unwrap();
+        let mut constant = String::from("YAML_VALUE_");
+        for (idx, chr) in name.char_indices() {
+            if idx > 0 && chr.is_ascii_uppercase() {
+                constant.push('_');
+            }
+            constant.push(chr.to_ascii_uppercase());
+        }
+        assert!(
+            header.contains(&format!("{constant} = {value}")),
+            "{constant} is not in the header"
+        );
+    }
+
+    let (_, parameters) = source
+        .split_once("pub type YAMLCallback = extern \"C\" fn(")
+        .unwrap();
+    let (parameters, _) = parameters.split_once(')').unwrap();
+    let (_, declaration) = header.split_once("(*yaml_callback)(").unwrap();
+    let (declaration, _) = declaration.split_once(')').unwrap();
+    let declared: Vec<&str> = declaration
+        .split(',')
+        .map(|parameter| parameter.rsplit([' ', '*']).next().unwrap())
+        .collect();
+    let defined: Vec<&str> = parameters
+        .split(',')
+        .filter_map(|parameter| parameter.split_once(':'))
+        .map(|(name, _)| name.trim())
+        .collect();
+    assert_eq!(declared, defined, "yaml_callback parameters differ");
+
+    for line in source.lines() {
+        if let Some(rest) = line.strip_prefix("pub unsafe extern \"C\" fn ") {
+            let name = rest.split('(').next().unwrap();
+            assert!(
+                header.contains(&format!(" {name}(")),
+                "{name} is not in the header"
+            );
+        }
+        if let Some(rest) = line.strip_prefix("pub const ") {
+            let (name, rest) = rest.split_once(':').unwrap();
+            let value = rest.rsplit(' ').next().unwrap().trim_end_matches(';');
+            assert!(
+                header.contains(&format!("#define {name} {value}")),
+                "{name} is not in the header"
+            );
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! `flatten`
+
+use simple_yaml_parser::{flatten, flatten_key, parse_key_path, FlattenOptions, KeyCase};
+
+#[test]
+fn dotenv() {
+    let on = "server:\n  port: 8080\n  host-name: example.com\n  hosts: [a, b]\ndebug: true\nempty:\nbase: &base x\ncopy: *base\n";
+    assert_eq!(
+        flatten(on, &FlattenOptions::default()).unwrap(),
+        "SERVER__PORT=8080\nSERVER__HOST_NAME=example.com\nSERVER__HOSTS__0=a\nSERVER__HOSTS__1=b\nDEBUG=true\nEMPTY=\nBASE=x\nCOPY=x\n"
+    );
+}
+
+#[test]
+fn quoting() {
+    let on = "a: hello world\nb: \"say \\\"hi\\\"\"\nc: |\n  line 1\n  line 2\nd: $HOME\ne: x#y\n";
+    assert_eq!(
+        flatten(on, &FlattenOptions::default()).unwrap(),
+        "A=\"hello world\"\nB=\"say \\\"hi\\\"\"\nC=\"line 1\\nline 2\\n\"\nD=\"\\$HOME\"\nE=\"x#y\"\n"
+    );
+}
+
+#[test]
+fn separator_and_case() {
+    let keys = parse_key_path("server.host-name[1]");
+    let options = |separator, case| FlattenOptions { separator, case };
+    assert_eq!(
+        flatten_key(&keys, &FlattenOptions::default()),
+        "SERVER__HOST_NAME__1"
+    );
+    assert_eq!(
+        flatten_key(&keys, &options("_", KeyCase::Lower)),
+        "server_host_name_1"
+    );
+    assert_eq!(
+        flatten_key(&keys, &options(".", KeyCase::Preserve)),
+        "server.host-name.1"
+    );
+}
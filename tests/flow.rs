@@ -0,0 +1,94 @@
+//! Flow collections (`{a: b}` and `[a, b]`) as the whole document, such as JSON
+
+mod common;
+
+use common::values;
+use simple_yaml_parser::{documents, parse, YAMLParseErrorReason};
+
+#[test]
+fn root_mapping() {
+    let on = "{\n  \"name\": \"a\",\n  \"tags\": [\"b\", \"c\"],\n  \"inner\": {\"d\": \"e\"}\n}\n";
+    assert_eq!(
+        values(on),
+        ["name = a", "tags[0] = b", "tags[1] = c", "inner.d = e"]
+    );
+}
+
+#[test]
+fn root_sequence() {
+    assert_eq!(values("[a, {b: c}]"), ["[0] = a", "[1].b = c"]);
+}
+
+#[test]
+fn root_collections_in_documents() {
+    let on = "{a: b}\n---\n[c]\n";
+    let values: Vec<Vec<String>> = documents(on).map(|(_, on)| values(on)).collect();
+    assert_eq!(values, [vec!["a = b"], vec!["[0] = c"]]);
+}
+
+#[test]
+fn unclosed() {
+    assert!(parse("{a: b\n", |_, _| {}).is_err());
+}
+
+#[test]
+fn sequences() {
+    assert_eq!(
+        values("list: [\"something\", here, 'x, y']\n"),
+        ["list[0] = something", "list[1] = here", "list[2] = x, y"]
+    );
+    assert_eq!(
+        values("nested: [a, [b, [c]], d]\n"),
+        [
+            "nested[0] = a",
+            "nested[1][0] = b",
+            "nested[1][1][0] = c",
+            "nested[2] = d"
+        ]
+    );
+    // over several lines, with a trailing comma
+    assert_eq!(
+        values("list: [\n  a,\n  b,\n]\nnext: c\n"),
+        ["list[0] = a", "list[1] = b", "next = c"]
+    );
+    assert_eq!(
+        values("- [a, b]\n- c\n"),
+        ["[0][0] = a", "[0][1] = b", "[1] = c"]
+    );
+}
+
+#[test]
+fn sequence_errors() {
+    let err = parse("list: [a, b\nnext: c\n", |_, _| {}).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::ExpectedBracket));
+    assert!(parse("list: [a] b\n", |_, _| {}).is_err());
+}
+
+#[test]
+fn mappings() {
+    assert_eq!(
+        values("point: {x: 1, y: 2}\n"),
+        ["point.x = 1", "point.y = 2"]
+    );
+    assert_eq!(
+        values("a: {b: {c: d}, e: [f, {g: h}]}\n"),
+        ["a.b.c = d", "a.e[0] = f", "a.e[1].g = h"]
+    );
+    assert_eq!(
+        values("- {name: a, 'quoted key': \"b, c\"}\n"),
+        ["[0].name = a", "[0].quoted key = b, c"]
+    );
+    // over several lines
+    assert_eq!(
+        values("a: {\n  b: 1,\n  c: 2\n}\nd: 3\n"),
+        ["a.b = 1", "a.c = 2", "d = 3"]
+    );
+}
+
+#[test]
+fn mapping_errors() {
+    let err = parse("a: {b: 1\nc: 2\n", |_, _| {}).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::ExpectedBrace));
+    let err = parse("a: {: 1}\n", |_, _| {}).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::ExpectedFlowKey));
+}
@@ -0,0 +1,139 @@
+//! `format_yaml`
+
+use simple_yaml_parser::{
+    format_yaml, parse_events, FormatOptions, ParseOptions, QuoteStyle, SortKeys,
+};
+
+const SOURCE: &str = r#"# config
+name:    'hello'   # trailing
+version: "1.0"
+
+server:
+    # the host
+    host: example.com
+    ports:
+        - 80   # http
+        # secure
+        - 443
+script: |
+    echo hi
+---
+other: doc
+"#;
+
+#[test]
+fn layout_and_comments() {
+    let formatted = format_yaml(SOURCE, &FormatOptions::default()).unwrap();
+    assert_eq!(
+        formatted,
+        "# config\nname: hello # trailing\nversion: \"1.0\"\n\nserver:\n  # the host\n  host: \
+         example.com\n  ports:\n    - 80 # http\n    # secure\n    - 443\nscript: |\n  echo \
+         hi\n---\nother: doc\n"
+    );
+    assert_eq!(
+        format_yaml(&formatted, &FormatOptions::default()).unwrap(),
+        formatted
+    );
+}
+
+#[test]
+fn options() {
+    let options = FormatOptions {
+        indent_size: 4,
+        quotes: QuoteStyle::Preserve,
+        sort_keys: SortKeys::Alphabetical,
+    };
+    assert_eq!(
+        format_yaml("b: 'x'\na:\n  d: 1\n  c: [2, 3]\n", &options).unwrap(),
+        "a:\n    c:\n        - 2\n        - 3\n    d: 1\nb: 'x'\n"
+    );
+}
+
+#[test]
+fn sort_keys() {
+    let source = "b: 1\nitems:\n  - z: 1\n    a: 2\n  - m: 3\nA: 4\n";
+    let sorted = |sort_keys| {
+        let options = FormatOptions {
+            sort_keys,
+            ..FormatOptions::default()
+        };
+        format_yaml(source, &options).unwrap()
+    };
+    assert_eq!(sorted(SortKeys::Preserve), source);
+    assert_eq!(
+        sorted(SortKeys::Alphabetical),
+        "A: 4\nb: 1\nitems:\n  - a: 2\n    z: 1\n  - m: 3\n"
+    );
+    assert_eq!(
+        sorted(SortKeys::By(|a, b| b.to_lowercase().cmp(&a.to_lowercase()))),
+        "items:\n  - z: 1\n    a: 2\n  - m: 3\nb: 1\nA: 4\n"
+    );
+}
+
+#[test]
+fn properties_are_kept() {
+    let format = |on| format_yaml(on, &FormatOptions::default()).unwrap();
+    assert_eq!(format("a: &x 1\nb: *x\n"), "a: &x 1\nb: *x\n");
+    assert_eq!(
+        format("base: &base\n    x: 1\nother:\n    <<: *base\n    y: 2\n"),
+        "base: &base\n  x: 1\nother:\n  <<: *base\n  y: 2\n"
+    );
+    assert_eq!(
+        format("m: !!map {a: 1}\nl: &l !!seq\n- &i !!str 1\n- !!str '2'\n- !custom  thing\n"),
+        "m: !!map\n  a: 1\nl: &l !!seq\n  - &i !!str 1\n  - !!str \"2\"\n  - !custom thing\n"
+    );
+    assert_eq!(
+        format("- &item\n    a: 1\n- *item\n- &e {}\n"),
+        "- &item\n  a: 1\n- *item\n- &e {}\n"
+    );
+}
+
+#[test]
+fn keys_and_empty_collections() {
+    let format = |on| format_yaml(on, &FormatOptions::default()).unwrap();
+    assert_eq!(format("a: []\nb: 1\nc: {}\n"), "a: []\nb: 1\nc: {}\n");
+    assert_eq!(
+        format("\"k\\\"q\": 1\n'it''s': 2\n\"plain\": {x: []}\n"),
+        "\"k\\\"q\": 1\n\"it's\": 2\n\"plain\":\n  x: []\n"
+    );
+}
+
+/// Events (with aliases resolved, so anchors have to be kept)
+fn events(on: &str) -> Vec<String> {
+    let mut events = Vec::new();
+    let options = ParseOptions::new().resolve_aliases(true);
+    parse_events(
+        on,
+        |event| {
+            events.push(format!("{event:?}"));
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    events
+}
+
+#[test]
+fn parses_the_same() {
+    let sources = [
+        "a: []\nb: 1\nc: {}\n",
+        "set: !!set\n  ? a\n  ? b\nm: !!map {x: 1}\nl: &l !!seq\n  - 1\nr: *l\n",
+        "\"k\\\"q\": 1\n\"a b\": {c: [], d: {}}\n",
+        "base: &base\n  x: 1\nitems:\n  - &first\n    a: [x]\n  - *first\n  - &s !!str 5\n  - *s\n",
+        "a: &t |\n  text\nb: *t\n---\n- []\n- {}\n",
+    ];
+    for source in sources {
+        let formatted = format_yaml(source, &FormatOptions::default()).unwrap();
+        assert_eq!(
+            events(&formatted),
+            events(source),
+            "{source:?} -> {formatted:?}"
+        );
+    }
+}
+
+#[test]
+fn error() {
+    assert!(format_yaml("a: [1\n", &FormatOptions::default()).is_err());
+}
@@ -0,0 +1,55 @@
+//! `extract_front_matter` and `parse_front_matter`
+
+mod common;
+
+use common::entry;
+use simple_yaml_parser::{extract_front_matter, parse_front_matter, ParseOptions};
+
+#[test]
+fn extract() {
+    let page = "---\ntitle: Hello\ntags: [a, b]\n---\n# Hello\n\n---\n";
+    assert_eq!(
+        extract_front_matter(page),
+        Some(("title: Hello\ntags: [a, b]\n", "# Hello\n\n---\n"))
+    );
+    assert_eq!(
+        extract_front_matter("---\r\na: 1\r\n...\r\nbody"),
+        Some(("a: 1\r\n", "body"))
+    );
+    assert_eq!(extract_front_matter("\u{feff}---\n---\n"), Some(("", "")));
+}
+
+#[test]
+fn no_front_matter() {
+    assert_eq!(extract_front_matter("# Hello\n---\n"), None);
+    assert_eq!(extract_front_matter("--- a\nb: 1\n---\n"), None);
+    assert_eq!(extract_front_matter("---\ntitle: unclosed\n"), None);
+    assert_eq!(extract_front_matter("----\n---\n"), None);
+}
+
+#[test]
+fn parse() {
+    let page = "---\ntitle: Hello\ndate: 2024-01-01\n---\nBody\n";
+    let mut values = Vec::new();
+    let body = parse_front_matter(
+        page,
+        |keys, value| {
+            values.push(entry(keys, &value));
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(body, "Body\n");
+    assert_eq!(values, ["title = Hello", "date = 2024-01-01"]);
+
+    let body = parse_front_matter("Body", |_, _| panic!(), &ParseOptions::default()).unwrap();
+    assert_eq!(body, "Body");
+}
+
+#[test]
+fn error_positions() {
+    let page = "---\na: 1\nb: [\n---\nBody\n";
+    let err = parse_front_matter(page, |_, _| {}, &ParseOptions::default()).unwrap_err();
+    assert_eq!(err.line, 3);
+    assert_eq!(err.at, "---\na: 1\nb: [".len());
+}
@@ -0,0 +1,211 @@
+#![cfg(feature = "include")]
+//! `!include` with `parse_with_includes`
+
+mod common;
+
+use common::entry;
+use simple_yaml_parser::{
+    format_key_chain, parse_with_includes, parse_with_provenance, DirectoryLoader, IncludeError,
+    ParseOptions, YAMLKey,
+};
+use std::collections::HashMap;
+
+/// Loads from a map of paths to contents, recording the paths asked for
+struct Files {
+    files: HashMap<&'static str, &'static str>,
+    loaded: Vec<String>,
+}
+
+impl Files {
+    fn new(files: &[(&'static str, &'static str)]) -> Self {
+        Self {
+            files: files.iter().copied().collect(),
+            loaded: Vec::new(),
+        }
+    }
+}
+
+impl simple_yaml_parser::FileLoader for Files {
+    fn load(&mut self, path: &str) -> Option<String> {
+        self.loaded.push(path.to_owned());
+        self.files.get(path).map(|source| (*source).to_owned())
+    }
+}
+
+fn values(
+    on: &str,
+    loader: &mut impl simple_yaml_parser::FileLoader,
+) -> Result<Vec<String>, IncludeError> {
+    let mut values = Vec::new();
+    parse_with_includes(
+        on,
+        "config.yaml",
+        loader,
+        |keys: &[YAMLKey<'_>], value| {
+            values.push(entry(keys, &value));
+            false
+        },
+        &ParseOptions::default(),
+    )?;
+    Ok(values)
+}
+
+#[test]
+fn includes() {
+    let mut files = Files::new(&[
+        (
+            "db/settings.yaml",
+            "host: localhost\nreplica: !include replica.yaml\n",
+        ),
+        ("db/replica.yaml", "host: backup\n"),
+        ("services.yaml", "- name: api\n- name: web\n"),
+    ]);
+    let on = "name: app\ndatabase: !include db/settings.yaml\nservices: !include 'services.yaml'\nlast: x\n";
+    assert_eq!(
+        values(on, &mut files).unwrap(),
+        [
+            "name = app",
+            "database.host = localhost",
+            "database.replica.host = backup",
+            "services[0].name = api",
+            "services[1].name = web",
+            "last = x"
+        ]
+    );
+    assert_eq!(
+        files.loaded,
+        ["db/settings.yaml", "db/replica.yaml", "services.yaml"]
+    );
+
+    // in sequences, and with a closure as the loader
+    let mut loader = |path: &str| (path == "item.yaml").then(|| "a: one\n".to_owned());
+    assert_eq!(
+        values("- !include item.yaml\n- b\n", &mut loader).unwrap(),
+        ["[0].a = one", "[1] = b"]
+    );
+}
+
+#[test]
+fn provenance() {
+    let mut files = Files::new(&[
+        ("db/settings.yaml", "host: localhost\nport: \"eighty\"\n"),
+        ("db/replica.yaml", "host: backup\n"),
+    ]);
+    let on = "name: app\ndatabase: !include db/settings.yaml\nreplica: !include db/replica.yaml\nagain: !include db/settings.yaml\n";
+    let mut values = Vec::new();
+    parse_with_provenance(
+        on,
+        "config.yaml",
+        &mut files,
+        |keys: &[YAMLKey<'_>], _value, provenance| {
+            values.push(format!(
+                "{} {} {provenance} {:?}",
+                format_key_chain(keys),
+                provenance.file,
+                provenance.span
+            ));
+            false
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        values,
+        [
+            "name 0 config.yaml:1:7 6..9",
+            "database.host 1 db/settings.yaml:1:7 6..15",
+            "database.port 1 db/settings.yaml:2:7 22..30",
+            "replica.host 2 db/replica.yaml:1:7 6..12",
+            "again.host 1 db/settings.yaml:1:7 6..15",
+            "again.port 1 db/settings.yaml:2:7 22..30",
+        ]
+    );
+
+    // reporting a bad value in the file it is in
+    let mut files = Files::new(&[("ports.yaml", "- 80\n- eighty\n")]);
+    let mut error = None;
+    parse_with_provenance(
+        "ports: !include ports.yaml\n",
+        "config.yaml",
+        &mut files,
+        |_keys: &[YAMLKey<'_>], value, provenance| {
+            if !matches!(value, simple_yaml_parser::RootYAMLValue::Number(_)) {
+                error = Some(format!("{provenance}: expected a port"));
+            }
+            false
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(error.as_deref(), Some("ports.yaml:2:3: expected a port"));
+}
+
+#[test]
+fn errors() {
+    let mut files = Files::new(&[
+        ("a.yaml", "b: !include b.yaml\n"),
+        ("b.yaml", "a: !include a.yaml\n"),
+        ("bad.yaml", "x: 1\ny: ]\n"),
+    ]);
+    match values("root: !include a.yaml\n", &mut files) {
+        Err(IncludeError::Cycle(path)) => assert_eq!(path, "a.yaml"),
+        result => panic!("{result:?}"),
+    }
+    match values("root: !include missing.yaml\n", &mut files) {
+        Err(IncludeError::NotFound { file, path }) => {
+            assert_eq!(
+                (file.as_str(), path.as_str()),
+                ("config.yaml", "missing.yaml")
+            );
+        }
+        result => panic!("{result:?}"),
+    }
+    match values("root: !include bad.yaml\n", &mut files) {
+        Err(IncludeError::Parse { file, error }) => {
+            assert_eq!(file, "bad.yaml");
+            assert_eq!((error.line, error.column), (2, 4));
+        }
+        result => panic!("{result:?}"),
+    }
+
+    // each file includes the next
+    let mut deep = |path: &str| {
+        let depth: usize = path.trim_end_matches(".yaml").parse().ok()?;
+        Some(format!("a: !include {}.yaml\n", depth + 1))
+    };
+    assert!(matches!(
+        values("a: !include 0.yaml\n", &mut deep),
+        Err(IncludeError::TooDeep(_))
+    ));
+}
+
+#[test]
+fn directory_loader() {
+    let root = std::env::temp_dir().join(format!("simple-yaml-includes-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("nested")).unwrap();
+    std::fs::write(
+        root.join("nested/inner.yaml"),
+        "value: inner\nsibling: !include ../other.yaml\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("other.yaml"), "value: other\n").unwrap();
+
+    let mut loader = DirectoryLoader::new(&root);
+    assert_eq!(
+        values("a: !include nested/inner.yaml\n", &mut loader).unwrap(),
+        ["a.value = inner", "a.sibling.value = other"]
+    );
+    // outside of the directory
+    for path in [
+        "../outside.yaml",
+        "/etc/hostname",
+        "nested/../../other.yaml",
+    ] {
+        let result = values(&format!("a: !include {path}\n"), &mut loader);
+        assert!(
+            matches!(result, Err(IncludeError::NotFound { .. })),
+            "{path}"
+        );
+    }
+    std::fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,112 @@
+//! Indentation detected from the columns of parent keys, and `indent_size`
+
+mod common;
+
+use common::{entry, try_values, values};
+use simple_yaml_parser::{parse_lenient, ParseOptions, YAMLParseErrorReason};
+
+/// The position of an `InvalidIndentation` error
+fn invalid_indentation(on: &str, options: &ParseOptions) -> (usize, usize) {
+    let error = try_values(on, options).unwrap_err();
+    assert!(
+        matches!(error.reason, YAMLParseErrorReason::InvalidIndentation),
+        "{error:?}"
+    );
+    (error.line, error.column)
+}
+
+#[test]
+fn detected() {
+    let expected = ["a.b.c = 1", "a.b.d = 2", "a.e = 3", "f = 4"];
+    for indent in [1, 2, 3, 4, 8] {
+        let pad = " ".repeat(indent);
+        let on = format!("a:\n{pad}b:\n{pad}{pad}c: 1\n{pad}{pad}d: 2\n{pad}e: 3\nf: 4\n");
+        assert_eq!(values(&on), expected, "{indent}");
+    }
+    // different indentation for each level
+    let on = "a:\n    b:\n      c: 1\n      d: 2\n    e: 3\nf: 4\n";
+    assert_eq!(values(on), expected);
+}
+
+#[test]
+fn sequences() {
+    let on = "items:\n    -   name: x\n        size: 1\n    -   name: y\nlast: z\n";
+    assert_eq!(
+        values(on),
+        [
+            "items[0].name = x",
+            "items[0].size = 1",
+            "items[1].name = y",
+            "last = z"
+        ]
+    );
+}
+
+#[test]
+fn indent_size() {
+    let on = "a:\n    b:\n        c: 1\n    d: 2\n";
+    let expected = ["a.b.c = 1", "a.d = 2"];
+    assert_eq!(values(on), expected);
+    assert_eq!(
+        try_values(on, &ParseOptions::new().indent_size(4)).unwrap(),
+        expected
+    );
+    // lines must be indented by a multiple of it
+    let options = ParseOptions::new().indent_size(2);
+    assert_eq!(
+        invalid_indentation("a:\n  b: 1\n   c: 2\n", &options),
+        (3, 1)
+    );
+    assert_eq!(invalid_indentation("a:\n - x\n", &options), (2, 1));
+    assert_eq!(
+        invalid_indentation("a:\n    b: 1\n", &ParseOptions::new().indent_size(3)),
+        (2, 1)
+    );
+    // or line up with the keys of a mapping after a `- `
+    let on = "items:\n    - name: a\n      size: 1\n";
+    assert_eq!(
+        try_values(on, &ParseOptions::new().indent_size(4)).unwrap(),
+        ["items[0].name = a", "items[0].size = 1"]
+    );
+}
+
+#[test]
+fn zero_indent_size() {
+    // is the same as detecting the indentation
+    let on = "a:\n   b:\n     c: 1\n";
+    let mut options = ParseOptions::new().indent_size(0);
+    assert_eq!(try_values(on, &options).unwrap(), ["a.b.c = 1"]);
+    options.indent_size = Some(0);
+    assert_eq!(try_values("a: x\n", &options).unwrap(), ["a = x"]);
+}
+
+#[test]
+fn nested_under_a_value() {
+    let options = ParseOptions::default();
+    assert_eq!(invalid_indentation("a: x\n b: y\n", &options), (2, 2));
+    assert_eq!(invalid_indentation("a: [1]\n  b: y\n", &options), (2, 3));
+    assert_eq!(invalid_indentation("- x\n  - y\n", &options), (2, 3));
+    assert_eq!(
+        invalid_indentation("a: x\n b: y\n", &ParseOptions::new().indent_size(1)),
+        (2, 2)
+    );
+    // properties and keys without values on their line have entries nested under them
+    assert_eq!(
+        try_values("a: &x\n  b: y\n", &options).unwrap(),
+        ["a.b = y"]
+    );
+    assert_eq!(
+        try_values("- a: 1\n  b: 2\n", &options).unwrap(),
+        ["[0].a = 1", "[0].b = 2"]
+    );
+
+    // the entry is parsed as a sibling
+    let mut values = Vec::new();
+    let errors = parse_lenient(
+        "a: x\n b: y\nc: z\n",
+        |keys, value| values.push(entry(keys, &value)),
+        &options,
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(values, ["a = x", "b = y", "c = z"]);
+}
@@ -0,0 +1,150 @@
+//! Indicator characters that cannot start a plain scalar and what can follow values on the same
+//! line
+
+use simple_yaml_parser::{parse, RootYAMLValue, YAMLParseErrorReason};
+
+fn reason(on: &str) -> YAMLParseErrorReason {
+    parse(on, |_, _| {}).unwrap_err().reason
+}
+
+#[test]
+fn reserved() {
+    assert!(matches!(
+        reason("a: @value\n"),
+        YAMLParseErrorReason::ReservedIndicator
+    ));
+    assert!(matches!(
+        reason("- `value`\n"),
+        YAMLParseErrorReason::ReservedIndicator
+    ));
+    assert!(matches!(
+        reason("a: [x, @y]\n"),
+        YAMLParseErrorReason::ReservedIndicator
+    ));
+}
+
+#[test]
+fn flow_and_other_indicators() {
+    for on in [
+        "a: ]\n", "a: }\n", "a: ,b\n", "- %x\n", "a: ? b\n", "a: : b\n",
+    ] {
+        assert!(
+            matches!(reason(on), YAMLParseErrorReason::UnexpectedIndicator),
+            "{on:?}"
+        );
+    }
+}
+
+#[test]
+fn sequence_on_line_of_key() {
+    for on in ["b: - x\n", "b: -\n", "- a: - x\n", "a:\n  b: - x\n  c: y\n"] {
+        let error = parse(on, |_, _| {}).unwrap_err();
+        assert!(
+            matches!(error.reason, YAMLParseErrorReason::UnexpectedIndicator),
+            "{on:?}"
+        );
+        assert_eq!(&on[error.span.clone()], "-", "{on:?}");
+    }
+    // the value of an explicit key can be
+    let mut values = Vec::new();
+    parse("? a\n: - x\n", |keys, _| values.push(keys.len())).unwrap();
+    assert_eq!(values, [2]);
+}
+
+#[test]
+fn indicators_in_plain_scalars() {
+    let mut values = Vec::new();
+    parse(
+        "a: -x\nb: ?x\nc: :x\nd: x@y\ne: a]b, c{d}\nf: ! x\n",
+        |_, value| values.push(value.as_str().map(std::borrow::Cow::into_owned)),
+    )
+    .unwrap();
+    assert_eq!(
+        values,
+        [
+            Some("-x".to_owned()),
+            Some("?x".to_owned()),
+            Some(":x".to_owned()),
+            Some("x@y".to_owned()),
+            Some("a]b, c{d}".to_owned()),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn block_scalar_headers() {
+    assert!(matches!(
+        reason("a: |x\n  text\n"),
+        YAMLParseErrorReason::InvalidBlockScalarHeader
+    ));
+    assert!(matches!(
+        reason("- >0\n  text\n"),
+        YAMLParseErrorReason::InvalidBlockScalarHeader
+    ));
+}
+
+#[test]
+fn anchors_and_aliases() {
+    for on in ["a: & x\n", "a: &\n  b: 1\n", "a: *\n", "- * x\n"] {
+        assert!(
+            matches!(reason(on), YAMLParseErrorReason::ExpectedAnchorName),
+            "{on:?}"
+        );
+    }
+    let err = parse("a: &x 1\nb: *x more\n", |_, _| {}).unwrap_err();
+    assert!(matches!(
+        err.reason,
+        YAMLParseErrorReason::ExpectedEndOfValue
+    ));
+    assert_eq!(err.span, 14..18);
+}
+
+#[test]
+fn after_flow_collections() {
+    for on in ["a: [1, 2] x\n", "- {a: 1} b\n", "a: [1]# not a comment\n"] {
+        assert!(
+            matches!(reason(on), YAMLParseErrorReason::ExpectedEndOfValue),
+            "{on:?}"
+        );
+    }
+    assert!(parse("a: [1, 2] # comment\n", |_, _| {}).is_ok());
+}
+
+#[test]
+fn tags_on_flow_collections() {
+    // the tag is dropped, as for block collections
+    let mut values = Vec::new();
+    parse(
+        "a: !custom [1, 2]\nb:\n  - !!map {c: d}\n",
+        |keys, value| {
+            values.push((keys.len(), matches!(value, RootYAMLValue::Tagged(_))));
+        },
+    )
+    .unwrap();
+    assert_eq!(values, [(2, false), (2, false), (3, false)]);
+}
+
+#[test]
+fn second_entry_on_line() {
+    let err = parse("key: value junk: more\n", |_, _| {}).unwrap_err();
+    assert!(matches!(
+        err.reason,
+        YAMLParseErrorReason::ExpectedEndOfValue
+    ));
+    assert_eq!(err.span, 15..16);
+    for on in ["- a: b: c\n", "a: !tag b: c\n", "a: 'b': c\n", "a: b:\n"] {
+        assert!(
+            matches!(reason(on), YAMLParseErrorReason::ExpectedEndOfValue),
+            "{on:?}"
+        );
+    }
+
+    let mut values = Vec::new();
+    parse(
+        "a: \"b: c\"\nd: http://e.f\ng: h # i: j\nk: l :m\n",
+        |_, value| values.push(value.as_str().unwrap().into_owned()),
+    )
+    .unwrap();
+    assert_eq!(values, ["b: c", "http://e.f", "h", "l :m"]);
+}
@@ -0,0 +1,77 @@
+//! `ParseOptions::interpolate`
+
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, RootYAMLValue};
+
+fn lookup(name: &str) -> Option<String> {
+    match name {
+        "HOST" => Some("localhost".to_owned()),
+        "PORT" => Some("8080".to_owned()),
+        _ => None,
+    }
+}
+
+fn values(on: &str, interpolate: Option<fn(&str) -> Option<String>>) -> Vec<String> {
+    let mut options = ParseOptions::new();
+    options.interpolate = interpolate;
+    let mut values = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |_, value| {
+            values.push(match value.as_str() {
+                Some(on) => on.into_owned(),
+                None => format!("{value:?}"),
+            });
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    values
+}
+
+#[test]
+fn placeholders() {
+    let on = "url: http://${HOST}:${PORT}/\nquoted: \"${HOST}\"\nblock: |\n  ${PORT}\n";
+    assert_eq!(
+        values(on, Some(lookup)),
+        ["http://localhost:8080/", "localhost", "8080\n"]
+    );
+    assert_eq!(
+        values(on, None),
+        ["http://${HOST}:${PORT}/", "${HOST}", "${PORT}\n"]
+    );
+}
+
+#[test]
+fn defaults_and_escapes() {
+    let on = "a: ${MISSING:-fallback}\nb: ${MISSING}\nc: $${HOST}\nd: ${HOST:-x} $5\ne: ${HOST\n";
+    assert_eq!(
+        values(on, Some(lookup)),
+        [
+            "fallback",
+            "${MISSING}",
+            "${HOST}",
+            "localhost $5",
+            "${HOST"
+        ]
+    );
+}
+
+#[test]
+fn interpolated_variant() {
+    let options = ParseOptions::new().interpolate(lookup);
+    let mut first = None;
+    parse_with_exit_signal(
+        "a: ${HOST}\nb: 1\n",
+        |_, value| {
+            first.get_or_insert(value);
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        first,
+        Some(RootYAMLValue::Interpolated("localhost".to_owned()))
+    );
+}
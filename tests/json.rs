@@ -0,0 +1,41 @@
+//! Converting to JSON with `yaml_to_json`
+
+use simple_yaml_parser::json::yaml_to_json;
+
+#[test]
+fn empty_collections() {
+    assert_eq!(
+        yaml_to_json("a: []\nb: 1\nc: {}\n").unwrap(),
+        r#"{"a":[],"b":1,"c":{}}"#
+    );
+    assert_eq!(
+        yaml_to_json("a:\n  - []\n  - {b: []}\n  - x\n").unwrap(),
+        r#"{"a":[[],{"b":[]},"x"]}"#
+    );
+    assert_eq!(yaml_to_json("[]\n").unwrap(), "[]");
+    assert_eq!(yaml_to_json("{}\n").unwrap(), "{}");
+}
+
+#[test]
+fn documents() {
+    assert_eq!(
+        yaml_to_json("a: 1\n---\n- x\n- y: []\n").unwrap(),
+        "{\"a\":1}\n[\"x\",{\"y\":[]}]"
+    );
+    // each document is valid JSON
+    assert_eq!(
+        yaml_to_json("---\n---\na: b\n...\n").unwrap(),
+        "null\n{\"a\":\"b\"}"
+    );
+    assert_eq!(yaml_to_json("").unwrap(), "{}");
+    assert_eq!(yaml_to_json("# comment\n").unwrap(), "{}");
+}
+
+#[test]
+fn values() {
+    let on = "name: \"say \\\"hi\\\"\\n\"\ncount: 0x1F\nratio: -1.5\non: true\nnothing: ~\ntab: \"\\t\"\ntext: |\n  a\n  b\nnested:\n  - {x: 1}\n  - [y]\n";
+    assert_eq!(
+        yaml_to_json(on).unwrap(),
+        r#"{"name":"say \"hi\"\n","count":31,"ratio":-1.5,"on":true,"nothing":null,"tab":"\t","text":"a\nb\n","nested":[{"x":1},["y"]]}"#
+    );
+}
@@ -0,0 +1,137 @@
+//! `JSONSchema`
+#![cfg(feature = "json-schema")]
+
+use simple_yaml_parser::{JSONSchema, ParseOptions, SchemaError};
+
+const SCHEMA: &str = r##"{
+  "type": "object",
+  "required": ["name", "port"],
+  "properties": {
+    "name": {"type": "string", "minLength": 1},
+    "port": {"$ref": "#/$defs/port"},
+    "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 2},
+    "mode": {"enum": ["fast", "slow"]},
+    "timeout": {"anyOf": [{"type": "integer"}, {"const": "never"}]}
+  },
+  "additionalProperties": false,
+  "$defs": {
+    "port": {"type": "integer", "minimum": 1, "maximum": 65535}
+  }
+}
+"##;
+
+fn violations(on: &str) -> Vec<String> {
+    let schema = JSONSchema::parse(SCHEMA).unwrap();
+    schema
+        .validate(on, &ParseOptions::default())
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn valid() {
+    let on = "name: server\nport: 8080\ntags: [a, b]\nmode: fast\ntimeout: never\n";
+    assert_eq!(violations(on), Vec::<String>::new());
+}
+
+#[test]
+fn scalars() {
+    let on = "name: \"\"\nport: 70000\nmode: medium\ntimeout: 1.5\n";
+    assert_eq!(
+        violations(on),
+        [
+            "name: shorter than 1 characters",
+            "port: 70000 is greater than 65535",
+            "mode: is not one of the values in enum",
+            "timeout: does not match any schema in anyOf",
+        ]
+    );
+}
+
+#[test]
+fn collections() {
+    let on = "name: server\ntags:\n  - 1\n  - b\n  - c\nextra:\n  a: 1\n  b: 2\n";
+    assert_eq!(
+        violations(on),
+        [
+            "tags[0]: expected string, found number",
+            "missing required property port",
+            "unexpected property extra",
+            "tags: more than 2 items",
+        ]
+    );
+}
+
+#[test]
+fn types_and_spans() {
+    let on = "name:\n  first: a\n  last: b\nport: high\n";
+    let schema = JSONSchema::parse(SCHEMA).unwrap();
+    let violations = schema.validate(on, &ParseOptions::default()).unwrap();
+    let messages: Vec<String> = violations.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        [
+            "name: expected string, found object",
+            "port: expected integer, found string"
+        ]
+    );
+    assert_eq!(&on[violations[1].span.clone()], "high");
+    assert_eq!(violations[1].keyword, "type");
+}
+
+#[test]
+fn integers_and_multiples() {
+    let schema = JSONSchema::parse(
+        r#"{"properties": {"n": {"type": "integer"}, "half": {"multipleOf": 0.5}}}"#,
+    )
+    .unwrap();
+    let messages = |on: &str| -> Vec<String> {
+        let violations = schema.validate(on, &ParseOptions::default()).unwrap();
+        violations.iter().map(ToString::to_string).collect()
+    };
+    assert_eq!(messages("n: 4\nhalf: 1.5\n"), Vec::<String>::new());
+    assert_eq!(messages("n: 4.0\nhalf: 2\n"), Vec::<String>::new());
+    assert_eq!(
+        messages("n: 1.5\nhalf: 0.2\n"),
+        [
+            "n: expected integer, found number",
+            "half: 0.2 is not a multiple of 0.5"
+        ]
+    );
+}
+
+#[test]
+fn unsupported_schemas() {
+    for (schema, expected) in [
+        (
+            r#"{"properties": {"name": {"type": "string", "pattern": "^a"}}}"#,
+            "properties.name.pattern: unsupported keyword pattern",
+        ),
+        (
+            r#"{"items": {"format": "email"}}"#,
+            "items.format: unsupported keyword format",
+        ),
+        (
+            r##"{"anyOf": [{"$ref": "#/$defs/missing"}], "$defs": {}}"##,
+            "anyOf[0].$ref: unresolved $ref #/$defs/missing",
+        ),
+        (
+            r#"{"$ref": "other.json#/port"}"#,
+            "$ref: unresolved $ref other.json#/port",
+        ),
+    ] {
+        let error = JSONSchema::parse(schema).unwrap_err();
+        assert_eq!(error.to_string(), expected);
+    }
+    assert!(matches!(
+        JSONSchema::parse(r#"{"pattern": "a"}"#),
+        Err(SchemaError::UnsupportedKeyword { keyword, .. }) if keyword == "pattern"
+    ));
+    assert!(matches!(JSONSchema::parse("{"), Err(SchemaError::Parse(_))));
+
+    // annotations are allowed, and property names are not keywords
+    let schema = r#"{"title": "T", "description": "D", "properties": {"pattern": {"default": 1}}}"#;
+    assert!(JSONSchema::parse(schema).is_ok());
+}
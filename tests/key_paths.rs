@@ -0,0 +1,56 @@
+//! `format_key_chain` and `parse_key_path`
+
+use simple_yaml_parser::{format_key_chain, parse_key_path, YAMLKey};
+
+#[test]
+fn format() {
+    use YAMLKey::{Index, Slice};
+
+    assert_eq!(format_key_chain(&[]), "");
+    assert_eq!(
+        format_key_chain(&[Slice("person"), Slice("address"), Slice("city")]),
+        "person.address.city"
+    );
+    assert_eq!(
+        format_key_chain(&[Slice("places"), Index(0), Slice("name")]),
+        "places[0].name"
+    );
+    assert_eq!(format_key_chain(&[Index(1), Index(2)]), "[1][2]");
+    assert_eq!(format_key_chain(&[Slice("a.b"), Slice("c")]), "[\"a.b\"].c");
+    assert_eq!(format_key_chain(&[Slice("x"), Slice("\"[")]), "x['\"[']");
+    assert_eq!(format_key_chain(&[Slice("")]), "[\"\"]");
+}
+
+#[test]
+fn round_trip() {
+    use YAMLKey::{Index, Slice};
+
+    let chains: &[&[YAMLKey<'_>]] = &[
+        &[Slice("a")],
+        &[Slice("a"), Index(3), Slice("b")],
+        &[Index(0), Index(10)],
+        &[Slice("a.b"), Slice("c]"), Slice("[d")],
+        &[Slice("quote\""), Index(1)],
+        &[Slice(""), Slice("e")],
+    ];
+    for keys in chains {
+        let path = format_key_chain(keys);
+        assert_eq!(parse_key_path(&path), *keys, "{path}");
+    }
+}
+
+#[test]
+fn parse() {
+    use YAMLKey::{Index, Slice};
+
+    assert_eq!(parse_key_path(""), []);
+    assert_eq!(
+        parse_key_path("places[0].name"),
+        [Slice("places"), Index(0), Slice("name")]
+    );
+    assert_eq!(parse_key_path("a['b.c']"), [Slice("a"), Slice("b.c")]);
+    // brackets without an index or a quoted key are part of the key
+    assert_eq!(parse_key_path("a[b].c"), [Slice("a[b]"), Slice("c")]);
+    assert_eq!(parse_key_path("a[1"), [Slice("a[1")]);
+    assert_eq!(parse_key_path("a[-1]"), [Slice("a[-1]")]);
+}
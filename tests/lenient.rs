@@ -0,0 +1,69 @@
+//! `parse_lenient` continues after errors
+
+mod common;
+
+use common::entry;
+use simple_yaml_parser::{parse, parse_lenient, ParseOptions, YAMLParseErrorReason};
+
+fn lenient(on: &str, options: &ParseOptions) -> (Vec<String>, Vec<(usize, usize)>) {
+    let mut values = Vec::new();
+    let errors = parse_lenient(
+        on,
+        |keys, value| {
+            values.push(entry(keys, &value));
+        },
+        options,
+    );
+    let positions = errors.iter().map(|err| (err.line, err.column)).collect();
+    (values, positions)
+}
+
+#[test]
+fn collects_errors() {
+    let on = "a: 1\nb: \"x\" y\nc: 3\nd: @x\ne: 5\nf: [1] 2\ng: 7\n";
+    let (values, errors) = lenient(on, &ParseOptions::default());
+    assert_eq!(values, ["a = 1", "c = 3", "e = 5", "f[0] = 1", "g = 7"]);
+    // one error for each invalid line
+    assert_eq!(errors, [(2, 7), (4, 4), (6, 8)]);
+
+    let errors = parse_lenient(on, |_, _| {}, &ParseOptions::default());
+    assert!(matches!(
+        errors[1].reason,
+        YAMLParseErrorReason::ReservedIndicator
+    ));
+}
+
+#[test]
+fn unclosed_flow_collection() {
+    // the collection ends at the next line that is not nested under its key, where the error is
+    let (values, errors) = lenient("a: [1, 2\nb: 3\n", &ParseOptions::default());
+    assert_eq!(values, ["a[0] = 1", "a[1] = 2", "b = 3"]);
+    assert_eq!(errors, [(1, 9)]);
+
+    let on = "a:\n  c: {x: 1,\n    y: 2 # comment\n\n  d: 4\n";
+    let (values, errors) = lenient(on, &ParseOptions::default());
+    assert_eq!(values, ["a.c.x = 1", "a.c.y = 2", "a.d = 4"]);
+    assert_eq!(errors, [(3, 19)]);
+
+    let on = "- [1, 2\n- 3\n";
+    let (values, errors) = lenient(on, &ParseOptions::default());
+    assert_eq!(values, ["[0][0] = 1", "[0][1] = 2", "[1] = 3"]);
+    assert_eq!(errors, [(1, 8)]);
+
+    // continuation lines of a closed collection do not need to be nested
+    let (values, errors) = lenient("a: [1,\n2]\nb: 3\n", &ParseOptions::default());
+    assert_eq!(values, ["a[0] = 1", "a[1] = 2", "b = 3"]);
+    assert_eq!(errors, []);
+}
+
+#[test]
+fn valid_input() {
+    let on = "a: 1\nb:\n  - x\n  - y: z\n";
+    let (values, errors) = lenient(on, &ParseOptions::default());
+    assert_eq!(values, ["a = 1", "b[0] = x", "b[1].y = z"]);
+    assert_eq!(errors, []);
+    // the same values as `parse`
+    let mut count = 0;
+    parse(on, |_, _| count += 1).unwrap();
+    assert_eq!(count, values.len());
+}
@@ -0,0 +1,110 @@
+//! `ParseOptions::max_depth` and `ParseOptions::max_input_len`
+
+use simple_yaml_parser::{
+    parse_with_exit_signal, ParseOptions, Parser, RootYAMLValue, YAMLKey, YAMLParseError,
+    YAMLParseErrorReason,
+};
+
+fn parse(on: &str, options: &ParseOptions) -> Result<usize, YAMLParseError> {
+    let mut count = 0;
+    parse_with_exit_signal(
+        on,
+        |_, _| {
+            count += 1;
+            false
+        },
+        options,
+    )
+    .map(|_| count)
+}
+
+#[test]
+fn max_depth() {
+    let options = ParseOptions::new().max_depth(3);
+    assert_eq!(parse("a:\n  b:\n    c: 1\n", &options).unwrap(), 1);
+    assert_eq!(parse("a: [[1]]\n", &options).unwrap(), 1);
+
+    for on in [
+        "a:\n  b:\n    c:\n      d: 1\n",
+        "a: [[[1]]]\n",
+        "- - - - x\n",
+        "a: {b: {c: {d: 1}}}\n",
+    ] {
+        let error = parse(on, &options).unwrap_err();
+        assert!(
+            matches!(error.reason, YAMLParseErrorReason::MaxDepthExceeded),
+            "{on:?} {error:?}"
+        );
+    }
+
+    // the depth is checked before the value is passed
+    let error = parse("a: 1\nb:\n  c:\n    d:\n      e: 2\n", &options).unwrap_err();
+    assert_eq!(error.line, 5);
+}
+
+#[test]
+fn flow_nesting_without_max_depth() {
+    // flow collections are parsed recursively, so their nesting is always limited
+    let on = format!("a: {}\n", "[".repeat(100_000));
+    let error = parse(&on, &ParseOptions::default()).unwrap_err();
+    assert!(matches!(
+        error.reason,
+        YAMLParseErrorReason::MaxDepthExceeded
+    ));
+}
+
+#[test]
+fn max_input_len() {
+    let on = "a: 1\nb: 2\n";
+    let options = ParseOptions::new().max_input_len(on.len());
+    assert_eq!(parse(on, &options).unwrap(), 2);
+
+    let options = ParseOptions::new().max_input_len(4);
+    let error = parse(on, &options).unwrap_err();
+    assert!(matches!(error.reason, YAMLParseErrorReason::InputTooLong));
+    // nothing is parsed
+    let mut called = false;
+    let _ = parse_with_exit_signal(
+        on,
+        |_, _| {
+            called = true;
+            false
+        },
+        &options,
+    );
+    assert!(!called);
+}
+
+#[test]
+fn max_input_len_in_chunks() {
+    let options = ParseOptions::new().max_input_len(100);
+    let mut values = 0;
+    let mut parser = Parser::new(
+        |_: &[YAMLKey<'_>], _: RootYAMLValue<'_>| {
+            values += 1;
+            false
+        },
+        &options,
+    );
+    // each chunk is within the limit, their total is not
+    let mut result = Ok(());
+    let mut fed = 0;
+    while result.is_ok() && fed < 100_000 {
+        result = parser.feed("key: value\n");
+        fed += 1;
+    }
+    let error = result.unwrap_err();
+    assert!(matches!(error.reason, YAMLParseErrorReason::InputTooLong));
+    assert_eq!(fed, 10);
+    assert_eq!(error.span, 100..110);
+    assert_eq!((error.line, error.column), (10, 2));
+    drop(parser);
+    // the values before the chunk over the limit
+    assert_eq!(values, 8);
+
+    // the same for input that is all one chunk
+    let on = "key: value\n".repeat(10);
+    let error = parse(&on, &options).unwrap_err();
+    assert_eq!(error.span, 100..110);
+    assert_eq!((error.line, error.column), (10, 2));
+}
@@ -0,0 +1,65 @@
+//! Input with `\r\n` line endings parses the same as with `\n`
+
+use simple_yaml_parser::{format_key_chain, parse_with_comments, ParseOptions};
+
+/// Values as `path = value` and comments as `# comment`
+fn events(on: &str, options: &ParseOptions) -> Vec<String> {
+    let mut events = Vec::new();
+    let mut comments = Vec::new();
+    parse_with_comments(
+        on,
+        |keys, value| {
+            // strings are written resolved, other values with `Debug`
+            let value = match value.as_str() {
+                Some(text) => format!("{text:?}"),
+                None => format!("{value:?}"),
+            };
+            events.push(format!("{} = {value}", format_key_chain(keys)));
+            false
+        },
+        |_, comment| comments.push(format!("#{comment}")),
+        options,
+    )
+    .unwrap();
+    events.extend(comments);
+    events
+}
+
+const INPUT: &str = "\
+a: 1
+b: text # comment
+c: \"quoted\"
+# full line comment
+list:
+  - x
+  - k: v
+    j: w
+literal: |
+  one
+
+  two
+folded: >-
+  three
+  four
+flow: [1, 2]
+nested:
+
+  key: value
+empty:
+";
+
+#[test]
+fn same_as_line_feeds() {
+    let options = ParseOptions::new().emit_comments(true);
+    let crlf = INPUT.replace('\n', "\r\n");
+    assert_eq!(events(&crlf, &options), events(INPUT, &options));
+}
+
+#[test]
+fn keep_line_endings_in_block_scalars() {
+    let options = ParseOptions::new().normalize_newlines(false);
+    let crlf = INPUT.replace('\n', "\r\n");
+    let events = events(&crlf, &options);
+    assert!(events.contains(&"literal = \"one\\r\\n\\r\\ntwo\\r\\n\"".to_owned()));
+    assert!(events.contains(&"folded = \"three four\"".to_owned()));
+}
@@ -0,0 +1,69 @@
+//! Layering documents with `merge`
+
+use simple_yaml_parser::{merge, MergeError, ParseOptions};
+
+fn merged(base: &str, overlay: &str) -> String {
+    merge(base, overlay, &ParseOptions::default()).unwrap()
+}
+
+#[test]
+fn overlay_replaces_values() {
+    let base = "name: app\nserver:\n    host: localhost\n    port: 80\n";
+    let overlay = "server:\n  port: 8080\n";
+    assert_eq!(
+        merged(base, overlay),
+        "name: app\nserver:\n    host: localhost\n    port: 8080\n"
+    );
+}
+
+#[test]
+fn new_keys_after_siblings() {
+    let base = "server:\n  host: localhost\nlogging: info\n";
+    let overlay = "server:\n  tls: true\nfeatures:\n  - a\n";
+    assert_eq!(
+        merged(base, overlay),
+        "server:\n  host: localhost\n  tls: true\nlogging: info\nfeatures:\n  - a\n"
+    );
+}
+
+#[test]
+fn sequences_are_replaced() {
+    let base = "ports:\n  - 80\n  - 443\nname: app\n";
+    let overlay = "ports:\n  - 8080\n";
+    assert_eq!(merged(base, overlay), "ports:\n  - 8080\nname: app\n");
+
+    // sequences of mappings are not merged item by item
+    let base = "env:\n  - {name: a, value: 1}\n  - {name: b, value: 2}\n";
+    let overlay = "env:\n  - name: c\n";
+    assert_eq!(merged(base, overlay), "env:\n  - name: c\n");
+
+    assert_eq!(merged("a: [1, 2]\nb: 3\n", "a: []\n"), "a: []\nb: 3\n");
+    assert_eq!(merged("- 1\n- 2\n", "- 3\n"), "- 3\n");
+}
+
+#[test]
+fn empty_collections() {
+    // from both documents
+    assert_eq!(merged("a: []\nb: {}\n", "c: {}\n"), "a: []\nb: {}\nc: {}\n");
+    // filled by the overlay
+    assert_eq!(
+        merged("a: {}\nb: []\n", "a:\n  x: 1\nb: [z]\n"),
+        "a:\n  x: 1\nb:\n  - z\n"
+    );
+    // an empty mapping keeps the mapping it is merged into
+    assert_eq!(merged("a:\n  x: 1\n", "a: {}\n"), "a:\n  x: 1\n");
+}
+
+#[test]
+fn different_shapes() {
+    // a scalar replacing a mapping and a mapping replacing a scalar
+    let base = "a:\n  b: 1\n  c: 2\nd: 3\n";
+    let overlay = "a: none\nd:\n  e: 4\n";
+    assert_eq!(merged(base, overlay), "a: none\nd:\n  e: 4\n");
+}
+
+#[test]
+fn invalid_overlay() {
+    let result = merge("a: 1\n", "a: [\n", &ParseOptions::default());
+    assert!(matches!(result, Err(MergeError::Overlay(_))));
+}
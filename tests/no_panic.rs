@@ -0,0 +1,139 @@
+//! Parsing never panics (or overflows the stack) on any `&str` input. Inputs are generated from
+//! fragments of YAML syntax with a fixed seed, so failures are reproducible. `fuzz/` has a
+//! `cargo fuzz` target for finding more
+
+use simple_yaml_parser::{
+    cst, parse_documents, parse_lenient, parse_with_comments, ParseOptions, TabPolicy,
+};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const FRAGMENTS: &[&str] = &[
+    "a",
+    "key",
+    ":",
+    ": ",
+    " ",
+    "   ",
+    "\t",
+    "\n",
+    "\r\n",
+    "\r",
+    "- ",
+    "-",
+    "[",
+    "]",
+    "{",
+    "}",
+    ",",
+    "#",
+    " # c",
+    "'",
+    "\"",
+    "\\",
+    "\\u00",
+    "|",
+    ">",
+    "|-",
+    ">+",
+    "|2",
+    "&x",
+    "*x",
+    "<<: ",
+    "!",
+    "!!str ",
+    "!t ",
+    "---",
+    "...",
+    "? ",
+    "%YAML 1.2",
+    "1",
+    "0x1f",
+    "0o7",
+    "1.5e3",
+    ".inf",
+    "~",
+    "null",
+    "true",
+    "é",
+    "日本",
+    "🦀",
+    "\u{feff}",
+    "\u{0}",
+];
+
+/// xorshift, to not depend on a random number crate
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        usize::try_from(self.0 % (u64::from(u32::MAX) + 1)).unwrap()
+    }
+}
+
+fn options_from(flags: usize) -> ParseOptions {
+    let mut options = ParseOptions::new()
+        .resolve_aliases(flags & 2 != 0)
+        .resolve_merge_keys(flags & 4 != 0)
+        .deny_duplicate_keys(flags & 8 != 0)
+        .emit_comments(flags & 16 != 0)
+        .strict_null(flags & 32 != 0)
+        .implicit_typing(flags & 64 != 0)
+        .tabs(match (flags >> 7) % 3 {
+            0 => TabPolicy::Error,
+            1 => TabPolicy::TreatAsSpaces(4),
+            _ => TabPolicy::Allow,
+        });
+    options.indent_size = (flags & 1 != 0).then_some(2);
+    options
+}
+
+fn parse_all(on: &str, options: &ParseOptions) {
+    let _ = parse_with_comments(
+        on,
+        |_keys, value| {
+            let _ = value.as_str();
+            let _ = value.as_f64();
+            false
+        },
+        |_, _| {},
+        options,
+    );
+    let _ = parse_lenient(on, |_, _| {}, options);
+    let _ = parse_documents(on, |_, _, _| false, options);
+    let _ = cst::parse(on);
+}
+
+#[test]
+fn generated_inputs() {
+    let mut rng = Rng(0x5eed);
+    for _ in 0..100_000 {
+        let mut on = String::new();
+        for _ in 0..rng.next() % 24 {
+            on.push_str(FRAGMENTS[rng.next() % FRAGMENTS.len()]);
+        }
+        let flags = rng.next();
+        let result = catch_unwind(AssertUnwindSafe(|| parse_all(&on, &options_from(flags))));
+        assert!(result.is_ok(), "panicked on {on:?} with flags {flags}");
+    }
+}
+
+#[test]
+fn deep_nesting() {
+    let options = ParseOptions::default();
+    for on in [
+        format!("a: {}", "[".repeat(100_000)),
+        format!("a: {}", "{a: ".repeat(100_000)),
+        format!("- {}", "[".repeat(100_000)),
+        "- ".repeat(100_000),
+        (0..1_000).fold(String::new(), |mut on, depth| {
+            on.push_str(&" ".repeat(depth));
+            on.push_str("a:\n");
+            on
+        }),
+    ] {
+        parse_all(&on, &options);
+    }
+}
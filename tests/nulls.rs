@@ -0,0 +1,54 @@
+//! `null` spellings and `ParseOptions::strict_null`
+
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, RootYAMLValue};
+
+fn value<'a>(on: &'a str, options: &ParseOptions) -> RootYAMLValue<'a> {
+    let mut first = None;
+    parse_with_exit_signal(
+        on,
+        |_, value| {
+            first = Some(value);
+            true
+        },
+        options,
+    )
+    .unwrap();
+    first.unwrap()
+}
+
+#[test]
+fn spellings() {
+    let options = ParseOptions::default();
+    for on in [
+        "a: null",
+        "a: ~",
+        "a: Null",
+        "a: NULL",
+        "a:",
+        "a: # comment",
+        "- ~",
+    ] {
+        assert_eq!(value(on, &options), RootYAMLValue::Null, "{on:?}");
+    }
+    for on in ["a: nULL", "a: nil", "a: none", "a: ~~", "a: null-ish"] {
+        assert!(
+            matches!(value(on, &options), RootYAMLValue::String(_)),
+            "{on:?}"
+        );
+    }
+    // quoted values are strings
+    assert!(matches!(
+        value("a: \"null\"", &options),
+        RootYAMLValue::QuotedString(_)
+    ));
+}
+
+#[test]
+fn strict() {
+    let options = ParseOptions::new().strict_null(true);
+    for on in ["a: null", "a: ~", "a:"] {
+        assert_eq!(value(on, &options), RootYAMLValue::Null, "{on:?}");
+    }
+    assert_eq!(value("a: Null", &options), RootYAMLValue::String("Null"));
+    assert_eq!(value("a: NULL", &options), RootYAMLValue::String("NULL"));
+}
@@ -0,0 +1,98 @@
+//! Number recognition under the core schema
+
+use simple_yaml_parser::{
+    json::yaml_to_json, parse, parse_with_exit_signal, ParseOptions, RootYAMLValue,
+};
+
+fn first_value(on: &str) -> RootYAMLValue<'_> {
+    let mut first = None;
+    parse(on, |_, value| {
+        first.get_or_insert(value);
+    })
+    .unwrap();
+    first.unwrap()
+}
+
+#[test]
+fn special_floats() {
+    for (on, expected) in [
+        ("a: .inf", f64::INFINITY),
+        ("a: +.Inf", f64::INFINITY),
+        ("a: -.INF", f64::NEG_INFINITY),
+    ] {
+        let value = first_value(on);
+        assert!(matches!(value, RootYAMLValue::Number(_)), "{on}");
+        assert_eq!(value.as_f64(), Some(expected));
+    }
+    for on in ["a: .nan", "a: .NaN", "a: .NAN"] {
+        assert!(first_value(on).as_f64().is_some_and(f64::is_nan), "{on}");
+    }
+}
+
+#[test]
+fn not_special_floats() {
+    // signed NaN and mixed case are not in the core schema
+    for on in ["a: -.nan", "a: .Nan", "a: inf", "a: .infinity"] {
+        assert!(matches!(first_value(on), RootYAMLValue::String(_)), "{on}");
+    }
+}
+
+#[test]
+fn special_floats_to_json() {
+    let json = yaml_to_json("a: .inf\nb: .nan\n").unwrap();
+    assert_eq!(json, r#"{"a":null,"b":null}"#);
+}
+
+#[test]
+fn forms() {
+    for on in [
+        "a: 30",
+        "a: -17",
+        "a: +4",
+        "a: 0",
+        "a: 3.14",
+        "a: -0.5",
+        "a: 1e3",
+        "a: 6.02E+23",
+        "a: 1.5e-3",
+        "a: .5",
+        "a: 0x1F",
+        "a: 0o17",
+    ] {
+        assert!(matches!(first_value(on), RootYAMLValue::Number(_)), "{on}");
+    }
+    for on in [
+        "a: 1.2.3", "a: 0x", "a: 0xZZ", "a: 12abc", "a: 1_000", "a: e3",
+    ] {
+        assert!(matches!(first_value(on), RootYAMLValue::String(_)), "{on}");
+    }
+    // numbers are kept as written
+    assert_eq!(first_value("a: 0x1F"), RootYAMLValue::Number("0x1F"));
+    assert_eq!(first_value("a: 0x1F").as_i64(), Some(31));
+    assert_eq!(first_value("a: 0o17").as_i64(), Some(15));
+    assert_eq!(first_value("a: 6.02E+23").as_f64(), Some(6.02e23));
+}
+
+#[test]
+fn without_implicit_typing() {
+    let options = ParseOptions::new().implicit_typing(false);
+    let mut values = Vec::new();
+    parse_with_exit_signal(
+        "a: 30\nb: 0x1F\nc: true\nd: null\n",
+        |_, value| {
+            values.push(value);
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        values,
+        [
+            RootYAMLValue::String("30"),
+            RootYAMLValue::String("0x1F"),
+            RootYAMLValue::String("true"),
+            RootYAMLValue::String("null")
+        ]
+    );
+}
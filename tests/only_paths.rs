@@ -0,0 +1,118 @@
+//! `ParseOptions::only_paths`
+
+use simple_yaml_parser::{
+    format_key_chain, parse_with_exit_signal, ParseOptions, PathFilter, YAMLKey,
+};
+
+const MANIFEST: &str = "name: app
+person:
+  name: Ben
+  address:
+    city: London
+  tags: [a, b]
+places:
+  list:
+    - name: Paris
+      size: 2
+    - name: Rome
+  other:
+    - x
+people:
+  - name: A
+    roles:
+      - admin
+  # comment
+  - name: B
+    roles:
+    - user
+version: 1
+";
+
+fn paths(on: &str, options: &ParseOptions) -> Vec<String> {
+    let mut paths = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |keys, _| {
+            paths.push(format_key_chain(keys));
+            false
+        },
+        options,
+    )
+    .unwrap();
+    paths
+}
+
+fn only(patterns: &[&str]) -> ParseOptions {
+    ParseOptions::new().only_paths(PathFilter::new(patterns.iter().copied()))
+}
+
+#[test]
+fn patterns() {
+    assert_eq!(
+        paths(MANIFEST, &only(&["person.*"])),
+        [
+            "person.name",
+            "person.address.city",
+            "person.tags[0]",
+            "person.tags[1]"
+        ]
+    );
+    assert_eq!(
+        paths(MANIFEST, &only(&["places.list[*].name", "version"])),
+        ["places.list[0].name", "places.list[1].name", "version"]
+    );
+    assert_eq!(
+        paths(MANIFEST, &only(&["people[1]", "places.other"])),
+        ["places.other[0]", "people[1].name", "people[1].roles[0]"]
+    );
+    assert_eq!(paths(MANIFEST, &only(&["missing"])), Vec::<String>::new());
+}
+
+#[test]
+fn same_as_filtering_afterwards() {
+    let filter = PathFilter::new(["person.address", "places.list[1]", "people[*].roles"]);
+    let everything = paths(MANIFEST, &ParseOptions::default());
+    let expected: Vec<_> = everything
+        .into_iter()
+        .filter(|path| {
+            let keys = simple_yaml_parser::parse_key_path(path);
+            filter.matches(&keys)
+        })
+        .collect();
+    assert_eq!(
+        paths(
+            MANIFEST,
+            &only(&["person.address", "places.list[1]", "people[*].roles"])
+        ),
+        expected
+    );
+}
+
+#[test]
+fn filter_matching() {
+    let filter = PathFilter::new(["a.*.c", "b[*]", "[\"x.y\"]"]);
+    assert!(filter.matches(&[
+        YAMLKey::Slice("a"),
+        YAMLKey::Slice("b"),
+        YAMLKey::Slice("c")
+    ]));
+    assert!(!filter.matches(&[YAMLKey::Slice("a"), YAMLKey::Index(0), YAMLKey::Slice("c")]));
+    assert!(filter.matches(&[YAMLKey::Slice("b"), YAMLKey::Index(3), YAMLKey::Slice("d")]));
+    assert!(!filter.matches(&[YAMLKey::Slice("b")]));
+    assert!(filter.matches(&[YAMLKey::Slice("x.y")]));
+}
+
+#[test]
+fn skipped_entries_are_not_parsed() {
+    // the unclosed flow sequence is not under `b`
+    let on = "a:\n  x: [1, 2\nb: 3\n";
+    assert_eq!(paths(on, &only(&["b"])), ["b"]);
+    assert!(parse_with_exit_signal(on, |_, _| false, &ParseOptions::default()).is_err());
+}
+
+#[test]
+fn aliases_outside_paths() {
+    let on = "defaults: &defaults\n  retries: 3\nservice:\n  <<: *defaults\n  name: api\n";
+    let options = only(&["service"]).resolve_merge_keys(true);
+    assert_eq!(paths(on, &options), ["service.retries", "service.name"]);
+}
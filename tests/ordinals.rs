@@ -0,0 +1,49 @@
+//! `parse_with_ordinals`
+
+use simple_yaml_parser::{format_key_chain, parse_with_ordinals, ParseOptions};
+
+fn ordinals(on: &str) -> Vec<(String, Vec<usize>)> {
+    let mut values = Vec::new();
+    parse_with_ordinals(
+        on,
+        |keys, ordinals, _| {
+            values.push((format_key_chain(keys), ordinals.to_vec()));
+            false
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    values
+}
+
+#[test]
+fn mappings_and_sequences() {
+    let on = "z: 1\nm:\n  b: 2\n  a:\n    - x\n    - y: 3\n      c: 4\nb: {q: 5, p: 6}\n";
+    assert_eq!(
+        ordinals(on),
+        [
+            ("z".to_owned(), vec![0]),
+            ("m.b".to_owned(), vec![1, 0]),
+            ("m.a[0]".to_owned(), vec![1, 1, 0]),
+            ("m.a[1].y".to_owned(), vec![1, 1, 1, 0]),
+            ("m.a[1].c".to_owned(), vec![1, 1, 1, 1]),
+            ("b.q".to_owned(), vec![2, 0]),
+            ("b.p".to_owned(), vec![2, 1]),
+        ]
+    );
+}
+
+#[test]
+fn reordering() {
+    let order = |on| {
+        ordinals(on)
+            .into_iter()
+            .map(|(path, ordinals)| (path, ordinals[0]))
+            .collect::<Vec<_>>()
+    };
+    let before = order("a: 1\nb: 2\n");
+    let mut after = order("b: 2\na: 1\n");
+    after.sort();
+    assert_ne!(before, after);
+    assert_eq!(after, [("a".to_owned(), 1), ("b".to_owned(), 0)]);
+}
@@ -0,0 +1,99 @@
+//! Owned keys and values from `parse_collect`
+
+use simple_yaml_parser::{
+    parse_collect, parse_key_path, OwnedKeyChain, OwnedYAMLKey, OwnedYAMLValue, ParseOptions,
+    YAMLParseError, YAMLParseErrorReason,
+};
+
+#[test]
+fn values_outlive_input() {
+    let values = {
+        let on = String::from("a:\n  b: 'quoted'\n  c:\n    - 1\nd: |\n  text\n");
+        parse_collect(&on, &ParseOptions::default()).unwrap()
+    };
+    assert_eq!(
+        values,
+        [
+            (
+                OwnedKeyChain(vec![
+                    OwnedYAMLKey::Key("a".into()),
+                    OwnedYAMLKey::Key("b".into())
+                ]),
+                OwnedYAMLValue::String("quoted".into())
+            ),
+            (
+                OwnedKeyChain(vec![
+                    OwnedYAMLKey::Key("a".into()),
+                    OwnedYAMLKey::Key("c".into()),
+                    OwnedYAMLKey::Index(0)
+                ]),
+                OwnedYAMLValue::Number("1".into())
+            ),
+            (
+                OwnedKeyChain(vec![OwnedYAMLKey::Key("d".into())]),
+                OwnedYAMLValue::String("text\n".into())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn compare_with_paths() {
+    let values = parse_collect("a:\n  - b: 1\n", &ParseOptions::default()).unwrap();
+    let (keys, _) = &values[0];
+    assert_eq!(*keys, *parse_key_path("a[0].b"));
+    assert_eq!(keys.to_string(), "a[0].b");
+}
+
+#[test]
+fn as_an_iterator() -> Result<(), YAMLParseError> {
+    let paths: Vec<String> = parse_collect("a: 1\nb:\n  - x\n  - y\n", &ParseOptions::default())?
+        .into_iter()
+        .map(|(keys, _)| keys.to_string())
+        .collect();
+    assert_eq!(paths, ["a", "b[0]", "b[1]"]);
+
+    let error = parse_collect("a: 1\nb: [2\n", &ParseOptions::default()).unwrap_err();
+    assert!(matches!(
+        error.reason,
+        YAMLParseErrorReason::ExpectedBracket
+    ));
+    Ok(())
+}
+
+#[test]
+fn owned_value_accessors() {
+    let values = parse_collect(
+        "a: 0x10\nb: 1.5\nc: yes\nd: \"text\\n\"\ne: ~\nf: !Ref name\n",
+        &ParseOptions::new().bool_style(simple_yaml_parser::BoolStyle::Yaml11),
+    )
+    .unwrap();
+    let values: Vec<&OwnedYAMLValue> = values.iter().map(|(_, value)| value).collect();
+    assert_eq!(values[0].as_i64(), Some(16));
+    assert_eq!(values[1].as_f64(), Some(1.5));
+    assert_eq!(values[2].as_bool(), Some(true));
+    assert_eq!(values[3].as_str(), Some("text\n"));
+    assert!(values[4].is_null());
+    assert_eq!(
+        *values[5],
+        OwnedYAMLValue::Tagged {
+            tag: "!Ref".into(),
+            value: "name".into()
+        }
+    );
+}
+
+#[test]
+fn owned_value_round_trip() {
+    let mut pairs = Vec::new();
+    simple_yaml_parser::parse("a: 'it''s'\nb: !Ref x\nc: 2\n", |_, value| {
+        let owned = value.to_owned_value();
+        assert_eq!(owned, value);
+        pairs.push(owned);
+    })
+    .unwrap();
+    let borrowed: Vec<_> = pairs.iter().map(OwnedYAMLValue::as_value).collect();
+    assert_eq!(borrowed[0].as_str().as_deref(), Some("it's"));
+    assert_eq!(borrowed[1].to_owned_value(), pairs[1]);
+    assert_eq!(borrowed[2].as_i64(), Some(2));
+}
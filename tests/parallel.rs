@@ -0,0 +1,101 @@
+#![cfg(feature = "parallel")]
+//! `parse_documents_parallel` against `parse_documents`
+
+use simple_yaml_parser::{
+    format_key_chain, parse_documents, parse_documents_parallel, ParseOptions, YAMLKey,
+};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+fn stream(documents: usize) -> String {
+    let mut on = String::new();
+    for idx in 0..documents {
+        on.push_str("---\n");
+        write!(
+            on,
+            "kind: Pod\nmetadata:\n  name: pod-{idx}\n  labels: {{ app: web, tier: \"{}\" }}\nspec:\n  containers:\n",
+            idx % 3
+        )
+        .unwrap();
+        for container in 0..idx % 5 {
+            writeln!(on, "    - name: c{container}\n      image: app:{idx}").unwrap();
+        }
+    }
+    on
+}
+
+fn value(keys: &[YAMLKey<'_>], value: &simple_yaml_parser::RootYAMLValue<'_>) -> String {
+    format!(
+        "{} = {}",
+        format_key_chain(keys),
+        value.as_str().unwrap_or_default()
+    )
+}
+
+#[test]
+fn same_as_sequential() {
+    let on = stream(200);
+    let options = ParseOptions::default();
+    let mut expected: Vec<Vec<String>> = Vec::new();
+    parse_documents(
+        &on,
+        |index, keys, item| {
+            if expected.len() <= index {
+                expected.resize_with(index + 1, Vec::new);
+            }
+            expected[index].push(value(keys, &item));
+            false
+        },
+        &options,
+    )
+    .unwrap();
+
+    let documents: Mutex<Vec<Vec<String>>> = Mutex::new(vec![Vec::new(); expected.len()]);
+    parse_documents_parallel(
+        &on,
+        |index, keys, item| {
+            documents.lock().unwrap()[index].push(value(keys, &item));
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    assert_eq!(documents.into_inner().unwrap(), expected);
+
+    // no documents
+    parse_documents_parallel("", |_, _, _| panic!(), &options).unwrap();
+}
+
+#[test]
+fn errors() {
+    let mut on = stream(50);
+    on.push_str("---\na: [1\n");
+    let expected = parse_documents(&on, |_, _, _| false, &ParseOptions::default()).unwrap_err();
+    let error =
+        parse_documents_parallel(&on, |_, _, _| false, &ParseOptions::default()).unwrap_err();
+    assert_eq!(error.to_string(), expected.to_string());
+
+    // the first invalid document is reported (its flow sequence is unclosed at the end of its line)
+    let on = "---\na: [1\n---\nb: c\n---\nd: [\n";
+    let error =
+        parse_documents_parallel(on, |_, _, _| false, &ParseOptions::default()).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 6));
+}
+
+#[test]
+fn exit() {
+    let on = stream(500);
+    let values = AtomicUsize::new(0);
+    parse_documents_parallel(
+        &on,
+        |_, _, _| {
+            values.fetch_add(1, Ordering::Relaxed);
+            true
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    // each thread stops after at most one value
+    assert!(values.into_inner() <= std::thread::available_parallelism().map_or(1, usize::from));
+}
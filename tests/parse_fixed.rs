@@ -0,0 +1,85 @@
+//! `parse_fixed`
+
+use simple_yaml_parser::{format_key_chain, parse_fixed, YAMLParseErrorReason};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts the allocations made by each thread
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const CONFIG: &str = "device: sensor-1
+wifi:
+  ssid: 'home'
+  channels: [1, 6, 11]
+  security: {mode: wpa2, hidden: false}
+pins:
+  - number: 4
+    mode: input
+  - 5
+note: |
+  calibrated
+  on site
+";
+
+#[test]
+fn no_allocations() {
+    let mut values = 0;
+    let before = ALLOCATIONS.with(Cell::get);
+    let parsed = parse_fixed::<4>(CONFIG, |_, _| {
+        values += 1;
+        false
+    })
+    .unwrap();
+    assert_eq!(ALLOCATIONS.with(Cell::get), before);
+    assert_eq!(values, 11);
+    assert!(!parsed.exited);
+}
+
+#[test]
+fn same_events_as_parse() {
+    let mut fixed = Vec::new();
+    parse_fixed::<4>(CONFIG, |keys, value| {
+        fixed.push((format_key_chain(keys), format!("{value:?}")));
+        false
+    })
+    .unwrap();
+    let mut expected = Vec::new();
+    simple_yaml_parser::parse(CONFIG, |keys, value| {
+        expected.push((format_key_chain(keys), format!("{value:?}")));
+    })
+    .unwrap();
+    assert_eq!(fixed, expected);
+}
+
+#[test]
+fn depth_limit() {
+    let on = "a:\n  b:\n    c: [d, {e: f}]\n";
+    assert!(parse_fixed::<5>(on, |_, _| false).is_ok());
+    let err = parse_fixed::<4>(on, |_, _| false).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::MaxDepthExceeded));
+    assert_eq!((err.line, err.column), (3, 13));
+
+    let err = parse_fixed::<2>(on, |_, _| false).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::MaxDepthExceeded));
+    assert_eq!((err.line, err.column), (3, 5));
+    let err = parse_fixed::<0>("- a\n", |_, _| false).unwrap_err();
+    assert!(matches!(err.reason, YAMLParseErrorReason::MaxDepthExceeded));
+}
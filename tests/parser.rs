@@ -0,0 +1,189 @@
+//! `Parser`, fed input in chunks, against parsing the whole input
+
+mod common;
+
+use common::entry;
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, Parser, RootYAMLValue, YAMLKey};
+
+fn whole(on: &str, options: &ParseOptions) -> Vec<String> {
+    let mut values = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |keys, item| {
+            values.push(entry(keys, &item));
+            false
+        },
+        options,
+    )
+    .unwrap();
+    values
+}
+
+/// Feeds `on` in chunks of `size` bytes
+fn chunked(on: &str, size: usize, options: &ParseOptions) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut parser = Parser::new(
+        |keys: &[YAMLKey<'_>], item: RootYAMLValue<'_>| {
+            values.push(entry(keys, &item));
+            false
+        },
+        options,
+    );
+    let mut rest = on;
+    while !rest.is_empty() {
+        let mut end = size.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end += 1;
+        }
+        parser.feed(&rest[..end]).unwrap();
+        rest = &rest[end..];
+    }
+    parser.finish().unwrap();
+    values
+}
+
+fn same_in_chunks(on: &str, options: &ParseOptions) {
+    let expected = whole(on, options);
+    for size in [1, 2, 3, 7, 16, on.len()] {
+        assert_eq!(chunked(on, size, options), expected, "chunks of {size}");
+    }
+}
+
+#[test]
+fn mappings() {
+    let on =
+        "# header\na: 1\nb:\n  c: [x, y]\n  d:\n    - e\n    - f: g\nlist:\n- h\n- i\n\"q\": 'é'\n";
+    same_in_chunks(on, &ParseOptions::default());
+}
+
+#[test]
+fn top_level_sequences() {
+    let on = "- a\n- b: 1\n  c: 2\n-\n  - d\n- - e\n  - f\n- last\n";
+    same_in_chunks(on, &ParseOptions::default());
+    assert_eq!(whole(on, &ParseOptions::default())[4], "[3][0] = e");
+
+    // complete items are passed before the end of the input
+    let mut values = 0;
+    let options = ParseOptions::default();
+    let mut parser = Parser::new(
+        |_: &[YAMLKey<'_>], _: RootYAMLValue<'_>| {
+            values += 1;
+            false
+        },
+        &options,
+    );
+    parser.feed("- a\n- b\n- c").unwrap();
+    parser.finish().unwrap();
+    assert_eq!(values, 3);
+}
+
+#[test]
+fn anchors_across_entries() {
+    let options = ParseOptions::new()
+        .resolve_aliases(true)
+        .resolve_merge_keys(true);
+    let on = "base: &base\n  x: 1\nother: 2\ncopy: *base\nmerged:\n  <<: *base\n  y: 3\nscalar: &s v\nlast: *s\n";
+    same_in_chunks(on, &options);
+    let on = "- &a one\n- two\n- *a\n- &b {c: d}\n- *b\n";
+    same_in_chunks(on, &options);
+    assert_eq!(whole(on, &options)[2], "[2] = one");
+    // without resolving, aliases are passed as they are
+    same_in_chunks(on, &ParseOptions::default());
+}
+
+#[test]
+fn documents() {
+    let options = ParseOptions::new().resolve_aliases(true);
+    let on = "a: &x 1\nb: 2\n---\n- *x\n- c\n...\n---\nd: *x\n";
+    same_in_chunks(on, &options);
+    same_in_chunks(on, &ParseOptions::default());
+}
+
+#[test]
+fn lines_continuing_an_entry() {
+    for on in [
+        "a: [1,\n2]\nb: 3\n",
+        "a: {x: 1,\ny: [2,\n3]}\nb: 3\n",
+        "{\n\"a\": 1,\n\"b\": [2]\n}\n",
+        "a: [\"x, ]\", # ]\ny]\nb: 3\n",
+        "a: [\"multi\nline]\", 'it''s\n]'\n]\nb: 5\n",
+        "- [a,\nb]\n- c\n",
+        "a: &x [1,\n2]\nb: *x\n",
+        "a: |\n  text [\nb: [1]\n",
+        "a: x[1\nb: 2\n",
+    ] {
+        same_in_chunks(on, &ParseOptions::default());
+    }
+}
+
+#[test]
+fn errors() {
+    let on = "a: 1\nb: 2\nc: - 3\nd: 4\n";
+    let expected = parse_with_exit_signal(on, |_, _| false, &ParseOptions::default()).unwrap_err();
+    let options = ParseOptions::new().resolve_aliases(true);
+    for size in [1, 4, 9, on.len()] {
+        let mut parser = Parser::new(|_: &[YAMLKey<'_>], _: RootYAMLValue<'_>| false, &options);
+        let mut result = Ok(());
+        for chunk in on.as_bytes().chunks(size) {
+            result = result.and_then(|()| parser.feed(std::str::from_utf8(chunk).unwrap()));
+        }
+        let error = result.and_then(|()| parser.finish()).unwrap_err();
+        assert_eq!(
+            (error.at, error.line, error.column),
+            (expected.at, expected.line, expected.column),
+            "chunks of {size}"
+        );
+    }
+
+    // with entries kept for their anchors before the error
+    let on = "a: &x 1\nb: *x\nc: d: e\n";
+    let options = ParseOptions::new().resolve_aliases(true);
+    let mut parser = Parser::new(|_: &[YAMLKey<'_>], _: RootYAMLValue<'_>| false, &options);
+    let error = parser.feed(on).and_then(|()| parser.finish()).unwrap_err();
+    let expected = parse_with_exit_signal(on, |_, _| false, &options).unwrap_err();
+    assert_eq!(
+        (error.at, error.line, error.column),
+        (expected.at, expected.line, expected.column)
+    );
+}
+
+#[test]
+fn exit() {
+    let options = ParseOptions::default();
+    let mut values = 0;
+    let mut parser = Parser::new(
+        |_: &[YAMLKey<'_>], _: RootYAMLValue<'_>| {
+            values += 1;
+            values == 2
+        },
+        &options,
+    );
+    parser.feed("- a\n- b\n- c\n").unwrap();
+    assert!(parser.exited());
+    parser.feed("- d\n").unwrap();
+    parser.finish().unwrap();
+    assert_eq!(values, 2);
+}
+
+#[test]
+fn values_before_finish() {
+    let values = std::cell::RefCell::new(Vec::new());
+    let options = ParseOptions::default();
+    let mut parser = Parser::new(
+        |keys: &[YAMLKey<'_>], item: RootYAMLValue<'_>| {
+            values.borrow_mut().push(entry(keys, &item));
+            false
+        },
+        &options,
+    );
+    parser.feed("a: 1\nb:\n  c: 2\n").unwrap();
+    // `b` may continue in the next chunk
+    assert_eq!(*values.borrow(), ["a = 1"]);
+    // the line of the next entry is not complete
+    parser.feed("  d: 3\ne: 4").unwrap();
+    assert_eq!(values.borrow().len(), 1);
+    parser.feed("5\n").unwrap();
+    assert_eq!(*values.borrow(), ["a = 1", "b.c = 2", "b.d = 3"]);
+    parser.finish().unwrap();
+    assert_eq!(values.borrow().last().unwrap(), "e = 45");
+}
@@ -0,0 +1,69 @@
+//! `path_at_offset`
+
+use simple_yaml_parser::{format_key_chain, path_at_offset, OwnedYAMLKey};
+
+/// The path at the `|` in `on`
+fn path_at_cursor(on: &str) -> Option<String> {
+    let offset = on.find('|').unwrap();
+    let on = on.replacen('|', "", 1);
+    path_at_offset(&on, offset).map(|keys| {
+        let keys: Vec<_> = keys.iter().map(OwnedYAMLKey::as_key).collect();
+        format_key_chain(&keys)
+    })
+}
+
+#[test]
+fn keys_and_values() {
+    let on = "name: app\nserver:\n  host: example.com\n  port: 80\n";
+    assert_eq!(path_at_cursor("na|me: app\n").as_deref(), Some("name"));
+    assert_eq!(
+        path_at_cursor(&on.replace("app", "a|pp")).as_deref(),
+        Some("name")
+    );
+    assert_eq!(
+        path_at_cursor(&on.replace("server", "ser|ver")).as_deref(),
+        Some("server")
+    );
+    assert_eq!(
+        path_at_cursor(&on.replace("example", "exa|mple")).as_deref(),
+        Some("server.host")
+    );
+    // at the end of a value
+    assert_eq!(
+        path_at_cursor(&on.replace("80", "80|")).as_deref(),
+        Some("server.port")
+    );
+}
+
+#[test]
+fn sequences() {
+    let on = "items:\n  - a: 1\n    b: 2\n  - two\n  - [x, y]\n";
+    assert_eq!(
+        path_at_cursor(&on.replace("b: 2", "b: |2")).as_deref(),
+        Some("items[0].b")
+    );
+    assert_eq!(
+        path_at_cursor(&on.replace("two", "t|wo")).as_deref(),
+        Some("items[1]")
+    );
+    assert_eq!(
+        path_at_cursor(&on.replace("y]", "|y]")).as_deref(),
+        Some("items[2][1]")
+    );
+}
+
+#[test]
+fn between_entries() {
+    assert_eq!(
+        path_at_cursor("server:\n  host: a\n|\n  port: 80\nother: 1\n").as_deref(),
+        Some("server")
+    );
+    assert_eq!(path_at_cursor("server:\n  host: a\n|\nother: 1\n"), None);
+}
+
+#[test]
+fn outside() {
+    assert_eq!(path_at_cursor("# comment|\na: 1\n"), None);
+    assert_eq!(path_at_cursor("a: 1\n\n|"), None);
+    assert_eq!(path_at_offset("a: 1\n", 100), None);
+}
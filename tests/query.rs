@@ -0,0 +1,63 @@
+//! `query` paths
+
+use simple_yaml_parser::{
+    format_key_chain, parse_with_exit_signal, query, ParseOptions, RootYAMLValue,
+};
+
+const SOURCE: &str = "person:\n  name: Ben\n  places: [Paris, London]\n  a.b: dotted\n  '1': string\nbase: &base x\ncopy: *base\n";
+
+fn text(path: &str) -> Option<String> {
+    query(SOURCE, path)
+        .unwrap()
+        .map(|value| value.as_str().unwrap_or_default().into_owned())
+}
+
+#[test]
+fn paths() {
+    assert_eq!(text("person.name").as_deref(), Some("Ben"));
+    assert_eq!(text("person.places[1]").as_deref(), Some("London"));
+    assert_eq!(text("person[\"a.b\"]").as_deref(), Some("dotted"));
+    assert_eq!(text("copy").as_deref(), Some("x"));
+    // numeric segments match indices and keys
+    assert_eq!(text("person.places.1").as_deref(), Some("London"));
+    assert_eq!(text("person.1").as_deref(), Some("string"));
+    assert_eq!(text("person[1]"), None);
+    assert_eq!(text("person.places[\"1\"]"), None);
+    // escapes
+    assert_eq!(text("person.a\\.b").as_deref(), Some("dotted"));
+    assert_eq!(text("person.a.b"), None);
+    // collections are not values
+    assert_eq!(text("person.places"), None);
+    assert_eq!(text("missing"), None);
+}
+
+#[test]
+fn formatted_key_chains() {
+    // every key chain passed to the callback finds its value
+    parse_with_exit_signal(
+        SOURCE,
+        |keys, value| {
+            let path = format_key_chain(keys);
+            assert_eq!(query(SOURCE, &path).unwrap(), Some(value), "{path}");
+            false
+        },
+        &ParseOptions::new().resolve_aliases(true),
+    )
+    .unwrap();
+}
+
+#[test]
+fn stops_at_the_value() {
+    // invalid input after the value is not parsed
+    let on = "person:\n  address:\n    city: Paris\nbroken: [\n";
+    assert_eq!(
+        query(on, "person.address.city").unwrap(),
+        Some(RootYAMLValue::String("Paris"))
+    );
+    assert!(query(on, "broken").is_err());
+    // the first document with the path
+    assert_eq!(
+        query("a: 1\n---\na: 2\n", "a").unwrap(),
+        Some(RootYAMLValue::Number("1"))
+    );
+}
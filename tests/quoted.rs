@@ -0,0 +1,66 @@
+//! Quoted scalars and `QuotedString::decode`
+
+use simple_yaml_parser::{parse, RootYAMLValue, YAMLParseErrorReason};
+use std::borrow::Cow;
+
+fn quoted(on: &str) -> (String, String, bool) {
+    let mut found = None;
+    parse(on, |_, value| {
+        let RootYAMLValue::QuotedString(quoted) = value else {
+            panic!("{value:?} for {on:?}");
+        };
+        found = Some((
+            quoted.raw().to_owned(),
+            quoted.decode().into_owned(),
+            quoted.is_double_quoted(),
+        ));
+    })
+    .unwrap();
+    found.unwrap()
+}
+
+#[test]
+fn double_quoted() {
+    let (raw, decoded, double) = quoted(r#"name: "John \"Doe\"""#);
+    assert_eq!(
+        (raw.as_str(), decoded.as_str(), double),
+        (r#"John \"Doe\""#, "John \"Doe\"", true)
+    );
+    assert_eq!(quoted(r#"a: "tab\tnew\nline""#).1, "tab\tnew\nline");
+    assert_eq!(quoted(r#"a: "\x41é\U0001F600\\""#).1, "Aé😀\\");
+    // invalid escapes are left as is
+    assert_eq!(quoted(r#"a: "\q \u12""#).1, r"\q \u12");
+    // `#` and `: ` inside quotes are part of the value
+    assert_eq!(quoted("a: \"x # y: z\" # comment").1, "x # y: z");
+}
+
+#[test]
+fn single_quoted() {
+    let (raw, decoded, double) = quoted("a: 'it''s \\n'");
+    assert_eq!(
+        (raw.as_str(), decoded.as_str(), double),
+        ("it''s \\n", "it's \\n", false)
+    );
+    assert_eq!(quoted("- 'x, y'").1, "x, y");
+}
+
+#[test]
+fn borrows_without_escapes() {
+    parse("a: \"plain\"\nb: 'also'\n", |_, value| {
+        let RootYAMLValue::QuotedString(quoted) = value else {
+            panic!();
+        };
+        assert!(matches!(quoted.decode(), Cow::Borrowed(_)));
+    })
+    .unwrap();
+}
+
+#[test]
+fn unclosed() {
+    let err = parse("a: \"open\nb: c\n", |_, _| {}).unwrap_err();
+    assert!(matches!(
+        err.reason,
+        YAMLParseErrorReason::ExpectedClosingQuote
+    ));
+    assert_eq!((err.line, err.column), (1, 4));
+}
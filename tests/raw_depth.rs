@@ -0,0 +1,127 @@
+//! `ParseOptions::raw_depth`
+
+use simple_yaml_parser::{
+    format_key_chain, parse_with_exit_signal, ParseOptions, RootYAMLValue, YAMLWriter,
+};
+
+const SOURCE: &str = "name: app # the name
+server:
+  host: example.com
+  ports: [80, 443]
+items:
+  - a: 1
+    b:
+      c: 2
+  - plain
+  - [x, \"]\", y] # comment
+  - - nested
+    - seq
+flow: {a: [1, 2], b: \"}\"}
+script: |
+  echo hi
+";
+
+/// The path and (for raw blocks) text of each value
+fn values(on: &str, depth: usize) -> Vec<(String, Option<String>)> {
+    let options = ParseOptions::new().raw_depth(depth);
+    let mut values = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |keys, value| {
+            let raw = match value {
+                RootYAMLValue::RawBlock(raw) => Some(raw.to_owned()),
+                _ => None,
+            };
+            values.push((format_key_chain(keys), raw));
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    values
+}
+
+fn raw(path: &str, text: &str) -> (String, Option<String>) {
+    (path.to_owned(), Some(text.to_owned()))
+}
+
+fn parsed(path: &str) -> (String, Option<String>) {
+    (path.to_owned(), None)
+}
+
+#[test]
+fn top_level_keys() {
+    assert_eq!(
+        values(SOURCE, 1),
+        [
+            parsed("name"),
+            raw("server", "  host: example.com\n  ports: [80, 443]"),
+            raw(
+                "items",
+                "  - a: 1\n    b:\n      c: 2\n  - plain\n  - [x, \"]\", y] # comment\n  - - \
+                 nested\n    - seq"
+            ),
+            raw("flow", "{a: [1, 2], b: \"}\"}"),
+            parsed("script"),
+        ]
+    );
+}
+
+#[test]
+fn second_level() {
+    assert_eq!(
+        values(SOURCE, 2),
+        [
+            parsed("name"),
+            parsed("server.host"),
+            raw("server.ports", "[80, 443]"),
+            raw("items[0]", "a: 1\n    b:\n      c: 2"),
+            parsed("items[1]"),
+            raw("items[2]", "[x, \"]\", y]"),
+            raw("items[3]", "- nested\n    - seq"),
+            raw("flow.a", "[1, 2]"),
+            parsed("flow.b"),
+            parsed("script"),
+        ]
+    );
+}
+
+#[test]
+fn deeper_than_document() {
+    let all = values(SOURCE, usize::MAX);
+    assert!(all.iter().all(|(_, raw)| raw.is_none()));
+    assert_eq!(all.len(), 16);
+}
+
+#[test]
+fn properties() {
+    let on = "base: &base !!map\n  a: 1\nother: *base\n";
+    assert_eq!(values(on, 1), [raw("base", "  a: 1"), parsed("other")]);
+}
+
+#[test]
+fn unclosed_flow_collection_is_an_error() {
+    let options = ParseOptions::new().raw_depth(1);
+    assert!(parse_with_exit_signal("a: [1, 2\n", |_, _| false, &options).is_err());
+}
+
+#[test]
+fn written_back() {
+    let options = ParseOptions::new().raw_depth(2);
+    let mut writer = YAMLWriter::default();
+    parse_with_exit_signal(
+        SOURCE,
+        |keys, value| {
+            writer.write(keys, &value);
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        writer.finish(),
+        "name: app\nserver:\n  host: example.com\n  ports: [80, 443]\nitems:\n  - a: 1\n    b:\n      \
+         c: 2\n  - plain\n  - [x, \"]\", y]\n  - - nested\n    - seq\nflow:\n  a: [1, 2]\n  b: \
+         \"}\"\nscript: |\n  echo hi\n"
+    );
+}
@@ -0,0 +1,80 @@
+//! `parse_reader`
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::entry;
+use simple_yaml_parser::{parse_reader, parse_with_exit_signal, ParseOptions, ReadError};
+use std::fmt::Write;
+use std::io::Read;
+
+/// Returns at most `size` bytes per read
+struct Chunks<'a> {
+    on: &'a [u8],
+    size: usize,
+}
+
+impl Read for Chunks<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.size.min(buf.len()).min(self.on.len());
+        buf[..length].copy_from_slice(&self.on[..length]);
+        self.on = &self.on[length..];
+        Ok(length)
+    }
+}
+
+struct Failing;
+
+impl Read for Failing {
+    fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("failed"))
+    }
+}
+
+fn values(on: &[u8], size: usize) -> Result<Vec<String>, ReadError> {
+    let mut values = Vec::new();
+    parse_reader(
+        Chunks { on, size },
+        |keys, value| {
+            values.push(entry(keys, &value));
+            false
+        },
+        &ParseOptions::default(),
+    )?;
+    Ok(values)
+}
+
+#[test]
+fn same_as_parsing_the_whole_input() {
+    let mut on = String::new();
+    for idx in 0..2000 {
+        write!(on, "entry{idx}:\n  name: café {idx}\n  tags: [a, \"b\"]\n").unwrap();
+    }
+    let mut expected = Vec::new();
+    parse_with_exit_signal(
+        &on,
+        |keys, value| {
+            expected.push(entry(keys, &value));
+            false
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    // reads that split `é` between them
+    for size in [1, 5, 4096, usize::MAX] {
+        assert_eq!(values(on.as_bytes(), size).unwrap(), expected, "{size}");
+    }
+}
+
+#[test]
+fn errors() {
+    let err = values(b"a: 1\nb: \xff\n", 3).unwrap_err();
+    assert!(matches!(err, ReadError::InvalidUTF8(8)), "{err:?}");
+    let err = values(b"a: 1\nb: ]\n", 3).unwrap_err();
+    let ReadError::Parse(err) = err else {
+        panic!("{err:?}");
+    };
+    assert_eq!((err.line, err.column), (2, 4));
+    let result = parse_reader(Failing, |_, _| false, &ParseOptions::default());
+    assert!(matches!(result, Err(ReadError::IO(_))));
+}
@@ -0,0 +1,254 @@
+//! Generated documents written with `YAMLWriter` parse back to the same values. Documents are generated
+//! with a fixed seed (as in `tests/no_panic.rs`), covering the scalar types, nesting of mappings and
+//! sequences and block scalar styles
+
+use simple_yaml_parser::{
+    format_key_chain, json::yaml_to_json, parse, RootYAMLValue, YAMLKey, YAMLWriter,
+};
+
+/// xorshift, to not depend on a random number crate
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        usize::try_from(self.0 % (u64::from(u32::MAX) + 1)).unwrap()
+    }
+
+    fn pick<'a>(&mut self, from: &[&'a str]) -> &'a str {
+        from[self.next() % from.len()]
+    }
+}
+
+/// Pieces of strings, including ones that need quoting
+const STRING_FRAGMENTS: &[&str] = &[
+    "a", "word", " ", "  ", ":", ": ", "-", "- ", "#", " #", "'", "\"", "\\", ",", "[", "]", "{",
+    "}", "&", "*", "!", "%", "@", "`", "|", ">", "?", "~", "1", "0x1f", "true", "null", "yes", "é",
+    "日本", "🦀", "\t", "\n",
+];
+
+const NUMBERS: &[&str] = &[
+    "0", "1", "-2", "3.5", "1e3", "-0.25", "0x1f", "0o17", ".inf", "-.inf",
+];
+
+/// Lines of block scalars (before indentation)
+const BLOCK_LINES: &[&str] = &[
+    "text",
+    "more words",
+    "",
+    "  indented",
+    "# not a comment",
+    "a: b",
+];
+
+#[derive(Debug)]
+enum Node {
+    Map(Vec<(String, Node)>),
+    Sequence(Vec<Node>),
+    String(String),
+    Number(&'static str),
+    Bool(bool),
+    Null,
+    /// The source of a document with a block scalar under `value`
+    BlockScalar(String),
+}
+
+/// A value as parsed, for comparing
+#[derive(Debug, PartialEq)]
+enum Value {
+    String(String),
+    Number(String),
+    Bool(bool),
+    Null,
+}
+
+fn string(rng: &mut Rng) -> String {
+    (0..=rng.next() % 4)
+        .map(|_| rng.pick(STRING_FRAGMENTS))
+        .collect()
+}
+
+/// Keys are slices of the input, so escapes in quoted keys are not decoded and keys are written in
+/// quotes without escaping. Keys with line breaks (and some with `'` and `"` or `\`) would need
+/// escaping, so are not generated
+fn key(rng: &mut Rng) -> String {
+    loop {
+        let key = string(rng);
+        let both_quotes = key.contains('\'') && key.contains(['"', '\\']);
+        if !both_quotes && !key.contains('\n') {
+            return key;
+        }
+    }
+}
+
+fn block_scalar(rng: &mut Rng) -> String {
+    let style = rng.pick(&["|", ">"]);
+    let chomping = rng.pick(&["", "-", "+"]);
+    // a first line that starts with spaces needs an indentation indicator
+    let first = rng.pick(&["text", "  indented"]);
+    let indicator = if first.starts_with(' ') { "2" } else { "" };
+    let mut source = format!("value: {style}{indicator}{chomping}\n  {first}\n");
+    for _ in 0..rng.next() % 4 {
+        let line = rng.pick(BLOCK_LINES);
+        if !line.is_empty() {
+            source.push_str("  ");
+        }
+        source.push_str(line);
+        source.push('\n');
+    }
+    source
+}
+
+fn node(rng: &mut Rng, depth: usize) -> Node {
+    let choice = if depth >= 4 {
+        2 + rng.next() % 5
+    } else {
+        rng.next() % 7
+    };
+    match choice {
+        0 => {
+            let mut entries: Vec<(String, Node)> = Vec::new();
+            for _ in 0..=rng.next() % 3 {
+                let key = key(rng);
+                if !entries.iter().any(|(existing, _)| *existing == key) {
+                    entries.push((key, node(rng, depth + 1)));
+                }
+            }
+            Node::Map(entries)
+        }
+        1 => Node::Sequence((0..=rng.next() % 3).map(|_| node(rng, depth + 1)).collect()),
+        2 => Node::String(string(rng)),
+        3 => Node::Number(rng.pick(NUMBERS)),
+        4 => Node::Bool(rng.next().is_multiple_of(2)),
+        5 => Node::Null,
+        _ => Node::BlockScalar(block_scalar(rng)),
+    }
+}
+
+fn value_of(value: &RootYAMLValue<'_>) -> Value {
+    match value {
+        RootYAMLValue::Number(on) => Value::Number((*on).to_owned()),
+        RootYAMLValue::True => Value::Bool(true),
+        RootYAMLValue::False => Value::Bool(false),
+        RootYAMLValue::Null => Value::Null,
+        value => Value::String(value.as_str().expect("a string").into_owned()),
+    }
+}
+
+/// Calls `cb` with the block scalar in `source`
+fn with_block_scalar(source: &str, cb: impl FnOnce(&RootYAMLValue<'_>)) {
+    let mut cb = Some(cb);
+    parse(source, |_, value| {
+        if let Some(cb) = cb.take() {
+            cb(&value);
+        }
+    })
+    .unwrap();
+}
+
+/// Writes the values under `node` and records what they should parse back as
+fn write<'a>(
+    node: &'a Node,
+    keys: &mut Vec<YAMLKey<'a>>,
+    writer: &mut YAMLWriter<'a>,
+    expected: &mut Vec<(String, Value)>,
+) {
+    let mut push = |keys: &[YAMLKey<'a>], value: &RootYAMLValue<'_>| {
+        writer.write(keys, value);
+        expected.push((format_key_chain(keys), value_of(value)));
+    };
+    match node {
+        Node::Map(entries) => {
+            for (key, value) in entries {
+                keys.push(YAMLKey::Slice(key));
+                write(value, keys, writer, expected);
+                keys.pop();
+            }
+        }
+        Node::Sequence(items) => {
+            for (index, item) in items.iter().enumerate() {
+                keys.push(YAMLKey::Index(index));
+                write(item, keys, writer, expected);
+                keys.pop();
+            }
+        }
+        Node::String(on) => push(keys, &RootYAMLValue::String(on)),
+        Node::Number(on) => push(keys, &RootYAMLValue::Number(on)),
+        Node::Bool(true) => push(keys, &RootYAMLValue::True),
+        Node::Bool(false) => push(keys, &RootYAMLValue::False),
+        Node::Null => push(keys, &RootYAMLValue::Null),
+        Node::BlockScalar(source) => with_block_scalar(source, |value| push(keys, value)),
+    }
+}
+
+#[test]
+fn generated_documents() {
+    let mut rng = Rng(0x5eed);
+    for _ in 0..5_000 {
+        // the root is a collection
+        let document = loop {
+            let node = node(&mut rng, 0);
+            if matches!(node, Node::Map(_) | Node::Sequence(_)) {
+                break node;
+            }
+        };
+        let mut writer = YAMLWriter::new();
+        let mut expected = Vec::new();
+        write(&document, &mut Vec::new(), &mut writer, &mut expected);
+        let output = writer.finish();
+
+        let mut parsed = Vec::new();
+        let result = parse(&output, |keys, value| {
+            parsed.push((format_key_chain(keys), value_of(&value)));
+        });
+        assert!(
+            result.is_ok(),
+            "{result:?} parsing {output:?} from {document:?}"
+        );
+        assert_eq!(parsed, expected, "parsing {output:?} from {document:?}");
+    }
+}
+
+/// The values of `on`, as `(path, value)`
+fn entries(on: &str) -> Vec<(String, Value)> {
+    let mut entries = Vec::new();
+    parse(on, |keys, value| {
+        entries.push((format_key_chain(keys), value_of(&value)));
+    })
+    .unwrap();
+    entries
+}
+
+/// Keys are slices of the input, so escapes in quoted keys are not decoded (other than by
+/// `yaml_to_json`). Keys are written in quotes without escapes, so they are parsed back the same
+#[test]
+fn quoted_keys() {
+    let source = "\"k\\\"q\": 1\n";
+    let parsed = entries(source);
+    assert_eq!(
+        parsed,
+        [("k\\\"q".to_owned(), Value::Number("1".to_owned()))]
+    );
+    assert_eq!(yaml_to_json(source).unwrap(), "{\"k\\\"q\":1}");
+    assert_eq!(yaml_to_json("'it''s': 1").unwrap(), "{\"it's\":1}");
+
+    for (key, written) in [
+        ("k\\\"q", "k\\\"q: 1\n"),
+        ("\"q", "'\"q': 1\n"),
+        ("'q", "\"'q\": 1\n"),
+        ("a: \\", "'a: \\': 1\n"),
+        ("\t", "'\t': 1\n"),
+    ] {
+        let mut writer = YAMLWriter::new();
+        writer.write(&[YAMLKey::Slice(key)], &RootYAMLValue::Number("1"));
+        let output = writer.finish();
+        assert_eq!(output, written);
+        assert_eq!(
+            entries(&output),
+            [(key.to_owned(), Value::Number("1".to_owned()))],
+            "{output:?}"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! The parser jumps to the characters each state acts on. These are inputs where the other
+//! characters must be passed over
+
+mod common;
+
+use common::values;
+
+#[test]
+fn collection_characters_inside_values() {
+    assert_eq!(
+        values("a: x-y [z] {w}\nb: -1\n"),
+        ["a = x-y [z] {w}", "b = -1"]
+    );
+    let on = "- a-b: c-d\n- e [f]\n- &anchor [g]\n";
+    assert_eq!(values(on), ["[0].a-b = c-d", "[1] = e [f]", "[2][0] = g"]);
+    assert_eq!(values("a: &anchor {b: c}\n"), ["a.b = c"]);
+}
+
+#[test]
+fn long_values() {
+    let long = "word ".repeat(10_000);
+    let long = long.trim_end();
+    let on = format!("a: {long}\nb: {long} # comment\nc#d: e#f\n");
+    assert_eq!(
+        values(&on),
+        [
+            format!("a = {long}"),
+            format!("b = {long}"),
+            "c#d = e#f".to_owned()
+        ]
+    );
+}
+
+#[test]
+fn multibyte_characters() {
+    assert_eq!(values("ключ: значение\n"), ["ключ = значение"]);
+    assert_eq!(values("- ✓: 🎉\n-    ü\n"), ["[0].✓ = 🎉", "[1] = ü"]);
+    assert_eq!(
+        values("a:\n      b: «c»\n      d: 1\n"),
+        ["a.b = «c»", "a.d = 1"]
+    );
+}
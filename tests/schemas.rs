@@ -0,0 +1,76 @@
+//! `ParseOptions::schema`
+
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, RootYAMLValue, Schema};
+
+/// The type of each value
+fn values(on: &str, schema: Schema) -> Vec<String> {
+    let options = ParseOptions::new().schema(schema);
+    let mut values = Vec::new();
+    parse_with_exit_signal(
+        on,
+        |_, value| {
+            values.push(match value {
+                RootYAMLValue::String(_) => "string".to_owned(),
+                RootYAMLValue::Number(_) => "number".to_owned(),
+                RootYAMLValue::Timestamp(_) => "timestamp".to_owned(),
+                RootYAMLValue::True | RootYAMLValue::False => "bool".to_owned(),
+                RootYAMLValue::Null => "null".to_owned(),
+                value => format!("{value:?}"),
+            });
+            false
+        },
+        &options,
+    )
+    .unwrap();
+    values
+}
+
+const INPUT: &str = "a: [null, ~, true, yes, 12, 0x1f, 1.5e3, .inf, 2001-12-14, text]\n";
+
+#[test]
+fn failsafe() {
+    assert!(values(INPUT, Schema::Failsafe)
+        .iter()
+        .all(|value| value == "string"));
+}
+
+#[test]
+fn json() {
+    assert_eq!(
+        values(INPUT, Schema::Json),
+        [
+            "null", "string", "bool", "string", "number", "string", "number", "string", "string",
+            "string"
+        ]
+    );
+}
+
+#[test]
+fn core() {
+    assert_eq!(
+        values(INPUT, Schema::Core),
+        [
+            "null", "null", "bool", "string", "number", "number", "number", "number", "string",
+            "string"
+        ]
+    );
+}
+
+#[test]
+fn yaml11() {
+    assert_eq!(
+        values(INPUT, Schema::Yaml11),
+        [
+            "null",
+            "null",
+            "bool",
+            "bool",
+            "number",
+            "number",
+            "number",
+            "number",
+            "timestamp",
+            "string"
+        ]
+    );
+}
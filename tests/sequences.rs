@@ -0,0 +1,164 @@
+//! Keys of a mapping in a sequence item (`- name: a\n  role: b`) are under the same index
+
+mod common;
+
+use common::values;
+use simple_yaml_parser::{
+    format_key_chain, parse, parse_with_comments, ParseOptions, RootYAMLValue,
+};
+
+#[test]
+fn mapping_items() {
+    let on = "- name: a\n  role: b\n- name: c\n  role: d\n";
+    assert_eq!(
+        values(on),
+        [
+            "[0].name = a",
+            "[0].role = b",
+            "[1].name = c",
+            "[1].role = d"
+        ]
+    );
+}
+
+#[test]
+fn nested_sequence_in_item() {
+    let on = "- name: a\n  nested:\n    - x: p\n      y: q\n    - x: r\n  after: s\n- name: t\n";
+    assert_eq!(
+        values(on),
+        [
+            "[0].name = a",
+            "[0].nested[0].x = p",
+            "[0].nested[0].y = q",
+            "[0].nested[1].x = r",
+            "[0].after = s",
+            "[1].name = t"
+        ]
+    );
+}
+
+#[test]
+fn item_under_key() {
+    // items at the same column as their key
+    let on = "items:\n- name: a\n  role: b\nother: c\n";
+    assert_eq!(
+        values(on),
+        ["items[0].name = a", "items[0].role = b", "other = c"]
+    );
+}
+
+#[test]
+fn extra_spaces_after_dash() {
+    let on = "a:\n  -   name: b\n      role: c\n  - d\n";
+    assert_eq!(values(on), ["a[0].name = b", "a[0].role = c", "a[1] = d"]);
+}
+
+#[test]
+fn sequence_of_sequences() {
+    let on = "- - name: a\n    role: b\n  - c\n- d\n";
+    assert_eq!(
+        values(on),
+        [
+            "[0][0].name = a",
+            "[0][0].role = b",
+            "[0][1] = c",
+            "[1] = d"
+        ]
+    );
+}
+
+#[test]
+fn inline_comments() {
+    let on = "- item # comment\n- x#y\n- \"a # b\" # c\n- k: v # d\n-\tz\t# e\r\n";
+    assert_eq!(
+        values(on),
+        [
+            "[0] = item",
+            "[1] = x#y",
+            "[2] = a # b",
+            "[3].k = v",
+            "[4] = z"
+        ]
+    );
+
+    let mut comments = Vec::new();
+    parse_with_comments(
+        on,
+        |_, _| false,
+        |_, comment| comments.push(comment),
+        &ParseOptions::new().emit_comments(true),
+    )
+    .unwrap();
+    assert_eq!(comments, [" comment", " c", " d", " e"]);
+}
+
+#[test]
+fn empty_items() {
+    let on = "-   \n- # comment\n- x\n-\na:\n  -\n  - y\n";
+    let mut items = Vec::new();
+    parse(on, |keys, value| {
+        items.push((format_key_chain(keys), matches!(value, RootYAMLValue::Null)));
+    })
+    .unwrap();
+    assert_eq!(
+        items,
+        [
+            ("[0]".to_owned(), true),
+            ("[1]".to_owned(), true),
+            ("[2]".to_owned(), false),
+            ("[3]".to_owned(), true),
+            ("a[0]".to_owned(), true),
+            ("a[1]".to_owned(), false),
+        ]
+    );
+}
+
+#[test]
+fn block_scalar_items() {
+    let on =
+        "- |\n  line one\n  line two\n- >-\n  folded\n  text\n- &x | # comment\n  a\n- after\n";
+    assert_eq!(
+        values(on),
+        [
+            "[0] = line one\nline two\n",
+            "[1] = folded text",
+            "[2] = a\n",
+            "[3] = after"
+        ]
+    );
+    let nested = "items:\n  - - |\n      nested\n    - b\n  - c\n";
+    assert_eq!(
+        values(nested),
+        ["items[0][0] = nested\n", "items[0][1] = b", "items[1] = c"]
+    );
+}
+
+#[test]
+fn kubernetes_deployment() {
+    let on = include_str!("fixtures/deployment.yaml");
+    assert_eq!(
+        values(on),
+        [
+            "apiVersion = apps/v1",
+            "kind = Deployment",
+            "metadata.name = web",
+            "metadata.labels.app = web",
+            "spec.replicas = 2",
+            "spec.template.spec.containers[0].name = app",
+            "spec.template.spec.containers[0].image = example/app:1.2",
+            "spec.template.spec.containers[0].args[0] = --port=8080",
+            "spec.template.spec.containers[0].args[1] = --verbose",
+            "spec.template.spec.containers[0].ports[0].name = http",
+            "spec.template.spec.containers[0].ports[0].containerPort = 8080",
+            "spec.template.spec.containers[0].ports[0].protocol = TCP",
+            "spec.template.spec.containers[0].ports[1].containerPort = 9090",
+            "spec.template.spec.containers[0].env[0].name = MODE",
+            "spec.template.spec.containers[0].env[0].value = production",
+            "spec.template.spec.containers[1].name = sidecar",
+            "spec.template.spec.containers[1].image = example/proxy:3",
+            "spec.template.spec.containers[1].ports[0].containerPort = 15001",
+            "spec.template.spec.volumes[0].name = config",
+            "spec.template.spec.volumes[0].configMap.name = web-config",
+        ]
+    );
+}
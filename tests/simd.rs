@@ -0,0 +1,32 @@
+#![cfg(feature = "simd")]
+//! Structural characters found 16 bytes at a time, at each position around the block boundaries
+
+mod common;
+
+use common::values;
+
+#[test]
+fn keys_of_each_length() {
+    for length in 1..50 {
+        let key = "k".repeat(length);
+        let on = format!("{key}: 1\n{key}x:\n  - {key}: [2]\n");
+        assert_eq!(
+            values(&on),
+            [format!("{key} = 1"), format!("{key}x[0].{key}[0] = 2")],
+            "{length}"
+        );
+    }
+}
+
+#[test]
+fn values_of_each_length() {
+    for length in 0..50 {
+        let value = "v".repeat(length);
+        let on = format!("a: x{value}\nb: {value}é # comment\n");
+        assert_eq!(
+            values(&on),
+            [format!("a = x{value}"), format!("b = {value}é")],
+            "{length}"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! `YAMLSink` and `parse_with_sink`
+
+use simple_yaml_parser::{
+    format_key_chain, parse_with_sink, ParseOptions, RootYAMLValue, YAMLKey, YAMLParseError,
+    YAMLSink, YAMLWriter,
+};
+use std::ops::ControlFlow;
+
+/// Records values as `document: path = value`, and the lines of errors
+#[derive(Default)]
+struct Recorder {
+    document: usize,
+    values: Vec<String>,
+    errors: Vec<usize>,
+}
+
+impl<'a> YAMLSink<'a> for Recorder {
+    fn value(&mut self, keys: &[YAMLKey<'a>], value: RootYAMLValue<'a>) -> ControlFlow<()> {
+        let value = value.as_str().unwrap_or_default();
+        let path = format_key_chain(keys);
+        self.values
+            .push(format!("{}: {path} = {value}", self.document));
+        ControlFlow::Continue(())
+    }
+
+    fn error(&mut self, error: &YAMLParseError) -> ControlFlow<()> {
+        self.errors.push(error.line);
+        ControlFlow::Continue(())
+    }
+
+    fn document_end(&mut self, index: usize) {
+        self.document = index + 1;
+    }
+}
+
+#[test]
+fn struct_sink() {
+    let on = "a: one\nb: [x, y]\n---\nc: two\n---\n- item\n";
+    let mut recorder = Recorder::default();
+    parse_with_sink(on, &mut recorder, &ParseOptions::default()).unwrap();
+    assert_eq!(
+        recorder.values,
+        [
+            "0: a = one",
+            "0: b[0] = x",
+            "0: b[1] = y",
+            "1: c = two",
+            "2: [0] = item"
+        ]
+    );
+    assert_eq!(recorder.document, 3);
+    assert!(recorder.errors.is_empty());
+}
+
+#[test]
+fn continuing_after_errors() {
+    let on = "a: x\nb: 'open\nc: y\n---\nd: ]\ne: z\n";
+    let mut recorder = Recorder::default();
+    parse_with_sink(on, &mut recorder, &ParseOptions::default()).unwrap();
+    assert_eq!(recorder.errors, [2, 5]);
+    assert_eq!(recorder.values, ["0: a = x", "0: c = y", "1: e = z"]);
+}
+
+#[test]
+fn closures() {
+    let on = "a: 1\nb: 2\nc: 3\n";
+    let mut seen = Vec::new();
+    let mut sink = |keys: &[YAMLKey<'_>], _: RootYAMLValue<'_>| {
+        seen.push(format_key_chain(keys));
+        if seen.len() == 2 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    };
+    parse_with_sink(on, &mut sink, &ParseOptions::default()).unwrap();
+    assert_eq!(seen, ["a", "b"]);
+
+    // the default is to stop on the first error
+    let mut sink = |_: &[YAMLKey<'_>], _: RootYAMLValue<'_>| ControlFlow::Continue(());
+    let err = parse_with_sink("a: 1\nb: ]\n", &mut sink, &ParseOptions::default()).unwrap_err();
+    assert_eq!((err.line, err.column), (2, 4));
+}
+
+#[test]
+fn writer() {
+    let on = "a: 1\nb:\n  - x\n  - c: 'y'\n";
+    let mut writer = YAMLWriter::new();
+    parse_with_sink(on, &mut writer, &ParseOptions::default()).unwrap();
+    assert_eq!(writer.finish(), on);
+}
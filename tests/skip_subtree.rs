@@ -0,0 +1,67 @@
+//! `parse_with_signal` and `Signal::SkipSubtree`
+
+use simple_yaml_parser::{format_key_chain, parse_with_signal, ParseOptions, Signal, YAMLKey};
+
+/// Key chains passed before skipping the collection containing `key`
+fn skipping(on: &str, key: &str) -> Vec<String> {
+    let mut keys_seen = Vec::new();
+    parse_with_signal(
+        on,
+        |keys, _| {
+            keys_seen.push(format_key_chain(keys));
+            if keys.last() == Some(&YAMLKey::Slice(key)) {
+                Signal::SkipSubtree
+            } else {
+                Signal::Continue
+            }
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    keys_seen
+}
+
+#[test]
+fn skips_the_rest_of_the_collection() {
+    let on = "a:\n  kind: skip\n  b: 1\n  c:\n    d: 2\ne: 3\n";
+    assert_eq!(skipping(on, "kind"), ["a.kind", "e"]);
+    // sequences
+    let on = "list:\n  - kind: skip\n    x: 1\n  - kind: other\n    x: 2\nend: 4\n";
+    assert_eq!(
+        skipping(on, "kind"),
+        ["list[0].kind", "list[1].kind", "end"]
+    );
+    // flow collections
+    let on = "a: {kind: skip, b: [1, 2]}\nc: [kind, x]\n";
+    assert_eq!(skipping(on, "kind"), ["a.kind", "c[0]", "c[1]"]);
+    // at the top level the rest of the document is skipped
+    assert_eq!(
+        skipping("kind: 1\nb: 2\n---\nc: 3\n", "kind"),
+        ["kind", "c"]
+    );
+}
+
+#[test]
+fn skipped_lines_are_not_parsed() {
+    let on = "a:\n  kind: skip\n  b: [unclosed\n  c: \"unclosed\nd: 1\n";
+    assert_eq!(skipping(on, "kind"), ["a.kind", "d"]);
+}
+
+#[test]
+fn stop() {
+    let mut count = 0;
+    parse_with_signal(
+        "a: 1\nb: 2\nc: 3\n",
+        |_, _| {
+            count += 1;
+            if count == 2 {
+                Signal::Stop
+            } else {
+                Signal::Continue
+            }
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(count, 2);
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "smallkeys")]
+//! Key chains stored inline, across the inline limit of 16 levels
+
+mod common;
+
+use common::values;
+use std::fmt::Write;
+
+#[test]
+fn block_nesting() {
+    for depth in [15, 16, 17, 40] {
+        let mut on = String::new();
+        let mut path = Vec::new();
+        for level in 0..depth {
+            writeln!(on, "{}k{level}:", "  ".repeat(level)).unwrap();
+            path.push(format!("k{level}"));
+        }
+        // a value at the deepest level, then back at the second level
+        writeln!(on, "{}deep: 1\n  shallow: 2", "  ".repeat(depth)).unwrap();
+        assert_eq!(
+            values(&on),
+            [
+                format!("{}.deep = 1", path.join(".")),
+                "k0.shallow = 2".to_owned()
+            ],
+            "{depth}"
+        );
+    }
+}
+
+#[test]
+fn flow_and_sequence_nesting() {
+    let on = format!("a: {}x{}\nb: 1\n", "[".repeat(20), "]".repeat(20));
+    assert_eq!(
+        values(&on),
+        [format!("a{} = x", "[0]".repeat(20)), "b = 1".to_owned()]
+    );
+    let on = format!("{}y\n- z\n", "- ".repeat(20));
+    assert_eq!(
+        values(&on),
+        [format!("{} = y", "[0]".repeat(20)), "[1] = z".to_owned()]
+    );
+}
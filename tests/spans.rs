@@ -0,0 +1,48 @@
+//! Key and value spans from `parse_with_spans`
+
+use simple_yaml_parser::{format_key_chain, parse_with_spans, ParseOptions};
+
+/// `path: key text = value text` for each value
+fn spans(on: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    parse_with_spans(
+        on,
+        |keys, _, spans| {
+            let (key, value) = (&on[spans.key], &on[spans.value]);
+            found.push(format!("{}: {key} = {value}", format_key_chain(keys)));
+            false
+        },
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    found
+}
+
+#[test]
+fn block() {
+    let on = "name: app # comment\nserver:\n  host: \"local host\"\n  port: 80\nempty:\n";
+    assert_eq!(
+        spans(on),
+        [
+            "name: name = app",
+            "server.host: host = \"local host\"",
+            "server.port: port = 80",
+            "empty: empty = "
+        ]
+    );
+}
+
+#[test]
+fn sequences_and_flow() {
+    let on = "items:\n  - a\n  - 'b'\npoint: {x: 1, y: [2, 3]}\n";
+    assert_eq!(
+        spans(on),
+        [
+            "items[0]: a = a",
+            "items[1]: 'b' = 'b'",
+            "point.x: x = 1",
+            "point.y[0]: 2 = 2",
+            "point.y[1]: 3 = 3"
+        ]
+    );
+}
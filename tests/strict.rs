@@ -0,0 +1,68 @@
+//! `ParseOptions::strict`
+
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, YAMLParseErrorReason};
+
+fn strict_error(on: &str) -> Option<(YAMLParseErrorReason, usize, usize)> {
+    let options = ParseOptions::new().strict(true);
+    parse_with_exit_signal(on, |_, _| false, &options)
+        .err()
+        .map(|err| (err.reason, err.line, err.column))
+}
+
+#[test]
+fn subset() {
+    let on = "name: app\nport: 8080\nratio: 0.5\nenabled: true\nempty:\nitems:\n  - 'yes'\n  - \"a: b\"\n  - [x, 10]\nurl: 'http://example.com'\n";
+    assert!(strict_error(on).is_none());
+    // the same input is accepted without `strict`
+    for on in ["a: yes\n", "a:\n\t- b\n", "a: 1\na: 2\n", "a: b:c\n"] {
+        assert!(parse_with_exit_signal(on, |_, _| false, &ParseOptions::default()).is_ok());
+    }
+}
+
+#[test]
+fn ambiguous_scalars() {
+    for on in [
+        "a: yes\n",
+        "a: Off\n",
+        "a: N\n",
+        "- [ON]\n",
+        "a: 0777\n",
+        "a: -012\n",
+    ] {
+        assert!(
+            matches!(
+                strict_error(on),
+                Some((YAMLParseErrorReason::AmbiguousScalar, 1, _))
+            ),
+            "{on:?}"
+        );
+    }
+    // only integers with more digits after a leading zero
+    for on in ["a: 0\n", "a: 0.5\n", "a: 10\n", "a: yesterday\n"] {
+        assert!(strict_error(on).is_none(), "{on:?}");
+    }
+}
+
+#[test]
+fn unquoted_colons() {
+    assert!(matches!(
+        strict_error("a: 1\nurl: http://example.com\n"),
+        Some((YAMLParseErrorReason::UnquotedColon, 2, 10))
+    ));
+    assert!(matches!(
+        strict_error("a: [b:c]\n"),
+        Some((YAMLParseErrorReason::UnquotedColon, 1, 6))
+    ));
+}
+
+#[test]
+fn tabs_and_duplicate_keys() {
+    assert!(matches!(
+        strict_error("a:\n\tb: 1\n"),
+        Some((YAMLParseErrorReason::TabIndentation, 2, 1))
+    ));
+    assert!(matches!(
+        strict_error("a: 1\nb: 2\na: 3\n"),
+        Some((YAMLParseErrorReason::DuplicateKey { first_at: 0, .. }, 3, 1))
+    ));
+}
@@ -0,0 +1,42 @@
+//! `ParseOptions::tabs`
+
+mod common;
+
+use common::try_values;
+use simple_yaml_parser::{ParseOptions, TabPolicy, YAMLParseErrorReason};
+
+#[test]
+fn allow() {
+    let on = "a:\n\tb: 1\n\tc:\n\t\td: 2\ne: 3\n";
+    assert_eq!(
+        try_values(on, &ParseOptions::default()).unwrap(),
+        ["a.b = 1", "a.c.d = 2", "e = 3"]
+    );
+}
+
+#[test]
+fn treat_as_spaces() {
+    // a tab lines up with four spaces
+    let on = "a:\n\tb: 1\n    c: 2\n\td:\n\t  e: 3\n";
+    assert_eq!(
+        try_values(on, &ParseOptions::new().tabs(TabPolicy::TreatAsSpaces(4))).unwrap(),
+        ["a.b = 1", "a.c = 2", "a.d.e = 3"]
+    );
+}
+
+#[test]
+fn error() {
+    let options = ParseOptions::new().tabs(TabPolicy::Error);
+    let error = try_values("a:\n  b: 1\n\tc: 2\n", &options).unwrap_err();
+    assert!(matches!(error.reason, YAMLParseErrorReason::TabIndentation));
+    assert_eq!((error.line, error.column), (3, 1));
+    // tabs after the indentation are allowed
+    assert_eq!(
+        try_values("a: x\ty\nb:\t1\n", &options).unwrap(),
+        ["a = x\ty", "b = 1"]
+    );
+    // strict mode denies tabs whatever the policy is
+    let strict = ParseOptions::new().tabs(TabPolicy::Allow).strict(true);
+    let error = try_values("a:\n\tb: 1\n", &strict).unwrap_err();
+    assert!(matches!(error.reason, YAMLParseErrorReason::TabIndentation));
+}
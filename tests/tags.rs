@@ -0,0 +1,61 @@
+//! Core schema tags and custom tags (`RootYAMLValue::Tagged`)
+
+use simple_yaml_parser::{format_key_chain, parse, RootYAMLValue};
+
+fn values(on: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    parse(on, |keys, value| {
+        values.push(format!("{} = {value:?}", format_key_chain(keys)));
+    })
+    .unwrap();
+    values
+}
+
+#[test]
+fn core_schema_tags() {
+    let on = "a: !!str 12\nb: !!int 12\nc: !!float 1.5\nd: !!bool true\ne: !!null null\n";
+    assert_eq!(
+        values(on),
+        [
+            "a = String(\"12\")",
+            "b = Number(\"12\")",
+            "c = Number(\"1.5\")",
+            "d = True",
+            "e = Null"
+        ]
+    );
+    // quoted values stay quoted
+    let mut value = None;
+    parse("a: !!str \"x\"\n", |_, found| value = Some(found)).unwrap();
+    assert!(matches!(value, Some(RootYAMLValue::QuotedString(_))));
+}
+
+#[test]
+fn custom_tags() {
+    let on = "BucketName: !Ref MyBucket\nArn: !GetAtt Bucket.Arn\ndata: !!binary aGk=\n";
+    let mut tagged = Vec::new();
+    parse(on, |_, value| {
+        let RootYAMLValue::Tagged(value) = value else {
+            panic!("{value:?}");
+        };
+        tagged.push((value.tag(), value.value()));
+    })
+    .unwrap();
+    assert_eq!(
+        tagged,
+        [
+            ("!Ref", "MyBucket"),
+            ("!GetAtt", "Bucket.Arn"),
+            ("!!binary", "aGk=")
+        ]
+    );
+}
+
+#[test]
+fn tags_on_collections() {
+    // tags on collections are not passed on
+    assert_eq!(
+        values("a: !!map\n  b: 1\nc: !custom [d]\n"),
+        ["a.b = Number(\"1\")", "c[0] = String(\"d\")"]
+    );
+}
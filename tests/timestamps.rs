@@ -0,0 +1,50 @@
+//! `ParseOptions::detect_timestamps`
+
+use simple_yaml_parser::{parse_with_exit_signal, ParseOptions, RootYAMLValue};
+
+fn value(on: &str, detect_timestamps: bool) -> RootYAMLValue<'_> {
+    let options = ParseOptions::new().detect_timestamps(detect_timestamps);
+    let mut first = None;
+    parse_with_exit_signal(
+        on,
+        |_, value| {
+            first = Some(value);
+            true
+        },
+        &options,
+    )
+    .unwrap();
+    first.unwrap()
+}
+
+#[test]
+fn timestamps() {
+    for timestamp in [
+        "2001-12-14",
+        "2001-12-14t21:59:43.10-05:00",
+        "2001-12-14T21:59:43Z",
+        "2001-12-14 21:59:43.10 -5",
+        "2001-12-14T21:59:43+05:30",
+        "2001-1-1T1:00:00",
+    ] {
+        let on = format!("a: {timestamp}\n");
+        assert_eq!(value(&on, true), RootYAMLValue::Timestamp(timestamp));
+        assert_eq!(value(&on, false), RootYAMLValue::String(timestamp));
+    }
+}
+
+#[test]
+fn not_timestamps() {
+    for on in [
+        "a: 2001-1-1",
+        "a: 2001-12-14x",
+        "a: 2001-12-14T21:59",
+        "a: 2001-12-14T21:59:43+",
+        "a: '2001-12-14'",
+    ] {
+        assert!(
+            !matches!(value(on, true), RootYAMLValue::Timestamp(_)),
+            "{on}"
+        );
+    }
+}
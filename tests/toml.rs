@@ -0,0 +1,147 @@
+//! Converting to TOML with `yaml_to_toml`
+
+use simple_yaml_parser::toml::{yaml_to_toml, ConvertError};
+
+#[test]
+fn tables_and_arrays() {
+    let on = "title: example
+owner:
+  name: Tom
+  dob: 1979-05-27
+database:
+  ports: [8000, 8001]
+  limits: {cpu: 0.5, memory: 0x100}
+  enabled: true
+  connection:
+    timeout: .inf
+servers:
+  - name: alpha
+    ip: 10.0.0.1
+    roles:
+      - primary
+  - name: beta
+    ip: 10.0.0.2
+matrix:
+  - [1, 2]
+  - [a, {b: c}]
+";
+    assert_eq!(
+        yaml_to_toml(on).unwrap(),
+        r#"title = "example"
+matrix = [[1, 2], ["a", { b = "c" }]]
+
+[owner]
+name = "Tom"
+dob = "1979-05-27"
+
+[database]
+ports = [8000, 8001]
+enabled = true
+
+[database.limits]
+cpu = 0.5
+memory = 256
+
+[database.connection]
+timeout = inf
+
+[[servers]]
+name = "alpha"
+ip = "10.0.0.1"
+roles = ["primary"]
+
+[[servers]]
+name = "beta"
+ip = "10.0.0.2"
+"#
+    );
+}
+
+#[test]
+fn keys_and_strings() {
+    let on =
+        "'a key': \"line\\nbreak\"\nwith.dot:\n  quote: 'say \"hi\"'\nblock: |\n  one\n  two\n";
+    assert_eq!(
+        yaml_to_toml(on).unwrap(),
+        "\"a key\" = \"line\\nbreak\"\nblock = \"one\\ntwo\\n\"\n\n[\"with.dot\"]\nquote = \"say \\\"hi\\\"\"\n"
+    );
+}
+
+#[test]
+fn nested_arrays_of_tables() {
+    let on = "a:\n  b:\n    - c: 1\n      d:\n        e: 2\n      f:\n        - g: 3\n";
+    assert_eq!(
+        yaml_to_toml(on).unwrap(),
+        "[[a.b]]\nc = 1\n\n[a.b.d]\ne = 2\n\n[[a.b.f]]\ng = 3\n"
+    );
+}
+
+#[test]
+fn empty_collections() {
+    assert_eq!(yaml_to_toml("a: []\nb: {}\n").unwrap(), "a = []\n\n[b]\n");
+    assert_eq!(
+        yaml_to_toml("a:\n  b: {}\n  c: []\nd: [{}, []]\ne:\n  - {}\n").unwrap(),
+        "d = [{}, []]\n\n[a]\nc = []\n\n[a.b]\n\n[[e]]\n"
+    );
+    assert_eq!(yaml_to_toml("{}\n").unwrap(), "");
+}
+
+#[test]
+fn aliases() {
+    let on = "base: &base\n  x: 1\ncopy: *base\n";
+    assert_eq!(
+        yaml_to_toml(on).unwrap(),
+        "[base]\nx = 1\n\n[copy]\nx = 1\n"
+    );
+}
+
+#[test]
+fn errors() {
+    assert!(matches!(
+        yaml_to_toml("- a\n"),
+        Err(ConvertError::RootNotMapping)
+    ));
+    match yaml_to_toml("a:\n  b: [1, ~]\n") {
+        Err(ConvertError::Null(path)) => assert_eq!(path, "a.b[1]"),
+        result => panic!("{result:?}"),
+    }
+    match yaml_to_toml("a:\n  b: 1\n  b: 2\n") {
+        Err(ConvertError::DuplicateKey(path)) => assert_eq!(path, "a.b"),
+        result => panic!("{result:?}"),
+    }
+    assert!(matches!(yaml_to_toml("a: ]"), Err(ConvertError::Parse(_))));
+    assert_eq!(yaml_to_toml("").unwrap(), "");
+}
+
+#[test]
+fn out_of_range_integers() {
+    assert_eq!(
+        yaml_to_toml("a: 9223372036854775807\nb: -9223372036854775808\nc: 1e30\n").unwrap(),
+        "a = 9223372036854775807\nb = -9223372036854775808\nc = 1e30\n"
+    );
+    for on in [
+        "a: [9223372036854775808]\n",
+        "a: [-9223372036854775809]\n",
+        "a: [0xffffffffffffffff]\n",
+    ] {
+        match yaml_to_toml(on) {
+            Err(ConvertError::NumberOutOfRange(path)) => assert_eq!(path, "a[0]"),
+            result => panic!("{on:?} {result:?}"),
+        }
+    }
+}
+
+#[test]
+fn custom_tags() {
+    match yaml_to_toml("a:\n  b: !color red\n") {
+        Err(ConvertError::Tagged { tag, path }) => {
+            assert_eq!(tag, "!color");
+            assert_eq!(path, "a.b");
+        }
+        result => panic!("{result:?}"),
+    }
+    assert_eq!(
+        yaml_to_toml("a: !!str 1\nb: !!int 2\n").unwrap(),
+        "a = \"1\"\nb = 2\n"
+    );
+}
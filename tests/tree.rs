@@ -0,0 +1,106 @@
+//! `parse_to_tree` and duplicate keys
+#![cfg(feature = "tree")]
+
+use simple_yaml_parser::{
+    parse_key_path, parse_to_tree, parse_to_tree_with_policy, parse_with_sink, DuplicateKeyPolicy,
+    ParseOptions, RootYAMLValue, YAMLNode,
+};
+
+const DUPLICATES: &str = "name: first
+server:
+  host: a
+  port: 1
+other: x
+name: second
+server:
+  host: b
+";
+
+fn keys<'a>(node: &YAMLNode<'a>) -> Vec<&'a str> {
+    match node {
+        YAMLNode::Map(entries) => entries.iter().map(|(key, _)| *key).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn scalar<'a>(node: &'a YAMLNode<'_>, path: &str) -> Option<&'a str> {
+    let mut node = node;
+    for key in path.split('.') {
+        node = node.get(key)?;
+    }
+    match node.as_scalar()? {
+        RootYAMLValue::String(on) | RootYAMLValue::Number(on) => Some(on),
+        _ => None,
+    }
+}
+
+#[test]
+fn last_wins() {
+    let tree = parse_to_tree(DUPLICATES).unwrap();
+    assert_eq!(keys(&tree), ["name", "server", "other"]);
+    assert_eq!(scalar(&tree, "name"), Some("second"));
+    // the whole mapping is replaced, rather than merged
+    assert_eq!(keys(tree.get("server").unwrap()), ["host"]);
+    assert_eq!(scalar(&tree, "server.host"), Some("b"));
+}
+
+#[test]
+fn first_wins() {
+    let tree = parse_to_tree_with_policy(DUPLICATES, DuplicateKeyPolicy::FirstWins).unwrap();
+    assert_eq!(keys(&tree), ["name", "server", "other"]);
+    assert_eq!(scalar(&tree, "name"), Some("first"));
+    assert_eq!(keys(tree.get("server").unwrap()), ["host", "port"]);
+    assert_eq!(scalar(&tree, "server.host"), Some("a"));
+}
+
+#[test]
+fn duplicates_in_sequence_items() {
+    let on = "items:\n  - a: 1\n    a: 2\n  - a: 3\n";
+    let item = |tree: &YAMLNode<'_>, index| {
+        let item = tree.get("items").and_then(|items| items.get_index(index));
+        item.and_then(|item| scalar(item, "a")).map(str::to_owned)
+    };
+    let first = parse_to_tree_with_policy(on, DuplicateKeyPolicy::FirstWins).unwrap();
+    assert_eq!(item(&first, 0).as_deref(), Some("1"));
+    assert_eq!(item(&first, 1).as_deref(), Some("3"));
+    let last = parse_to_tree(on).unwrap();
+    assert_eq!(item(&last, 0).as_deref(), Some("2"));
+    assert_eq!(item(&last, 1).as_deref(), Some("3"));
+}
+
+#[test]
+fn as_sink() {
+    let mut tree = YAMLNode::Map(Vec::new());
+    parse_with_sink(DUPLICATES, &mut tree, &ParseOptions::default()).unwrap();
+    assert_eq!(tree, parse_to_tree(DUPLICATES).unwrap());
+}
+
+#[test]
+fn navigation() {
+    let on = "name: app\nserver:\n  ports: [80, 443]\n  hosts:\n    - name: a\n    - name: b\n";
+    let tree = parse_to_tree(on).unwrap();
+    assert_eq!(keys(&tree), ["name", "server"]);
+    let ports = tree.get("server").and_then(|server| server.get("ports"));
+    assert!(matches!(ports, Some(YAMLNode::Sequence(items)) if items.len() == 2));
+    assert_eq!(
+        ports.and_then(|ports| ports.get_index(1)),
+        Some(&YAMLNode::Scalar(RootYAMLValue::Number("443")))
+    );
+    let host = tree.get_path(&parse_key_path("server.hosts[1].name"));
+    assert_eq!(
+        host.and_then(YAMLNode::as_scalar),
+        Some(&RootYAMLValue::String("b"))
+    );
+    assert_eq!(tree.get_path(&parse_key_path("server.hosts[2]")), None);
+    // scalars and sequences have no keys
+    assert_eq!(tree.get("name").and_then(|name| name.get("x")), None);
+    assert_eq!(tree.get_index(0), None);
+}
+
+#[test]
+fn to_yaml_string() {
+    let on = "name: app\nports:\n  - 80\n  - 443\nserver:\n  host: a\n";
+    let tree = parse_to_tree(on).unwrap();
+    let written = tree.to_yaml_string();
+    assert_eq!(parse_to_tree(&written).unwrap(), tree);
+}
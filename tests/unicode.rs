@@ -0,0 +1,70 @@
+//! Non-ASCII keys and values. Only spaces, tabs and line breaks are whitespace in YAML
+
+mod common;
+
+use common::values;
+use simple_yaml_parser::{parse, trim_whitespace};
+
+#[test]
+fn cjk_keys() {
+    let on = "名前: 値\nリスト:\n  - 一\n  - キー: 値\n    別: \"引用\"\n";
+    assert_eq!(
+        values(on),
+        [
+            "名前 = 値",
+            "リスト[0] = 一",
+            "リスト[1].キー = 値",
+            "リスト[1].別 = 引用"
+        ]
+    );
+}
+
+#[test]
+fn emoji_values() {
+    let on = "crab: 🦀🎉\nfamily: 👩\u{200d}👩\u{200d}👧 # comment\nflow: [🦀, 🎉]\n";
+    assert_eq!(
+        values(on),
+        [
+            "crab = 🦀🎉",
+            "family = 👩\u{200d}👩\u{200d}👧",
+            "flow[0] = 🦀",
+            "flow[1] = 🎉"
+        ]
+    );
+}
+
+#[test]
+fn combining_characters() {
+    let on = "cafe\u{301}: e\u{301}\n";
+    assert_eq!(values(on), ["cafe\u{301} = e\u{301}"]);
+}
+
+#[test]
+fn unicode_whitespace_is_content() {
+    // a non-breaking space at the end of a value and an ideographic space at the start of a line
+    let on = "nbsp: x\u{a0}\nwide:\n\u{3000}key: value\n";
+    assert_eq!(
+        values(on),
+        ["nbsp = x\u{a0}", "wide = ", "\u{3000}key = value"]
+    );
+}
+
+#[test]
+fn error_column_counts_characters() {
+    let error = parse("キー: {a 1}\n", |_, _| {}).unwrap_err();
+    // the missing `:` before `}`
+    assert_eq!((error.line, error.column), (1, 9));
+    assert_eq!(error.at, "キー: {a 1".len());
+}
+
+#[test]
+fn trim() {
+    assert_eq!(trim_whitespace("  value\t\r\n"), "value");
+    assert_eq!(
+        trim_whitespace("\u{a0}value\u{3000}"),
+        "\u{a0}value\u{3000}"
+    );
+    // the space and combining acute accent are one grapheme
+    assert_eq!(trim_whitespace("  \u{301}x "), " \u{301}x");
+    assert_eq!(trim_whitespace(" \t "), "");
+}
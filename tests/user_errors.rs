@@ -0,0 +1,68 @@
+//! `try_parse_with_exit_signal` with callbacks that return errors
+
+use simple_yaml_parser::{
+    try_parse_with_exit_signal, ParseOptions, ParseOrUserError, RootYAMLValue,
+};
+
+/// Fails on values that are not numbers
+fn numbers(on: &str) -> (Vec<i64>, Result<(), ParseOrUserError<String>>) {
+    let mut numbers = Vec::new();
+    let result = try_parse_with_exit_signal(
+        on,
+        |_, value| match value.as_i64() {
+            Some(number) => {
+                numbers.push(number);
+                Ok(false)
+            }
+            None => Err(format!("{value:?} is not a number")),
+        },
+        &ParseOptions::default(),
+    );
+    (numbers, result)
+}
+
+#[test]
+fn user_error() {
+    let on = "a: 1\nb: two\nc: 3\n";
+    let (numbers, result) = numbers(on);
+    // parsing stops at the error
+    assert_eq!(numbers, [1]);
+    let Err(ParseOrUserError::User { error, span }) = result else {
+        panic!("{result:?}");
+    };
+    assert_eq!(error, "String(\"two\") is not a number");
+    assert_eq!(&on[span.clone()], "two");
+    assert_eq!(
+        ParseOrUserError::User { error, span }.to_string(),
+        "String(\"two\") is not a number (at 8..11)"
+    );
+}
+
+#[test]
+fn parse_error() {
+    let (numbers, result) = numbers("a: 1\nb: [2\n");
+    assert_eq!(numbers, [1, 2]);
+    let Err(ParseOrUserError::Parse(error)) = result else {
+        panic!("{result:?}");
+    };
+    assert_eq!((error.line, error.column), (2, 6));
+}
+
+#[test]
+fn exit() {
+    let mut values = Vec::new();
+    let result: Result<(), ParseOrUserError<()>> = try_parse_with_exit_signal(
+        "a: 1\nb: 2\nc: [\n",
+        |_, value| {
+            values.push(value);
+            Ok(values.len() == 2)
+        },
+        &ParseOptions::default(),
+    );
+    // the invalid input after the exit is not parsed
+    assert!(result.is_ok());
+    assert_eq!(
+        values,
+        [RootYAMLValue::Number("1"), RootYAMLValue::Number("2")]
+    );
+}
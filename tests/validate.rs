@@ -0,0 +1,61 @@
+//! `validate`
+
+use simple_yaml_parser::{validate, ParseOptions, Rule, ValueType, ViolationKind};
+
+const SOURCE: &str =
+    "server:\n  port: \"80\"\n  host: example.com\n  url: http://example.com\ntags: [a, b]\n";
+
+fn violations(rules: &[Rule<'_>]) -> Vec<String> {
+    validate(SOURCE, rules, &ParseOptions::default())
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn types() {
+    assert_eq!(
+        violations(&[
+            Rule::required("server.port", ValueType::Number),
+            Rule::required("server.host", ValueType::String),
+            Rule::required("server", ValueType::Mapping),
+            Rule::required("tags", ValueType::Mapping),
+            Rule::optional("tags[1]", ValueType::Any),
+        ]),
+        [
+            "server.port: expected Number, found String",
+            "tags: expected Mapping, found Sequence"
+        ]
+    );
+}
+
+#[test]
+fn missing() {
+    assert_eq!(
+        violations(&[
+            Rule::required("server.timeout", ValueType::Number),
+            Rule::optional("server.retries", ValueType::Number),
+            Rule::required("tags[2]", ValueType::String),
+        ]),
+        ["server.timeout: missing", "tags[2]: missing"]
+    );
+}
+
+#[test]
+fn constraints() {
+    let rules = [
+        Rule::required("server.url", ValueType::String)
+            .matching("must use https", |on| on.starts_with("https://")),
+        Rule::required("server.port", ValueType::String)
+            .matching("must be a port", |on| on.parse::<u16>().is_ok()),
+    ];
+    let violations = validate(SOURCE, &rules, &ParseOptions::default()).unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].kind,
+        ViolationKind::Constraint("must use https")
+    );
+    let span = violations[0].span.clone().unwrap();
+    assert_eq!(&SOURCE[span], "http://example.com");
+}
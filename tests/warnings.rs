@@ -0,0 +1,114 @@
+//! `parse_with_warnings`
+
+use simple_yaml_parser::{parse_with_warnings, ParseOptions, TabPolicy, YAMLWarningKind};
+
+fn warnings(on: &str, tabs: TabPolicy) -> Vec<(YAMLWarningKind, usize, usize)> {
+    let mut warnings = Vec::new();
+    parse_with_warnings(
+        on,
+        |_, _| false,
+        |warning| warnings.push((warning.kind, warning.line, warning.column)),
+        &ParseOptions::new().tabs(tabs),
+    )
+    .unwrap();
+    warnings
+}
+
+#[test]
+fn clean() {
+    let on = "a:\n  b: 1\n  c:\n    - x\n    - y: 2\n      z: 3\nd: [1, 2]\n";
+    assert_eq!(warnings(on, TabPolicy::default()), []);
+}
+
+#[test]
+fn trailing_whitespace() {
+    let on = "a: 1  \r\nb: |\n  text\t\nc: 3";
+    assert_eq!(
+        warnings(on, TabPolicy::default()),
+        [
+            (YAMLWarningKind::TrailingWhitespace, 1, 5),
+            (YAMLWarningKind::TrailingWhitespace, 3, 7),
+        ]
+    );
+}
+
+#[test]
+fn tabs() {
+    assert_eq!(
+        warnings("a:\n\tb: 1\n  c: \"\t\"\n", TabPolicy::TreatAsSpaces(2)),
+        [(YAMLWarningKind::TabIndentation, 2, 1)]
+    );
+}
+
+#[test]
+fn inconsistent_indentation() {
+    let on = "a:\n  b:\n      c: 1\nd:\n  - 1\ne:\n   f: 2\n";
+    assert_eq!(
+        warnings(on, TabPolicy::default()),
+        [
+            (
+                YAMLWarningKind::InconsistentIndentation {
+                    expected: 2,
+                    found: 4
+                },
+                3,
+                1
+            ),
+            (
+                YAMLWarningKind::InconsistentIndentation {
+                    expected: 2,
+                    found: 3
+                },
+                7,
+                1
+            ),
+        ]
+    );
+}
+
+#[test]
+fn duplicate_keys() {
+    let on = "a: 1\nb:\n  a: 2\na: 3\n";
+    assert_eq!(
+        warnings(on, TabPolicy::default()),
+        [(
+            YAMLWarningKind::DuplicateKey {
+                key: "a".to_owned(),
+                first_at: 0
+            },
+            4,
+            1
+        )]
+    );
+}
+
+#[test]
+fn duplicate_keys_are_still_emitted() {
+    let mut values = 0;
+    let mut warnings = 0;
+    parse_with_warnings(
+        "a: 1\na: 2\n",
+        |_, _| {
+            values += 1;
+            false
+        },
+        |_| warnings += 1,
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!((values, warnings), (2, 1));
+}
+
+#[test]
+fn exit() {
+    let mut warnings = 0;
+    parse_with_warnings(
+        "a: 1 \nb: 2 \n",
+        |_, _| true,
+        |_| warnings += 1,
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    // trailing whitespace is found before parsing
+    assert_eq!(warnings, 2);
+}
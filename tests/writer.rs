@@ -0,0 +1,65 @@
+//! The text written by `YAMLWriter`
+
+use simple_yaml_parser::{parse, parse_key_path, RootYAMLValue, YAMLWriter};
+
+fn written(events: &[(&str, RootYAMLValue<'_>)], indent_size: usize) -> String {
+    let mut writer = YAMLWriter::with_indent_size(indent_size);
+    for (path, value) in events {
+        writer.write(&parse_key_path(path), value);
+    }
+    writer.finish()
+}
+
+#[test]
+fn nesting() {
+    let events = [
+        ("name", RootYAMLValue::String("app")),
+        ("server.host", RootYAMLValue::String("localhost")),
+        ("server.ports[0]", RootYAMLValue::Number("80")),
+        ("server.ports[1]", RootYAMLValue::Number("443")),
+        ("users[0].name", RootYAMLValue::String("a")),
+        ("users[0].admin", RootYAMLValue::True),
+        ("users[1].name", RootYAMLValue::Null),
+        ("matrix[0][0]", RootYAMLValue::Number("1")),
+    ];
+    assert_eq!(
+        written(&events, 2),
+        "name: app\nserver:\n  host: localhost\n  ports:\n    - 80\n    - 443\nusers:\n  - name: a\n    admin: true\n  - name: null\nmatrix:\n  - - 1\n"
+    );
+    assert_eq!(
+        written(&events[..3], 4),
+        "name: app\nserver:\n    host: localhost\n    ports:\n        - 80\n"
+    );
+}
+
+#[test]
+fn quoting() {
+    let events = [
+        ("a", RootYAMLValue::String("true")),
+        ("b", RootYAMLValue::String("12")),
+        ("c", RootYAMLValue::String("x: y")),
+        ("d", RootYAMLValue::String("# z")),
+        ("e", RootYAMLValue::String("")),
+        ("f", RootYAMLValue::String("plain text")),
+    ];
+    let text = written(&events, 2);
+    assert_eq!(
+        text,
+        "a: \"true\"\nb: \"12\"\nc: \"x: y\"\nd: \"# z\"\ne: \"\"\nf: plain text\n"
+    );
+    // and parse back to strings
+    let mut values = Vec::new();
+    parse(&text, |_, value| {
+        values.push(value.as_str().unwrap().into_owned());
+    })
+    .unwrap();
+    assert_eq!(values, ["true", "12", "x: y", "# z", "", "plain text"]);
+}
+
+#[test]
+fn block_scalars() {
+    let on = "text: |\n  line 1\n  line 2\nfolded: >-\n  a\n  b\n";
+    let mut writer = YAMLWriter::new();
+    parse(on, |keys, value| writer.write(keys, &value)).unwrap();
+    assert_eq!(writer.finish(), on);
+}
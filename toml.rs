@@ -0,0 +1,306 @@
+//! Converting YAML to TOML
+use super::json::{json_number, write_string};
+use super::{
+    format_key_chain, parse_events, special_float, ParseOptions, RootYAMLValue, YAMLEvent, YAMLKey,
+    YAMLParseError,
+};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Error from [`yaml_to_toml`]
+#[derive(Debug)]
+pub enum ConvertError {
+    Parse(YAMLParseError),
+    /// The document is a sequence. TOML documents are tables
+    RootNotMapping,
+    /// A null value (TOML has no null), with the path to it
+    Null(String),
+    /// A key that appears twice in a mapping, with the path to it
+    DuplicateKey(String),
+    /// An integer outside of the range of TOML integers (`i64`), with the path to it
+    NumberOutOfRange(String),
+    /// A value with a tag that is not a core schema tag (`!!str`, `!!int` etc), which TOML has no
+    /// equivalent of
+    Tagged {
+        tag: String,
+        path: String,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConvertError {}
+
+impl core::fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            ConvertError::Parse(err) => core::fmt::Display::fmt(err, f),
+            ConvertError::RootNotMapping => f.write_str("document is not a mapping"),
+            ConvertError::Null(path) => write!(f, "null value at {path}"),
+            ConvertError::DuplicateKey(path) => write!(f, "duplicate key {path}"),
+            ConvertError::NumberOutOfRange(path) => write!(f, "number out of range at {path}"),
+            ConvertError::Tagged { tag, path } => write!(f, "value with tag {tag} at {path}"),
+        }
+    }
+}
+
+impl From<YAMLParseError> for ConvertError {
+    fn from(err: YAMLParseError) -> Self {
+        ConvertError::Parse(err)
+    }
+}
+
+/// A value, with scalars already in TOML syntax
+enum Value<'a> {
+    Table(Vec<(&'a str, Value<'a>)>),
+    Array(Vec<Value<'a>>),
+    Scalar(String),
+}
+
+impl Value<'_> {
+    fn is_table(&self) -> bool {
+        matches!(self, Value::Table(_))
+    }
+
+    /// Non-empty arrays of tables are written as `[[path]]` sections
+    fn is_array_of_tables(&self) -> bool {
+        matches!(self, Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_table))
+    }
+}
+
+/// Converts YAML to TOML. Mappings (block or flow) become tables (`[a.b]`), sequences of mappings
+/// become arrays of tables (`[[a.b]]`) and other sequences become arrays. Aliases and merge keys are resolved.
+/// Timestamps are written as strings
+///
+/// A table's values have to be written before its sub-tables, so (unlike
+/// [`crate::json::yaml_to_json`]) values are collected before writing. Empty mappings are written as
+/// a header without values (`[a]`) and empty sequences as `a = []`
+///
+/// # Errors
+/// Returns an error for invalid YAML input and for what TOML cannot express: a document that is a
+/// sequence, null values, duplicate keys, integers outside of the `i64` range and values with
+/// custom tags
+pub fn yaml_to_toml(on: &str) -> Result<String, ConvertError> {
+    let options = ParseOptions::new()
+        .resolve_aliases(true)
+        .resolve_merge_keys(true);
+    // open collections, with the key for the next value of tables
+    let mut stack: Vec<(Value<'_>, Option<&str>)> = Vec::new();
+    let mut root = None;
+    let mut error = None;
+    parse_events(
+        on,
+        |event| {
+            let value = match event {
+                YAMLEvent::DocumentStart | YAMLEvent::DocumentEnd => return false,
+                YAMLEvent::MapStart => {
+                    stack.push((Value::Table(Vec::new()), None));
+                    return false;
+                }
+                YAMLEvent::SeqStart => {
+                    stack.push((Value::Array(Vec::new()), None));
+                    return false;
+                }
+                YAMLEvent::Key(key) => {
+                    if let Some((_, next_key)) = stack.last_mut() {
+                        *next_key = Some(key);
+                    }
+                    return false;
+                }
+                YAMLEvent::MapEnd | YAMLEvent::SeqEnd => match stack.pop() {
+                    Some((value, _)) => value,
+                    None => return false,
+                },
+                YAMLEvent::Scalar(RootYAMLValue::Null) => {
+                    error = Some(ConvertError::Null(path_of(&stack)));
+                    return true;
+                }
+                YAMLEvent::Scalar(RootYAMLValue::Tagged(tagged)) => {
+                    error = Some(ConvertError::Tagged {
+                        tag: tagged.tag().into(),
+                        path: path_of(&stack),
+                    });
+                    return true;
+                }
+                YAMLEvent::Scalar(value) => {
+                    if let Some(scalar) = toml_scalar(&value) {
+                        Value::Scalar(scalar)
+                    } else {
+                        error = Some(ConvertError::NumberOutOfRange(path_of(&stack)));
+                        return true;
+                    }
+                }
+            };
+            if let Some((Value::Table(entries), Some(key))) = stack.last() {
+                if entries.iter().any(|(existing, _)| existing == key) {
+                    error = Some(ConvertError::DuplicateKey(path_of(&stack)));
+                    return true;
+                }
+            }
+            match stack.last_mut() {
+                Some((Value::Table(entries), key)) => {
+                    entries.push((key.take().unwrap_or_default(), value));
+                }
+                Some((Value::Array(items), _)) => items.push(value),
+                Some((Value::Scalar(_), _)) => {}
+                None => root = Some(value),
+            }
+            false
+        },
+        &options,
+    )?;
+    if let Some(error) = error {
+        return Err(error);
+    }
+
+    let mut output = String::new();
+    match root {
+        Some(Value::Table(entries)) => write_table(&mut output, &mut Vec::new(), &entries, false),
+        Some(_) => return Err(ConvertError::RootNotMapping),
+        None => {}
+    }
+    Ok(output)
+}
+
+/// The path to the next value, for errors
+fn path_of<'a>(stack: &[(Value<'a>, Option<&'a str>)]) -> String {
+    let keys: Vec<YAMLKey<'a>> = stack
+        .iter()
+        .filter_map(|(value, key)| match value {
+            Value::Table(_) => key.map(YAMLKey::Slice),
+            Value::Array(items) => Some(YAMLKey::Index(items.len())),
+            Value::Scalar(_) => None,
+        })
+        .collect();
+    format_key_chain(&keys)
+}
+
+/// `None` for numbers that cannot be written in TOML
+fn toml_scalar(value: &RootYAMLValue<'_>) -> Option<String> {
+    let mut output = String::new();
+    match value {
+        RootYAMLValue::Number(on) => output.push_str(&toml_number(on)?),
+        RootYAMLValue::True => output.push_str("true"),
+        RootYAMLValue::False => output.push_str("false"),
+        RootYAMLValue::String(on) | RootYAMLValue::Timestamp(on) | RootYAMLValue::RawBlock(on) => {
+            write_string(&mut output, on);
+        }
+        RootYAMLValue::QuotedString(quoted) => write_string(&mut output, &quoted.decode()),
+        RootYAMLValue::MultilineString(multiline) => {
+            write_string(&mut output, &multiline.resolve());
+        }
+        RootYAMLValue::Interpolated(on) => write_string(&mut output, on),
+        // aliases are resolved and nulls and tagged values are errors
+        RootYAMLValue::Null | RootYAMLValue::Alias(_) | RootYAMLValue::Tagged(_) => {}
+    }
+    Some(output)
+}
+
+/// Numbers in JSON syntax are valid TOML, apart from infinity and NaN which TOML writes as
+/// `inf` and `nan`. TOML integers are `i64`, so `None` for integers outside of that range
+fn toml_number(on: &str) -> Option<Cow<'_, str>> {
+    match special_float(on) {
+        Some(value) if value.is_nan() => Some(Cow::Borrowed("nan")),
+        Some(value) if value.is_sign_negative() => Some(Cow::Borrowed("-inf")),
+        Some(_) => Some(Cow::Borrowed("inf")),
+        None => {
+            let number = json_number(on);
+            let unsigned = on.strip_prefix(['-', '+']).unwrap_or(on);
+            let is_integer = unsigned.starts_with("0x")
+                || unsigned.starts_with("0o")
+                || unsigned.chars().all(|chr| chr.is_ascii_digit());
+            let out_of_range = number == "null" || (is_integer && number.parse::<i64>().is_err());
+            (!out_of_range).then_some(number)
+        }
+    }
+}
+
+fn write_key(output: &mut String, key: &str) {
+    let is_bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|chr| chr.is_ascii_alphanumeric() || chr == '_' || chr == '-');
+    if is_bare {
+        output.push_str(key);
+    } else {
+        write_string(output, key);
+    }
+}
+
+fn write_header(output: &mut String, path: &[&str], brackets: &str) {
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output.push_str(brackets);
+    for (idx, key) in path.iter().enumerate() {
+        if idx > 0 {
+            output.push('.');
+        }
+        write_key(output, key);
+    }
+    output.push_str(&brackets.replace('[', "]"));
+    output.push('\n');
+}
+
+/// Writes the values of a table and then its sub-tables. `has_header` is whether a `[[path]]`
+/// header was written for it (otherwise a `[path]` header is written if it has values or is empty)
+fn write_table<'a>(
+    output: &mut String,
+    path: &mut Vec<&'a str>,
+    entries: &[(&'a str, Value<'a>)],
+    has_header: bool,
+) {
+    let is_inline = |value: &Value<'_>| !(value.is_table() || value.is_array_of_tables());
+    let needs_header = entries.is_empty() || entries.iter().any(|(_, value)| is_inline(value));
+    if !has_header && !path.is_empty() && needs_header {
+        write_header(output, path, "[");
+    }
+    for (key, value) in entries.iter().filter(|(_, value)| is_inline(value)) {
+        write_key(output, key);
+        output.push_str(" = ");
+        write_inline(output, value);
+        output.push('\n');
+    }
+    for (key, value) in entries.iter().filter(|(_, value)| !is_inline(value)) {
+        path.push(key);
+        match value {
+            Value::Table(entries) => write_table(output, path, entries, false),
+            Value::Array(items) => {
+                for item in items {
+                    if let Value::Table(entries) = item {
+                        write_header(output, path, "[[");
+                        write_table(output, path, entries, true);
+                    }
+                }
+            }
+            Value::Scalar(_) => {}
+        }
+        path.pop();
+    }
+}
+
+fn write_inline(output: &mut String, value: &Value<'_>) {
+    match value {
+        Value::Scalar(on) => output.push_str(on),
+        Value::Array(items) => {
+            output.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    output.push_str(", ");
+                }
+                write_inline(output, item);
+            }
+            output.push(']');
+        }
+        Value::Table(entries) => {
+            output.push('{');
+            for (idx, (key, value)) in entries.iter().enumerate() {
+                output.push_str(if idx > 0 { ", " } else { " " });
+                write_key(output, key);
+                output.push_str(" = ");
+                write_inline(output, value);
+            }
+            output.push_str(if entries.is_empty() { "}" } else { " }" });
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use super::{parse, RootYAMLValue, YAMLKey, YAMLParseError, YAMLSink, YAMLWriter};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::ControlFlow;
+
+/// Which value a [`YAMLNode::Map`] keeps for a key that appears more than once in a mapping. The
+/// entry stays at the position of the first occurrence. Duplicates can be reported with
+/// [`crate::parse_with_warnings`] or rejected with [`crate::ParseOptions::deny_duplicate_keys`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// The value of the last occurrence replaces the earlier ones (as in JSON parsers and most
+    /// YAML libraries)
+    #[default]
+    LastWins,
+    /// Later occurrences are ignored
+    FirstWins,
+}
+
+/// A navigable tree built from the parse events. Mappings keep document order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YAMLNode<'a> {
+    Map(Vec<(&'a str, YAMLNode<'a>)>),
+    Sequence(Vec<YAMLNode<'a>>),
+    Scalar(RootYAMLValue<'a>),
+}
+
+impl<'a> YAMLNode<'a> {
+    /// Value under `key` if this is a mapping
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&YAMLNode<'a>> {
+        if let YAMLNode::Map(entries) = self {
+            entries
+                .iter()
+                .find_map(|(k, value)| (*k == key).then_some(value))
+        } else {
+            None
+        }
+    }
+
+    /// Item at `index` if this is a sequence
+    #[must_use]
+    pub fn get_index(&self, index: usize) -> Option<&YAMLNode<'a>> {
+        if let YAMLNode::Sequence(items) = self {
+            items.get(index)
+        } else {
+            None
+        }
+    }
+
+    /// Follows a key chain from this node
+    #[must_use]
+    pub fn get_path(&self, keys: &[YAMLKey<'_>]) -> Option<&YAMLNode<'a>> {
+        keys.iter().try_fold(self, |node, key| match key {
+            YAMLKey::Slice(key) => node.get(key),
+            YAMLKey::Index(index) => node.get_index(*index),
+        })
+    }
+
+    #[must_use]
+    pub fn as_scalar(&self) -> Option<&RootYAMLValue<'a>> {
+        if let YAMLNode::Scalar(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Writes the tree as YAML text
+    #[must_use]
+    pub fn to_yaml_string(&self) -> String {
+        fn write<'a>(
+            node: &YAMLNode<'a>,
+            keys: &mut Vec<YAMLKey<'a>>,
+            writer: &mut YAMLWriter<'a>,
+        ) {
+            match node {
+                YAMLNode::Map(entries) if entries.is_empty() => writer.write_raw(keys, "{}"),
+                YAMLNode::Sequence(items) if items.is_empty() => writer.write_raw(keys, "[]"),
+                YAMLNode::Map(entries) => {
+                    for (key, value) in entries {
+                        keys.push(YAMLKey::Slice(key));
+                        write(value, keys, writer);
+                        keys.pop();
+                    }
+                }
+                YAMLNode::Sequence(items) => {
+                    for (index, item) in items.iter().enumerate() {
+                        keys.push(YAMLKey::Index(index));
+                        write(item, keys, writer);
+                        keys.pop();
+                    }
+                }
+                YAMLNode::Scalar(value) => writer.write(keys, value),
+            }
+        }
+
+        let mut writer = YAMLWriter::new();
+        write(self, &mut Vec::new(), &mut writer);
+        writer.finish()
+    }
+
+    /// Places `value` at `keys` (relative to this node), creating mappings and sequences as needed.
+    /// Keys are slices of the input, so occurrences of the same key are told apart by position
+    fn insert(
+        &mut self,
+        keys: &[YAMLKey<'a>],
+        value: RootYAMLValue<'a>,
+        duplicates: DuplicateKeyPolicy,
+    ) {
+        let Some((first, rest)) = keys.split_first() else {
+            *self = YAMLNode::Scalar(value);
+            return;
+        };
+        let child = match first {
+            YAMLKey::Slice(key) => {
+                if !matches!(self, YAMLNode::Map(_)) {
+                    *self = YAMLNode::Map(Vec::new());
+                }
+                let YAMLNode::Map(entries) = self else {
+                    unreachable!()
+                };
+                if let Some(position) = entries.iter().position(|(k, _)| k == key) {
+                    let entry = &mut entries[position];
+                    if !core::ptr::eq(entry.0, *key) {
+                        match duplicates {
+                            DuplicateKeyPolicy::LastWins => {
+                                *entry = (key, YAMLNode::empty_for(rest));
+                            }
+                            DuplicateKeyPolicy::FirstWins => return,
+                        }
+                    }
+                    &mut entry.1
+                } else {
+                    entries.push((key, YAMLNode::empty_for(rest)));
+                    &mut entries.last_mut().unwrap().1
+                }
+            }
+            YAMLKey::Index(index) => {
+                if !matches!(self, YAMLNode::Sequence(_)) {
+                    *self = YAMLNode::Sequence(Vec::new());
+                }
+                let YAMLNode::Sequence(items) = self else {
+                    unreachable!()
+                };
+                while items.len() <= *index {
+                    items.push(YAMLNode::empty_for(rest));
+                }
+                &mut items[*index]
+            }
+        };
+        child.insert(rest, value, duplicates);
+    }
+
+    fn empty_for(keys: &[YAMLKey<'_>]) -> Self {
+        match keys.first() {
+            Some(YAMLKey::Index(_)) => YAMLNode::Sequence(Vec::new()),
+            _ => YAMLNode::Map(Vec::new()),
+        }
+    }
+}
+
+/// Inserts each value (as [`parse_to_tree`] does, starting from an empty [`YAMLNode::Map`])
+impl<'a> YAMLSink<'a> for YAMLNode<'a> {
+    fn value(&mut self, keys: &[YAMLKey<'a>], value: RootYAMLValue<'a>) -> ControlFlow<()> {
+        self.insert(keys, value, DuplicateKeyPolicy::default());
+        ControlFlow::Continue(())
+    }
+}
+
+/// Parses the whole input into a [`YAMLNode`]. The root is always a [`YAMLNode::Map`]
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse_to_tree(on: &str) -> Result<YAMLNode<'_>, YAMLParseError> {
+    parse_to_tree_with_policy(on, DuplicateKeyPolicy::default())
+}
+
+/// [`parse_to_tree`] with a policy for keys that appear more than once in a mapping
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn parse_to_tree_with_policy(
+    on: &str,
+    duplicates: DuplicateKeyPolicy,
+) -> Result<YAMLNode<'_>, YAMLParseError> {
+    let mut root = YAMLNode::Map(Vec::new());
+    parse(on, |keys, value| root.insert(keys, value, duplicates))?;
+    Ok(root)
+}
@@ -0,0 +1,205 @@
+//! Checking documents against a list of rules
+use super::{
+    format_key_chain, parse_key_path, parse_with_spans, ParseOptions, RootYAMLValue, YAMLKey,
+    YAMLParseError,
+};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// The type of a value, for [`Rule`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    /// Any string (including quoted strings and block scalars) or tagged value
+    String,
+    Number,
+    Bool,
+    Null,
+    Timestamp,
+    Mapping,
+    Sequence,
+    Any,
+}
+
+impl ValueType {
+    /// `None` for aliases and raw blocks (which are not checked)
+    fn of(value: &RootYAMLValue<'_>) -> Option<Self> {
+        match value {
+            RootYAMLValue::String(_)
+            | RootYAMLValue::QuotedString(_)
+            | RootYAMLValue::MultilineString(_)
+            | RootYAMLValue::Interpolated(_)
+            | RootYAMLValue::Tagged(_) => Some(Self::String),
+            RootYAMLValue::Number(_) => Some(Self::Number),
+            RootYAMLValue::True | RootYAMLValue::False => Some(Self::Bool),
+            RootYAMLValue::Null => Some(Self::Null),
+            RootYAMLValue::Timestamp(_) => Some(Self::Timestamp),
+            RootYAMLValue::Alias(_) | RootYAMLValue::RawBlock(_) => None,
+        }
+    }
+}
+
+/// A check on the text of a value (as written for numbers, timestamps and booleans, decoded for strings)
+#[derive(Debug, Clone, Copy)]
+pub struct Constraint<'a> {
+    /// Shown in [`ViolationKind::Constraint`]
+    pub description: &'a str,
+    pub check: fn(&str) -> bool,
+}
+
+/// A rule for the value at a path (in the format of [`crate::parse_key_path`])
+#[derive(Debug, Clone)]
+pub struct Rule<'a> {
+    pub path: Vec<YAMLKey<'a>>,
+    pub required: bool,
+    pub value_type: ValueType,
+    pub constraint: Option<Constraint<'a>>,
+}
+
+impl<'a> Rule<'a> {
+    /// The value at `path` must exist and be of type `value_type`
+    #[must_use]
+    pub fn required(path: &'a str, value_type: ValueType) -> Self {
+        Self {
+            path: parse_key_path(path),
+            required: true,
+            value_type,
+            constraint: None,
+        }
+    }
+
+    /// If there is a value at `path` it must be of type `value_type`
+    #[must_use]
+    pub fn optional(path: &'a str, value_type: ValueType) -> Self {
+        Self {
+            required: false,
+            ..Self::required(path, value_type)
+        }
+    }
+
+    /// Adds a check on the text of the value (for example `|on| on.starts_with("https://")`)
+    #[must_use]
+    pub fn matching(mut self, description: &'a str, check: fn(&str) -> bool) -> Self {
+        self.constraint = Some(Constraint { description, check });
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViolationKind<'a> {
+    /// There is no value for a required rule
+    Missing,
+    WrongType {
+        expected: ValueType,
+        found: ValueType,
+    },
+    /// The value does not pass [`Rule::matching`]
+    Constraint(&'a str),
+}
+
+/// A rule that the document does not follow, from [`validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation<'a> {
+    /// The path of the rule
+    pub keys: Vec<YAMLKey<'a>>,
+    /// The span of the value (or the first entry of a collection). `None` for [`ViolationKind::Missing`]
+    pub span: Option<Range<usize>>,
+    pub kind: ViolationKind<'a>,
+}
+
+impl core::fmt::Display for Violation<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        let path = format_key_chain(&self.keys);
+        match &self.kind {
+            ViolationKind::Missing => f.write_fmt(format_args!("{path}: missing")),
+            ViolationKind::WrongType { expected, found } => f.write_fmt(format_args!(
+                "{path}: expected {expected:?}, found {found:?}"
+            )),
+            ViolationKind::Constraint(description) => {
+                f.write_fmt(format_args!("{path}: {description}"))
+            }
+        }
+    }
+}
+
+/// Checks `on` against `rules` in one pass, returning violations in the order of the document (followed
+/// by missing values in the order of `rules`). A path is a mapping or sequence if there are values nested
+/// under it. Aliases are not checked (unless `options` resolves them)
+///
+/// # Errors
+/// Returns an error if it tries to parse invalid YAML input
+pub fn validate<'r>(
+    on: &str,
+    rules: &[Rule<'r>],
+    options: &ParseOptions,
+) -> Result<Vec<Violation<'r>>, YAMLParseError> {
+    let mut found = alloc::vec![false; rules.len()];
+    let mut violations = Vec::new();
+    parse_with_spans(
+        on,
+        |keys, value, spans| {
+            for (rule, found) in rules.iter().zip(found.iter_mut()) {
+                let (value_type, text) = if keys == rule.path.as_slice() {
+                    (ValueType::of(&value), text(&value))
+                } else if !*found && keys.starts_with(&rule.path) {
+                    // the first entry nested under the path
+                    match keys[rule.path.len()] {
+                        YAMLKey::Slice(_) => (Some(ValueType::Mapping), None),
+                        YAMLKey::Index(_) => (Some(ValueType::Sequence), None),
+                    }
+                } else {
+                    continue;
+                };
+                *found = true;
+                let violation = |kind| Violation {
+                    keys: rule.path.clone(),
+                    span: Some(spans.value.clone()),
+                    kind,
+                };
+                match value_type {
+                    Some(value_type)
+                        if rule.value_type != ValueType::Any && value_type != rule.value_type =>
+                    {
+                        violations.push(violation(ViolationKind::WrongType {
+                            expected: rule.value_type,
+                            found: value_type,
+                        }));
+                    }
+                    _ => {
+                        if let (Some(constraint), Some(text)) = (&rule.constraint, text) {
+                            if !(constraint.check)(&text) {
+                                violations.push(violation(ViolationKind::Constraint(
+                                    constraint.description,
+                                )));
+                            }
+                        }
+                    }
+                }
+            }
+            false
+        },
+        options,
+    )?;
+    violations.extend(
+        rules
+            .iter()
+            .zip(found)
+            .filter(|(rule, found)| rule.required && !found)
+            .map(|(rule, _)| Violation {
+                keys: rule.path.clone(),
+                span: None,
+                kind: ViolationKind::Missing,
+            }),
+    );
+    Ok(violations)
+}
+
+fn text<'a>(value: &RootYAMLValue<'a>) -> Option<Cow<'a, str>> {
+    match value {
+        RootYAMLValue::Number(on) | RootYAMLValue::Timestamp(on) => Some(Cow::Borrowed(on)),
+        RootYAMLValue::True => Some(Cow::Borrowed("true")),
+        RootYAMLValue::False => Some(Cow::Borrowed("false")),
+        RootYAMLValue::Tagged(tagged) => Some(Cow::Borrowed(tagged.value())),
+        value => value.as_str(),
+    }
+}
@@ -0,0 +1,65 @@
+//! Non-fatal findings passed to the callback of [`crate::parse_with_warnings`]
+use super::line_and_column;
+use alloc::string::String;
+use core::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YAMLWarningKind {
+    /// A tab in indentation, when [`crate::ParseOptions::tabs`] allows them
+    TabIndentation,
+    /// Spaces or tabs at the end of a line
+    TrailingWhitespace,
+    /// A nested block entry indented by a different number of columns than the first nested entry
+    /// in the document
+    InconsistentIndentation { expected: usize, found: usize },
+    /// A key that already appeared in the same mapping, when [`crate::ParseOptions::deny_duplicate_keys`]
+    /// is not set. `first_at` is the byte offset of the first occurrence
+    DuplicateKey { key: String, first_at: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YAMLWarning {
+    /// Byte offset of the warning (same as `span.start`)
+    pub at: usize,
+    /// 1-based line of `at`
+    pub line: usize,
+    /// 1-based column (in characters) of `at`
+    pub column: usize,
+    pub span: Range<usize>,
+    pub kind: YAMLWarningKind,
+}
+
+impl core::fmt::Display for YAMLWarning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        f.write_fmt(format_args!(
+            "YAMLWarning: {:?} at {}:{}",
+            self.kind, self.line, self.column
+        ))
+    }
+}
+
+/// A warning for `span` of `on`
+pub(crate) fn new(on: &str, kind: YAMLWarningKind, span: Range<usize>) -> YAMLWarning {
+    let (line, column) = line_and_column(on, span.start);
+    YAMLWarning {
+        at: span.start,
+        line,
+        column,
+        span,
+        kind,
+    }
+}
+
+/// Calls `warn` for whitespace at the end of each line of `on`
+pub(crate) fn trailing_whitespace(on: &str, mut warn: impl FnMut(YAMLWarningKind, Range<usize>)) {
+    let mut line_start = 0;
+    for raw_line in on.split('\n') {
+        let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+        let trimmed = line.trim_end_matches([' ', '\t']).len();
+        if trimmed < line.len() {
+            let span = line_start + trimmed..line_start + line.len();
+            warn(YAMLWarningKind::TrailingWhitespace, span);
+        }
+        line_start += raw_line.len() + '\n'.len_utf8();
+    }
+}
@@ -0,0 +1,411 @@
+use super::{
+    is_number, is_yaml_whitespace, quoted_end, split_comment, trim_whitespace, BlockStyle,
+    BoolStyle, Chomping, MultilineString, RootYAMLValue, YAMLKey, YAMLSink,
+};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::Write;
+use core::ops::ControlFlow;
+
+/// The order of the keys in each mapping. Sequence items keep their order
+#[derive(Debug, Clone, Copy, Default)]
+pub enum SortKeys {
+    /// As written
+    #[default]
+    Preserve,
+    /// By comparing keys as strings
+    Alphabetical,
+    By(fn(&str, &str) -> Ordering),
+}
+
+impl SortKeys {
+    /// Compares the key chains of two events. Sorting events with this (using a stable sort)
+    /// before passing them to [`YAMLWriter`] sorts each mapping
+    #[must_use]
+    pub fn compare(&self, a: &[YAMLKey<'_>], b: &[YAMLKey<'_>]) -> Ordering {
+        for (a, b) in a.iter().zip(b) {
+            let ordering = match (self, a, b) {
+                (SortKeys::Preserve, ..) => return Ordering::Equal,
+                (SortKeys::By(compare), YAMLKey::Slice(a), YAMLKey::Slice(b)) => compare(a, b),
+                (_, a, b) => a.cmp(b),
+            };
+            if ordering.is_ne() {
+                return ordering;
+            }
+        }
+        a.len().cmp(&b.len())
+    }
+}
+
+/// Builds YAML text from `(keys, value)` events, as given by [`crate::parse`]. Events should be in
+/// document order (keys under the same parent should be written together)
+pub struct YAMLWriter<'a> {
+    output: String,
+    previous: Vec<YAMLKey<'a>>,
+    indent_size: usize,
+    /// Whether keys are already quoted (if needed), rather than quoted by the writer
+    keys_as_written: bool,
+}
+
+impl Default for YAMLWriter<'_> {
+    fn default() -> Self {
+        Self::with_indent_size(2)
+    }
+}
+
+impl<'a> YAMLWriter<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_indent_size(indent_size: usize) -> Self {
+        Self {
+            output: String::new(),
+            previous: Vec::new(),
+            indent_size,
+            keys_as_written: false,
+        }
+    }
+
+    /// Writes keys as they are given, for keys that are already quoted where they need to be
+    pub(crate) fn keys_as_written(mut self) -> Self {
+        self.keys_as_written = true;
+        self
+    }
+
+    pub fn write(&mut self, keys: &[YAMLKey<'a>], value: &RootYAMLValue<'_>) {
+        let column = self.write_keys(keys);
+        self.write_value(value, column);
+    }
+
+    /// Writes `&anchor` and `!tag` `properties` (as written) and then `value`
+    pub(crate) fn write_with_properties(
+        &mut self,
+        keys: &[YAMLKey<'a>],
+        properties: &str,
+        value: &RootYAMLValue<'_>,
+    ) {
+        let column = self.write_keys(keys);
+        self.output.push_str(properties);
+        self.output.push(' ');
+        self.write_value(value, column);
+    }
+
+    /// Writes `&anchor` and `!tag` `properties` (as written) for the collection under `keys`,
+    /// before its entries
+    pub(crate) fn write_collection_properties(&mut self, keys: &[YAMLKey<'a>], properties: &str) {
+        self.write_keys(keys);
+        self.output.push_str(properties);
+        self.output.push('\n');
+    }
+
+    /// Writes `value` after its keys, which are at `column`
+    fn write_value(&mut self, value: &RootYAMLValue<'_>, column: usize) {
+        match value {
+            RootYAMLValue::String(on) => write_string(&mut self.output, on),
+            RootYAMLValue::Interpolated(on) => write_string(&mut self.output, on),
+            RootYAMLValue::QuotedString(quoted) => {
+                let quote = if quoted.is_double_quoted() { '"' } else { '\'' };
+                self.output.push(quote);
+                self.output.push_str(quoted.raw());
+                self.output.push(quote);
+            }
+            RootYAMLValue::MultilineString(multiline) => {
+                self.write_block_scalar(multiline, column + self.indent_size);
+                return;
+            }
+            RootYAMLValue::Number(on) | RootYAMLValue::Timestamp(on) => self.output.push_str(on),
+            RootYAMLValue::True => self.output.push_str("true"),
+            RootYAMLValue::False => self.output.push_str("false"),
+            RootYAMLValue::Null => self.output.push_str("null"),
+            RootYAMLValue::Alias(name) => {
+                self.output.push('*');
+                self.output.push_str(name);
+            }
+            RootYAMLValue::Tagged(tagged) => {
+                self.output.push_str(tagged.tag());
+                self.output.push(' ');
+                self.output.push_str(tagged.value());
+            }
+            RootYAMLValue::RawBlock(raw) => {
+                self.write_raw_block(raw, column + self.indent_size);
+                return;
+            }
+        }
+        self.output.push('\n');
+    }
+
+    /// Writes `value` as is (for example `{}` or `[]` for empty collections)
+    pub fn write_raw(&mut self, keys: &[YAMLKey<'a>], value: &str) {
+        self.write_keys(keys);
+        self.output.push_str(value);
+        self.output.push('\n');
+    }
+
+    #[must_use]
+    pub fn finish(self) -> String {
+        self.output
+    }
+
+    /// Writes a `#` comment line (`comment` is the text after the `#`) indented for the last of
+    /// `keys`. The keys before it are written first if they are not already
+    pub(crate) fn write_comment(&mut self, keys: &[YAMLKey<'a>], comment: &str) {
+        let parents = &keys[..keys.len().saturating_sub(1)];
+        let shared = parents
+            .iter()
+            .zip(&self.previous)
+            .take_while(|(key, previous)| key == previous)
+            .count();
+        let mut column = self.column(&parents[..shared]);
+        for key in &parents[shared..] {
+            self.output.extend(core::iter::repeat_n(' ', column));
+            self.write_key(key);
+            self.output.push('\n');
+            column += self.column(core::slice::from_ref(key));
+        }
+        self.output.extend(core::iter::repeat_n(' ', column));
+        self.output.push('#');
+        self.output.push_str(comment);
+        self.output.push('\n');
+
+        self.previous.clear();
+        self.previous.extend_from_slice(parents);
+    }
+
+    /// Adds a `#` comment to the end of the last line
+    pub(crate) fn append_comment(&mut self, comment: &str) {
+        if self.output.ends_with('\n') {
+            self.output.pop();
+        }
+        self.output.push_str(" #");
+        self.output.push_str(comment);
+        self.output.push('\n');
+    }
+
+    /// Writes an empty line, unless at the start or after another empty line
+    pub(crate) fn write_blank_line(&mut self) {
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+
+    /// The column of the entries under `keys`
+    fn column(&self, keys: &[YAMLKey<'_>]) -> usize {
+        keys.iter()
+            .map(|key| match key {
+                YAMLKey::Slice(_) => self.indent_size,
+                YAMLKey::Index(_) => 2,
+            })
+            .sum()
+    }
+
+    /// Writes the keys that differ from the last event, ready for the value. Returns the column of
+    /// the last key
+    fn write_keys(&mut self, keys: &[YAMLKey<'a>]) -> usize {
+        let shared = keys
+            .iter()
+            .zip(&self.previous)
+            .take_while(|(key, previous)| key == previous)
+            .count()
+            .min(keys.len().saturating_sub(1));
+
+        let mut column = 0;
+        for (depth, key) in keys.iter().enumerate() {
+            if depth >= shared {
+                // The first key of a sequence item goes on the same line as the `-`
+                let after_dash = depth > shared && matches!(keys[depth - 1], YAMLKey::Index(_));
+                if !after_dash {
+                    self.output.extend(core::iter::repeat_n(' ', column));
+                }
+                self.write_key(key);
+                let is_last = depth + 1 == keys.len();
+                if is_last || matches!(key, YAMLKey::Index(_)) {
+                    self.output.push(' ');
+                } else {
+                    self.output.push('\n');
+                }
+            }
+            if depth + 1 < keys.len() {
+                column += self.column(core::slice::from_ref(key));
+            }
+        }
+
+        self.previous.clear();
+        self.previous.extend_from_slice(keys);
+        column
+    }
+
+    /// Writes `key:` or `-`
+    fn write_key(&mut self, key: &YAMLKey<'_>) {
+        match key {
+            YAMLKey::Slice(key) if self.keys_as_written => {
+                self.output.push_str(key);
+                self.output.push(':');
+            }
+            YAMLKey::Slice(key) => {
+                write_key_string(&mut self.output, key);
+                self.output.push(':');
+            }
+            YAMLKey::Index(_) => self.output.push('-'),
+        }
+    }
+
+    /// Writes the lines of a [`RootYAMLValue::RawBlock`] at `column`, keeping their relative
+    /// indentation. Flow collections and the first line of a sequence item stay on the line of the
+    /// key (or `-`)
+    fn write_raw_block(&mut self, raw: &str, column: usize) {
+        let indentation = |line: &str| line.len() - line.trim_start_matches(' ').len();
+        let mut lines = raw.lines();
+        let first = lines.next().unwrap_or_default();
+        let rest = lines
+            .clone()
+            .filter(|line| !trim_whitespace(line).is_empty());
+        // the column the first line was at
+        let base = if first.starts_with(' ') {
+            indentation(first)
+        } else if raw.starts_with(['[', '{']) {
+            rest.clone().map(indentation).min().unwrap_or(0)
+        } else {
+            // sequence items start after the `- ` so this is found from the lines after it
+            let least = rest.clone().map(indentation).min().unwrap_or(0);
+            let (first_line, _) = split_comment(first);
+            let last_token = first_line.rsplit(' ').next().unwrap_or_default();
+            let opens_block = last_token.ends_with(':')
+                || last_token == "-"
+                || last_token.starts_with(['|', '>']);
+            let next_is_least = rest
+                .clone()
+                .next()
+                .is_some_and(|line| indentation(line) == least);
+            if opens_block && next_is_least {
+                // all following lines are nested under the first
+                least.saturating_sub(self.indent_size)
+            } else {
+                least
+            }
+        };
+        if first.starts_with(' ') {
+            if self.output.ends_with(' ') {
+                self.output.pop();
+            }
+            self.output.push('\n');
+            lines = raw.lines();
+        } else {
+            self.output.push_str(first);
+            self.output.push('\n');
+        }
+        for line in lines {
+            if !trim_whitespace(line).is_empty() {
+                let indent = column + indentation(line).saturating_sub(base);
+                self.output.extend(core::iter::repeat_n(' ', indent));
+                self.output.push_str(line.trim_start_matches(' '));
+            }
+            self.output.push('\n');
+        }
+    }
+
+    fn write_block_scalar(&mut self, multiline: &MultilineString<'_>, column: usize) {
+        let mut lines = multiline.lines().peekable();
+        self.output.push(match multiline.style() {
+            BlockStyle::Literal => '|',
+            BlockStyle::Folded => '>',
+        });
+        // the first line needs an indentation indicator if it starts with spaces
+        if lines
+            .peek()
+            .is_some_and(|line| line.starts_with(is_yaml_whitespace))
+        {
+            let _ = write!(self.output, "{}", self.indent_size);
+        }
+        match multiline.chomping() {
+            Chomping::Strip => self.output.push('-'),
+            Chomping::Clip => {}
+            Chomping::Keep => self.output.push('+'),
+        }
+        self.output.push('\n');
+        for line in lines {
+            if !trim_whitespace(line).is_empty() {
+                self.output.extend(core::iter::repeat_n(' ', column));
+                self.output.push_str(line);
+            }
+            self.output.push('\n');
+        }
+    }
+}
+
+/// Writes each value, see [`YAMLWriter::write`]
+impl<'a> YAMLSink<'a> for YAMLWriter<'a> {
+    fn value(&mut self, keys: &[YAMLKey<'a>], value: RootYAMLValue<'a>) -> ControlFlow<()> {
+        self.write(keys, &value);
+        ControlFlow::Continue(())
+    }
+}
+
+/// Writes `on` plain if it would be parsed back as the same string, otherwise double quoted
+fn write_string(output: &mut String, on: &str) {
+    if needs_quotes(on) {
+        write_double_quoted(output, on);
+    } else {
+        output.push_str(on);
+    }
+}
+
+/// Writes a mapping key. Keys are parsed as the text between any quotes without decoding escapes,
+/// so keys that need quotes are written in single or double quotes that they do not end early.
+/// Other keys (with line breaks, or quotes that end both) are escaped
+fn write_key_string(output: &mut String, key: &str) {
+    if !needs_quotes(key) {
+        output.push_str(key);
+        return;
+    }
+    for quote in ['\'', '"'] {
+        let quoted = format!("{quote}{key}{quote}");
+        if !key.contains(['\n', '\r']) && quoted_end(&quoted) == Some(quoted.len()) {
+            output.push_str(&quoted);
+            return;
+        }
+    }
+    write_double_quoted(output, key);
+}
+
+/// Writes `on` in double quotes, escaping quotes, backslashes and control characters
+pub(crate) fn write_double_quoted(output: &mut String, on: &str) {
+    output.push('"');
+    for chr in on.chars() {
+        match chr {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            '\r' => output.push_str("\\r"),
+            chr if chr.is_control() => {
+                let _ = write!(output, "\\u{:04x}", u32::from(chr));
+            }
+            chr => output.push(chr),
+        }
+    }
+    output.push('"');
+}
+
+fn needs_quotes(on: &str) -> bool {
+    let Some(first) = on.chars().next() else {
+        return true;
+    };
+    // quoted if any reader would parse it as another type (such as `yes` under YAML 1.1)
+    let ambiguous = matches!(on, "~" | "null" | "Null" | "NULL")
+        || BoolStyle::Yaml11.parse(on).is_some()
+        || is_number(on);
+    let indicator = "-?:,[]{}#&*!|>'\"%@`".contains(first);
+    ambiguous
+        || indicator
+        || on.starts_with(char::is_whitespace)
+        || on.ends_with(char::is_whitespace)
+        || on.ends_with(':')
+        || on.contains(": ")
+        || on.contains(" #")
+        || on.chars().any(char::is_control)
+}